@@ -1,13 +1,234 @@
+use std::collections::HashMap;
+
 use bevy::prelude::*;
 
 use crate::app::SimulationState;
+use crate::astro::binary::{BinaryCompanion, BinarySettings, CompactBinary, CompactBinaryMerger};
 use crate::astro::black_hole::BlackHole;
-use crate::astro::formation::FormationSettings;
-use crate::astro::galaxy::Galaxy;
+use crate::astro::formation::{FormationSettings, SupernovaEvent};
+use crate::astro::galaxy::{Galaxy, StarGalaxyMembership};
+use crate::astro::planet::PlanetarySystem;
 use crate::astro::star::Star;
+use crate::pru::cell::{DerivedFields, PruCell};
+use crate::pru::gravity::GravityParams;
+use crate::pru::universe::PruUniverse;
+use crate::spatial::SpatialOctree;
+
+use super::astro_agent::{
+    AgentEvent, AgentTelemetry, AstroAgent, ACCRETION_HISTORY_LEN, INSPIRAL_HISTORY_LEN,
+    METALLICITY_HISTORY_LEN,
+};
+use super::events::{AstroReport, AstroReportLog, GalaxyMergerEvent, ReportSeverity};
+
+/// Ticks without accretion before a black hole is reported as quiescent.
+pub const QUIESCENT_ACCRETION_TICKS: u64 = 50;
+/// Multiple of the mean accretion rate that counts as a starburst/merger spike.
+pub const ACCRETION_SPIKE_FACTOR: f32 = 5.0;
+
+/// Flat ΛCDM distance measures in simulation units (c = 1).
+#[derive(Resource, Clone, Copy)]
+pub struct CosmologyDistanceCalculator {
+    pub omega_m: f32,
+    pub omega_lambda: f32,
+    /// Hubble rate used by the HUD when converting distances to redshift.
+    pub hubble: f32,
+    /// Even number of Simpson's-rule intervals for the comoving integral.
+    pub simpson_intervals: u32,
+}
+
+impl Default for CosmologyDistanceCalculator {
+    fn default() -> Self {
+        Self {
+            omega_m: 0.3,
+            omega_lambda: 0.7,
+            hubble: 0.01,
+            simpson_intervals: 64,
+        }
+    }
+}
+
+impl CosmologyDistanceCalculator {
+    /// Dimensionless expansion rate E(z) = H(z) / H0 for a flat universe.
+    fn expansion_rate(&self, z: f32) -> f32 {
+        let a_inv = 1.0 + z;
+        (self.omega_m * a_inv * a_inv * a_inv + self.omega_lambda).sqrt()
+    }
+
+    /// Comoving distance `c/H0 * ∫0^z dz'/E(z')`, integrated with Simpson's rule.
+    pub fn comoving_distance(&self, z: f32, hubble: f32) -> f32 {
+        if z <= 0.0 || hubble <= 0.0 {
+            return 0.0;
+        }
+        let n = (self.simpson_intervals.max(2) + 1) & !1;
+        let h = z / n as f32;
+        let mut sum = 1.0 / self.expansion_rate(0.0) + 1.0 / self.expansion_rate(z);
+        for i in 1..n {
+            let weight = if i % 2 == 1 { 4.0 } else { 2.0 };
+            sum += weight / self.expansion_rate(i as f32 * h);
+        }
+        sum * h / 3.0 / hubble
+    }
+
+    /// Luminosity distance `d_L = (1 + z) * d_C`.
+    pub fn luminosity_distance(&self, z: f32, hubble: f32) -> f32 {
+        (1.0 + z) * self.comoving_distance(z, hubble)
+    }
+
+    /// Low-redshift Hubble-law redshift for a proper distance.
+    pub fn redshift_for_distance(&self, distance: f32, hubble: f32) -> f32 {
+        (distance * hubble).max(0.0)
+    }
+}
+
+/// Role of a galaxy MST edge in the cosmic web.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FilamentKind {
+    /// Short edge linking galaxies along a filament.
+    Filament,
+    /// Long edge spanning a void between structures.
+    VoidBoundary,
+}
+
+/// Euclidean minimum spanning tree over galaxy centers.
+#[derive(Resource)]
+pub struct GalaxyFilamentGraph {
+    /// `(galaxy_id_a, galaxy_id_b, length)` for each tree edge.
+    pub edges: Vec<(u32, u32, f32)>,
+    pub mean_edge_length: f32,
+    pub last_build_tick: u64,
+    /// Draw the tree edges as line segments in the scene.
+    pub render_edges: bool,
+}
+
+impl Default for GalaxyFilamentGraph {
+    fn default() -> Self {
+        Self {
+            edges: Vec::new(),
+            mean_edge_length: 0.0,
+            last_build_tick: 0,
+            render_edges: true,
+        }
+    }
+}
+
+impl GalaxyFilamentGraph {
+    /// Edges under half the mean length trace filaments; longer ones border voids.
+    pub fn edge_kind(&self, length: f32) -> FilamentKind {
+        if length < self.mean_edge_length * 0.5 {
+            FilamentKind::Filament
+        } else {
+            FilamentKind::VoidBoundary
+        }
+    }
+}
+
+/// A density bridge found between two galaxies.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DensityFilament {
+    pub a: u32,
+    pub b: u32,
+    /// Bridge endpoints on the two halo edges.
+    pub start: Vec3,
+    pub end: Vec3,
+    /// Mean local density sampled along the bridge.
+    pub mean_density: f32,
+}
+
+/// Filaments confirmed by overdense cells along the line between galaxy pairs.
+///
+/// Unlike [`GalaxyFilamentGraph`], which classifies MST edges by length alone,
+/// a pair only counts here if every cell sampled between the two halos is
+/// denser than `min_overdensity` times the lattice mean.
+#[derive(Resource)]
+pub struct DensityFilaments {
+    pub filaments: Vec<DensityFilament>,
+    /// Galaxy pairs farther apart than this are never connected.
+    pub max_separation: f32,
+    /// Multiple of the mean local density every bridge sample must reach.
+    pub min_overdensity: f32,
+    pub last_scan_tick: u64,
+}
+
+impl Default for DensityFilaments {
+    fn default() -> Self {
+        Self {
+            filaments: Vec::new(),
+            max_separation: 6.0,
+            min_overdensity: 1.2,
+            last_scan_tick: 0,
+        }
+    }
+}
+
+/// Galaxy pairs within `max_separation` joined by a bridge denser than `threshold`.
+///
+/// The segment between the two halo edges is sampled every `step`;
+/// `density_at` returns `None` off the lattice, which breaks the bridge.
+/// Pairs whose halos overlap have no bridge to test, and a bridge that runs
+/// through a third galaxy's halo is two filaments rather than one, so both
+/// are skipped.
+pub fn detect_density_filaments(
+    galaxies: &[(u32, Vec3, f32)],
+    density_at: impl Fn(Vec3) -> Option<f32>,
+    threshold: f32,
+    max_separation: f32,
+    step: f32,
+) -> Vec<DensityFilament> {
+    let step = step.max(f32::EPSILON);
+    let mut filaments = Vec::new();
+    for (i, &(a, center_a, radius_a)) in galaxies.iter().enumerate() {
+        for &(b, center_b, radius_b) in &galaxies[i + 1..] {
+            let separation = center_a.distance(center_b);
+            if separation > max_separation {
+                continue;
+            }
+            let Some(direction) = (center_b - center_a).try_normalize() else {
+                continue;
+            };
+            let gap = separation - radius_a - radius_b;
+            if gap <= 0.0 {
+                continue;
+            }
+            let start = center_a + direction * radius_a;
+            let end = start + direction * gap;
+            let occluded = galaxies.iter().any(|&(id, center, radius)| {
+                id != a && id != b && distance_to_segment(center, start, end) < radius
+            });
+            if occluded {
+                continue;
+            }
+            let samples = (gap / step).ceil().max(1.0) as usize;
+            let mut total = 0.0;
+            let mut bridged = true;
+            for k in 0..=samples {
+                let point = start + direction * (gap * k as f32 / samples as f32);
+                match density_at(point) {
+                    Some(density) if density >= threshold => total += density,
+                    _ => {
+                        bridged = false;
+                        break;
+                    }
+                }
+            }
+            if bridged {
+                filaments.push(DensityFilament {
+                    a,
+                    b,
+                    start,
+                    end,
+                    mean_density: total / (samples + 1) as f32,
+                });
+            }
+        }
+    }
+    filaments
+}
 
-use super::astro_agent::{AgentTelemetry, AstroAgent};
-use super::events::{AstroReport, AstroReportLog};
+fn distance_to_segment(point: Vec3, start: Vec3, end: Vec3) -> f32 {
+    let span = end - start;
+    let t = ((point - start).dot(span) / span.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+    point.distance(start + span * t)
+}
 
 #[derive(Resource, Default)]
 pub struct AnalysisSchedule {
@@ -15,33 +236,362 @@ pub struct AnalysisSchedule {
     pub agent_interval: u64,
 }
 
+impl AnalysisSchedule {
+    /// Forget when agents were last analyzed, as after a reset or a load to an earlier tick.
+    pub fn reset(&mut self) {
+        self.last_agent_tick = 0;
+    }
+}
+
+/// Reset analysis cadences when the tick counter moves backwards.
+pub fn rewind_analysis_schedule(
+    sim_state: Res<SimulationState>,
+    mut schedule: ResMut<AnalysisSchedule>,
+    mut graph: ResMut<GalaxyFilamentGraph>,
+    mut filaments: ResMut<DensityFilaments>,
+) {
+    if schedule.last_agent_tick > sim_state.tick {
+        schedule.reset();
+    }
+    if graph.last_build_tick > sim_state.tick {
+        graph.last_build_tick = 0;
+    }
+    if filaments.last_scan_tick > sim_state.tick {
+        filaments.last_scan_tick = 0;
+    }
+}
+
+/// Kruskal's algorithm over all O(N²) center pairs, returning tree edges by id.
+pub fn euclidean_mst(points: &[(u32, Vec3)]) -> Vec<(u32, u32, f32)> {
+    let mut candidates = Vec::with_capacity(points.len() * points.len().saturating_sub(1) / 2);
+    for a in 0..points.len() {
+        for b in (a + 1)..points.len() {
+            candidates.push((a, b, points[a].1.distance(points[b].1)));
+        }
+    }
+    candidates.sort_by(|x, y| x.2.total_cmp(&y.2));
+
+    // Union-find with path halving; indices are positions in `points`.
+    let mut parent: Vec<usize> = (0..points.len()).collect();
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    let mut edges = Vec::with_capacity(points.len().saturating_sub(1));
+    for (a, b, length) in candidates {
+        let (root_a, root_b) = (find(&mut parent, a), find(&mut parent, b));
+        if root_a == root_b {
+            continue;
+        }
+        parent[root_a] = root_b;
+        edges.push((points[a].0, points[b].0, length));
+        if edges.len() + 1 == points.len() {
+            break;
+        }
+    }
+    edges
+}
+
+/// Rebuild the galaxy filament graph every ten galaxy refresh intervals.
+pub fn build_galaxy_mst(
+    sim_state: Res<SimulationState>,
+    settings: Res<FormationSettings>,
+    mut graph: ResMut<GalaxyFilamentGraph>,
+    galaxies: Query<&Galaxy>,
+) {
+    if !sim_state.advanced_this_frame() {
+        return;
+    }
+    let interval = settings.galaxy_interval * 10;
+    if sim_state.tick.saturating_sub(graph.last_build_tick) < interval {
+        return;
+    }
+    graph.last_build_tick = sim_state.tick;
+
+    let points: Vec<(u32, Vec3)> = galaxies.iter().map(|g| (g.id, g.center)).collect();
+    graph.edges = euclidean_mst(&points);
+    graph.mean_edge_length = if graph.edges.is_empty() {
+        0.0
+    } else {
+        graph.edges.iter().map(|(_, _, length)| length).sum::<f32>() / graph.edges.len() as f32
+    };
+}
+
+/// Look for density bridges between galaxies on the galaxy refresh cadence.
+///
+/// Densities are read at the lattice site nearest each sample point, and the
+/// threshold is relative to the mean over all cells. A change in the number of
+/// filaments is logged.
+pub fn detect_galaxy_filaments(
+    sim_state: Res<SimulationState>,
+    settings: Res<FormationSettings>,
+    universe: Res<PruUniverse>,
+    mut filaments: ResMut<DensityFilaments>,
+    mut reports: ResMut<AstroReportLog>,
+    galaxies: Query<&Galaxy>,
+    cells: Query<(&PruCell, &DerivedFields)>,
+) {
+    if !sim_state.advanced_this_frame() {
+        return;
+    }
+    if sim_state.tick.saturating_sub(filaments.last_scan_tick) < settings.galaxy_interval {
+        return;
+    }
+    filaments.last_scan_tick = sim_state.tick;
+    let _span = info_span!("density_filaments", galaxies = galaxies.iter().len()).entered();
+
+    let dims = universe.grid_dimensions;
+    let mut densities = vec![0.0f32; (dims.x * dims.y * dims.z) as usize];
+    let mut total = 0.0;
+    for (cell, derived) in cells.iter() {
+        let c = cell.grid_coords.min(dims.saturating_sub(UVec3::ONE));
+        densities[(c.x + dims.x * (c.y + dims.y * c.z)) as usize] = derived.local_density;
+        total += derived.local_density;
+    }
+    let mean = total / cells.iter().len().max(1) as f32;
+    let density_at = |point: Vec3| {
+        let lattice = (point + universe.center_offset()) / universe.spacing;
+        if lattice.min_element() < -0.5 || lattice.cmpgt(dims.as_vec3() - 0.5).any() {
+            return None;
+        }
+        let c = universe.nearest_grid_coords(point);
+        Some(densities[(c.x + dims.x * (c.y + dims.y * c.z)) as usize])
+    };
+
+    let centers: Vec<(u32, Vec3, f32)> = galaxies
+        .iter()
+        .map(|galaxy| (galaxy.id, galaxy.center, galaxy.radius))
+        .collect();
+    let previous = filaments.filaments.len();
+    filaments.filaments = detect_density_filaments(
+        &centers,
+        density_at,
+        mean * filaments.min_overdensity,
+        filaments.max_separation,
+        universe.spacing,
+    );
+
+    let count = filaments.filaments.len();
+    if count != previous {
+        reports.push(AstroReport {
+            tick: sim_state.tick,
+            severity: ReportSeverity::Info,
+            summary: format!(
+                "cosmic web: {count} density filaments between galaxies (was {previous})"
+            ),
+            position: None,
+        });
+    }
+}
+
+/// Copy each black hole's latest accretion pass into its agent's rolling history.
+pub fn record_accretion_history(
+    mut agents: Query<(&mut AgentTelemetry, &BlackHole), With<AstroAgent>>,
+) {
+    for (mut telemetry, bh) in agents.iter_mut() {
+        if bh.last_accretion_tick <= telemetry.last_accretion_sample_tick {
+            continue;
+        }
+        if telemetry.accretion_rate_history.is_empty() || bh.last_accreted > 0.0 {
+            telemetry.last_active_accretion_tick = bh.last_accretion_tick;
+            telemetry.quiescent = false;
+        }
+        telemetry.last_accretion_sample_tick = bh.last_accretion_tick;
+        telemetry.accretion_rate_history.push_back(bh.last_accreted);
+        while telemetry.accretion_rate_history.len() > ACCRETION_HISTORY_LEN {
+            telemetry.accretion_rate_history.pop_front();
+        }
+    }
+}
+
+/// Sample the Peters coalescence time of every black hole agent that is the primary of a binary.
+pub fn record_inspiral_timescales(
+    sim_state: Res<SimulationState>,
+    settings: Res<BinarySettings>,
+    gravity: Res<GravityParams>,
+    mut agents: Query<
+        (&mut AgentTelemetry, &BlackHole, Option<&mut CompactBinary>),
+        With<AstroAgent>,
+    >,
+    companions: Query<&BlackHole, (With<BinaryCompanion>, Without<CompactBinary>)>,
+) {
+    if !sim_state.advanced_this_frame() {
+        return;
+    }
+    for (mut telemetry, bh, binary) in agents.iter_mut() {
+        let timescale = binary.and_then(|mut binary| {
+            let companion = binary.secondary.get(&companions)?;
+            Some(binary.inspiral_time(
+                bh.mass,
+                companion.mass,
+                gravity.g_effective,
+                settings.speed_of_light,
+            ))
+        });
+        telemetry.inspiral_timescale = timescale;
+        if let Some(timescale) = timescale {
+            telemetry.inspiral_history.push_back(timescale);
+            while telemetry.inspiral_history.len() > INSPIRAL_HISTORY_LEN {
+                telemetry.inspiral_history.pop_front();
+            }
+        }
+    }
+}
+
+/// Log every compact binary merger as a gravitational wave event.
+pub fn report_compact_mergers(
+    mut mergers: EventReader<CompactBinaryMerger>,
+    mut reports: ResMut<AstroReportLog>,
+) {
+    for merger in mergers.read() {
+        reports.push(AstroReport {
+            tick: merger.tick,
+            severity: ReportSeverity::Critical,
+            summary: format!(
+                "Gravitational waves: black holes {:.2} + {:.2} merged into {:.2} at {:.1} after {} ticks of inspiral, radiating {:.3e}",
+                merger.primary_mass,
+                merger.secondary_mass,
+                merger.remnant_mass,
+                merger.position,
+                merger.inspiral_ticks,
+                merger.radiated_energy
+            ),
+            position: Some(merger.position),
+        });
+    }
+}
+
+/// Queue galaxy mergers, supernovae, and compact mergers on the galaxy agents they touch.
+///
+/// A galaxy merger goes to the surviving galaxy's agent; the other events go
+/// to every galaxy whose halo contains them. [`analyze_agents`] drains the
+/// queues on the same frame instead of waiting for its interval.
+pub fn queue_agent_events(
+    mut galaxy_mergers: EventReader<GalaxyMergerEvent>,
+    mut supernovae: EventReader<SupernovaEvent>,
+    mut compact_mergers: EventReader<CompactBinaryMerger>,
+    mut agents: Query<(&mut AstroAgent, &Galaxy)>,
+) {
+    for merger in galaxy_mergers.read() {
+        for (mut agent, galaxy) in agents.iter_mut() {
+            if galaxy.id == merger.survivor {
+                agent.event_queue.push(AgentEvent::GalaxyMerger {
+                    absorbed: merger.absorbed,
+                    absorbed_mass: merger.absorbed_mass,
+                });
+            }
+        }
+    }
+    let located = supernovae
+        .read()
+        .map(|supernova| (supernova.position, AgentEvent::Supernova))
+        .chain(
+            compact_mergers
+                .read()
+                .map(|merger| (merger.position, AgentEvent::CompactBinaryMerger)),
+        );
+    for (position, event) in located {
+        for (mut agent, galaxy) in agents.iter_mut() {
+            if galaxy.contains(position) {
+                agent.event_queue.push(event.clone());
+            }
+        }
+    }
+}
+
+/// The agent's report of a queued event, if nobody else reports it.
+///
+/// Stellar collapses and compact mergers already have their own reports, from
+/// formation and [`report_compact_mergers`]; for those the agent only
+/// refreshes its telemetry.
+fn agent_event_report(tick: u64, galaxy: &Galaxy, event: &AgentEvent) -> Option<AstroReport> {
+    let AgentEvent::GalaxyMerger {
+        absorbed,
+        absorbed_mass,
+    } = event
+    else {
+        return None;
+    };
+    Some(AstroReport {
+        tick,
+        severity: ReportSeverity::Critical,
+        summary: format!("{galaxy} absorbed Galaxy#{absorbed} (m={absorbed_mass:.1})"),
+        position: Some(galaxy.center),
+    })
+}
+
+/// Refresh agent telemetry every `agent_interval` ticks, and at once for agents with queued events.
+#[allow(clippy::too_many_arguments)]
 pub fn analyze_agents(
     sim_state: Res<SimulationState>,
     settings: Res<FormationSettings>,
     mut schedule: ResMut<AnalysisSchedule>,
     mut reports: ResMut<AstroReportLog>,
-    mut agents: Query<(&mut AstroAgent, &mut AgentTelemetry, &Galaxy)>,
-    black_holes: Query<&Transform, With<BlackHole>>,
-    stars: Query<&Transform, With<Star>>,
+    octree: Res<SpatialOctree>,
+    mut agents: Query<(Entity, &mut AstroAgent, &mut AgentTelemetry, &Galaxy)>,
+    mut hole_agents: Query<
+        (&AstroAgent, &mut AgentTelemetry, &BlackHole, &Transform),
+        Without<Galaxy>,
+    >,
+    black_holes: Query<(), With<BlackHole>>,
+    galaxy_entities: Query<Entity, With<Galaxy>>,
+    mut stars: Query<(&Star, &mut StarGalaxyMembership, Option<&PlanetarySystem>)>,
 ) {
+    if !sim_state.advanced_this_frame() {
+        return;
+    }
     if schedule.agent_interval == 0 {
-        schedule.agent_interval = settings.galaxy_refresh_interval.max(4);
+        schedule.agent_interval = settings.galaxy_interval.max(4);
     }
-    if sim_state.tick - schedule.last_agent_tick < schedule.agent_interval {
+    let due = sim_state.tick.saturating_sub(schedule.last_agent_tick) >= schedule.agent_interval;
+    if !due
+        && agents
+            .iter()
+            .all(|(_, agent, _, _)| agent.event_queue.is_empty())
+    {
         return;
     }
-    schedule.last_agent_tick = sim_state.tick;
+    if due {
+        schedule.last_agent_tick = sim_state.tick;
+    }
+    let _span = info_span!("agent_analysis", galaxies = agents.iter().len()).entered();
+
+    // Member metallicities and planet-hosting counts per galaxy, from one pass over the stars.
+    let mut members: HashMap<Entity, (Vec<f32>, u32)> = HashMap::new();
+    for (star, mut membership, system) in stars.iter_mut() {
+        let Some(galaxy) = membership.galaxy.get(&galaxy_entities) else {
+            continue;
+        };
+        let entry = members.entry(galaxy).or_default();
+        entry.0.push(star.metallicity);
+        if system.is_some_and(|system| !system.planets.is_empty()) {
+            entry.1 += 1;
+        }
+    }
 
-    for (mut agent, mut telemetry, galaxy) in agents.iter_mut() {
+    for (entity, mut agent, mut telemetry, galaxy) in agents.iter_mut() {
+        if !due && agent.event_queue.is_empty() {
+            continue;
+        }
+        for event in std::mem::take(&mut agent.event_queue) {
+            if let Some(report) = agent_event_report(sim_state.tick, galaxy, &event) {
+                reports.push(report);
+            }
+        }
         let region_radius = galaxy.radius.max(0.1);
-        let bh_count = black_holes
-            .iter()
-            .filter(|t| (t.translation - galaxy.center).length() < region_radius)
-            .count() as u32;
-        let star_count = stars
-            .iter()
-            .filter(|t| (t.translation - galaxy.center).length() < region_radius)
-            .count() as u32;
+        let nearby = octree.query_radius(galaxy.center, region_radius);
+        let bh_count = nearby.iter().filter(|e| black_holes.contains(**e)).count() as u32;
+        let (metallicities, planetary_systems) = members.remove(&entity).unwrap_or_default();
+        let star_count = metallicities.len() as u32;
+        let mean_metallicity = if metallicities.is_empty() {
+            0.0
+        } else {
+            metallicities.iter().sum::<f32>() / star_count as f32
+        };
 
         let mass_change = (galaxy.total_mass - telemetry.last_mass).abs();
         let star_change = star_count.abs_diff(telemetry.last_star_count);
@@ -53,23 +603,101 @@ pub fn analyze_agents(
 
         if mass_change > galaxy.total_mass * 0.05 || star_change > 0 || bh_change > 0 {
             let summary = format!(
-                "Galaxy {} mass {:.2} (Δ{:.2}), stars {}, black holes {}",
-                galaxy.id, galaxy.total_mass, mass_change, star_count, bh_count
+                "{galaxy} (Δm {mass_change:.2}), member stars {star_count}, black holes {bh_count}, mean Z {mean_metallicity:.4}"
             );
             reports.push(AstroReport {
                 tick: sim_state.tick,
-                agent_id: agent.id,
-                agent_kind: agent.kind,
+                severity: ReportSeverity::Info,
                 summary,
+                position: Some(galaxy.center),
             });
         }
 
         telemetry.last_mass = galaxy.total_mass;
         telemetry.last_star_count = star_count;
         telemetry.last_black_holes = bh_count;
-        agent.tracked_region = Some(crate::agents::astro_agent::TrackedRegion {
-            min: UVec3::ZERO,
-            max: UVec3::splat(settings.region_size * 3),
-        });
+        telemetry.mean_metallicity = mean_metallicity;
+        telemetry.planetary_systems = planetary_systems;
+        telemetry.metallicity_history.push_back(mean_metallicity);
+        while telemetry.metallicity_history.len() > METALLICITY_HISTORY_LEN {
+            telemetry.metallicity_history.pop_front();
+        }
+    }
+
+    if !due {
+        return;
+    }
+    for (agent, mut telemetry, bh, transform) in hole_agents.iter_mut() {
+        let Some(&latest) = telemetry.accretion_rate_history.back() else {
+            continue;
+        };
+
+        let quiet_ticks = sim_state
+            .tick
+            .saturating_sub(telemetry.last_active_accretion_tick);
+        if quiet_ticks >= QUIESCENT_ACCRETION_TICKS && !telemetry.quiescent {
+            telemetry.quiescent = true;
+            reports.push(AstroReport {
+                tick: sim_state.tick,
+                severity: ReportSeverity::Warning,
+                summary: format!(
+                    "Black hole {} quiescent: no accretion for {} ticks ({bh})",
+                    agent.id, quiet_ticks
+                ),
+                position: Some(transform.translation),
+            });
+        }
+
+        if telemetry.last_accretion_sample_tick > telemetry.last_spike_check_tick {
+            telemetry.last_spike_check_tick = telemetry.last_accretion_sample_tick;
+            let mean_rate = telemetry.mean_prior_accretion_rate();
+            if mean_rate > 0.0 && latest > ACCRETION_SPIKE_FACTOR * mean_rate {
+                reports.push(AstroReport {
+                    tick: sim_state.tick,
+                    severity: ReportSeverity::Critical,
+                    summary: format!(
+                        "Black hole {} accretion spike {:.3} (mean {:.3}, {bh})",
+                        agent.id, latest, mean_rate
+                    ),
+                    position: Some(transform.translation),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn an_overdense_bridge_between_two_galaxies_is_a_filament() {
+        // Galaxies 1 and 2 are joined by a dense bar along x; galaxy 3 sits as
+        // close to galaxy 1 but across empty space.
+        let galaxies = [
+            (1, Vec3::ZERO, 1.0),
+            (2, Vec3::new(5.0, 0.0, 0.0), 1.0),
+            (3, Vec3::new(0.0, 5.0, 0.0), 1.0),
+        ];
+        let density_at = |p: Vec3| {
+            Some(if p.y.abs() < 0.5 && p.z.abs() < 0.5 {
+                2.0
+            } else {
+                0.5
+            })
+        };
+
+        let found = detect_density_filaments(&galaxies, density_at, 1.2, 6.0, 0.5);
+        assert_eq!(found.len(), 1, "{found:?}");
+        let filament = found[0];
+        assert_eq!((filament.a, filament.b), (1, 2));
+        assert!(filament.start.distance(Vec3::X) < 1e-5);
+        assert!(filament.end.distance(Vec3::new(4.0, 0.0, 0.0)) < 1e-5);
+        assert!((filament.mean_density - 2.0).abs() < 1e-5);
+
+        assert!(
+            detect_density_filaments(&galaxies, density_at, 1.2, 4.0, 0.5).is_empty(),
+            "pairs beyond max_separation are never connected"
+        );
     }
 }
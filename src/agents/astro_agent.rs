@@ -1,50 +1,94 @@
+use std::collections::VecDeque;
+
 use bevy::prelude::*;
 
+use crate::astro::black_hole::BlackHole;
 use crate::astro::galaxy::Galaxy;
 
-/// Region tracked by an agent.
-#[derive(Debug, Clone)]
-pub struct TrackedRegion {
-    pub min: UVec3,
-    pub max: UVec3,
-}
+/// Number of accretion-rate samples retained per black hole agent.
+pub const ACCRETION_HISTORY_LEN: usize = 64;
+/// Number of mean-metallicity samples retained per galaxy agent.
+pub const METALLICITY_HISTORY_LEN: usize = 64;
+/// Number of inspiral-timescale samples retained per black hole agent.
+pub const INSPIRAL_HISTORY_LEN: usize = 64;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AstroAgentKind {
     GalaxyAgent,
-    ClusterAgent,
     BlackHoleAgent,
 }
 
+/// A physical event that touched an agent's region, awaiting its next analysis.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AgentEvent {
+    /// Another galaxy was folded into the agent's galaxy.
+    GalaxyMerger { absorbed: u32, absorbed_mass: f32 },
+    /// Stars collapsed into a new black hole inside the galaxy.
+    Supernova,
+    /// A compact binary coalesced inside the galaxy.
+    CompactBinaryMerger,
+}
+
 /// Higher-level observer that summarizes regional behavior.
 #[derive(Component, Debug, Clone)]
 pub struct AstroAgent {
     pub id: u32,
     pub kind: AstroAgentKind,
-    pub name: Option<String>,
-    pub tracked_region: Option<TrackedRegion>,
+    /// Events queued by the listener systems, drained by `analyze_agents`.
+    pub event_queue: Vec<AgentEvent>,
 }
 
 impl AstroAgent {
-    pub fn new(id: u32, kind: AstroAgentKind, name: impl Into<Option<String>>) -> Self {
+    pub fn new(id: u32, kind: AstroAgentKind) -> Self {
         Self {
             id,
             kind,
-            name: name.into(),
-            tracked_region: None,
+            event_queue: Vec::new(),
         }
     }
 }
 
 /// Attach agents directly to galaxies to simplify bookkeeping.
+///
+/// The agent takes its id from [`AgentIdCounter`], not the galaxy, so it
+/// never collides with a black hole agent's.
 pub fn attach_agents_to_galaxies(
     mut commands: Commands,
-    galaxies: Query<(Entity, &Galaxy), Without<AstroAgent>>,
+    mut ids: ResMut<AgentIdCounter>,
+    galaxies: Query<Entity, (With<Galaxy>, Without<AstroAgent>)>,
 ) {
-    for (entity, galaxy) in galaxies.iter() {
-        let name = format!("Galaxy Agent {}", galaxy.id);
+    for entity in galaxies.iter() {
         commands.entity(entity).insert((
-            AstroAgent::new(galaxy.id, AstroAgentKind::GalaxyAgent, Some(name)),
+            AstroAgent::new(ids.next(), AstroAgentKind::GalaxyAgent),
+            AgentTelemetry::default(),
+        ));
+    }
+}
+
+/// Hands out agent ids, unique across every agent kind.
+#[derive(Resource, Default)]
+pub struct AgentIdCounter {
+    pub next_id: u32,
+}
+
+impl AgentIdCounter {
+    pub fn next(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+}
+
+/// Attach an observer agent to every black hole so accretion can be monitored.
+pub fn attach_agents_to_black_holes(
+    mut commands: Commands,
+    mut ids: ResMut<AgentIdCounter>,
+    holes: Query<Entity, (With<BlackHole>, Without<AstroAgent>)>,
+) {
+    for entity in holes.iter() {
+        commands.entity(entity).insert((
+            AstroAgent::new(ids.next(), AstroAgentKind::BlackHoleAgent),
             AgentTelemetry::default(),
         ));
     }
@@ -56,4 +100,35 @@ pub struct AgentTelemetry {
     pub last_mass: f32,
     pub last_star_count: u32,
     pub last_black_holes: u32,
+    /// Mass accreted per formation interval, oldest first.
+    pub accretion_rate_history: VecDeque<f32>,
+    /// Tick of the newest accretion sample.
+    pub last_accretion_sample_tick: u64,
+    /// Tick of the newest sample with non-zero accretion.
+    pub last_active_accretion_tick: u64,
+    /// Whether a quiescence warning has already been issued.
+    pub quiescent: bool,
+    /// Tick up to which accretion samples have been checked for spikes.
+    pub last_spike_check_tick: u64,
+    /// Mean metallicity of stars in the galaxy at the last analysis pass.
+    pub mean_metallicity: f32,
+    /// Mean stellar metallicity per analysis pass, oldest first.
+    pub metallicity_history: VecDeque<f32>,
+    /// Stars in the galaxy with at least one planet, at the last analysis pass.
+    pub planetary_systems: u32,
+    /// Peters time to coalescence while the black hole is the primary of a binary.
+    pub inspiral_timescale: Option<f32>,
+    /// Inspiral timescale per advanced tick while bound, oldest first.
+    pub inspiral_history: VecDeque<f32>,
+}
+
+impl AgentTelemetry {
+    /// Mean accretion rate over the history, excluding the newest sample.
+    pub fn mean_prior_accretion_rate(&self) -> f32 {
+        let prior = self.accretion_rate_history.len().saturating_sub(1);
+        if prior == 0 {
+            return 0.0;
+        }
+        self.accretion_rate_history.iter().take(prior).sum::<f32>() / prior as f32
+    }
 }
@@ -1,11 +1,11 @@
 use bevy::prelude::*;
 
-use super::astro_agent::AstroAgentKind;
-
 #[derive(Resource, Default)]
 pub struct AstroReportLog {
     pub reports: Vec<AstroReport>,
     pub max_reports: usize,
+    /// Reports pushed since startup, including any dropped from `reports`.
+    pub pushed: u64,
 }
 
 impl AstroReportLog {
@@ -14,6 +14,7 @@ impl AstroReportLog {
             self.max_reports = 128;
         }
         self.reports.push(report);
+        self.pushed += 1;
         if self.reports.len() > self.max_reports {
             let overflow = self.reports.len() - self.max_reports;
             self.reports.drain(0..overflow);
@@ -21,16 +22,30 @@ impl AstroReportLog {
     }
 }
 
+/// How urgently a report should be surfaced to the user, least urgent first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum ReportSeverity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
 #[derive(Debug, Clone)]
 pub struct AstroReport {
     pub tick: u64,
-    pub agent_id: u32,
-    pub agent_kind: AstroAgentKind,
+    pub severity: ReportSeverity,
     pub summary: String,
+    /// Where it happened, for reports about one place in the lattice.
+    pub position: Option<Vec3>,
 }
 
-#[derive(Event)]
+/// Sent when one galaxy is folded into another.
+#[derive(Event, Clone, Debug)]
 pub struct GalaxyMergerEvent {
-    pub a: u32,
-    pub b: u32,
+    /// Id of the galaxy that remains.
+    pub survivor: u32,
+    /// Id of the galaxy folded into it, now despawned.
+    pub absorbed: u32,
+    pub absorbed_mass: f32,
 }
@@ -7,7 +7,9 @@
 
 use bevy::prelude::*;
 
-use crate::astro::formation::identify_galaxies;
+use crate::astro::binary::evolve_compact_binaries;
+use crate::astro::black_hole::black_hole_accretion;
+use crate::astro::formation::{identify_galaxies, spawn_black_holes_from_density};
 
 pub mod analysis;
 pub mod astro_agent;
@@ -19,12 +21,37 @@ impl Plugin for AgentsPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<events::AstroReportLog>()
             .init_resource::<analysis::AnalysisSchedule>()
+            .init_resource::<analysis::CosmologyDistanceCalculator>()
+            .init_resource::<analysis::GalaxyFilamentGraph>()
+            .init_resource::<analysis::DensityFilaments>()
+            .init_resource::<astro_agent::AgentIdCounter>()
             .add_event::<events::GalaxyMergerEvent>()
             .add_systems(
                 Update,
                 (
+                    analysis::rewind_analysis_schedule
+                        .before(analysis::build_galaxy_mst)
+                        .before(analysis::analyze_agents),
                     astro_agent::attach_agents_to_galaxies.after(identify_galaxies),
-                    analysis::analyze_agents.after(astro_agent::attach_agents_to_galaxies),
+                    astro_agent::attach_agents_to_black_holes.after(identify_galaxies),
+                    analysis::record_accretion_history.after(black_hole_accretion),
+                    analysis::record_inspiral_timescales
+                        .after(evolve_compact_binaries)
+                        .after(astro_agent::attach_agents_to_black_holes),
+                    analysis::report_compact_mergers.after(evolve_compact_binaries),
+                    analysis::build_galaxy_mst.after(identify_galaxies),
+                    analysis::detect_galaxy_filaments
+                        .after(identify_galaxies)
+                        .after(analysis::rewind_analysis_schedule),
+                    analysis::queue_agent_events
+                        .after(identify_galaxies)
+                        .after(spawn_black_holes_from_density)
+                        .after(evolve_compact_binaries)
+                        .after(astro_agent::attach_agents_to_galaxies),
+                    analysis::analyze_agents
+                        .after(analysis::queue_agent_events)
+                        .after(astro_agent::attach_agents_to_galaxies)
+                        .after(analysis::record_accretion_history),
                 ),
             );
     }
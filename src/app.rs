@@ -1,34 +1,120 @@
-use bevy::ecs::schedule::common_conditions::resource_exists;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::Duration;
+
+use bevy::ecs::system::RunSystemOnce;
+use bevy::log::LogPlugin;
 use bevy::prelude::*;
+use bevy::utils::tracing::field::Empty;
 
-use crate::pru::cell::DerivedFields;
-use crate::pru::gravity::{
-    compute_energy_metrics, simulate_gravity_step, GravityParams, SimulationEnergy,
+use crate::agents::analysis::{DensityFilaments, GalaxyFilamentGraph};
+use crate::agents::events::{AstroReportLog, ReportSeverity};
+use crate::astro::binary::evolve_compact_binaries;
+use crate::astro::black_hole::BlackHole;
+use crate::astro::galaxy::Galaxy;
+use crate::astro::planet::Planet;
+use crate::astro::star::Star;
+use crate::breakpoints::BreakpointPlugin;
+use crate::experiment::comparison::{comparison_from_args, ComparisonPlugin};
+use crate::profiling::{chrome_trace_layer, trace_output_from_args};
+use crate::pru::cell::{DerivedFields, PruCell, PruDynamics};
+use crate::pru::cmb::{apply_cmb_pressure, CmbSettings};
+use crate::pru::correlation::{
+    collect_correlation, dispatch_correlation, CorrelationSettings, CorrelationTask,
+    TwoPointCorrelation,
+};
+use crate::pru::gravity::{GravityParams, GravityPlugin, GravitySet, SimulationEnergy};
+use crate::pru::gravity_relational::rebuild_relational_kernel;
+use crate::pru::growth::{grow_lattice, LatticeGrowthSettings};
+use crate::pru::igm::{label_igm_cells, Igm, IgmSettings};
+use crate::pru::initial::InitialConditions;
+use crate::pru::rewind::RewindPlugin;
+use crate::pru::universe::{
+    compute_complexity, compute_derived_fields, compute_roi_metrics, log_acceleration,
+    setup_universe, BoundaryCondition, FieldMetrics, FieldSettings, LatticeComplexity,
+    RegionOfInterest, RoiMetrics, UniverseSettings,
+};
+use crate::render::animation::{
+    advance_animation_clock, set_scale, AnimationClock, AnimationSettings, ScaleModifier,
 };
-use crate::pru::gravity_relational::initialize_relational_kernel;
-use crate::pru::universe::{compute_derived_fields, setup_universe, FieldMetrics, PruUniverse};
+use crate::render::camera::{OrbitCamera, OrbitCameraSettings};
+use crate::render::colors::seed_color;
+use crate::render::interpolation::{
+    interpolate_render_positions, record_tick_positions, restore_tick_positions,
+    RenderInterpolation,
+};
+use crate::render::lod::{LodState, MeshQuality};
 use crate::render::RenderPlugin;
-use crate::ui::controls::VisualModeSettings;
+use crate::shutdown::{final_report_path_from_args, ShutdownPlugin};
+use crate::spatial::{update_spatial_octree, SpatialOctree};
+use crate::ui::controls::{
+    OverlayRange, ScaleMode, SplitScreenMode, VisualMode, VisualModeSettings,
+};
+use crate::ui::toasts::{Toasts, TOAST_SECS};
 use crate::ui::UiPlugin;
-use crate::{agents::AgentsPlugin, astro::AstroPlugin};
+use crate::{agents::AgentsPlugin, astro::AstroPlugin, experiment::ExperimentPlugin};
+
+/// Scenario name used when none is given on the command line.
+pub const DEFAULT_SCENARIO_NAME: &str = "Default";
+
+/// Valid `SimConfig::physics_hz` range.
+pub const PHYSICS_HZ_RANGE: (f64, f64) = (1.0, 1000.0);
+
+/// Rate of the physics tick, independent of the render frame rate.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct SimConfig {
+    /// Physics ticks per simulated second; `SimulationState::dt` is its reciprocal.
+    pub physics_hz: f64,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self { physics_hz: 60.0 }
+    }
+}
+
+impl SimConfig {
+    /// Simulated seconds per tick.
+    pub fn dt(&self) -> f32 {
+        (1.0 / self
+            .physics_hz
+            .clamp(PHYSICS_HZ_RANGE.0, PHYSICS_HZ_RANGE.1)) as f32
+    }
+
+    /// Wall-clock time between `FixedUpdate` runs at `time_scale`.
+    pub fn tick_period(&self, time_scale: f32) -> Duration {
+        Duration::from_secs_f64(self.dt() as f64 / time_scale.max(f32::EPSILON) as f64)
+    }
+}
 
 /// Global simulation state controlling the PRU tick loop and time scaling.
-#[derive(Resource, Clone, Copy)]
+#[derive(Resource, Clone)]
 pub struct SimulationState {
     /// Whether the simulation is currently advancing.
     pub running: bool,
     /// Multiplier applied to real time to speed up or slow down ticks.
+    ///
+    /// Scales how often `FixedUpdate` runs; each tick still covers `dt`.
     pub time_scale: f32,
     /// Current discrete tick counter.
     pub tick: u64,
-    /// Fixed simulation delta time (seconds per tick).
+    /// Fixed simulation delta time (seconds per tick), kept at `1 / SimConfig::physics_hz`.
     pub dt: f32,
-    /// Accumulated (scaled) time used to trigger ticks.
-    pub accumulated_time: f32,
     /// Total simulated time in seconds.
     pub simulation_time: f32,
-    /// Ticks that should be simulated by downstream systems this frame.
+    /// Ticks queued for the next gravity step.
     pub pending_steps: u32,
+    /// Incremented whenever the world is reset so stale background results can be discarded.
+    pub generation: u64,
+    /// Most ticks a single frame may run; time beyond this budget is dropped.
+    pub max_steps_per_frame: u32,
+    /// Whether the last frame hit `max_steps_per_frame` and discarded time.
+    pub behind_real_time: bool,
+    /// Ticks the gravity step consumed this frame, over all of its `FixedUpdate` runs;
+    /// zero on frames that did not advance.
+    pub steps_this_frame: u32,
+    /// Label for this run, shown in the window title and HUD header.
+    pub scenario_name: String,
 }
 
 impl Default for SimulationState {
@@ -37,10 +123,14 @@ impl Default for SimulationState {
             running: true,
             time_scale: 1.0,
             tick: 0,
-            dt: 1.0 / 60.0,
-            accumulated_time: 0.0,
+            dt: SimConfig::default().dt(),
             simulation_time: 0.0,
             pending_steps: 0,
+            generation: 0,
+            max_steps_per_frame: 8,
+            behind_real_time: false,
+            steps_this_frame: 0,
+            scenario_name: DEFAULT_SCENARIO_NAME.to_string(),
         }
     }
 }
@@ -64,127 +154,614 @@ impl SimulationState {
     }
 
     /// Consume any pending steps, returning how many fixed ticks should be simulated.
+    ///
+    /// Called by the gravity step on every `FixedUpdate` run, which adds the
+    /// count to `steps_this_frame` for the `Update` systems that follow.
     pub fn take_pending_steps(&mut self) -> u32 {
         let steps = self.pending_steps;
         self.pending_steps = 0;
+        self.steps_this_frame += steps;
         steps
     }
+
+    /// Rewind the clock to tick 0 for a rebuilt world.
+    ///
+    /// Bumps `generation` so background work started on the old world is
+    /// discarded; running state, time scale, and the scenario name carry over.
+    pub fn restart(&mut self) {
+        self.tick = 0;
+        self.simulation_time = 0.0;
+        self.pending_steps = 0;
+        self.steps_this_frame = 0;
+        self.behind_real_time = false;
+        self.generation += 1;
+    }
+
+    /// Whether the simulation advanced this frame.
+    ///
+    /// Formation, analysis, and agent systems early-out otherwise, so a paused
+    /// universe stays frozen even while work queued before the pause is pending.
+    pub fn advanced_this_frame(&self) -> bool {
+        self.steps_this_frame > 0
+    }
 }
 
 /// Plugin responsible for initializing the PRU universe and advancing ticks.
+///
+/// Physics (the tick advance, the gravity step and its diagnostics, and the
+/// derived fields) runs in `FixedUpdate` at `SimConfig::physics_hz` scaled by
+/// `SimulationState::time_scale`; `Update` only reacts to the result and drives
+/// rendering, formation, and UI.
 pub struct PruSimulationPlugin;
 
 impl Plugin for PruSimulationPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, (setup_universe,))
+        app.init_resource::<SimConfig>()
+            .init_resource::<SimulationState>()
+            .init_resource::<UniverseSettings>()
+            .init_resource::<FieldMetrics>()
+            .init_resource::<FieldSettings>()
+            .init_resource::<LatticeComplexity>()
+            .init_resource::<RegionOfInterest>()
+            .init_resource::<RoiMetrics>()
+            .init_resource::<CorrelationSettings>()
+            .init_resource::<CorrelationTask>()
+            .init_resource::<TwoPointCorrelation>()
+            .init_resource::<IgmSettings>()
+            .init_resource::<CmbSettings>()
+            .init_resource::<LatticeGrowthSettings>()
+            .init_resource::<VisualModeSettings>()
+            .init_resource::<SplitScreenMode>()
+            .init_resource::<MaterialUpdateStats>()
+            .init_resource::<SpatialOctree>()
+            .init_resource::<AnimationSettings>()
+            .init_resource::<AnimationClock>()
+            .init_resource::<RenderInterpolation>()
+            .add_event::<ResetUniverse>()
+            .add_plugins(GravityPlugin)
+            .configure_sets(FixedUpdate, GravitySet::Step.after(advance_simulation_tick))
+            .add_systems(Startup, (setup_universe,))
             .add_systems(
-                Update,
-                initialize_relational_kernel
-                    .run_if(resource_exists::<PruUniverse>)
-                    .run_if(not(resource_exists::<
-                        crate::pru::gravity_relational::RelationalKernel,
-                    >)),
+                PreUpdate,
+                (
+                    configure_physics_rate,
+                    reset_universe,
+                    restore_tick_positions,
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    advance_simulation_tick,
+                    compute_derived_fields.after(GravitySet::Step),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                interpolate_render_positions.before(TransformSystem::TransformPropagate),
             )
             .add_systems(
                 Update,
                 (
-                    advance_simulation_time,
-                    simulate_gravity_step.after(advance_simulation_time),
-                    compute_derived_fields,
-                    compute_energy_metrics.after(simulate_gravity_step),
-                    update_cell_materials.after(compute_derived_fields),
+                    record_tick_positions.after(evolve_compact_binaries),
+                    advance_animation_clock,
+                    grow_lattice.before(update_spatial_octree),
+                    update_spatial_octree,
+                    apply_cmb_pressure.after(update_spatial_octree),
+                    compute_complexity,
+                    compute_roi_metrics,
+                    collect_correlation.before(dispatch_correlation),
+                    dispatch_correlation,
+                    label_igm_cells,
+                    update_cell_materials.after(label_igm_cells),
                     animate_cells.after(update_cell_materials),
                 ),
             );
     }
 }
 
-/// Drive the fixed-timestep tick counter using real time scaled by the simulation speed.
-fn advance_simulation_time(time: Res<Time>, mut sim_state: ResMut<SimulationState>) {
+/// Start a frame: match the fixed timestep to `SimConfig` and the time scale.
+///
+/// The virtual clock's `max_delta` caps a frame at `max_steps_per_frame`
+/// ticks; time past that budget is dropped so one slow frame does not make
+/// the next one try to catch up with even more work.
+fn configure_physics_rate(
+    config: Res<SimConfig>,
+    real_time: Res<Time<Real>>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut sim_state: ResMut<SimulationState>,
+) {
+    let dt = config.dt();
+    if sim_state.dt != dt {
+        sim_state.dt = dt;
+    }
+    let period = config.tick_period(sim_state.time_scale);
+    if fixed_time.timestep() != period {
+        fixed_time.set_timestep(period);
+    }
+    let budget = period * sim_state.max_steps_per_frame.max(1);
+    if virtual_time.max_delta() != budget {
+        virtual_time.set_max_delta(budget);
+    }
+    sim_state.behind_real_time = sim_state.running && real_time.delta() > budget;
+    sim_state.steps_this_frame = 0;
+}
+
+/// Queue one tick per `FixedUpdate` run while the simulation is running.
+fn advance_simulation_tick(mut sim_state: ResMut<SimulationState>) {
     if !sim_state.running {
         return;
     }
+    let dt = sim_state.dt;
+    sim_state.tick += 1;
+    sim_state.simulation_time += dt;
+    sim_state.pending_steps += 1;
+}
+
+/// Request to rebuild the universe at tick 0 without restarting the app.
+#[derive(Event, Clone, Copy, Debug, Default)]
+pub struct ResetUniverse {
+    /// Also return the camera to its default orbit.
+    pub reset_camera: bool,
+}
+
+/// Tear down the lattice and every structure, then build a fresh universe.
+///
+/// The new lattice comes from the current `UniverseSettings`. Tick-keyed
+/// caches (formation cadences, rate bins, structure counts) notice the clock
+/// going backwards or the new generation on their own; the metrics history,
+/// energy baseline, report log, and filament graphs are cleared here.
+pub fn reset_universe(world: &mut World) {
+    let Some(request) = world.resource_mut::<Events<ResetUniverse>>().drain().last() else {
+        return;
+    };
+    let _span = info_span!("reset_universe").entered();
+
+    let doomed: Vec<Entity> = world
+        .query_filtered::<Entity, Or<(
+            With<PruCell>,
+            With<Star>,
+            With<BlackHole>,
+            With<Galaxy>,
+            With<Planet>,
+        )>>()
+        .iter(world)
+        .collect();
+    for entity in doomed {
+        if let Some(entity) = world.get_entity_mut(entity) {
+            entity.despawn_recursive();
+        }
+    }
 
-    sim_state.accumulated_time += time.delta_seconds() * sim_state.time_scale;
-    while sim_state.accumulated_time >= sim_state.dt {
-        sim_state.accumulated_time -= sim_state.dt;
-        sim_state.tick += 1;
-        sim_state.simulation_time += sim_state.dt;
-        sim_state.pending_steps += 1;
+    world.resource_mut::<SimulationState>().restart();
+    *world.resource_mut::<FieldMetrics>() = FieldMetrics::default();
+    *world.resource_mut::<SimulationEnergy>() = SimulationEnergy::default();
+    *world.resource_mut::<TwoPointCorrelation>() = TwoPointCorrelation::default();
+    if let Some(mut reports) = world.get_resource_mut::<AstroReportLog>() {
+        reports.reports.clear();
+    }
+    if let Some(mut graph) = world.get_resource_mut::<GalaxyFilamentGraph>() {
+        graph.edges.clear();
+        graph.mean_edge_length = 0.0;
+        graph.last_build_tick = 0;
+    }
+    if let Some(mut filaments) = world.get_resource_mut::<DensityFilaments>() {
+        filaments.filaments.clear();
+        filaments.last_scan_tick = 0;
+    }
+    if request.reset_camera {
+        if let Some(mut camera) = world.get_resource_mut::<OrbitCameraSettings>() {
+            *camera = OrbitCameraSettings::default();
+        }
+    }
+
+    world.run_system_once(setup_universe);
+    world.run_system_once(rebuild_relational_kernel);
+    info!("Universe reset");
+    if let Some(mut toasts) = world.get_resource_mut::<Toasts>() {
+        toasts.push("Universe reset", ReportSeverity::Info, TOAST_SECS);
+    }
+}
+
+/// Cell scale in [`ScaleMode::Uniform`], where size carries no information.
+const UNIFORM_CELL_SCALE: f32 = 0.12;
+
+/// Scale a cell gets from the quantity `mode` maps to size, before the
+/// curvature and pulse terms; `None` for [`ScaleMode::Uniform`], which skips both.
+fn base_cell_scale(
+    mode: ScaleMode,
+    cell: &PruCell,
+    derived: &DerivedFields,
+    dynamics: &PruDynamics,
+) -> Option<f32> {
+    match mode {
+        ScaleMode::DerivedDensity => Some(0.1 + derived.local_density * 0.03),
+        ScaleMode::UaMassLock => Some((cell.ua_mass_lock as f32 * 0.08).clamp(0.02, 0.5)),
+        ScaleMode::Velocity => Some(0.06 + dynamics.velocity_at_integer_tick.length() * 0.4),
+        ScaleMode::Uniform => None,
     }
 }
 
 /// Animate cell visuals slightly using their lock values to hint at PRU activity.
+#[allow(clippy::type_complexity)]
 fn animate_cells(
-    time: Res<Time>,
-    mut query: Query<(&crate::pru::cell::PruCell, &DerivedFields, &mut Transform)>,
+    clock: Res<AnimationClock>,
+    animation: Res<AnimationSettings>,
+    modes: Res<VisualModeSettings>,
+    mut query: Query<(
+        &PruCell,
+        &DerivedFields,
+        &PruDynamics,
+        Option<&LodState>,
+        Option<&ScaleModifier>,
+        &mut Transform,
+    )>,
 ) {
-    let elapsed = time.elapsed_seconds();
-    for (cell, derived, mut transform) in query.iter_mut() {
-        let base_scale = 0.1 + derived.local_density * 0.03;
+    let amplitude = animation.amplitude(animation.cell_pulse);
+    for (cell, derived, dynamics, lod, modifier, mut transform) in query.iter_mut() {
+        // Sub-pixel cells keep their last scale until the camera moves closer.
+        if lod.is_some_and(|lod| !lod.animate) {
+            continue;
+        }
+        let Some(base_scale) = base_cell_scale(modes.scale_mode, cell, derived, dynamics) else {
+            set_scale(
+                &mut transform,
+                Vec3::splat(UNIFORM_CELL_SCALE * modifier.map_or(1.0, |m| m.0)),
+            );
+            continue;
+        };
         let curvature_amp = (derived.curvature_proxy.abs() * 0.2).min(0.08);
-        let pulse = (elapsed * 0.7 + cell.ub_geom_lock as f32).sin() * 0.025;
-        transform.scale = Vec3::splat((base_scale + curvature_amp + pulse).clamp(0.02, 0.5));
+        let pulse = (clock.time * 0.7 + cell.ub_geom_lock as f32).sin() * 0.025 * amplitude;
+        let scale = (base_scale + curvature_amp + pulse).clamp(0.02, 0.5);
+        set_scale(
+            &mut transform,
+            Vec3::splat(scale * modifier.map_or(1.0, |m| m.0)),
+        );
     }
 }
 
+/// Smallest per-channel color change (linear space) worth uploading to the GPU.
+const COLOR_QUANTIZATION_STEP: f32 = 1.0 / 64.0;
+
+/// Last colors written to a cell's material, used to skip redundant uploads.
+#[derive(Component, Clone, Copy)]
+pub struct AppliedCellColor {
+    pub base: LinearRgba,
+    pub emissive: LinearRgba,
+}
+
+impl AppliedCellColor {
+    pub fn new(base: Color) -> Self {
+        Self {
+            base: base.to_linear(),
+            emissive: LinearRgba::BLACK,
+        }
+    }
+
+    fn differs(&self, base: LinearRgba, emissive: LinearRgba) -> bool {
+        channel_delta(self.base, base) > COLOR_QUANTIZATION_STEP
+            || channel_delta(self.emissive, emissive) > COLOR_QUANTIZATION_STEP
+    }
+}
+
+/// Color a cell showed when the overlay last switched, the start of its cross-fade.
+#[derive(Component, Clone, Copy)]
+pub struct CellColorState {
+    pub old_color: Color,
+    pub old_emissive: Color,
+    /// Overlay the cell is fading toward.
+    pub current_mode: VisualMode,
+}
+
+impl CellColorState {
+    pub fn new(base: Color) -> Self {
+        Self {
+            old_color: base,
+            old_emissive: Color::BLACK,
+            current_mode: VisualMode::Seed,
+        }
+    }
+}
+
+fn channel_delta(a: LinearRgba, b: LinearRgba) -> f32 {
+    (a.red - b.red)
+        .abs()
+        .max((a.green - b.green).abs())
+        .max((a.blue - b.blue).abs())
+        .max((a.alpha - b.alpha).abs())
+}
+
+/// Recolor this cell on the next material pass, outside its round-robin slice.
+///
+/// Inserted by edits that change what a cell should show, such as the
+/// inspector's lock buttons; removed once the cell has been recolored.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct RefreshCellColor;
+
+/// Bookkeeping for the round-robin material refresh.
+#[derive(Resource, Default)]
+pub struct MaterialUpdateStats {
+    /// Frames processed so far; selects which slice of cells refreshes.
+    pub frame: u64,
+    /// Material assets mutated during the most recent frame.
+    pub uploads_last_frame: usize,
+}
+
 /// Adjust materials based on derived fields and visualization toggles.
-fn update_cell_materials(
-    modes: Res<VisualModeSettings>,
+///
+/// To limit `Assets<StandardMaterial>` change traffic, only one slice of the
+/// lattice (`overlay_update_slices`) is recomputed per frame in round-robin
+/// order, and a material is touched only when its color moved by more than
+/// [`COLOR_QUANTIZATION_STEP`]. Overlay switches refresh every cell at once
+/// and cross-fade from each cell's old color over `1 / transition_speed`
+/// seconds, refreshing every cell each frame until the fade settles. Cells
+/// marked [`RefreshCellColor`] are refreshed right away.
+///
+/// With [`SplitScreenMode`] on, a cell takes the left or right overlay by
+/// which side of the camera's vertical center plane it is drawn on. Cells
+/// that crossed the plane since their last refresh, as the camera or the
+/// cell moved, are refreshed out of turn.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(crate) fn update_cell_materials(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut modes: ResMut<VisualModeSettings>,
+    split: Res<SplitScreenMode>,
+    mut last_modes: Local<Option<(VisualMode, VisualMode)>>,
+    metrics: Res<FieldMetrics>,
+    energy: Res<SimulationEnergy>,
+    gravity: Res<GravityParams>,
+    mut stats: ResMut<MaterialUpdateStats>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    cameras: Query<&GlobalTransform, With<OrbitCamera>>,
     mut query: Query<(
+        Entity,
         &crate::pru::cell::PruCell,
         &DerivedFields,
+        &PruDynamics,
+        &Transform,
         &Handle<StandardMaterial>,
+        &mut AppliedCellColor,
+        &mut CellColorState,
+        Option<&Igm>,
+        Has<RefreshCellColor>,
     )>,
 ) {
-    for (cell, derived, material_handle) in query.iter_mut() {
-        if let Some(material) = materials.get_mut(material_handle) {
-            let (base_color, emissive) = if modes.show_density_coloring {
-                (density_color(derived.local_density), Color::BLACK)
-            } else if modes.show_curvature_coloring {
-                let intensity = (derived.curvature_proxy.abs() * 0.6).min(1.2);
+    let span = info_span!("cell_materials", uploads = Empty).entered();
+    let (left_mode, right_mode) = split.modes(modes.active_mode());
+    // The split runs through the camera along its view direction; without a camera every cell is "left".
+    let camera = cameras.get_single().ok();
+    let split_plane = camera
+        .as_ref()
+        .filter(|_| split.enabled)
+        .map(|transform| (transform.translation(), transform.right()));
+    let previous_blend = modes.color_blend_alpha;
+    let layout_changed = last_modes.is_some_and(|last| last != (left_mode, right_mode));
+    let blend = if layout_changed {
+        0.0
+    } else if modes.transition_speed > 0.0 {
+        (previous_blend + modes.transition_speed * time.delta_seconds()).min(1.0)
+    } else {
+        1.0
+    };
+    *last_modes = Some((left_mode, right_mode));
+    if blend != previous_blend {
+        // The fade is bookkeeping, not a settings change other systems should react to.
+        modes.bypass_change_detection().color_blend_alpha = blend;
+    }
+
+    let slices = modes.overlay_update_slices.max(1) as u64;
+    let active_slice = stats.frame % slices;
+    let refresh_all = modes.is_changed() || previous_blend < 1.0 || split.is_changed();
+    stats.frame += 1;
+    stats.uploads_last_frame = 0;
+
+    let density_bounds = modes.density_range.bounds(
+        (metrics.min_density, metrics.max_density),
+        metrics.density_percentiles,
+    );
+    let curvature_bounds = modes
+        .curvature_range
+        .symmetric_bounds(metrics.max_abs_curvature, metrics.abs_curvature_percentile);
+    let velocity_bounds = modes
+        .velocity_range
+        .bounds(metrics.capped_speed_extent(), metrics.speed_percentiles);
+    let acceleration_bounds = modes.acceleration_range.bounds(
+        (metrics.min_log_acceleration, metrics.max_log_acceleration),
+        metrics.log_acceleration_percentiles,
+    );
+    let ua_gradient_bounds = modes.ua_gradient_range.bounds(
+        (0.0, metrics.max_ua_gradient_magnitude),
+        metrics.ua_gradient_percentiles,
+    );
+
+    for (
+        entity,
+        cell,
+        derived,
+        dynamics,
+        transform,
+        material_handle,
+        mut applied,
+        mut color_state,
+        igm,
+        refresh,
+    ) in query.iter_mut()
+    {
+        let mode = match split_plane {
+            Some((origin, right)) if (transform.translation - origin).dot(*right) > 0.0 => {
+                right_mode
+            }
+            _ => left_mode,
+        };
+        let crossed_split = mode != color_state.current_mode;
+        if refresh {
+            commands.entity(entity).remove::<RefreshCellColor>();
+        } else if !refresh_all && !crossed_split && entity.index() as u64 % slices != active_slice {
+            continue;
+        }
+
+        // Start the fade from whatever the cell shows now, even mid-fade. A cell
+        // keeping its mode while the other half switches fades from itself.
+        if color_state.current_mode != mode || layout_changed {
+            *color_state = CellColorState {
+                old_color: Color::LinearRgba(applied.base),
+                old_emissive: Color::LinearRgba(applied.emissive),
+                current_mode: mode,
+            };
+        }
+
+        let (mut base_color, mut emissive) = match mode {
+            VisualMode::Density => {
+                let norm = OverlayRange::normalize(derived.local_density, density_bounds);
+                (density_color(norm), Color::BLACK)
+            }
+            VisualMode::Curvature => {
+                let norm = OverlayRange::normalize(derived.curvature_proxy, curvature_bounds);
+                let signed = norm * 2.0 - 1.0;
+                let color = curvature_color(signed);
                 (
-                    curvature_color(derived.curvature_proxy),
-                    Color::srgb(intensity * 0.4, intensity * 0.2, intensity * 0.9),
+                    color,
+                    Color::LinearRgba(color.to_linear() * (signed.abs() * 0.5)),
                 )
-            } else {
+            }
+            VisualMode::Velocity => {
+                let norm = OverlayRange::normalize(
+                    dynamics.velocity_at_integer_tick.length(),
+                    velocity_bounds,
+                );
+                (velocity_color(norm), Color::BLACK)
+            }
+            VisualMode::Acceleration => {
+                let norm = OverlayRange::normalize(
+                    log_acceleration(dynamics.acceleration),
+                    acceleration_bounds,
+                );
+                (acceleration_color(norm), Color::BLACK)
+            }
+            VisualMode::Binding => {
+                let bound =
+                    energy.is_bound(cell.position, dynamics.velocity_at_integer_tick, &gravity);
                 (
-                    seed_color_from_locks(cell.ua_mass_lock, cell.ub_geom_lock),
+                    if bound { BOUND_COLOR } else { UNBOUND_COLOR },
                     Color::BLACK,
                 )
-            };
+            }
+            VisualMode::UaGradient => {
+                let norm =
+                    OverlayRange::normalize(derived.ua_gradient.length(), ua_gradient_bounds);
+                (ua_gradient_color(norm), Color::BLACK)
+            }
+            VisualMode::Seed if igm.is_some() => (IGM_COLOR, Color::BLACK),
+            VisualMode::Seed => (
+                seed_color(
+                    cell.ua_mass_lock,
+                    cell.ub_geom_lock,
+                    cell.grid_coords,
+                    modes.seed_hue_offset,
+                ),
+                Color::BLACK,
+            ),
+        };
+        if blend < 1.0 {
+            base_color = lerp_color(color_state.old_color, base_color, blend);
+            emissive = lerp_color(color_state.old_emissive, emissive, blend);
+        }
 
+        let base = base_color.to_linear();
+        let emissive = emissive.to_linear();
+        if !applied.differs(base, emissive) {
+            continue;
+        }
+
+        if let Some(material) = materials.get_mut(material_handle) {
             material.base_color = base_color;
-            material.emissive = emissive.into();
+            material.emissive = emissive;
+            material.alpha_mode = if base.alpha < 1.0 {
+                AlphaMode::Blend
+            } else {
+                AlphaMode::Opaque
+            };
+            applied.base = base;
+            applied.emissive = emissive;
+            stats.uploads_last_frame += 1;
         }
     }
+    span.record("uploads", stats.uploads_last_frame);
 }
 
-fn density_color(density: f32) -> Color {
-    let norm = (density / 3.5).clamp(0.0, 1.0);
+/// Binding overlay colors: bodies below escape speed stay green, escapers turn red.
+const BOUND_COLOR: Color = Color::srgb(0.3, 0.85, 0.4);
+const UNBOUND_COLOR: Color = Color::srgb(0.95, 0.25, 0.2);
+
+/// Faint translucent blue for inter-galactic medium cells when no overlay is active.
+const IGM_COLOR: Color = Color::srgba(0.35, 0.5, 0.95, 0.15);
+
+/// Map a normalized (0..1) density onto the cold→warm ramp.
+pub(crate) fn density_color(norm: f32) -> Color {
+    let norm = norm.clamp(0.0, 1.0);
     let cold = Color::srgb(0.2, 0.4, 0.9);
     let warm = Color::srgb(1.0, 0.9, 0.2);
     lerp_color(cold, warm, norm)
 }
 
-fn curvature_color(curvature: f32) -> Color {
-    let norm = (curvature * 0.8).clamp(-1.0, 1.0);
-    if norm >= 0.0 {
-        Color::srgb(0.3 + 0.5 * norm, 0.25, 0.85)
+/// Curvature overlay ends: UB above its neighbors (positive) and below them (negative).
+pub(crate) const CURVATURE_ABOVE_COLOR: Color = Color::srgb(0.9, 0.25, 0.2);
+pub(crate) const CURVATURE_BELOW_COLOR: Color = Color::srgb(0.2, 0.4, 0.95);
+/// Curvature overlay midpoint, where UB matches its neighbors.
+const CURVATURE_NEUTRAL_COLOR: Color = Color::srgb(0.85, 0.85, 0.85);
+
+/// Map a normalized (-1..1) curvature onto a diverging ramp centered at zero.
+///
+/// Equal magnitudes of either sign sit equally far from the neutral midpoint.
+fn curvature_color(norm: f32) -> Color {
+    let norm = norm.clamp(-1.0, 1.0);
+    let end = if norm >= 0.0 {
+        CURVATURE_ABOVE_COLOR
     } else {
-        Color::srgb(0.15, 0.65 + norm * -0.5, 0.3 + -norm * 0.4)
+        CURVATURE_BELOW_COLOR
+    };
+    lerp_color(CURVATURE_NEUTRAL_COLOR, end, norm.abs())
+}
+
+/// Map a normalized (0..1) |∇UA| from dim slate through magenta to pale gold.
+fn ua_gradient_color(norm: f32) -> Color {
+    let norm = norm.clamp(0.0, 1.0);
+    let low = Color::srgb(0.15, 0.18, 0.3);
+    let mid = Color::srgb(0.85, 0.25, 0.7);
+    let high = Color::srgb(1.0, 0.9, 0.55);
+    if norm < 0.5 {
+        lerp_color(low, mid, norm * 2.0)
+    } else {
+        lerp_color(mid, high, (norm - 0.5) * 2.0)
     }
 }
 
-fn seed_color_from_locks(ua: f64, ub: f64) -> Color {
-    let mass = (ua as f32).clamp(0.0, 2.0);
-    let geom = ((ub as f32) + 1.0) * 0.5; // map -1..1 to 0..1
+/// Map a normalized (0..1) speed onto a dark-violet → teal → yellow ramp.
+fn velocity_color(norm: f32) -> Color {
+    let norm = norm.clamp(0.0, 1.0);
+    let slow = Color::srgb(0.25, 0.05, 0.35);
+    let mid = Color::srgb(0.1, 0.6, 0.6);
+    let fast = Color::srgb(1.0, 0.95, 0.2);
+    if norm < 0.5 {
+        lerp_color(slow, mid, norm * 2.0)
+    } else {
+        lerp_color(mid, fast, norm * 2.0 - 1.0)
+    }
+}
 
-    let r = 0.2 + 0.6 * geom;
-    let g = 0.2 + 0.6 * (1.0 - geom);
-    let b = 0.4 + 0.5 * (1.0 - mass * 0.5);
-    Color::srgb(r.min(1.0), g.min(1.0), b.min(1.0))
+/// Map a normalized (0..1) `log10 |a|` from deep blue through red to near white.
+fn acceleration_color(norm: f32) -> Color {
+    let norm = norm.clamp(0.0, 1.0);
+    let weak = Color::srgb(0.05, 0.08, 0.3);
+    let mid = Color::srgb(0.85, 0.2, 0.15);
+    let strong = Color::srgb(1.0, 0.95, 0.85);
+    if norm < 0.5 {
+        lerp_color(weak, mid, norm * 2.0)
+    } else {
+        lerp_color(mid, strong, norm * 2.0 - 1.0)
+    }
 }
 
 fn lerp_color(a: Color, b: Color, t: f32) -> Color {
@@ -195,32 +772,474 @@ fn lerp_color(a: Color, b: Color, t: f32) -> Color {
     Color::LinearRgba(mixed)
 }
 
+/// Value of `--flag <value>` or `--flag=<value>` in the process arguments, parsed as `T`.
+pub(crate) fn arg_value<T: FromStr>(flag: &str) -> Option<T>
+where
+    T::Err: Display,
+{
+    arg_value_in(std::env::args().skip(1), flag)
+}
+
+/// [`arg_value`] over an explicit argument list.
+///
+/// The first occurrence of `flag` wins; a value that fails to parse is
+/// logged and ignored rather than falling through to a later one.
+pub(crate) fn arg_value_in<T: FromStr>(
+    args: impl IntoIterator<Item = String>,
+    flag: &str,
+) -> Option<T>
+where
+    T::Err: Display,
+{
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let value = if arg == flag {
+            args.next()
+        } else {
+            arg.strip_prefix(flag)
+                .and_then(|rest| rest.strip_prefix('='))
+                .map(str::to_string)
+        };
+        if let Some(value) = value {
+            return match value.parse() {
+                Ok(parsed) => Some(parsed),
+                Err(err) => {
+                    warn!("Ignoring {flag} {value}: {err}");
+                    None
+                }
+            };
+        }
+    }
+    None
+}
+
 /// Build and run the Bevy application with simulation, rendering, and UI layers.
 pub fn run_app() {
-    App::new()
-        .insert_resource(SimulationState::default())
-        .init_resource::<FieldMetrics>()
-        .init_resource::<GravityParams>()
-        .init_resource::<SimulationEnergy>()
-        .init_resource::<VisualModeSettings>()
-        .insert_resource(ClearColor(Color::srgb(0.02, 0.02, 0.05)))
+    let mut app = App::new();
+    // Must be in place before `LogPlugin` builds, which is when the trace layer is installed.
+    if let Some(output) = trace_output_from_args() {
+        app.insert_resource(output);
+    }
+
+    app.insert_resource(ClearColor(Color::srgb(0.02, 0.02, 0.05)))
         .insert_resource(AmbientLight {
             color: Color::srgb(0.4, 0.45, 0.5),
             brightness: 0.35,
         })
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "PRU Universe Bevy Simulation".to_string(),
-                ..Default::default()
-            }),
-            ..Default::default()
-        }))
+        .add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        title: format!("PRU Universe — {DEFAULT_SCENARIO_NAME} [tick=0]"),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+                .set(LogPlugin {
+                    custom_layer: chrome_trace_layer,
+                    ..Default::default()
+                }),
+        )
         .add_plugins((
             RenderPlugin,
             UiPlugin,
             PruSimulationPlugin,
             AstroPlugin,
             AgentsPlugin,
-        ))
-        .run();
+            ExperimentPlugin,
+            BreakpointPlugin,
+            RewindPlugin,
+            ShutdownPlugin,
+        ));
+
+    if let Some(path) = final_report_path_from_args() {
+        app.insert_resource(path);
+    }
+    if let Some(name) = arg_value::<String>("--scenario-name") {
+        app.world_mut()
+            .resource_mut::<SimulationState>()
+            .scenario_name = name;
+    }
+    // `--physics-hz <rate>`, clamped to `PHYSICS_HZ_RANGE`.
+    if let Some(physics_hz) = arg_value::<f64>("--physics-hz").filter(|hz| hz.is_finite()) {
+        app.world_mut().resource_mut::<SimConfig>().physics_hz =
+            physics_hz.clamp(PHYSICS_HZ_RANGE.0, PHYSICS_HZ_RANGE.1);
+    }
+    // `--macro-block <n>`: turn macro mode on with `n`³-cell super-cells.
+    if let Some(block_size) = arg_value::<u32>("--macro-block") {
+        app.world_mut()
+            .resource_mut::<GravityParams>()
+            .set_macro_block_size(Some(block_size));
+    }
+    if let Some(initial_conditions) = arg_value::<InitialConditions>("--initial-conditions") {
+        app.world_mut()
+            .resource_mut::<UniverseSettings>()
+            .initial_conditions = initial_conditions;
+    }
+    if let Some(boundary) = arg_value::<BoundaryCondition>("--boundary") {
+        app.world_mut().resource_mut::<UniverseSettings>().boundary = boundary;
+    }
+    // `--grow-lattice`: let the lattice grow past crowded boundary faces.
+    if std::env::args().skip(1).any(|arg| arg == "--grow-lattice") {
+        app.world_mut()
+            .resource_mut::<LatticeGrowthSettings>()
+            .enabled = true;
+    }
+    if let Some(quality) = arg_value::<MeshQuality>("--mesh-quality") {
+        info!("Mesh quality: {}", quality.label());
+        app.insert_resource(quality);
+    }
+
+    if let Some(spec) = comparison_from_args() {
+        app.add_plugins(ComparisonPlugin { spec });
+    }
+
+    #[cfg(feature = "metrics-endpoint")]
+    app.add_plugins(crate::metrics::MetricsEndpointPlugin);
+
+    app.run();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astro::formation::{FormationPreset, FormationSettings};
+    use crate::experiment::comparison::ComparisonSpec;
+    use crate::experiment::sweep::SweepParameter;
+    use crate::pru::universe::AxisBoundary;
+    use crate::ui::controls::{
+        update_formation_controls, FormationPresetButton, OverlayRange, OverlayScaling,
+    };
+    use bevy::color::color_difference::EuclideanDistance;
+    use bevy::ecs::event::ManualEventReader;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn adaptive_density_range_spans_the_cold_to_warm_ramp() {
+        let cold = Color::srgb(0.2, 0.4, 0.9).to_linear();
+        let warm = Color::srgb(1.0, 0.9, 0.2).to_linear();
+        let range = OverlayRange {
+            scaling: OverlayScaling::Adaptive,
+            ..OverlayRange::fixed(0.0, 3.5)
+        };
+        let bounds = range.bounds((0.4, 2.6), (0.6, 2.1));
+        assert_eq!(bounds, (0.4, 2.6));
+        let color = |density| density_color(OverlayRange::normalize(density, bounds)).to_linear();
+        assert!(color(0.4).distance(&cold) < 1e-5);
+        assert!(color(2.6).distance(&warm) < 1e-5);
+        // A fixed range ignores the field extent, so the same max lands mid-ramp.
+        let fixed = OverlayRange::normalize(
+            2.6,
+            OverlayRange::fixed(0.0, 3.5).bounds((0.4, 2.6), (0.6, 2.1)),
+        );
+        assert!(fixed > 0.7 && fixed < 0.8);
+    }
+
+    #[test]
+    fn scale_modes_map_their_quantity_to_cell_size() {
+        let cell = PruCell::new(Vec3::ZERO, UVec3::ZERO, 2.0, 0.0);
+        let derived = DerivedFields {
+            local_density: 5.0,
+            ..Default::default()
+        };
+        let still = PruDynamics::default();
+        let moving = PruDynamics {
+            velocity_at_integer_tick: Vec3::new(0.3, 0.4, 0.0),
+            ..Default::default()
+        };
+        let scale = |mode, dynamics| base_cell_scale(mode, &cell, &derived, dynamics);
+
+        assert_eq!(scale(ScaleMode::DerivedDensity, &still), Some(0.25));
+        assert_eq!(scale(ScaleMode::UaMassLock, &still), Some(0.16));
+        assert_eq!(scale(ScaleMode::Velocity, &still), Some(0.06));
+        let fast = scale(ScaleMode::Velocity, &moving).unwrap();
+        assert!((fast - 0.26).abs() < 1e-6);
+        assert_eq!(scale(ScaleMode::Uniform, &moving), None);
+        // The lock mapping saturates instead of growing without bound.
+        let heavy = PruCell::new(Vec3::ZERO, UVec3::ZERO, 100.0, 0.0);
+        assert_eq!(
+            base_cell_scale(ScaleMode::UaMassLock, &heavy, &derived, &still),
+            Some(0.5)
+        );
+    }
+
+    #[test]
+    fn velocity_overlay_runs_from_stationary_to_fast_cells() {
+        let slow = Color::srgb(0.25, 0.05, 0.35).to_linear();
+        let fast = Color::srgb(1.0, 0.95, 0.2).to_linear();
+        let mut metrics = FieldMetrics::default();
+        metrics.min_speed = 0.0;
+        metrics.max_speed = 50.0;
+        metrics.speed_percentiles = (0.05, 1.0);
+        let range = OverlayRange {
+            scaling: OverlayScaling::Adaptive,
+            ..OverlayRange::fixed(0.0, 1.0)
+        };
+        // One escaper doesn't stretch the range past twice the 95th percentile.
+        let bounds = range.bounds(metrics.capped_speed_extent(), metrics.speed_percentiles);
+        assert_eq!(bounds, (0.0, 2.0));
+        let color = |speed| velocity_color(OverlayRange::normalize(speed, bounds)).to_linear();
+
+        assert!(color(0.0).distance(&slow) < 1e-5);
+        assert!(color(1.8).distance(&fast) < color(1.8).distance(&slow));
+        assert!(color(50.0).distance(&fast) < 1e-5);
+    }
+
+    #[test]
+    fn a_long_frame_runs_at_most_max_steps_per_frame_ticks() {
+        use bevy::time::TimeUpdateStrategy;
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<SimConfig>()
+            .insert_resource(SimulationState {
+                running: true,
+                ..Default::default()
+            })
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                16,
+            )))
+            .add_systems(PreUpdate, configure_physics_rate)
+            .add_systems(FixedUpdate, advance_simulation_tick);
+        app.update();
+        app.update();
+        let state = |app: &App| app.world().resource::<SimulationState>().clone();
+        let max_steps = state(&app).max_steps_per_frame as u64;
+
+        // A five second stall would be 300 ticks at 60 Hz.
+        let before = state(&app).tick;
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs(5)));
+        app.update();
+        let stalled = state(&app);
+        assert!(stalled.tick > before);
+        assert!(
+            stalled.tick - before <= max_steps,
+            "ran {} ticks",
+            stalled.tick - before
+        );
+        assert!(stalled.behind_real_time);
+
+        // The dropped time is not made up for on the next frame.
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            16,
+        )));
+        app.update();
+        let recovered = state(&app);
+        assert!(recovered.tick - stalled.tick <= 2);
+        assert!(!recovered.behind_real_time);
+    }
+
+    /// Headless app running only [`update_cell_materials`] over `count` density-colored cells.
+    fn material_test_app(count: u32) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<StandardMaterial>()
+            .insert_resource(VisualModeSettings {
+                show_density_coloring: true,
+                density_range: OverlayRange::fixed(0.0, 1.0),
+                ..Default::default()
+            })
+            .insert_resource(SplitScreenMode::default())
+            .init_resource::<FieldMetrics>()
+            .init_resource::<SimulationEnergy>()
+            .init_resource::<GravityParams>()
+            .init_resource::<MaterialUpdateStats>()
+            .add_systems(Update, update_cell_materials);
+        for i in 0..count {
+            let material = app
+                .world_mut()
+                .resource_mut::<Assets<StandardMaterial>>()
+                .add(StandardMaterial::default());
+            app.world_mut().spawn((
+                PruCell::new(Vec3::ZERO, UVec3::new(i, 0, 0), 1.0, 0.0),
+                DerivedFields {
+                    local_density: i as f32 / count as f32,
+                    ..Default::default()
+                },
+                PruDynamics::default(),
+                Transform::default(),
+                material,
+                AppliedCellColor::new(Color::WHITE),
+                CellColorState::new(Color::WHITE),
+            ));
+        }
+        app
+    }
+
+    /// Run one frame and count the material assets it modified.
+    fn material_mutations(
+        app: &mut App,
+        reader: &mut ManualEventReader<AssetEvent<StandardMaterial>>,
+    ) -> usize {
+        app.update();
+        let events = app
+            .world()
+            .resource::<Events<AssetEvent<StandardMaterial>>>();
+        reader
+            .read(events)
+            .filter(|event| matches!(event, AssetEvent::Modified { .. }))
+            .count()
+    }
+
+    #[test]
+    fn static_scenes_stop_mutating_materials() {
+        const CELLS: u32 = 1000;
+        const FRAMES: usize = 20;
+        let mut app = material_test_app(CELLS);
+        let mut reader = ManualEventReader::default();
+        // The first pass colors every cell; a static scene touches none after that.
+        assert_eq!(material_mutations(&mut app, &mut reader), CELLS as usize);
+        let settled: usize = (0..FRAMES)
+            .map(|_| material_mutations(&mut app, &mut reader))
+            .sum();
+        // Recoloring every cell every frame would be CELLS * FRAMES mutations.
+        assert!(
+            settled * 10 <= CELLS as usize * FRAMES,
+            "{settled} mutations over {FRAMES} static frames"
+        );
+        assert_eq!(settled, 0);
+
+        // A sub-quantization wobble in the field is not worth an upload either.
+        let mut cells = app.world_mut().query::<&mut DerivedFields>();
+        for mut derived in cells.iter_mut(app.world_mut()) {
+            derived.local_density += 0.001;
+        }
+        let wobble: usize = (0..FRAMES)
+            .map(|_| material_mutations(&mut app, &mut reader))
+            .sum();
+        assert_eq!(wobble, 0);
+
+        // A real change is spread over the round-robin slices.
+        let slices = VisualModeSettings::default().overlay_update_slices as usize;
+        for mut derived in cells.iter_mut(app.world_mut()) {
+            derived.local_density = 1.0 - derived.local_density;
+        }
+        let per_frame: Vec<usize> = (0..slices)
+            .map(|_| material_mutations(&mut app, &mut reader))
+            .collect();
+        assert!(per_frame
+            .iter()
+            .all(|count| *count <= CELLS as usize / slices + 1));
+        assert!(per_frame.iter().sum::<usize>() > CELLS as usize * 9 / 10);
+    }
+
+    #[test]
+    fn scenario_name_comes_from_the_flag_or_the_picked_preset() {
+        let args = |list: &[&str]| list.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let scenario_name = |list: &[&str]| arg_value_in::<String>(args(list), "--scenario-name");
+        assert_eq!(
+            scenario_name(&["--physics-hz", "90", "--scenario-name", "Run A"]),
+            Some("Run A".to_string())
+        );
+        assert_eq!(
+            scenario_name(&["--scenario-name=Run B"]),
+            Some("Run B".to_string())
+        );
+        assert_eq!(scenario_name(&["--scenario-name"]), None);
+        assert_eq!(scenario_name(&["--grow-lattice"]), None);
+
+        let mut world = World::new();
+        world.init_resource::<SimulationState>();
+        world.init_resource::<FormationSettings>();
+        world.init_resource::<FieldSettings>();
+        world.spawn((Interaction::Pressed, FormationPresetButton));
+        assert_eq!(
+            world.resource::<SimulationState>().scenario_name,
+            DEFAULT_SCENARIO_NAME
+        );
+        world.run_system_once(update_formation_controls);
+        let preset = world.resource::<FormationSettings>().preset;
+        assert_eq!(preset, FormationPreset::Sparse);
+        assert_eq!(
+            world.resource::<SimulationState>().scenario_name,
+            preset.scenario_name()
+        );
+    }
+
+    #[test]
+    fn flag_values_parse_as_their_type_in_either_form() {
+        let args = |list: &[&str]| list.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let line = args(&[
+            "--physics-hz",
+            "90",
+            "--macro-block=3",
+            "--initial-conditions",
+            "spiral-disk",
+            "--boundary=torus-2d-reflect",
+            "--grow-lattice",
+            "--mesh-quality",
+            "low",
+        ]);
+        assert_eq!(
+            arg_value_in::<f64>(line.clone(), "--physics-hz"),
+            Some(90.0)
+        );
+        assert_eq!(arg_value_in::<u32>(line.clone(), "--macro-block"), Some(3));
+        assert_eq!(
+            arg_value_in::<InitialConditions>(line.clone(), "--initial-conditions"),
+            Some(InitialConditions::spiral_disk(
+                UniverseSettings::default().spacing
+            ))
+        );
+        assert_eq!(
+            arg_value_in::<BoundaryCondition>(line.clone(), "--boundary"),
+            Some(BoundaryCondition::Torus2D {
+                y: AxisBoundary::Reflecting
+            })
+        );
+        assert_eq!(
+            arg_value_in::<MeshQuality>(line.clone(), "--mesh-quality"),
+            Some(MeshQuality::Low)
+        );
+        let compare =
+            arg_value_in::<ComparisonSpec>(args(&["--compare=star_density=500"]), "--compare")
+                .expect("a valid comparison spec parses");
+        assert_eq!(compare.parameter, SweepParameter::StarDensityThreshold);
+        assert_eq!(
+            compare.value,
+            SweepParameter::StarDensityThreshold.limits().1
+        );
+        assert!(
+            arg_value_in::<ComparisonSpec>(args(&["--compare", "mass=2"]), "--compare").is_none()
+        );
+        // A flag is matched whole, not as a prefix of a longer one.
+        assert_eq!(arg_value_in::<u32>(line.clone(), "--macro"), None);
+
+        // Values that do not parse are dropped, and the first occurrence wins.
+        assert_eq!(
+            arg_value_in::<u32>(args(&["--macro-block", "four"]), "--macro-block"),
+            None
+        );
+        assert_eq!(
+            arg_value_in::<MeshQuality>(
+                args(&["--mesh-quality=ultra", "--mesh-quality=high"]),
+                "--mesh-quality"
+            ),
+            None
+        );
+        assert_eq!(
+            arg_value_in::<u32>(args(&["--macro-block"]), "--macro-block"),
+            None
+        );
+    }
+
+    #[test]
+    fn stronger_accelerations_land_higher_on_the_acceleration_colormap() {
+        let weak = Color::srgb(0.05, 0.08, 0.3).to_linear();
+        let strong = Color::srgb(1.0, 0.95, 0.85).to_linear();
+        // Typical pulls around 1e-2, with one body pinned near the clamp.
+        let bounds = VisualModeSettings::default()
+            .acceleration_range
+            .bounds((-6.0, 2.7), (-3.0, -1.0));
+        let norm =
+            |magnitude: f32| OverlayRange::normalize(log_acceleration(Vec3::Y * magnitude), bounds);
+        let color = |magnitude| acceleration_color(norm(magnitude)).to_linear();
+
+        assert!(norm(0.05) > norm(0.01));
+        assert!(color(0.05).distance(&strong) < color(0.01).distance(&strong));
+        // Log scaling keeps typical bodies spread out instead of crushed by the outlier.
+        assert!(norm(0.01) > 0.4 && norm(0.01) < 0.6);
+        assert!(color(500.0).distance(&strong) < 1e-5);
+        assert!(color(0.0).distance(&weak) < 1e-5);
+    }
 }
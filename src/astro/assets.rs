@@ -0,0 +1,63 @@
+//! Meshes and materials shared by every astro structure.
+//!
+//! Black holes, planets, and galaxy halos all look alike, so they share one material
+//! each, and every structure shares one mesh per kind. Stars keep their own
+//! material because color and glow follow each star's temperature,
+//! metallicity, and luminosity. Those handles are strong and held only by the
+//! star entity, so Bevy frees the material as soon as the star despawns;
+//! despawn paths need no asset bookkeeping of their own.
+
+use bevy::prelude::*;
+
+use crate::render::lod::MeshQuality;
+
+/// Radius of the shared star sphere before per-star scaling.
+pub const STAR_MESH_RADIUS: f32 = 0.3;
+/// Radius of the shared black hole sphere before per-hole scaling.
+pub const BLACK_HOLE_MESH_RADIUS: f32 = 0.4;
+
+/// Handles created once at startup and cloned onto each spawned structure.
+#[derive(Resource)]
+pub struct AstroAssets {
+    pub star_mesh: Handle<Mesh>,
+    pub black_hole_mesh: Handle<Mesh>,
+    pub black_hole_material: Handle<StandardMaterial>,
+    pub planet_mesh: Handle<Mesh>,
+    pub planet_material: Handle<StandardMaterial>,
+    pub halo_mesh: Handle<Mesh>,
+    pub halo_material: Handle<StandardMaterial>,
+}
+
+pub fn setup_astro_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mesh_quality: Option<Res<MeshQuality>>,
+) {
+    let quality = mesh_quality.as_deref().copied().unwrap_or_default();
+    let halo_color = Color::srgb(0.6, 0.8, 1.0);
+    commands.insert_resource(AstroAssets {
+        star_mesh: meshes.add(quality.sphere(STAR_MESH_RADIUS)),
+        black_hole_mesh: meshes.add(quality.sphere(BLACK_HOLE_MESH_RADIUS)),
+        black_hole_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.02, 0.02, 0.05),
+            perceptual_roughness: 0.9,
+            metallic: 0.7,
+            ..Default::default()
+        }),
+        planet_mesh: meshes.add(quality.sphere(1.0)),
+        planet_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.35, 0.55, 0.75),
+            perceptual_roughness: 0.8,
+            ..Default::default()
+        }),
+        halo_mesh: meshes.add(quality.sphere(1.0)),
+        halo_material: materials.add(StandardMaterial {
+            base_color: halo_color.with_alpha(0.1),
+            emissive: halo_color.to_linear() * 0.05,
+            alpha_mode: AlphaMode::Add,
+            unlit: true,
+            ..Default::default()
+        }),
+    });
+}
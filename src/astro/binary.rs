@@ -0,0 +1,292 @@
+//! Compact binaries: black hole pairs that inspiral by gravitational radiation and merge.
+//!
+//! Two unpaired black holes that come within [`BinarySettings::capture_radius`]
+//! lattice spacings of each other are bound into a binary. From then on they
+//! leave the gravity step (both are [`Pinned`]) and circle their barycenter at
+//! the Keplerian rate while the Peters (1964) formula shrinks the separation:
+//!
+//! `da/dt = -64/5 G³ m1 m2 (m1 + m2) / (c⁵ a³ (1 - e²)^{7/2})`
+//!
+//! Once the separation drops below the sum of the two Schwarzschild radii the
+//! pair is replaced by a single remnant black hole and a
+//! [`CompactBinaryMerger`] event is sent. Orbits are circular, so the
+//! eccentricity stays at its capture value.
+
+use bevy::prelude::*;
+
+use crate::app::SimulationState;
+use crate::pru::gravity::{GravityParams, Pinned};
+use crate::pru::universe::PruUniverse;
+use crate::spatial::SpatialOctree;
+use crate::tracked::TrackedEntity;
+
+use super::assets::AstroAssets;
+use super::black_hole::BlackHole;
+use super::formation::spawn_black_hole;
+
+/// Tunables for binary capture and inspiral.
+#[derive(Resource, Clone, Debug)]
+pub struct BinarySettings {
+    pub enabled: bool,
+    /// Distance, in lattice spacings, within which two black holes become bound.
+    pub capture_radius: f32,
+    /// Speed of light in simulation units.
+    ///
+    /// Accretion treats `c` as 1, but with that value every captured pair
+    /// would merge within a tick; a larger value stretches inspirals over a
+    /// few hundred ticks so they can be watched.
+    pub speed_of_light: f32,
+    /// Eccentricity newly captured binaries are given.
+    pub capture_eccentricity: f32,
+    /// Fraction of the pair's combined mass radiated away in the merger.
+    pub radiated_fraction: f32,
+    /// Spin of a merger remnant.
+    pub remnant_spin: f32,
+}
+
+impl Default for BinarySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            capture_radius: 1.5,
+            speed_of_light: 35.0,
+            capture_eccentricity: 0.0,
+            radiated_fraction: 0.05,
+            remnant_spin: 0.7,
+        }
+    }
+}
+
+/// Orbit of a bound black hole pair, stored on the heavier (primary) partner.
+#[derive(Component, Clone, Debug)]
+pub struct CompactBinary {
+    pub secondary: TrackedEntity,
+    /// Current separation of the two holes.
+    pub separation: f32,
+    pub eccentricity: f32,
+    /// Fixed center of mass the pair orbits.
+    pub barycenter: Vec3,
+    /// Orthonormal pair spanning the orbital plane.
+    pub plane: (Vec3, Vec3),
+    /// Orbital phase in radians.
+    pub phase: f32,
+    /// Tick at which the pair was captured.
+    pub formed_tick: u64,
+}
+
+impl CompactBinary {
+    /// Time until the separation reaches zero at the current masses.
+    pub fn inspiral_time(&self, m1: f32, m2: f32, g: f32, c: f32) -> f32 {
+        let beta = peters_beta(m1, m2, g, c, self.eccentricity);
+        if beta > 0.0 {
+            self.separation.powi(4) / (4.0 * beta)
+        } else {
+            f32::INFINITY
+        }
+    }
+}
+
+/// Marker on the lighter partner of a [`CompactBinary`].
+#[derive(Component, Clone, Copy, Debug)]
+pub struct BinaryCompanion {
+    pub primary: Entity,
+}
+
+/// Sent when a compact binary coalesces into a single black hole.
+#[derive(Event, Clone, Debug)]
+pub struct CompactBinaryMerger {
+    pub tick: u64,
+    pub primary_mass: f32,
+    pub secondary_mass: f32,
+    pub remnant_mass: f32,
+    pub position: Vec3,
+    /// Ticks from capture to merger.
+    pub inspiral_ticks: u64,
+    /// Rest-mass energy carried off by gravitational waves.
+    pub radiated_energy: f32,
+}
+
+/// `β` in `da/dt = -β / a³`, with the eccentricity enhancement folded in.
+pub fn peters_beta(m1: f32, m2: f32, g: f32, c: f32, eccentricity: f32) -> f32 {
+    let e2 = eccentricity.clamp(0.0, 0.99).powi(2);
+    64.0 / 5.0 * g.powi(3) * m1 * m2 * (m1 + m2) / (c.powi(5) * (1.0 - e2).powf(3.5))
+}
+
+/// Schwarzschild radius `2 G m / c²`.
+pub fn schwarzschild_radius(mass: f32, g: f32, c: f32) -> f32 {
+    2.0 * g * mass / (c * c)
+}
+
+/// Separation after `elapsed` time of Peters decay, clamped at zero.
+///
+/// Integrates `da/dt = -β / a³` exactly: `a⁴` falls linearly at `4β`.
+pub fn decayed_separation(separation: f32, beta: f32, elapsed: f32) -> f32 {
+    (separation.powi(4) - 4.0 * beta * elapsed)
+        .max(0.0)
+        .powf(0.25)
+}
+
+/// Bind unpaired black holes that come within the capture radius of each other.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn capture_compact_binaries(
+    mut commands: Commands,
+    sim_state: Res<SimulationState>,
+    settings: Res<BinarySettings>,
+    universe: Res<PruUniverse>,
+    octree: Res<SpatialOctree>,
+    holes: Query<
+        (Entity, &BlackHole, &Transform),
+        (Without<CompactBinary>, Without<BinaryCompanion>),
+    >,
+    companions: Query<(Entity, &BinaryCompanion)>,
+    primaries: Query<(), With<CompactBinary>>,
+) {
+    if !sim_state.advanced_this_frame() {
+        return;
+    }
+    // A companion whose primary vanished rejoins the gravity step.
+    for (entity, companion) in companions.iter() {
+        if !primaries.contains(companion.primary) {
+            commands
+                .entity(entity)
+                .remove::<(BinaryCompanion, Pinned)>();
+        }
+    }
+    if !settings.enabled {
+        return;
+    }
+    let capture_radius = settings.capture_radius * universe.spacing;
+    let mut bound: Vec<Entity> = Vec::new();
+    for (entity, bh, transform) in holes.iter() {
+        if bound.contains(&entity) {
+            continue;
+        }
+        let position = transform.translation;
+        let partner = octree
+            .query_radius(position, capture_radius)
+            .into_iter()
+            .filter(|other| *other != entity && !bound.contains(other))
+            .filter_map(|other| holes.get(other).ok())
+            .min_by(|a, b| {
+                a.2.translation
+                    .distance_squared(position)
+                    .total_cmp(&b.2.translation.distance_squared(position))
+            });
+        let Some((other, other_bh, other_transform)) = partner else {
+            continue;
+        };
+
+        let ((primary, m1, p1), (secondary, m2, p2)) = if bh.mass >= other_bh.mass {
+            (
+                (entity, bh.mass, position),
+                (other, other_bh.mass, other_transform.translation),
+            )
+        } else {
+            (
+                (other, other_bh.mass, other_transform.translation),
+                (entity, bh.mass, position),
+            )
+        };
+        let offset = p2 - p1;
+        let separation = offset.length();
+        let Some(direction) = offset.try_normalize() else {
+            continue;
+        };
+        let total = (m1 + m2).max(f32::EPSILON);
+        let (normal, _) = direction.any_orthonormal_pair();
+        let mut tracked = TrackedEntity::default();
+        tracked.set(secondary);
+
+        commands.entity(primary).insert((
+            CompactBinary {
+                secondary: tracked,
+                separation,
+                eccentricity: settings.capture_eccentricity,
+                barycenter: (p1 * m1 + p2 * m2) / total,
+                plane: (direction, normal),
+                phase: 0.0,
+                formed_tick: sim_state.tick,
+            },
+            Pinned,
+        ));
+        commands
+            .entity(secondary)
+            .insert((BinaryCompanion { primary }, Pinned));
+        bound.extend([primary, secondary]);
+        info!(
+            "Compact binary formed: m1 {m1:.2}, m2 {m2:.2}, separation {separation:.3} at tick {}",
+            sim_state.tick
+        );
+    }
+}
+
+/// Advance each binary's orbit and Peters decay, merging pairs that touch.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn evolve_compact_binaries(
+    mut commands: Commands,
+    sim_state: Res<SimulationState>,
+    settings: Res<BinarySettings>,
+    gravity: Res<GravityParams>,
+    assets: Res<AstroAssets>,
+    mut mergers: EventWriter<CompactBinaryMerger>,
+    mut primaries: Query<(Entity, &mut CompactBinary, &BlackHole, &mut Transform)>,
+    mut companions: Query<
+        (Entity, &BlackHole, &mut Transform),
+        (With<BinaryCompanion>, Without<CompactBinary>),
+    >,
+) {
+    if !sim_state.advanced_this_frame() {
+        return;
+    }
+    let elapsed = sim_state.steps_this_frame as f32 * sim_state.dt;
+    let g = gravity.g_effective;
+    let c = settings.speed_of_light.max(f32::EPSILON);
+
+    for (entity, mut binary, bh, mut transform) in primaries.iter_mut() {
+        let Some((secondary, companion, mut companion_transform)) =
+            binary.secondary.get_mut(&mut companions)
+        else {
+            // The partner vanished some other way; the survivor rejoins the gravity step.
+            commands.entity(entity).remove::<(CompactBinary, Pinned)>();
+            continue;
+        };
+        let (m1, m2) = (bh.mass, companion.mass);
+        let total = (m1 + m2).max(f32::EPSILON);
+
+        let beta = peters_beta(m1, m2, g, c, binary.eccentricity);
+        binary.separation = decayed_separation(binary.separation, beta, elapsed);
+        let merge_distance = schwarzschild_radius(m1, g, c) + schwarzschild_radius(m2, g, c);
+
+        if binary.separation <= merge_distance {
+            let remnant_mass = total * (1.0 - settings.radiated_fraction.clamp(0.0, 1.0));
+            spawn_black_hole(
+                &mut commands,
+                &assets,
+                binary.barycenter,
+                remnant_mass,
+                settings.remnant_spin,
+                "Merger Remnant",
+            );
+            commands.entity(entity).despawn_recursive();
+            commands.entity(secondary).despawn_recursive();
+            mergers.send(CompactBinaryMerger {
+                tick: sim_state.tick,
+                primary_mass: m1,
+                secondary_mass: m2,
+                remnant_mass,
+                position: binary.barycenter,
+                inspiral_ticks: sim_state.tick.saturating_sub(binary.formed_tick),
+                radiated_energy: (total - remnant_mass) * c * c,
+            });
+            continue;
+        }
+
+        let angular_speed = (g * total / binary.separation.powi(3)).sqrt();
+        binary.phase = (binary.phase + angular_speed * elapsed) % std::f32::consts::TAU;
+        let (u, v) = binary.plane;
+        let direction = u * binary.phase.cos() + v * binary.phase.sin();
+        transform.translation = binary.barycenter - direction * binary.separation * m2 / total;
+        companion_transform.translation =
+            binary.barycenter + direction * binary.separation * m1 / total;
+    }
+}
@@ -1,23 +1,196 @@
+use std::fmt;
+
 use bevy::prelude::*;
 
+use crate::app::SimulationState;
+use crate::pru::cell::{PruCell, PruDynamics};
+use crate::pru::gravity::SimulationEnergy;
+use crate::pru::universe::PruUniverse;
+use crate::render::animation::{set_scale, AnimationClock, AnimationSettings, ScaleModifier};
+use crate::spatial::SpatialOctree;
+
+use super::formation::{FormationSchedule, FormationSettings};
+
+/// Fraction of accreted rest mass radiated away (c = 1 in simulation units).
+pub const ACCRETION_EFFICIENCY: f64 = 0.1;
+
 /// A black hole, created when density & curvature exceed extreme thresholds.
 #[derive(Component, Debug, Clone)]
 pub struct BlackHole {
     pub mass: f32,
     pub radius: f32,
     pub spin: f32,
+    /// Mass swallowed during the most recent accretion pass.
+    pub last_accreted: f32,
+    /// Tick at which `last_accreted` was recorded.
+    pub last_accretion_tick: u64,
+}
+
+impl BlackHole {
+    pub fn new(mass: f32, radius: f32, spin: f32) -> Self {
+        Self {
+            mass,
+            radius,
+            spin,
+            last_accreted: 0.0,
+            last_accretion_tick: 0,
+        }
+    }
+}
+
+impl fmt::Display for BlackHole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "BH m={:.2} r={:.3} s={:.3}",
+            self.mass, self.radius, self.spin
+        )
+    }
+}
+
+/// Drain a fraction of the mass of nearby cells into each black hole.
+///
+/// Each cell's UA lock drains and its mass follows it down to
+/// [`MIN_CELL_MASS`](crate::pru::cell::MIN_CELL_MASS). Runs on the black hole formation
+/// cadence and records the mass swallowed per hole so the agents layer can
+/// build accretion-rate diagnostics. The radiated fraction is reported as
+/// `SimulationEnergy::accretion_power`.
+#[allow(clippy::too_many_arguments)]
+pub fn black_hole_accretion(
+    sim_state: Res<SimulationState>,
+    universe: Res<PruUniverse>,
+    settings: Res<FormationSettings>,
+    mut schedule: ResMut<FormationSchedule>,
+    mut energy: ResMut<SimulationEnergy>,
+    octree: Res<SpatialOctree>,
+    mut holes: Query<(&mut BlackHole, &Transform, Option<&mut PruDynamics>), Without<PruCell>>,
+    mut cells: Query<(&mut PruCell, &mut PruDynamics)>,
+) {
+    if !sim_state.advanced_this_frame() {
+        return;
+    }
+    let elapsed_ticks = sim_state.tick.saturating_sub(schedule.last_accretion_tick);
+    if elapsed_ticks < settings.black_hole_interval {
+        return;
+    }
+    schedule.last_accretion_tick = sim_state.tick;
+    let _span = info_span!("accretion", holes = holes.iter().len()).entered();
+
+    let mut total_accreted = 0.0f64;
+    for (mut bh, transform, hole_dynamics) in holes.iter_mut() {
+        let capture_radius = (bh.radius * 2.0).max(universe.spacing);
+
+        let mut accreted = 0.0f32;
+        for entity in octree.query_radius(transform.translation, capture_radius) {
+            let Ok((mut cell, mut dynamics)) = cells.get_mut(entity) else {
+                continue;
+            };
+            // Drain the UA lock and take whatever mass it stops implying, so a
+            // cell already at MIN_CELL_MASS gives up nothing.
+            let before = dynamics.mass;
+            let drained = cell.ua_mass_lock * (1.0 - settings.accretion_fraction as f64);
+            cell.set_ua_mass_lock(drained, &mut dynamics);
+            accreted += before - dynamics.mass;
+        }
+
+        bh.mass += accreted;
+        if let Some(mut hole_dynamics) = hole_dynamics {
+            hole_dynamics.mass = bh.mass;
+        }
+        bh.last_accreted = accreted;
+        bh.last_accretion_tick = sim_state.tick;
+        total_accreted += accreted as f64;
+    }
+
+    let interval_time = elapsed_ticks as f64 * sim_state.dt as f64;
+    energy.accretion_power = if interval_time > 0.0 {
+        ACCRETION_EFFICIENCY * total_accreted / interval_time
+    } else {
+        0.0
+    };
 }
 
 /// Simple visual hint for accretion disks.
-pub fn animate_black_holes(time: Res<Time>, mut query: Query<(&BlackHole, &mut Transform)>) {
-    let phase = time.elapsed_seconds();
-    for (bh, mut transform) in query.iter_mut() {
-        let wobble = (phase * 1.3 + bh.spin).sin() * 0.08;
-        let scale = 1.0 + wobble;
-        transform.scale = Vec3::new(
-            bh.radius * scale,
-            bh.radius * 0.5 * scale,
-            bh.radius * scale,
+pub fn animate_black_holes(
+    clock: Res<AnimationClock>,
+    animation: Res<AnimationSettings>,
+    mut query: Query<(&BlackHole, Option<&ScaleModifier>, &mut Transform)>,
+) {
+    let amplitude = animation.amplitude(animation.black_hole_wobble);
+    for (bh, modifier, mut transform) in query.iter_mut() {
+        let wobble = (clock.time * 1.3 + bh.spin).sin() * 0.08 * amplitude;
+        let scale = (1.0 + wobble) * modifier.map_or(1.0, |m| m.0);
+        set_scale(
+            &mut transform,
+            Vec3::new(
+                bh.radius * scale,
+                bh.radius * 0.5 * scale,
+                bh.radius * scale,
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::pru::cell::{mass_from_ua_lock, MIN_CELL_MASS};
+
+    #[test]
+    fn accretion_drains_cells_through_their_ua_lock_down_to_the_mass_floor() {
+        let mut world = World::new();
+        world.insert_resource(SimulationState {
+            tick: 100,
+            steps_this_frame: 1,
+            ..default()
+        });
+        world.insert_resource(PruUniverse::new(UVec3::splat(4), 1.0));
+        world.insert_resource(FormationSettings {
+            accretion_fraction: 0.5,
+            ..default()
+        });
+        world.init_resource::<FormationSchedule>();
+        world.init_resource::<SimulationEnergy>();
+
+        let spawn_cell = |world: &mut World, x: f32, ua_mass_lock: f64| {
+            let mut cell = PruCell::new(Vec3::new(x, 0.0, 0.0), UVec3::ZERO, 0.0, 1.0);
+            let mut dynamics = PruDynamics::default();
+            cell.set_ua_mass_lock(ua_mass_lock, &mut dynamics);
+            world.spawn((cell, dynamics)).id()
+        };
+        let heavy = spawn_cell(&mut world, 0.2, 2.0);
+        let light = spawn_cell(&mut world, -0.2, MIN_CELL_MASS as f64);
+        let hole = world
+            .spawn((BlackHole::new(5.0, 0.4, 0.0), Transform::IDENTITY))
+            .id();
+        let mut octree = SpatialOctree::default();
+        octree.rebuild(vec![
+            (heavy, Vec3::new(0.2, 0.0, 0.0)),
+            (light, Vec3::new(-0.2, 0.0, 0.0)),
+        ]);
+        world.insert_resource(octree);
+
+        world.run_system_once(black_hole_accretion);
+
+        for entity in [heavy, light] {
+            let cell = world.get::<PruCell>(entity).unwrap();
+            let dynamics = world.get::<PruDynamics>(entity).unwrap();
+            assert_eq!(dynamics.mass, mass_from_ua_lock(cell.ua_mass_lock));
+            assert!(dynamics.mass >= MIN_CELL_MASS);
+        }
+        assert_eq!(world.get::<PruCell>(heavy).unwrap().ua_mass_lock, 1.0);
+        // The light cell's lock halves, but its mass already sits on the floor.
+        let light_mass = world.get::<PruDynamics>(light).unwrap().mass;
+        assert_eq!(light_mass, MIN_CELL_MASS);
+
+        let bh = world.get::<BlackHole>(hole).unwrap();
+        assert!(
+            (bh.last_accreted - 1.0).abs() < 1e-6,
+            "{}",
+            bh.last_accreted
         );
+        assert!((bh.mass - 6.0).abs() < 1e-6, "{}", bh.mass);
     }
 }
@@ -1,84 +1,771 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
 
 use bevy::prelude::*;
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool, Task};
+use bevy::utils::tracing::field::Empty;
+use serde::{Deserialize, Serialize};
 
-use crate::app::SimulationState;
-use crate::pru::cell::{DerivedFields, PruCell};
+use crate::agents::events::{AstroReport, AstroReportLog, ReportSeverity};
+use crate::app::{SimulationState, DEFAULT_SCENARIO_NAME};
+use crate::pru::cell::{DerivedFields, PruCell, PruDynamics};
+use crate::pru::gravity::GravityParams;
 use crate::pru::universe::PruUniverse;
+use crate::render::lod::LodState;
+use crate::spatial::SpatialOctree;
 
+use super::assets::{AstroAssets, STAR_MESH_RADIUS};
 use super::black_hole::BlackHole;
-use super::galaxy::{Galaxy, GalaxyIdCounter};
-use super::star::{star_color_from_temperature, Star};
+use super::galaxy::{
+    circular_orbit_velocity, jacobi_radius, Galaxy, GalaxyIdCounter, HaloIndex,
+    StarGalaxyMembership,
+};
+use super::planet::{Planet, PlanetarySystem};
+use super::star::{Star, StarEmissive, SOLAR_METALLICITY};
 
 /// Tunable thresholds controlling when structures emerge.
+///
+/// Density thresholds compare against `DerivedFields::local_density`, whose
+/// scale depends on `FieldSettings::smoothing_radius` and `kernel`; the
+/// presets are tuned for the default Gaussian at 2.5 spacings.
 #[derive(Resource, Clone)]
 pub struct FormationSettings {
+    /// Parameter set these values came from.
+    pub preset: FormationPreset,
     pub star_density_threshold: f32,
     pub black_hole_density_threshold: f32,
     pub black_hole_curvature_threshold: f32,
+    /// Black holes do not form within this distance of an existing star.
+    pub black_hole_star_clearance: f32,
+    /// Instead of skipping a black hole candidate near stars, swallow those stars into it.
+    pub collapse_stars_into_black_holes: bool,
     pub galaxy_density_threshold: f32,
-    pub formation_interval: u64,
-    pub galaxy_refresh_interval: u64,
+    /// Least summed density a region needs to become a new galaxy.
+    pub min_galaxy_mass: f32,
+    /// Fewest stars a region's halo must already hold to become a new galaxy.
+    pub min_member_stars: u32,
+    /// Ticks between star formation scans.
+    pub star_interval: u64,
+    /// Ticks between black hole formation scans and accretion passes.
+    pub black_hole_interval: u64,
+    /// Ticks between galaxy identification scans.
+    pub galaxy_interval: u64,
+    /// Edge of the lattice regions galaxy scans group cells into, in cells.
+    ///
+    /// Changing it mid-run is safe: the next scan re-keys existing galaxies
+    /// (see [`remap_galaxy_keys`]).
     pub region_size: u32,
+    /// Size the regions from how clumped the density field is (see [`adaptive_region_size`]).
+    ///
+    /// Off by default, so every scan uses `region_size` as is. When on,
+    /// `region_size` is the size used at [`REFERENCE_CLUMPING`].
+    pub adaptive_region_size: bool,
+    /// Fraction of a nearby cell's mass swallowed by a black hole per accretion pass.
+    pub accretion_fraction: f32,
+    /// Seed a supermassive black hole at the barycenter of each newly found galaxy.
+    pub seed_central_black_holes: bool,
+    /// Central black hole mass as a fraction of the host galaxy's total mass.
+    pub central_black_hole_mass_ratio: f32,
+    /// Wall-clock milliseconds a star or black hole pass may spend spawning per frame.
+    pub formation_budget_ms: f32,
+    /// How strongly star and black hole passes favor cells with steep UA gradients (0 = density only).
+    pub ua_gradient_preference: f32,
+    /// Metal yield of an accretion event; each nearby star gains `0.01` times this.
+    pub enrichment_yield: f32,
+    /// Distance from an accreting black hole within which stars are enriched.
+    pub enrichment_radius: f32,
+    /// Planets form from cells denser than this multiple of the mean local
+    /// density (but below `star_density_threshold`).
+    pub planet_density_fraction: f32,
+    /// Planets only form where nearby cells' velocity dispersion is below this.
+    pub planet_max_velocity_dispersion: f32,
+    /// Farthest a planet-forming cell may be from its host star.
+    pub planet_host_radius: f32,
+    pub max_planets_per_star: usize,
+    /// Launch stars formed inside a galaxy on a circular orbit about its center.
+    ///
+    /// Off by default so stars start at rest; orbits only show when stars are
+    /// integrated (see `GravityParams::targets`).
+    pub assign_galaxy_orbits: bool,
 }
 
 impl Default for FormationSettings {
     fn default() -> Self {
         Self {
-            star_density_threshold: 1.8,
-            black_hole_density_threshold: 3.0,
+            preset: FormationPreset::Custom,
+            star_density_threshold: 30.0,
+            black_hole_density_threshold: 40.0,
             black_hole_curvature_threshold: 0.25,
-            galaxy_density_threshold: 1.2,
-            formation_interval: 8,
-            galaxy_refresh_interval: 24,
+            black_hole_star_clearance: 1.0,
+            collapse_stars_into_black_holes: false,
+            galaxy_density_threshold: 28.0,
+            min_galaxy_mass: 84.0,
+            min_member_stars: 1,
+            star_interval: 8,
+            black_hole_interval: 8,
+            galaxy_interval: 24,
             region_size: 3,
+            adaptive_region_size: false,
+            accretion_fraction: 0.02,
+            seed_central_black_holes: false,
+            central_black_hole_mass_ratio: 0.05,
+            formation_budget_ms: 1.0,
+            ua_gradient_preference: 0.5,
+            enrichment_yield: 1.0,
+            enrichment_radius: 4.0,
+            assign_galaxy_orbits: false,
+            planet_density_fraction: 1.0,
+            planet_max_velocity_dispersion: 0.1,
+            planet_host_radius: 3.0,
+            max_planets_per_star: 8,
         }
     }
 }
 
+/// Named, tuned sets of formation thresholds.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum FormationPreset {
+    Sparse,
+    Dense,
+    BlackHoleDominated,
+    GalaxyRich,
+    /// The default balance point, adjusted freely with the individual controls.
+    Custom,
+}
+
+impl FormationPreset {
+    pub fn next(self) -> Self {
+        match self {
+            FormationPreset::Sparse => FormationPreset::Dense,
+            FormationPreset::Dense => FormationPreset::BlackHoleDominated,
+            FormationPreset::BlackHoleDominated => FormationPreset::GalaxyRich,
+            FormationPreset::GalaxyRich => FormationPreset::Custom,
+            FormationPreset::Custom => FormationPreset::Sparse,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FormationPreset::Sparse => "Sparse",
+            FormationPreset::Dense => "Dense",
+            FormationPreset::BlackHoleDominated => "Black hole dominated",
+            FormationPreset::GalaxyRich => "Galaxy rich",
+            FormationPreset::Custom => "Custom",
+        }
+    }
+
+    /// Scenario name a run takes on when this preset is picked.
+    pub fn scenario_name(self) -> &'static str {
+        match self {
+            FormationPreset::Custom => DEFAULT_SCENARIO_NAME,
+            preset => preset.label(),
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            FormationPreset::Sparse => {
+                "High thresholds and slow cadences: a few stars in the densest knots, black holes rare."
+            }
+            FormationPreset::Dense => {
+                "Low thresholds and fast cadences: stars light up across most overdense cells."
+            }
+            FormationPreset::BlackHoleDominated => {
+                "Black holes form readily, accrete hard, and seed every galaxy core; stars are scarce."
+            }
+            FormationPreset::GalaxyRich => {
+                "Halos are identified early and often, and their stars start on rotating orbits."
+            }
+            FormationPreset::Custom => "Default thresholds; use the +/- buttons to tune cadences.",
+        }
+    }
+}
+
+/// Structure kinds that form on their own cadence.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FormationKind {
+    Stars,
+    BlackHoles,
+    Galaxies,
+}
+
+impl FormationSettings {
+    /// The tuned parameter set for `preset`; `Custom` yields the defaults.
+    pub fn from_preset(preset: FormationPreset) -> Self {
+        let defaults = Self::default();
+        let settings = match preset {
+            FormationPreset::Sparse => Self {
+                star_density_threshold: 44.0,
+                black_hole_density_threshold: 52.0,
+                galaxy_density_threshold: 40.0,
+                min_galaxy_mass: 120.0,
+                star_interval: 16,
+                black_hole_interval: 16,
+                galaxy_interval: 48,
+                ..defaults
+            },
+            FormationPreset::Dense => Self {
+                star_density_threshold: 24.0,
+                black_hole_density_threshold: 34.0,
+                black_hole_curvature_threshold: 0.2,
+                galaxy_density_threshold: 22.0,
+                min_galaxy_mass: 66.0,
+                star_interval: 4,
+                black_hole_interval: 4,
+                galaxy_interval: 16,
+                ..defaults
+            },
+            FormationPreset::BlackHoleDominated => Self {
+                black_hole_density_threshold: 30.0,
+                black_hole_curvature_threshold: 0.1,
+                accretion_fraction: 0.05,
+                seed_central_black_holes: true,
+                central_black_hole_mass_ratio: 0.1,
+                ..defaults
+            },
+            FormationPreset::GalaxyRich => Self {
+                galaxy_density_threshold: 24.0,
+                min_galaxy_mass: 72.0,
+                galaxy_interval: 12,
+                assign_galaxy_orbits: true,
+                ..defaults
+            },
+            FormationPreset::Custom => defaults,
+        };
+        Self { preset, ..settings }
+    }
+
+    /// Whether an unclaimed scan region is massive and populated enough to become a galaxy.
+    pub fn spawns_galaxy(&self, region: &GalaxyRegion) -> bool {
+        region.mass >= self.min_galaxy_mass && region.num_stars >= self.min_member_stars
+    }
+
+    /// Shift one cadence by `delta` ticks, never below one tick.
+    pub fn adjust_interval(&mut self, kind: FormationKind, delta: i64) {
+        let interval = match kind {
+            FormationKind::Stars => &mut self.star_interval,
+            FormationKind::BlackHoles => &mut self.black_hole_interval,
+            FormationKind::Galaxies => &mut self.galaxy_interval,
+        };
+        *interval = interval.saturating_add_signed(delta).max(1);
+    }
+}
+
 #[derive(Resource, Default)]
 pub struct FormationSchedule {
     pub last_star_tick: u64,
+    pub last_black_hole_tick: u64,
     pub last_galaxy_tick: u64,
+    pub last_accretion_tick: u64,
+    pub last_planet_tick: u64,
+}
+
+impl FormationSchedule {
+    /// Forget when each pass last ran, as after a reset or a load to an earlier tick.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Whether any recorded pass lies ahead of `tick`, i.e. time moved backwards.
+    fn is_ahead_of(&self, tick: u64) -> bool {
+        self.last_star_tick > tick
+            || self.last_black_hole_tick > tick
+            || self.last_galaxy_tick > tick
+            || self.last_accretion_tick > tick
+            || self.last_planet_tick > tick
+    }
+}
+
+/// Reset the formation cadence when the tick counter moves backwards.
+///
+/// Without this, every pass would wait until the tick caught up with its old
+/// last-run tick, skipping formation epochs after a reset.
+pub fn rewind_formation_schedule(
+    sim_state: Res<SimulationState>,
+    mut schedule: ResMut<FormationSchedule>,
+) {
+    if schedule.is_ahead_of(sim_state.tick) {
+        schedule.reset();
+    }
+}
+
+/// Formation-rate bins kept for the star formation history plot.
+pub const FORMATION_RATE_BINS: usize = 40;
+
+/// Star and black hole formation counts per fixed tick interval.
+///
+/// Spawning systems count into the open bin; [`bin_formation_rates`] closes it
+/// once `bin_width_ticks` have passed, appending empty bins for any interval
+/// the simulation skipped over entirely.
+#[derive(Resource)]
+pub struct FormationRateMonitor {
+    /// Stars formed per closed bin, oldest first.
+    pub star_rate_history: VecDeque<u32>,
+    /// Black holes formed per closed bin, oldest first.
+    pub bh_rate_history: VecDeque<u32>,
+    pub bin_width_ticks: u64,
+    /// First tick of the open bin.
+    pub bin_start_tick: u64,
+    stars_in_bin: u32,
+    black_holes_in_bin: u32,
+}
+
+impl Default for FormationRateMonitor {
+    fn default() -> Self {
+        Self {
+            star_rate_history: VecDeque::with_capacity(FORMATION_RATE_BINS),
+            bh_rate_history: VecDeque::with_capacity(FORMATION_RATE_BINS),
+            bin_width_ticks: 50,
+            bin_start_tick: 0,
+            stars_in_bin: 0,
+            black_holes_in_bin: 0,
+        }
+    }
+}
+
+impl FormationRateMonitor {
+    pub fn record_star(&mut self) {
+        self.stars_in_bin += 1;
+    }
+
+    pub fn record_black_hole(&mut self) {
+        self.black_holes_in_bin += 1;
+    }
+
+    /// Close every bin that ended at or before `tick`.
+    ///
+    /// A tick before the open bin means time moved backwards, so the history restarts.
+    pub fn advance_to(&mut self, tick: u64) {
+        if tick < self.bin_start_tick {
+            let bin_width_ticks = self.bin_width_ticks;
+            *self = Self {
+                bin_width_ticks,
+                ..Self::default()
+            };
+            return;
+        }
+
+        let width = self.bin_width_ticks.max(1);
+        while tick >= self.bin_start_tick + width {
+            let (stars, black_holes) = (self.stars_in_bin, self.black_holes_in_bin);
+            self.stars_in_bin = 0;
+            self.black_holes_in_bin = 0;
+            self.star_rate_history.push_back(stars);
+            self.bh_rate_history.push_back(black_holes);
+            while self.star_rate_history.len() > FORMATION_RATE_BINS {
+                self.star_rate_history.pop_front();
+                self.bh_rate_history.pop_front();
+            }
+            self.bin_start_tick += width;
+
+            // After a long jump only the last FORMATION_RATE_BINS bins can survive; skip ahead.
+            let skipped = (tick - self.bin_start_tick) / width;
+            if skipped > FORMATION_RATE_BINS as u64 {
+                let jump = skipped - FORMATION_RATE_BINS as u64;
+                self.bin_start_tick += jump * width;
+            }
+        }
+    }
+}
+
+/// Close formation-rate bins up to the current tick before this frame's spawns are counted.
+pub fn bin_formation_rates(
+    sim_state: Res<SimulationState>,
+    mut monitor: ResMut<FormationRateMonitor>,
+) {
+    // Leave change detection untouched while the open bin is still current.
+    let bin_end = monitor.bin_start_tick + monitor.bin_width_ticks.max(1);
+    if (monitor.bin_start_tick..bin_end).contains(&sim_state.tick) {
+        return;
+    }
+    monitor.advance_to(sim_state.tick);
+}
+
+/// Ticks between full recounts of [`StructureCounts`].
+pub const STRUCTURE_RECONCILE_TICKS: u64 = 300;
+
+/// Live structure counts for the HUD.
+///
+/// Kept current from spawns and despawns as they happen and recounted in
+/// full every [`STRUCTURE_RECONCILE_TICKS`] ticks (and after a reset), so the
+/// HUD never scans every structure each frame.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct StructureCounts {
+    pub stars: usize,
+    pub black_holes: usize,
+    pub galaxies: usize,
+    pub planets: usize,
+    /// Tick and world generation of the last full recount.
+    reconciled: Option<(u64, u64)>,
+}
+
+/// What formed on the most recent tick that formed anything.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct FormationActivity {
+    /// `None` until the first structure forms.
+    pub tick: Option<u64>,
+    pub stars: usize,
+    pub black_holes: usize,
+    pub galaxies: usize,
+}
+
+impl FormationActivity {
+    /// Note `stars`, `black_holes`, and `galaxies` formed at `tick`, adding to that tick's totals.
+    pub fn record(&mut self, tick: u64, stars: usize, black_holes: usize, galaxies: usize) {
+        if stars + black_holes + galaxies == 0 {
+            return;
+        }
+        if self.tick != Some(tick) {
+            *self = Self {
+                tick: Some(tick),
+                ..Self::default()
+            };
+        }
+        self.stars += stars;
+        self.black_holes += black_holes;
+        self.galaxies += galaxies;
+    }
+}
+
+/// Apply this frame's structure spawns and despawns to the counters and formation activity.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn track_structure_counts(
+    sim_state: Res<SimulationState>,
+    mut counts: ResMut<StructureCounts>,
+    mut activity: ResMut<FormationActivity>,
+    new_stars: Query<(), Added<Star>>,
+    new_black_holes: Query<(), Added<BlackHole>>,
+    new_galaxies: Query<(), Added<Galaxy>>,
+    new_planets: Query<(), Added<Planet>>,
+    mut removed_stars: RemovedComponents<Star>,
+    mut removed_black_holes: RemovedComponents<BlackHole>,
+    mut removed_galaxies: RemovedComponents<Galaxy>,
+    mut removed_planets: RemovedComponents<Planet>,
+    all: (
+        Query<(), With<Star>>,
+        Query<(), With<BlackHole>>,
+        Query<(), With<Galaxy>>,
+        Query<(), With<Planet>>,
+    ),
+) {
+    let (stars, black_holes, galaxies) = (
+        new_stars.iter().count(),
+        new_black_holes.iter().count(),
+        new_galaxies.iter().count(),
+    );
+    activity.record(sim_state.tick, stars, black_holes, galaxies);
+
+    let due = counts.reconciled.is_none_or(|(tick, generation)| {
+        generation != sim_state.generation
+            || sim_state.tick < tick
+            || sim_state.tick - tick >= STRUCTURE_RECONCILE_TICKS
+    });
+    if due {
+        removed_stars.clear();
+        removed_black_holes.clear();
+        removed_galaxies.clear();
+        removed_planets.clear();
+        let (all_stars, all_black_holes, all_galaxies, all_planets) = all;
+        *counts = StructureCounts {
+            stars: all_stars.iter().count(),
+            black_holes: all_black_holes.iter().count(),
+            galaxies: all_galaxies.iter().count(),
+            planets: all_planets.iter().count(),
+            reconciled: Some((sim_state.tick, sim_state.generation)),
+        };
+        return;
+    }
+
+    let planets = new_planets.iter().count();
+    let (gone_stars, gone_black_holes, gone_galaxies, gone_planets) = (
+        removed_stars.read().count(),
+        removed_black_holes.read().count(),
+        removed_galaxies.read().count(),
+        removed_planets.read().count(),
+    );
+    if stars
+        + black_holes
+        + galaxies
+        + planets
+        + gone_stars
+        + gone_black_holes
+        + gone_galaxies
+        + gone_planets
+        == 0
+    {
+        return;
+    }
+    counts.stars = (counts.stars + stars).saturating_sub(gone_stars);
+    counts.black_holes = (counts.black_holes + black_holes).saturating_sub(gone_black_holes);
+    counts.galaxies = (counts.galaxies + galaxies).saturating_sub(gone_galaxies);
+    counts.planets = (counts.planets + planets).saturating_sub(gone_planets);
+}
+
+/// Formation candidates carried over to later frames when a pass runs out of budget.
+///
+/// Queues hold source cells densest-first. They are dropped wholesale when the
+/// world generation changes, and each entry is re-checked against its threshold
+/// before spawning, since the cell may have thinned out while it waited.
+#[derive(Resource, Default)]
+pub struct PendingFormation {
+    pub generation: u64,
+    pub stars: VecDeque<Entity>,
+    pub black_holes: VecDeque<Entity>,
+}
+
+impl PendingFormation {
+    /// Forget queued work from an earlier world generation.
+    fn sync_generation(&mut self, generation: u64) {
+        if self.generation != generation {
+            self.generation = generation;
+            self.stars.clear();
+            self.black_holes.clear();
+        }
+    }
+}
+
+/// Star, black hole, and planet positions bucketed on a cubic grid, for formation avoidance checks.
+///
+/// Each formation system rebuilds its own map from the live bodies when it
+/// runs and inserts its spawns as it goes, so an avoidance check only visits
+/// the buckets overlapping its radius instead of every existing body. With
+/// `cell_size` at least the avoidance radius, that is one or two buckets per axis.
+#[derive(Resource, Default, Debug)]
+pub struct FormationSpatialHash {
+    pub cell_size: f32,
+    pub star_positions: HashMap<IVec3, Vec<Vec3>>,
+    pub bh_positions: HashMap<IVec3, Vec<Vec3>>,
+    pub planet_positions: HashMap<IVec3, Vec<Vec3>>,
 }
 
+impl FormationSpatialHash {
+    /// Re-bucket the stars from scratch.
+    pub fn rebuild_stars(&mut self, cell_size: f32, positions: impl IntoIterator<Item = Vec3>) {
+        self.sync_cell_size(cell_size);
+        self.star_positions.clear();
+        for position in positions {
+            self.insert_star(position);
+        }
+    }
+
+    /// Re-bucket the black holes from scratch.
+    pub fn rebuild_black_holes(
+        &mut self,
+        cell_size: f32,
+        positions: impl IntoIterator<Item = Vec3>,
+    ) {
+        self.sync_cell_size(cell_size);
+        self.bh_positions.clear();
+        for position in positions {
+            self.insert_black_hole(position);
+        }
+    }
+
+    /// Re-bucket the planets from scratch.
+    pub fn rebuild_planets(&mut self, cell_size: f32, positions: impl IntoIterator<Item = Vec3>) {
+        self.sync_cell_size(cell_size);
+        self.planet_positions.clear();
+        for position in positions {
+            self.insert_planet(position);
+        }
+    }
+
+    pub fn insert_star(&mut self, position: Vec3) {
+        let key = self.bucket(position);
+        self.star_positions.entry(key).or_default().push(position);
+    }
+
+    pub fn insert_black_hole(&mut self, position: Vec3) {
+        let key = self.bucket(position);
+        self.bh_positions.entry(key).or_default().push(position);
+    }
+
+    pub fn insert_planet(&mut self, position: Vec3) {
+        let key = self.bucket(position);
+        self.planet_positions.entry(key).or_default().push(position);
+    }
+
+    /// Whether a star lies strictly within `radius` of `position`.
+    pub fn star_within(&self, position: Vec3, radius: f32) -> bool {
+        self.any_within(&self.star_positions, position, radius)
+    }
+
+    /// Whether a black hole lies strictly within `radius` of `position`.
+    pub fn black_hole_within(&self, position: Vec3, radius: f32) -> bool {
+        self.any_within(&self.bh_positions, position, radius)
+    }
+
+    /// Whether a planet lies strictly within `radius` of `position`.
+    pub fn planet_within(&self, position: Vec3, radius: f32) -> bool {
+        self.any_within(&self.planet_positions, position, radius)
+    }
+
+    /// Buckets keyed under another size are meaningless, so a new size drops every map.
+    fn sync_cell_size(&mut self, cell_size: f32) {
+        let cell_size = cell_size.max(f32::EPSILON);
+        if self.cell_size != cell_size {
+            self.cell_size = cell_size;
+            self.star_positions.clear();
+            self.bh_positions.clear();
+            self.planet_positions.clear();
+        }
+    }
+
+    fn bucket(&self, position: Vec3) -> IVec3 {
+        (position / self.cell_size.max(f32::EPSILON))
+            .floor()
+            .as_ivec3()
+    }
+
+    fn any_within(&self, buckets: &HashMap<IVec3, Vec<Vec3>>, position: Vec3, radius: f32) -> bool {
+        let min = self.bucket(position - Vec3::splat(radius));
+        let max = self.bucket(position + Vec3::splat(radius));
+        (min.x..=max.x).any(|x| {
+            (min.y..=max.y).any(|y| {
+                (min.z..=max.z).any(|z| {
+                    buckets
+                        .get(&IVec3::new(x, y, z))
+                        .is_some_and(|points| points.iter().any(|p| p.distance(position) < radius))
+                })
+            })
+        })
+    }
+}
+
+/// Cells passing `eligible`, densest first.
+///
+/// Density is boosted by `1 + ua_gradient_preference * |∇UA| / mean`, the
+/// mean taken over the eligible cells, so sharp information contrasts are
+/// claimed first when the per-frame budget or avoidance radii run short.
+fn density_sorted_candidates(
+    cell_query: &Query<(Entity, &PruCell, &DerivedFields)>,
+    ua_gradient_preference: f32,
+    eligible: impl Fn(&DerivedFields) -> bool,
+) -> VecDeque<Entity> {
+    let eligible_cells: Vec<(Entity, f32, f32)> = cell_query
+        .iter()
+        .filter(|(_, _, derived)| eligible(derived))
+        .map(|(entity, _, derived)| (entity, derived.local_density, derived.ua_gradient.length()))
+        .collect();
+    let mean_gradient =
+        eligible_cells.iter().map(|(_, _, g)| g).sum::<f32>() / eligible_cells.len().max(1) as f32;
+    let mut candidates: Vec<(Entity, f32)> = eligible_cells
+        .into_iter()
+        .map(|(entity, density, gradient)| {
+            let contrast = if mean_gradient > f32::EPSILON {
+                gradient / mean_gradient
+            } else {
+                0.0
+            };
+            (entity, density * (1.0 + ua_gradient_preference * contrast))
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+    candidates.into_iter().map(|(entity, _)| entity).collect()
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_stars_from_density(
     mut commands: Commands,
     universe: Res<PruUniverse>,
     sim_state: Res<SimulationState>,
     settings: Res<FormationSettings>,
-    mut meshes: ResMut<Assets<Mesh>>,
+    star_emissive: Res<StarEmissive>,
+    assets: Res<AstroAssets>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut schedule: ResMut<FormationSchedule>,
-    cell_query: Query<(&PruCell, &DerivedFields)>,
+    // Paired to stay within the system parameter limit.
+    (mut pending, mut spatial_hash): (ResMut<PendingFormation>, ResMut<FormationSpatialHash>),
+    mut rates: ResMut<FormationRateMonitor>,
+    octree: Res<SpatialOctree>,
+    gravity: Res<GravityParams>,
+    cell_query: Query<(Entity, &PruCell, &DerivedFields)>,
     existing_stars: Query<&Transform, With<Star>>,
+    galaxies: Query<&Galaxy>,
+    masses: Query<&PruDynamics>,
 ) {
-    if sim_state.tick - schedule.last_star_tick < settings.formation_interval {
+    pending.sync_generation(sim_state.generation);
+    if !sim_state.advanced_this_frame() {
+        return;
+    }
+    let eligible =
+        |derived: &DerivedFields| derived.local_density >= settings.star_density_threshold;
+
+    if sim_state.tick.saturating_sub(schedule.last_star_tick) >= settings.star_interval {
+        schedule.last_star_tick = sim_state.tick;
+        pending.stars =
+            density_sorted_candidates(&cell_query, settings.ua_gradient_preference, eligible);
+    }
+    if pending.stars.is_empty() {
         return;
     }
-    schedule.last_star_tick = sim_state.tick;
+    let span = info_span!(
+        "formation_stars",
+        queued = pending.stars.len(),
+        processed = Empty
+    )
+    .entered();
+    let queued = pending.stars.len();
 
-    let star_mesh = meshes.add(Mesh::from(Sphere { radius: 0.3 }));
     let avoidance_radius = universe.spacing * 0.8;
+    let budget = Duration::from_secs_f32(settings.formation_budget_ms.max(0.0) / 1000.0);
+    let started = Instant::now();
+    // Spawns are deferred, so this frame's stars are added to the hash as they are queued.
+    spatial_hash.rebuild_stars(
+        universe.spacing,
+        existing_stars.iter().map(|transform| transform.translation),
+    );
 
-    for (cell, derived) in cell_query.iter() {
-        if derived.local_density < settings.star_density_threshold {
+    while let Some(entity) = pending.stars.pop_front() {
+        let Ok((_, cell, derived)) = cell_query.get(entity) else {
+            continue;
+        };
+        if !eligible(derived) {
             continue;
         }
 
-        let already_present = existing_stars
-            .iter()
-            .any(|t| (t.translation - cell.position).length() < avoidance_radius);
-        if already_present {
+        if spatial_hash.star_within(cell.position, avoidance_radius) {
             continue;
         }
 
         let radius = (derived.local_density * 0.08).clamp(0.05, 0.6);
-        let temperature = 4000.0 + derived.local_density * 3000.0;
-        let luminosity = derived.local_density * 2.0;
-        let color = star_color_from_temperature(temperature);
-        let emissive_scale = 1.2 + luminosity * 0.2;
-        let emissive = Color::LinearRgba(color.to_linear() * emissive_scale);
+        let star = Star {
+            mass: derived.local_density,
+            radius,
+            temperature: 4000.0 + derived.local_density * 3000.0,
+            luminosity: derived.local_density * 2.0,
+            metallicity: SOLAR_METALLICITY,
+        };
+        let (color, emissive) = star.material_colors(&star_emissive);
+
+        let host = settings
+            .assign_galaxy_orbits
+            .then(|| {
+                galaxies
+                    .iter()
+                    .filter(|galaxy| galaxy.contains(cell.position))
+                    .min_by(|a, b| {
+                        a.center
+                            .distance_squared(cell.position)
+                            .total_cmp(&b.center.distance_squared(cell.position))
+                    })
+            })
+            .flatten();
+        let velocity = host.map_or(Vec3::ZERO, |galaxy| {
+            let radius = galaxy.center.distance(cell.position);
+            let enclosed_mass: f32 = octree
+                .query_radius(galaxy.center, radius)
+                .into_iter()
+                .filter_map(|entity| masses.get(entity).ok())
+                .map(|dynamics| dynamics.mass)
+                .sum();
+            circular_orbit_velocity(
+                galaxy.center,
+                cell.position,
+                enclosed_mass,
+                gravity.g_effective,
+            )
+        });
 
         let material = materials.add(StandardMaterial {
             base_color: color,
@@ -89,131 +776,567 @@ pub fn spawn_stars_from_density(
 
         commands.spawn((
             PbrBundle {
-                mesh: star_mesh.clone(),
+                mesh: assets.star_mesh.clone(),
                 material,
                 transform: Transform::from_translation(cell.position)
                     .with_scale(Vec3::splat(radius)),
                 ..Default::default()
             },
-            Star {
+            star,
+            PruDynamics {
                 mass: derived.local_density,
-                radius,
-                temperature,
-                luminosity,
+                velocity,
+                velocity_at_integer_tick: velocity,
+                ..Default::default()
             },
+            LodState::new(STAR_MESH_RADIUS),
+            StarGalaxyMembership::default(),
+            PlanetarySystem::default(),
             Name::new("Star"),
         ));
+        rates.record_star();
+        spatial_hash.insert_star(cell.position);
+
+        if started.elapsed() >= budget {
+            break;
+        }
     }
+    span.record("processed", queued - pending.stars.len());
 }
 
+/// Sent when stars within `black_hole_star_clearance` collapse into a new black hole.
+///
+/// The collapse itself is reported where it happens, in the stellar-collapse report.
+#[derive(Event, Clone, Debug)]
+pub struct SupernovaEvent {
+    pub position: Vec3,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_black_holes_from_density(
     mut commands: Commands,
     universe: Res<PruUniverse>,
     sim_state: Res<SimulationState>,
     settings: Res<FormationSettings>,
-    schedule: Res<FormationSchedule>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    cell_query: Query<(&PruCell, &DerivedFields)>,
+    mut schedule: ResMut<FormationSchedule>,
+    assets: Res<AstroAssets>,
+    mut pending: ResMut<PendingFormation>,
+    mut rates: ResMut<FormationRateMonitor>,
+    mut spatial_hash: ResMut<FormationSpatialHash>,
+    octree: Res<SpatialOctree>,
+    mut reports: Option<ResMut<AstroReportLog>>,
+    mut supernovae: EventWriter<SupernovaEvent>,
+    cell_query: Query<(Entity, &PruCell, &DerivedFields)>,
     existing_bh: Query<&Transform, With<BlackHole>>,
+    stars: Query<(&Star, &Transform)>,
+    fresh_stars: Query<Entity, Added<Star>>,
 ) {
-    if sim_state.tick - schedule.last_star_tick < settings.formation_interval {
-        // Reuse same cadence as star formation.
+    pending.sync_generation(sim_state.generation);
+    if !sim_state.advanced_this_frame() {
+        return;
+    }
+    let eligible = |derived: &DerivedFields| {
+        derived.local_density >= settings.black_hole_density_threshold
+            && derived.curvature_proxy.abs() >= settings.black_hole_curvature_threshold
+    };
+
+    if sim_state.tick.saturating_sub(schedule.last_black_hole_tick) >= settings.black_hole_interval
+    {
+        schedule.last_black_hole_tick = sim_state.tick;
+        pending.black_holes =
+            density_sorted_candidates(&cell_query, settings.ua_gradient_preference, eligible);
+    }
+    if pending.black_holes.is_empty() {
         return;
     }
+    let span = info_span!(
+        "formation_black_holes",
+        queued = pending.black_holes.len(),
+        processed = Empty
+    )
+    .entered();
+    let queued = pending.black_holes.len();
 
     let avoidance_radius = universe.spacing * 0.9;
-    let bh_mesh = meshes.add(Mesh::from(Sphere { radius: 0.4 }));
+    let budget = Duration::from_secs_f32(settings.formation_budget_ms.max(0.0) / 1000.0);
+    let started = Instant::now();
+    spatial_hash.rebuild_black_holes(
+        universe.spacing,
+        existing_bh.iter().map(|transform| transform.translation),
+    );
+    let mut consumed: Vec<Entity> = Vec::new();
 
-    for (cell, derived) in cell_query.iter() {
-        if derived.local_density < settings.black_hole_density_threshold
-            || derived.curvature_proxy.abs() < settings.black_hole_curvature_threshold
-        {
+    while let Some(entity) = pending.black_holes.pop_front() {
+        let Ok((_, cell, derived)) = cell_query.get(entity) else {
+            continue;
+        };
+        if !eligible(derived) {
             continue;
         }
 
-        let already_present = existing_bh
-            .iter()
-            .any(|t| (t.translation - cell.position).length() < avoidance_radius);
-        if already_present {
+        if spatial_hash.black_hole_within(cell.position, avoidance_radius) {
+            continue;
+        }
+
+        // Stars formed this frame are not in the octree yet.
+        let clearance = settings.black_hole_star_clearance;
+        let mut candidates: Vec<Entity> = octree
+            .query_radius(cell.position, clearance)
+            .into_iter()
+            .chain(fresh_stars.iter())
+            .filter(|e| !consumed.contains(e))
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+        let nearby_stars: Vec<(Entity, f32)> = candidates
+            .into_iter()
+            .filter_map(|e| {
+                let (star, transform) = stars.get(e).ok()?;
+                (transform.translation.distance(cell.position) < clearance)
+                    .then_some((e, star.mass))
+            })
+            .collect();
+        if !nearby_stars.is_empty() && !settings.collapse_stars_into_black_holes {
             continue;
         }
 
-        let mass = derived.local_density * 4.0;
-        let radius = (mass * 0.05).clamp(0.2, 1.5);
+        let mut mass = derived.local_density * 4.0;
         let spin = derived.curvature_proxy.abs();
+        if !nearby_stars.is_empty() {
+            let star_mass: f32 = nearby_stars.iter().map(|(_, m)| m).sum();
+            for (star, _) in &nearby_stars {
+                commands.entity(*star).despawn_recursive();
+                consumed.push(*star);
+            }
+            mass += star_mass;
+            if let Some(reports) = reports.as_mut() {
+                reports.push(AstroReport {
+                    tick: sim_state.tick,
+                    severity: ReportSeverity::Info,
+                    summary: format!(
+                        "stellar collapse: {} stars (mass {star_mass:.2}) swallowed by a new black hole (mass {mass:.2})",
+                        nearby_stars.len()
+                    ),
+                    position: Some(cell.position),
+                });
+            }
+            supernovae.send(SupernovaEvent {
+                position: cell.position,
+            });
+        }
+        spawn_black_hole(
+            &mut commands,
+            &assets,
+            cell.position,
+            mass,
+            spin,
+            "Black Hole",
+        );
+        rates.record_black_hole();
+        spatial_hash.insert_black_hole(cell.position);
 
-        let material = materials.add(StandardMaterial {
-            base_color: Color::srgb(0.02, 0.02, 0.05),
-            perceptual_roughness: 0.9,
-            metallic: 0.7,
-            ..Default::default()
-        });
+        if started.elapsed() >= budget {
+            break;
+        }
+    }
+    span.record("processed", queued - pending.black_holes.len());
+}
 
-        commands.spawn((
+/// Spawn a black hole entity with the shared dark-sphere look.
+pub(super) fn spawn_black_hole(
+    commands: &mut Commands,
+    assets: &AstroAssets,
+    position: Vec3,
+    mass: f32,
+    spin: f32,
+    name: &'static str,
+) -> Entity {
+    let radius = (mass * 0.05).clamp(0.2, 1.5);
+    commands
+        .spawn((
             PbrBundle {
-                mesh: bh_mesh.clone(),
-                material,
-                transform: Transform::from_translation(cell.position)
-                    .with_scale(Vec3::splat(radius)),
+                mesh: assets.black_hole_mesh.clone(),
+                material: assets.black_hole_material.clone(),
+                transform: Transform::from_translation(position).with_scale(Vec3::splat(radius)),
                 ..Default::default()
             },
-            BlackHole { mass, radius, spin },
-            Name::new("Black Hole"),
-        ));
+            BlackHole::new(mass, radius, spin),
+            PruDynamics {
+                mass,
+                ..Default::default()
+            },
+            Name::new(name),
+        ))
+        .id()
+}
+
+/// Aggregated overdense region produced by a background galaxy scan.
+#[derive(Debug, Clone, Copy)]
+pub struct GalaxyRegion {
+    pub mass: f32,
+    pub center: Vec3,
+    pub radius: f32,
+    pub num_stars: u32,
+}
+
+/// Result of a background galaxy scan, tagged with the world generation it was taken from.
+pub struct GalaxyScan {
+    pub generation: u64,
+    /// `FormationSettings::region_size` the regions were keyed with.
+    pub region_size: u32,
+    pub regions: HashMap<UVec3, GalaxyRegion>,
+}
+
+/// Galaxy scan currently running on the async compute pool, if any.
+#[derive(Resource, Default)]
+pub struct GalaxyScanTask {
+    pub task: Option<Task<GalaxyScan>>,
+    /// Region size the existing galaxies' `region_key`s were computed with.
+    pub keyed_region_size: Option<u32>,
+}
+
+impl GalaxyScanTask {
+    /// Region size the current galaxies are keyed with, or `settings`' before the first scan lands.
+    pub fn region_size(&self, settings: &FormationSettings) -> u32 {
+        self.keyed_region_size
+            .unwrap_or(settings.region_size)
+            .max(1)
     }
 }
 
-pub fn identify_galaxies(
-    mut commands: Commands,
+/// Snapshot cell densities and star positions and launch galaxy identification
+/// on the async compute pool.
+///
+/// The scan result is applied by [`identify_galaxies`] once the task completes,
+/// typically a frame or two later, so galaxy data can lag the lattice by up to
+/// one `galaxy_interval`. A new scan is never launched while another is
+/// still in flight.
+pub fn dispatch_galaxy_scan(
     sim_state: Res<SimulationState>,
     universe: Res<PruUniverse>,
     settings: Res<FormationSettings>,
     mut schedule: ResMut<FormationSchedule>,
-    mut id_counter: ResMut<GalaxyIdCounter>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut scan_task: ResMut<GalaxyScanTask>,
     cell_query: Query<(&PruCell, &DerivedFields)>,
     stars: Query<&Transform, With<Star>>,
-    mut galaxies: Query<(Entity, &mut Galaxy, &mut Transform)>,
 ) {
-    if sim_state.tick - schedule.last_galaxy_tick < settings.galaxy_refresh_interval {
+    if !sim_state.advanced_this_frame() {
+        return;
+    }
+    if sim_state.tick.saturating_sub(schedule.last_galaxy_tick) < settings.galaxy_interval {
+        return;
+    }
+    if scan_task.task.is_some() {
         return;
     }
     schedule.last_galaxy_tick = sim_state.tick;
+    let _span = info_span!("galaxy_scan_dispatch").entered();
 
-    let mut regions: HashMap<UVec3, (f32, Vec3)> = HashMap::new();
-    let region_size = settings.region_size.max(1);
+    let threshold = settings.galaxy_density_threshold;
+    let cells: Vec<(UVec3, Vec3, f32)> = cell_query
+        .iter()
+        .filter(|(_, derived)| derived.local_density >= threshold)
+        .map(|(cell, derived)| (cell.grid_coords, cell.position, derived.local_density))
+        .collect();
+    let star_positions: Vec<Vec3> = stars.iter().map(|t| t.translation).collect();
+    let region_size = if settings.adaptive_region_size {
+        let clumping = clumping_factor(cell_query.iter().map(|(_, derived)| derived.local_density));
+        adaptive_region_size(settings.region_size, clumping, scan_task.keyed_region_size)
+    } else {
+        settings.region_size.max(1)
+    };
+    let spacing = universe.spacing;
+    let generation = sim_state.generation;
 
-    for (cell, derived) in cell_query.iter() {
-        if derived.local_density < settings.galaxy_density_threshold {
-            continue;
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        GalaxyScan {
+            generation,
+            region_size,
+            regions: scan_galaxy_regions(&cells, &star_positions, region_size, spacing),
         }
-        let key = UVec3::new(
-            cell.grid_coords.x / region_size,
-            cell.grid_coords.y / region_size,
-            cell.grid_coords.z / region_size,
+    });
+    scan_task.task = Some(task);
+}
+
+/// Clumping factor `⟨ρ²⟩ / ⟨ρ⟩²` of a density field: 1 when uniform, growing as mass gathers.
+///
+/// Mass spread evenly over a fraction `f` of the cells gives `1 / f`. An
+/// empty or massless field counts as uniform.
+pub fn clumping_factor(densities: impl IntoIterator<Item = f32>) -> f32 {
+    let (mut count, mut sum, mut sum_sq) = (0u64, 0.0f64, 0.0f64);
+    for density in densities {
+        let density = density.max(0.0) as f64;
+        count += 1;
+        sum += density;
+        sum_sq += density * density;
+    }
+    if count == 0 || sum <= 0.0 {
+        return 1.0;
+    }
+    ((sum_sq * count as f64 / (sum * sum)) as f32).max(1.0)
+}
+
+/// Clumping factor at which the adaptive region size equals `region_size`.
+///
+/// Mass filling a quarter of the volume. Smoother fields get larger regions,
+/// more clustered ones smaller regions.
+pub const REFERENCE_CLUMPING: f32 = 4.0;
+/// Largest adaptive region size, as a multiple of `region_size`.
+pub const MAX_REGION_GROWTH: u32 = 2;
+/// How far past the rounding midpoint, in cells, the adaptive size must move
+/// before it leaves the current size.
+pub const REGION_SIZE_HYSTERESIS: f32 = 0.25;
+
+/// Galaxy region size for a field of `clumping` factor, from the size `base`
+/// used at [`REFERENCE_CLUMPING`].
+///
+/// Mass filling `1 / C` of the volume sits in clumps about `C^(-1/3)` as
+/// wide, so regions scale by `(C_ref / C)^(1/3)` to group cells at the
+/// clumps' scale: shrinking so neighboring clumps aren't merged, growing so
+/// a sparse field's diffuse mass is gathered into regions big enough to pass
+/// the galaxy thresholds. Kept between one cell and [`MAX_REGION_GROWTH`]
+/// times `base`.
+///
+/// Every size change re-keys the galaxies, so the `current` size is kept
+/// until the ideal size is [`REGION_SIZE_HYSTERESIS`] past the midpoint to
+/// a neighbor. A field hovering near a midpoint then doesn't flip the size
+/// back and forth between scans.
+pub fn adaptive_region_size(base: u32, clumping: f32, current: Option<u32>) -> u32 {
+    let base = base.max(1);
+    let scale = (REFERENCE_CLUMPING / clumping.max(1.0)).cbrt();
+    let ideal = base as f32 * scale;
+    let size = match current {
+        Some(current) if (ideal - current as f32).abs() < 0.5 + REGION_SIZE_HYSTERESIS => current,
+        _ => ideal.round() as u32,
+    };
+    size.clamp(1, base * MAX_REGION_GROWTH)
+}
+
+/// Group overdense cells into coarse lattice regions and measure each region's
+/// mass, barycenter, radius, and enclosed star count.
+pub fn scan_galaxy_regions(
+    cells: &[(UVec3, Vec3, f32)],
+    star_positions: &[Vec3],
+    region_size: u32,
+    spacing: f32,
+) -> HashMap<UVec3, GalaxyRegion> {
+    let mut sums: HashMap<UVec3, (f32, Vec3)> = HashMap::new();
+    for (coords, position, density) in cells.iter() {
+        let entry = sums
+            .entry(*coords / region_size)
+            .or_insert((0.0, Vec3::ZERO));
+        entry.0 += *density;
+        entry.1 += *position * *density;
+    }
+
+    let halos = HaloIndex::new(
+        sums.into_iter()
+            .map(|(key, (mass, weighted_pos))| {
+                let center = weighted_pos / mass.max(1e-3);
+                let radius = (mass * 0.05).clamp(spacing, spacing * 8.0);
+                (center, radius, (key, mass))
+            })
+            .collect(),
+    );
+
+    // One pass over the stars, bumping every halo that contains each one.
+    let mut num_stars = vec![0u32; halos.len()];
+    for position in star_positions {
+        for (index, _) in halos.containing(*position) {
+            num_stars[index] += 1;
+        }
+    }
+
+    halos
+        .into_halos()
+        .into_iter()
+        .zip(num_stars)
+        .map(|((center, radius, (key, mass)), num_stars)| {
+            (
+                key,
+                GalaxyRegion {
+                    mass,
+                    center,
+                    radius,
+                    num_stars,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Galaxy state the region remap works on.
+#[derive(Debug, Clone, Copy)]
+pub struct KeyedGalaxy {
+    pub entity: Entity,
+    pub id: u32,
+    pub total_mass: f32,
+    pub radius: f32,
+    pub num_stars: u32,
+    pub center: Vec3,
+    pub region_key: UVec3,
+}
+
+/// Re-key galaxies for a new region size, folding those that now share a region.
+///
+/// Each galaxy's key is recomputed from its center. Galaxies landing in the
+/// same region merge into the one with the oldest (lowest) id, which takes
+/// their summed mass and star count, the mass-weighted center, and the
+/// largest radius. Returns the survivors and every absorbed galaxy with the
+/// id of the survivor it merged into.
+pub fn remap_galaxy_keys(
+    galaxies: &[KeyedGalaxy],
+    universe: &PruUniverse,
+    region_size: u32,
+) -> (Vec<KeyedGalaxy>, Vec<(KeyedGalaxy, u32)>) {
+    let region_size = region_size.max(1);
+    let mut by_key: HashMap<UVec3, Vec<KeyedGalaxy>> = HashMap::new();
+    for galaxy in galaxies {
+        let key = universe.nearest_grid_coords(galaxy.center) / region_size;
+        by_key.entry(key).or_default().push(*galaxy);
+    }
+
+    let mut survivors = Vec::with_capacity(by_key.len());
+    let mut absorbed = Vec::new();
+    for (region_key, mut members) in by_key {
+        members.sort_by_key(|galaxy| galaxy.id);
+        let total_mass: f32 = members.iter().map(|galaxy| galaxy.total_mass).sum();
+        let center = if total_mass > 0.0 {
+            members
+                .iter()
+                .map(|galaxy| galaxy.center * galaxy.total_mass)
+                .sum::<Vec3>()
+                / total_mass
+        } else {
+            members[0].center
+        };
+        survivors.push(KeyedGalaxy {
+            total_mass,
+            center,
+            radius: members
+                .iter()
+                .map(|galaxy| galaxy.radius)
+                .fold(0.0, f32::max),
+            num_stars: members.iter().map(|galaxy| galaxy.num_stars).sum(),
+            region_key,
+            ..members[0]
+        });
+        absorbed.extend(members[1..].iter().map(|galaxy| (*galaxy, members[0].id)));
+    }
+    survivors.sort_by_key(|galaxy| galaxy.id);
+    (survivors, absorbed)
+}
+
+/// Apply a finished background galaxy scan: refresh existing galaxies and
+/// spawn new ones for unclaimed regions.
+///
+/// Scans taken from an earlier world generation (before a reset) are discarded.
+/// A scan keyed with a different region size than the existing galaxies
+/// first re-keys and merges them ([`remap_galaxy_keys`]), so a region size
+/// change neither fades out every galaxy nor spawns duplicates. Those folds
+/// are bookkeeping, not physical mergers: they are logged as an info report
+/// rather than sent as [`GalaxyMergerEvent`](crate::agents::events::GalaxyMergerEvent)s.
+#[allow(clippy::too_many_arguments)]
+pub fn identify_galaxies(
+    mut commands: Commands,
+    sim_state: Res<SimulationState>,
+    universe: Res<PruUniverse>,
+    settings: Res<FormationSettings>,
+    mut scan_task: ResMut<GalaxyScanTask>,
+    mut id_counter: ResMut<GalaxyIdCounter>,
+    assets: Res<AstroAssets>,
+    octree: Res<SpatialOctree>,
+    mut galaxies: Query<(Entity, &mut Galaxy, &mut Transform)>,
+    black_holes: Query<(), (With<BlackHole>, Without<Galaxy>)>,
+    mut reports: Option<ResMut<AstroReportLog>>,
+) {
+    // A scan finishing while paused is held until the simulation moves again.
+    if !sim_state.advanced_this_frame() {
+        return;
+    }
+    let Some(task) = scan_task.task.as_mut() else {
+        return;
+    };
+    let Some(scan) = block_on(poll_once(task)) else {
+        return;
+    };
+    scan_task.task = None;
+
+    if scan.generation != sim_state.generation {
+        return;
+    }
+    let mut regions = scan.regions;
+    let _span = info_span!("galaxy_identify", regions = regions.len()).entered();
+
+    let mut absorbed = Vec::new();
+    if let Some(previous) = scan_task
+        .keyed_region_size
+        .filter(|size| *size != scan.region_size)
+    {
+        let keyed: Vec<KeyedGalaxy> = galaxies
+            .iter()
+            .map(|(entity, galaxy, _)| KeyedGalaxy {
+                entity,
+                id: galaxy.id,
+                total_mass: galaxy.total_mass,
+                radius: galaxy.radius,
+                num_stars: galaxy.num_stars,
+                center: galaxy.center,
+                region_key: galaxy.region_key,
+            })
+            .collect();
+        let (survivors, merged) = remap_galaxy_keys(&keyed, &universe, scan.region_size);
+        absorbed = merged.iter().map(|(galaxy, _)| galaxy.entity).collect();
+        for survivor in survivors {
+            if let Ok((_, mut galaxy, _)) = galaxies.get_mut(survivor.entity) {
+                galaxy.total_mass = survivor.total_mass;
+                galaxy.radius = survivor.radius;
+                galaxy.num_stars = survivor.num_stars;
+                galaxy.center = survivor.center;
+                galaxy.region_key = survivor.region_key;
+            }
+        }
+        for entity in &absorbed {
+            commands.entity(*entity).despawn_recursive();
+        }
+        info!(
+            "Galaxy region size {previous} -> {}: re-keyed {} galaxies, merged {}",
+            scan.region_size,
+            keyed.len(),
+            absorbed.len()
         );
-        let entry = regions.entry(key).or_insert((0.0, Vec3::ZERO));
-        entry.0 += derived.local_density;
-        entry.1 += cell.position * derived.local_density;
+        if let Some(reports) = reports.as_mut().filter(|_| !merged.is_empty()) {
+            let folds: Vec<String> = merged
+                .iter()
+                .map(|(galaxy, survivor)| format!("#{} into #{survivor}", galaxy.id))
+                .collect();
+            reports.push(AstroReport {
+                tick: sim_state.tick,
+                severity: ReportSeverity::Info,
+                summary: format!(
+                    "galaxy re-key: region size {previous} -> {}, folded {}",
+                    scan.region_size,
+                    folds.join(", ")
+                ),
+                position: None,
+            });
+        }
     }
+    scan_task.keyed_region_size = Some(scan.region_size);
 
     // Update existing galaxies if their region is still valid.
-    for (_entity, mut galaxy, mut transform) in galaxies.iter_mut() {
-        if let Some((mass, weighted_pos)) = regions.remove(&galaxy.region_key) {
-            let center = weighted_pos / mass.max(1e-3);
-            let radius = (mass * 0.05).clamp(universe.spacing, universe.spacing * 8.0);
-            galaxy.total_mass = mass;
-            galaxy.center = center;
-            galaxy.radius = radius;
-            galaxy.num_stars = stars
-                .iter()
-                .filter(|t| (t.translation - center).length() < radius)
-                .count() as u32;
+    for (entity, mut galaxy, mut transform) in galaxies.iter_mut() {
+        if absorbed.contains(&entity) {
+            continue;
+        }
+        if let Some(region) = regions.remove(&galaxy.region_key) {
+            galaxy.total_mass = region.mass;
+            galaxy.center = region.center;
+            galaxy.radius = region.radius;
+            galaxy.num_stars = region.num_stars;
 
-            transform.translation = center;
-            transform.scale = Vec3::splat(radius * 0.5);
+            transform.translation = region.center;
+            transform.scale = Vec3::splat(region.radius * 0.5);
         } else {
             // Fade out gracefully by shrinking the galaxy. If it becomes tiny, despawn later.
             galaxy.total_mass *= 0.9;
@@ -222,48 +1345,766 @@ pub fn identify_galaxies(
         }
     }
 
-    let halo_mesh = meshes.add(Mesh::from(Sphere { radius: 1.0 }));
-
     // Spawn new galaxies for remaining regions.
-    for (region_key, (mass, weighted_pos)) in regions.into_iter() {
-        if mass < settings.galaxy_density_threshold * 3.0 {
+    for (region_key, region) in regions.into_iter() {
+        if !settings.spawns_galaxy(&region) {
             continue;
         }
 
-        let center = weighted_pos / mass.max(1e-3);
-        let radius = (mass * 0.05).clamp(universe.spacing, universe.spacing * 8.0);
         let id = id_counter.next();
-
-        let color = Color::srgb(0.6, 0.8, 1.0);
-        let halo_emissive = Color::LinearRgba(color.to_linear() * 0.05);
-        let material = materials.add(StandardMaterial {
-            base_color: color.with_alpha(0.1),
-            emissive: halo_emissive.into(),
-            alpha_mode: AlphaMode::Add,
-            unlit: true,
-            ..Default::default()
-        });
-
         commands.spawn((
             PbrBundle {
-                mesh: halo_mesh.clone(),
-                material,
-                transform: Transform::from_translation(center)
-                    .with_scale(Vec3::splat(radius * 0.5)),
+                mesh: assets.halo_mesh.clone(),
+                material: assets.halo_material.clone(),
+                transform: Transform::from_translation(region.center)
+                    .with_scale(Vec3::splat(region.radius * 0.5)),
                 ..Default::default()
             },
             Galaxy {
                 id,
-                total_mass: mass,
-                radius,
-                num_stars: stars
-                    .iter()
-                    .filter(|t| (t.translation - center).length() < radius)
-                    .count() as u32,
-                center,
+                total_mass: region.mass,
+                radius: region.radius,
+                num_stars: region.num_stars,
+                center: region.center,
                 region_key,
             },
             Name::new(format!("Galaxy #{id}")),
         ));
+
+        if settings.seed_central_black_holes {
+            // Give each galaxy a central engine unless a hole already sits near its core.
+            let core_radius = region.radius * 0.5;
+            let has_central_hole = octree
+                .query_radius(region.center, core_radius)
+                .into_iter()
+                .any(|e| black_holes.contains(e));
+            if !has_central_hole {
+                spawn_black_hole(
+                    &mut commands,
+                    &assets,
+                    region.center,
+                    region.mass * settings.central_black_hole_mass_ratio,
+                    0.0,
+                    "Central Black Hole",
+                );
+            }
+        }
+    }
+}
+
+/// Attach stars without a galaxy to the nearest galaxy whose halo contains them.
+///
+/// Covers newly formed stars, stars a galaxy formed around, and members of a
+/// galaxy that has since despawned. Stripped stars stay unattached.
+pub fn assign_galaxy_membership(
+    sim_state: Res<SimulationState>,
+    galaxies: Query<(Entity, &Galaxy)>,
+    mut stars: Query<(&Transform, &mut StarGalaxyMembership), With<Star>>,
+) {
+    if !sim_state.advanced_this_frame() {
+        return;
+    }
+    let halos = HaloIndex::new(
+        galaxies
+            .iter()
+            .map(|(entity, galaxy)| (galaxy.center, galaxy.radius, entity))
+            .collect(),
+    );
+    for (transform, mut membership) in stars.iter_mut() {
+        if membership.stripped || membership.galaxy.get(&galaxies).is_some() {
+            continue;
+        }
+        if let Some(host) = halos.nearest_containing(transform.translation) {
+            membership.galaxy.set(*host);
+        }
+    }
+}
+
+/// Strip stars from galaxies that pass close to a more massive neighbor.
+///
+/// For each pair closer than `r_small + 3 r_large`, member stars of the
+/// lighter galaxy farther from its center than the Jacobi radius lose their
+/// membership for good; a report notes how many each galaxy lost. Stripped
+/// stars keep their velocity, so the host's tide draws them into tails.
+pub fn tidal_stripping(
+    sim_state: Res<SimulationState>,
+    mut reports: Option<ResMut<AstroReportLog>>,
+    galaxies: Query<(Entity, &Galaxy)>,
+    mut stars: Query<(&Transform, &mut StarGalaxyMembership), With<Star>>,
+) {
+    if !sim_state.advanced_this_frame() {
+        return;
+    }
+
+    // Tightest tidal radius each satellite feels, and the id of the galaxy imposing it.
+    let mut tidal_limits: HashMap<Entity, (f32, u32)> = HashMap::new();
+    let mut pairs = galaxies.iter_combinations();
+    while let Some([a, b]) = pairs.fetch_next() {
+        let ((small_entity, small), (_, large)) = if a.1.total_mass <= b.1.total_mass {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        if small.total_mass <= 0.0 {
+            continue;
+        }
+        let distance = small.center.distance(large.center);
+        if distance >= small.radius + large.radius * 3.0 {
+            continue;
+        }
+        let r_j = jacobi_radius(distance, small.total_mass, large.total_mass);
+        let limit = tidal_limits.entry(small_entity).or_insert((r_j, large.id));
+        if r_j < limit.0 {
+            *limit = (r_j, large.id);
+        }
+    }
+    if tidal_limits.is_empty() {
+        return;
+    }
+
+    let mut stripped: HashMap<Entity, u32> = HashMap::new();
+    for (transform, mut membership) in stars.iter_mut() {
+        let Some((entity, galaxy)) = membership.galaxy.get(&galaxies) else {
+            continue;
+        };
+        let Some((r_j, _)) = tidal_limits.get(&entity) else {
+            continue;
+        };
+        if transform.translation.distance(galaxy.center) > *r_j {
+            membership.strip();
+            *stripped.entry(entity).or_default() += 1;
+        }
+    }
+
+    let Some(reports) = reports.as_mut() else {
+        return;
+    };
+    for (entity, count) in stripped {
+        let (Ok((_, galaxy)), Some((_, perturber))) =
+            (galaxies.get(entity), tidal_limits.get(&entity))
+        else {
+            continue;
+        };
+        reports.push(AstroReport {
+            tick: sim_state.tick,
+            severity: ReportSeverity::Info,
+            summary: format!(
+                "tidal stripping: {count} stars lost from galaxy #{} near #{perturber}",
+                galaxy.id
+            ),
+            position: Some(galaxy.center),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::agents::events::GalaxyMergerEvent;
+    use crate::app::ResetUniverse;
+    use crate::astro::galaxy::GALAXY_ROTATION_AXIS;
+    use crate::astro::star::EMISSIVE_FLOOR;
+    use crate::experiment::headless::HeadlessRun;
+    use crate::pru::universe::UniverseSettings;
+
+    #[test]
+    fn clustered_fields_get_smaller_regions_than_uniform_ones() {
+        let uniform = clumping_factor(std::iter::repeat_n(1.0, 512));
+        // The same mass gathered into one cell in 64.
+        let clustered = clumping_factor((0..512).map(|i| if i % 64 == 0 { 64.0 } else { 0.0 }));
+        assert!((uniform - 1.0).abs() < 1e-6);
+        assert!((clustered - 64.0).abs() < 1e-3);
+
+        let base = 4;
+        let uniform_size = adaptive_region_size(base, uniform, None);
+        let clustered_size = adaptive_region_size(base, clustered, None);
+        assert!(
+            uniform_size > base,
+            "sparse fields grow, got {uniform_size}"
+        );
+        assert!(uniform_size <= base * MAX_REGION_GROWTH);
+        assert!(
+            clustered_size < base,
+            "clustered fields shrink, got {clustered_size}"
+        );
+        assert_eq!(adaptive_region_size(base, REFERENCE_CLUMPING, None), base);
+        assert_eq!(adaptive_region_size(base, 1e9, None), 1);
+    }
+
+    #[test]
+    fn adaptive_region_size_holds_near_a_rounding_midpoint() {
+        let base = 4;
+        // Clumping at which the ideal, unrounded region size is `size`.
+        let clumping_for = |size: f32| REFERENCE_CLUMPING * (base as f32 / size).powi(3);
+
+        // A field hovering around the 3/4 midpoint keeps whichever size it has.
+        let mut size = adaptive_region_size(base, clumping_for(3.4), None);
+        assert_eq!(size, 3);
+        for ideal in [3.55, 3.45, 3.6, 3.4, 3.7] {
+            size = adaptive_region_size(base, clumping_for(ideal), Some(size));
+            assert_eq!(size, 3, "ideal {ideal}");
+        }
+        size = adaptive_region_size(base, clumping_for(3.8), Some(size));
+        assert_eq!(size, 4);
+        for ideal in [3.45, 3.3, 3.55] {
+            size = adaptive_region_size(base, clumping_for(ideal), Some(size));
+            assert_eq!(size, 4, "ideal {ideal}");
+        }
+        assert_eq!(adaptive_region_size(base, clumping_for(3.2), Some(size)), 3);
+        // Large swings still move straight to the new size, within the clamp.
+        assert_eq!(adaptive_region_size(base, clumping_for(1.6), Some(4)), 2);
+        assert_eq!(adaptive_region_size(base, 1.0, Some(20)), 6);
+    }
+
+    #[test]
+    fn spatial_hash_queries_match_a_brute_force_scan() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut random_point = || {
+            Vec3::new(
+                rng.gen_range(-10.0..10.0),
+                rng.gen_range(-10.0..10.0),
+                rng.gen_range(-3.0..3.0),
+            )
+        };
+        let stars: Vec<Vec3> = (0..300).map(|_| random_point()).collect();
+        let black_holes: Vec<Vec3> = (0..40).map(|_| random_point()).collect();
+        let planets: Vec<Vec3> = (0..120).map(|_| random_point()).collect();
+        let queries: Vec<Vec3> = (0..200).map(|_| random_point()).collect();
+        let brute = |points: &[Vec3], position: Vec3, radius: f32| {
+            points.iter().any(|p| p.distance(position) < radius)
+        };
+
+        // Radii below, at, and well above the bucket size all visit the right buckets.
+        let mut hash = FormationSpatialHash::default();
+        for cell_size in [0.5, 1.5, 4.0] {
+            hash.rebuild_stars(cell_size, stars.iter().copied());
+            hash.rebuild_black_holes(cell_size, black_holes[..20].iter().copied());
+            for &position in &black_holes[20..] {
+                hash.insert_black_hole(position);
+            }
+            hash.rebuild_planets(cell_size, planets.iter().copied());
+            for &position in &queries {
+                for radius in [0.3, 1.5, 3.7] {
+                    let context = format!("cell {cell_size}, radius {radius} at {position}");
+                    assert_eq!(
+                        hash.star_within(position, radius),
+                        brute(&stars, position, radius),
+                        "stars, {context}"
+                    );
+                    assert_eq!(
+                        hash.black_hole_within(position, radius),
+                        brute(&black_holes, position, radius),
+                        "black holes, {context}"
+                    );
+                    assert_eq!(
+                        hash.planet_within(position, radius),
+                        brute(&planets, position, radius),
+                        "planets, {context}"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Astro assets with default handles, for worlds that never render.
+    fn placeholder_assets() -> AstroAssets {
+        AstroAssets {
+            star_mesh: Handle::default(),
+            black_hole_mesh: Handle::default(),
+            black_hole_material: Handle::default(),
+            planet_mesh: Handle::default(),
+            planet_material: Handle::default(),
+            halo_mesh: Handle::default(),
+            halo_material: Handle::default(),
+        }
+    }
+
+    /// A galaxy scan task that has already finished with `regions`.
+    fn finished_scan(region_size: u32, regions: HashMap<UVec3, GalaxyRegion>) -> Task<GalaxyScan> {
+        let pool = AsyncComputeTaskPool::get_or_init(Default::default);
+        let scan = GalaxyScan {
+            generation: 0,
+            region_size,
+            regions,
+        };
+        let task = pool.spawn(async move { scan });
+        while !task.is_finished() {
+            std::thread::yield_now();
+        }
+        task
+    }
+
+    /// World holding a finished galaxy scan of one region, ready for [`identify_galaxies`].
+    fn world_with_scanned_region(region: GalaxyRegion, settings: FormationSettings) -> World {
+        let task = finished_scan(settings.region_size, HashMap::from([(UVec3::ZERO, region)]));
+        world_with_scan(task, settings)
+    }
+
+    /// World holding `task` as the pending galaxy scan, ready for [`identify_galaxies`].
+    fn world_with_scan(task: Task<GalaxyScan>, settings: FormationSettings) -> World {
+        let mut world = World::new();
+        world.insert_resource(SimulationState {
+            steps_this_frame: 1,
+            ..Default::default()
+        });
+        world.insert_resource(PruUniverse::new(UVec3::splat(8), 1.0));
+        world.insert_resource(settings);
+        world.insert_resource(GalaxyScanTask {
+            task: Some(task),
+            keyed_region_size: None,
+        });
+        world.init_resource::<GalaxyIdCounter>();
+        world.insert_resource(placeholder_assets());
+        world.init_resource::<SpatialOctree>();
+        world.init_resource::<Events<GalaxyMergerEvent>>();
+        world
+    }
+
+    #[test]
+    fn new_galaxies_get_a_central_black_hole_scaled_to_their_mass() {
+        let settings = FormationSettings {
+            seed_central_black_holes: true,
+            ..Default::default()
+        };
+        let ratio = settings.central_black_hole_mass_ratio;
+        let mass = settings.min_galaxy_mass * 4.0;
+        let region = GalaxyRegion {
+            mass,
+            center: Vec3::new(1.0, -0.5, 0.25),
+            radius: 3.0,
+            num_stars: settings.min_member_stars,
+        };
+
+        let mut world = world_with_scanned_region(region, settings.clone());
+        world.run_system_once(identify_galaxies);
+        let holes: Vec<(BlackHole, Vec3)> = world
+            .query::<(&BlackHole, &Transform)>()
+            .iter(&world)
+            .map(|(hole, transform)| (hole.clone(), transform.translation))
+            .collect();
+        assert_eq!(world.query::<&Galaxy>().iter(&world).count(), 1);
+        assert_eq!(holes.len(), 1);
+        assert!((holes[0].0.mass - mass * ratio).abs() < 1e-4);
+        assert_eq!(holes[0].1, region.center);
+
+        // Without the setting the galaxy forms on its own.
+        let mut world = world_with_scanned_region(region, FormationSettings::default());
+        world.run_system_once(identify_galaxies);
+        assert_eq!(world.query::<&Galaxy>().iter(&world).count(), 1);
+        assert_eq!(world.query::<&BlackHole>().iter(&world).count(), 0);
+    }
+
+    #[test]
+    fn black_holes_skip_nearby_stars_or_swallow_them_when_collapse_is_on() {
+        // Returns (black hole masses, stars left, reports logged, mass from density alone).
+        let run = |collapse: bool| {
+            let settings = FormationSettings {
+                collapse_stars_into_black_holes: collapse,
+                ..Default::default()
+            };
+            let mut world = star_formation_world(&settings);
+            world.resource_mut::<SimulationState>().tick = settings.black_hole_interval;
+            world.init_resource::<Events<SupernovaEvent>>();
+            world.init_resource::<AstroReportLog>();
+            let density = settings.black_hole_density_threshold * 2.0;
+            world.spawn((
+                PruCell::new(Vec3::ZERO, UVec3::ZERO, 1.0, 0.0),
+                DerivedFields {
+                    local_density: density,
+                    curvature_proxy: settings.black_hole_curvature_threshold * 2.0,
+                    ..Default::default()
+                },
+            ));
+            world.spawn((
+                Star {
+                    mass: 3.0,
+                    radius: 0.3,
+                    temperature: 5800.0,
+                    luminosity: 1.0,
+                    metallicity: SOLAR_METALLICITY,
+                },
+                Transform::from_translation(Vec3::X * settings.black_hole_star_clearance * 0.5),
+            ));
+            world.run_system_once(spawn_black_holes_from_density);
+            let holes: Vec<f32> = world
+                .query::<&BlackHole>()
+                .iter(&world)
+                .map(|bh| bh.mass)
+                .collect();
+            let stars = world.query::<&Star>().iter(&world).count();
+            let reports = world.resource::<AstroReportLog>().reports.len();
+            (holes, stars, reports, density * 4.0)
+        };
+
+        let (holes, stars, reports, _) = run(false);
+        assert!(
+            holes.is_empty(),
+            "a candidate beside a star must be skipped"
+        );
+        assert_eq!(stars, 1);
+        assert_eq!(reports, 0);
+
+        let (holes, stars, reports, base_mass) = run(true);
+        assert_eq!(holes.len(), 1);
+        assert!((holes[0] - (base_mass + 3.0)).abs() < 1e-3, "{holes:?}");
+        assert_eq!(stars, 0, "the star is consumed");
+        assert_eq!(reports, 1);
+    }
+
+    #[test]
+    fn changing_the_region_size_mid_run_keeps_one_galaxy_per_region_and_all_the_mass() {
+        // Point clumps of mass, one star each; three share a region at size 4
+        // but sit in separate ones at size 2.
+        let clumps = [
+            (UVec3::new(1, 1, 1), 1.0),
+            (UVec3::new(2, 1, 1), 5.0),
+            (UVec3::new(1, 2, 1), 2.0),
+            (UVec3::new(6, 6, 6), 4.0),
+        ];
+        let total: f32 = clumps.iter().map(|(_, mass)| mass).sum();
+        let universe = PruUniverse::new(UVec3::splat(8), 1.0);
+        let scan_with = |region_size: u32| {
+            let mut regions: HashMap<UVec3, GalaxyRegion> = HashMap::new();
+            for (coords, mass) in clumps {
+                let region = regions.entry(coords / region_size).or_insert(GalaxyRegion {
+                    mass: 0.0,
+                    center: Vec3::ZERO,
+                    radius: 1.0,
+                    num_stars: 0,
+                });
+                region.center = (region.center * region.mass
+                    + universe.rest_position(coords) * mass)
+                    / (region.mass + mass);
+                region.mass += mass;
+                region.num_stars += 1;
+            }
+            let count = regions.len();
+            (finished_scan(region_size, regions), count)
+        };
+
+        let settings = FormationSettings {
+            min_galaxy_mass: 0.0,
+            min_member_stars: 0,
+            ..Default::default()
+        };
+        let (task, _) = scan_with(2);
+        let mut world = world_with_scan(task, settings);
+        world.run_system_once(identify_galaxies);
+        // The three clumps that merge at size 4 are every galaxy but the far one's.
+        let far_key = UVec3::splat(6) / 2;
+        let oldest_of_the_three = world
+            .query::<&Galaxy>()
+            .iter(&world)
+            .filter(|galaxy| galaxy.region_key != far_key)
+            .map(|galaxy| galaxy.id)
+            .min();
+
+        for region_size in [4, 2] {
+            let (task, regions) = scan_with(region_size);
+            world.resource_mut::<GalaxyScanTask>().task = Some(task);
+            world.run_system_once(identify_galaxies);
+            let found: Vec<Galaxy> = world.query::<&Galaxy>().iter(&world).cloned().collect();
+
+            let mut keys: Vec<[u32; 3]> = found
+                .iter()
+                .map(|galaxy| galaxy.region_key.to_array())
+                .collect();
+            keys.sort_unstable();
+            keys.dedup();
+            assert_eq!(
+                keys.len(),
+                found.len(),
+                "duplicate galaxies at size {region_size}"
+            );
+            assert_eq!(
+                found.len(),
+                regions,
+                "one galaxy per region at size {region_size}"
+            );
+            let mass: f32 = found.iter().map(|galaxy| galaxy.total_mass).sum();
+            assert!(
+                (mass - total).abs() < 1e-4,
+                "mass {mass} at size {region_size}"
+            );
+            assert!(
+                found
+                    .iter()
+                    .any(|galaxy| Some(galaxy.id) == oldest_of_the_three),
+                "the merged galaxy keeps the oldest id"
+            );
+        }
+    }
+
+    #[test]
+    fn region_size_folds_are_reported_not_sent_as_mergers() {
+        let universe = PruUniverse::new(UVec3::splat(8), 1.0);
+        // Neighboring regions at size 2 that share one region at size 4.
+        let scan = |region_size: u32| {
+            let regions: HashMap<UVec3, GalaxyRegion> = [UVec3::new(1, 1, 1), UVec3::new(2, 1, 1)]
+                .into_iter()
+                .map(|coords| {
+                    let region = GalaxyRegion {
+                        mass: 1.0,
+                        center: universe.rest_position(coords),
+                        radius: 1.0,
+                        num_stars: 1,
+                    };
+                    (coords / region_size, region)
+                })
+                .collect();
+            finished_scan(region_size, regions)
+        };
+        let settings = FormationSettings {
+            min_galaxy_mass: 0.0,
+            min_member_stars: 0,
+            ..Default::default()
+        };
+        let mut world = world_with_scan(scan(2), settings);
+        world.init_resource::<AstroReportLog>();
+        world.run_system_once(identify_galaxies);
+        assert_eq!(world.query::<&Galaxy>().iter(&world).count(), 2);
+
+        world.resource_mut::<GalaxyScanTask>().task = Some(scan(4));
+        world.run_system_once(identify_galaxies);
+        assert_eq!(world.query::<&Galaxy>().iter(&world).count(), 1);
+        assert!(world.resource::<Events<GalaxyMergerEvent>>().is_empty());
+        let reports = &world.resource::<AstroReportLog>().reports;
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].severity, ReportSeverity::Info);
+        assert!(
+            reports[0].summary.contains("re-key"),
+            "{}",
+            reports[0].summary
+        );
+    }
+
+    #[test]
+    fn massive_regions_without_enough_stars_stay_galaxy_free() {
+        let settings = FormationSettings {
+            min_member_stars: 5,
+            ..Default::default()
+        };
+        let region = |num_stars| GalaxyRegion {
+            mass: settings.min_galaxy_mass * 10.0,
+            center: Vec3::ZERO,
+            radius: 3.0,
+            num_stars,
+        };
+
+        let mut world = world_with_scanned_region(region(4), settings.clone());
+        world.run_system_once(identify_galaxies);
+        assert_eq!(world.query::<&Galaxy>().iter(&world).count(), 0);
+
+        let mut world = world_with_scanned_region(region(5), settings);
+        world.run_system_once(identify_galaxies);
+        assert_eq!(world.query::<&Galaxy>().iter(&world).count(), 1);
+    }
+
+    #[test]
+    fn formation_keeps_its_cadence_after_a_reset() {
+        let mut run = HeadlessRun::new(|world| {
+            world.resource_mut::<UniverseSettings>().grid_dimensions = UVec3::splat(6);
+        });
+        let interval = run
+            .world_mut()
+            .resource::<FormationSettings>()
+            .star_interval;
+        run.step(3 * interval as u32);
+        let before = run
+            .world_mut()
+            .resource::<FormationSchedule>()
+            .last_star_tick;
+        assert!(before >= 2 * interval);
+
+        run.world_mut().send_event(ResetUniverse::default());
+        run.step(1);
+        assert!(run.tick() < before, "the reset should rewind the clock");
+        run.step(2 * interval as u32);
+        let tick = run.tick();
+        let last = run
+            .world_mut()
+            .resource::<FormationSchedule>()
+            .last_star_tick;
+        assert!(
+            last <= tick && tick - last < interval,
+            "star pass last ran at {last}, now tick {tick}"
+        );
+    }
+
+    /// World on a star-formation tick, ready for [`spawn_stars_from_density`].
+    fn star_formation_world(settings: &FormationSettings) -> World {
+        let mut world = World::new();
+        world.insert_resource(SimulationState {
+            steps_this_frame: 1,
+            tick: settings.star_interval,
+            ..Default::default()
+        });
+        world.insert_resource(PruUniverse::new(UVec3::splat(8), 1.0));
+        world.insert_resource(placeholder_assets());
+        world.init_resource::<Assets<StandardMaterial>>();
+        world.init_resource::<StarEmissive>();
+        world.init_resource::<FormationSchedule>();
+        world.init_resource::<PendingFormation>();
+        world.init_resource::<FormationSpatialHash>();
+        world.init_resource::<FormationRateMonitor>();
+        world.init_resource::<SpatialOctree>();
+        world.init_resource::<GravityParams>();
+        world.insert_resource(settings.clone());
+        world
+    }
+
+    #[test]
+    fn emissive_scale_brightens_new_stars_proportionally() {
+        let settings = FormationSettings::default();
+        // Emissive above the luminosity-independent floor, per unit of base color.
+        let star_glow = |scale: f32| {
+            let mut world = star_formation_world(&settings);
+            world.insert_resource(StarEmissive {
+                scale,
+                exponent: 1.0,
+            });
+            world.spawn((
+                PruCell::new(Vec3::ZERO, UVec3::ZERO, 1.0, 0.0),
+                DerivedFields {
+                    local_density: settings.star_density_threshold * 2.0,
+                    ..Default::default()
+                },
+            ));
+            world.run_system_once(spawn_stars_from_density);
+            let handle = world
+                .query_filtered::<&Handle<StandardMaterial>, With<Star>>()
+                .single(&world)
+                .clone();
+            let material = world
+                .resource::<Assets<StandardMaterial>>()
+                .get(&handle)
+                .unwrap();
+            let base = material.base_color.to_linear();
+            material.emissive.red / base.red - EMISSIVE_FLOOR
+        };
+        let single = star_glow(0.2);
+        let double = star_glow(0.4);
+        assert!(single > 0.0);
+        assert!((double / single - 2.0).abs() < 1e-4, "{single} -> {double}");
+    }
+
+    #[test]
+    fn each_structure_kind_runs_on_its_own_cadence() {
+        let (stars, holes, galaxies) = (5, 7, 11);
+        let mut run = HeadlessRun::new(|world| {
+            world.resource_mut::<UniverseSettings>().grid_dimensions = UVec3::splat(6);
+            let mut settings = world.resource_mut::<FormationSettings>();
+            settings.star_interval = stars;
+            settings.black_hole_interval = holes;
+            settings.galaxy_interval = galaxies;
+            settings.star_density_threshold = 0.0;
+        });
+
+        let mut passes: [Vec<u64>; 3] = Default::default();
+        let mut last = [0; 3];
+        for _ in 0..40 {
+            run.step(1);
+            if run.tick() < stars {
+                assert_eq!(run.star_count(), 0, "stars before their first pass");
+            }
+            let schedule = run.world_mut().resource::<FormationSchedule>();
+            let now = [
+                schedule.last_star_tick,
+                schedule.last_black_hole_tick,
+                schedule.last_galaxy_tick,
+            ];
+            for kind in 0..3 {
+                if now[kind] != last[kind] {
+                    passes[kind].push(now[kind]);
+                }
+            }
+            last = now;
+        }
+        let multiples = |interval: u64| (1..).map(move |n| n * interval).take_while(|&t| t <= 40);
+        assert_eq!(passes[0], multiples(stars).collect::<Vec<_>>());
+        assert_eq!(passes[1], multiples(holes).collect::<Vec<_>>());
+        assert_eq!(passes[2], multiples(galaxies).collect::<Vec<_>>());
+        assert!(run.star_count() > 0);
+    }
+
+    #[test]
+    fn the_dense_preset_forms_at_least_three_times_the_stars_of_sparse() {
+        let stars_after_500_ticks = |preset| {
+            let mut run = HeadlessRun::new(|world| {
+                world.resource_mut::<UniverseSettings>().grid_dimensions = UVec3::splat(5);
+                world.insert_resource(FormationSettings::from_preset(preset));
+            });
+            run.step(500);
+            run.star_count()
+        };
+        // Same lattice seed for both; each run on its own thread, as sweeps do.
+        let (sparse, dense) = std::thread::scope(|scope| {
+            let sparse = scope.spawn(|| stars_after_500_ticks(FormationPreset::Sparse));
+            let dense = stars_after_500_ticks(FormationPreset::Dense);
+            (sparse.join().unwrap(), dense)
+        });
+        assert!(sparse > 0);
+        assert!(dense >= 3 * sparse, "dense {dense} vs sparse {sparse}");
+    }
+
+    #[test]
+    fn stars_forming_at_a_galaxy_edge_start_on_a_circular_orbit() {
+        let settings = FormationSettings {
+            assign_galaxy_orbits: true,
+            ..Default::default()
+        };
+        let gravity = GravityParams::default();
+        let center = Vec3::new(0.5, 0.0, -0.5);
+        let edge = center + Vec3::new(2.85, 0.0, 0.0);
+        let (core_mass, cell_mass) = (40.0, 2.0);
+
+        let mut world = star_formation_world(&settings);
+        world.insert_resource(gravity.clone());
+        world.spawn(Galaxy {
+            id: 1,
+            total_mass: core_mass,
+            radius: 3.0,
+            num_stars: 0,
+            center,
+            region_key: UVec3::ZERO,
+        });
+        let core = world
+            .spawn(PruDynamics {
+                mass: core_mass,
+                ..Default::default()
+            })
+            .id();
+        let cell = world
+            .spawn((
+                PruCell::new(edge, UVec3::ZERO, 1.0, 0.0),
+                DerivedFields {
+                    local_density: settings.star_density_threshold,
+                    ..Default::default()
+                },
+                PruDynamics {
+                    mass: cell_mass,
+                    ..Default::default()
+                },
+            ))
+            .id();
+        let mut octree = SpatialOctree::default();
+        octree.rebuild(vec![(core, center), (cell, edge)]);
+        world.insert_resource(octree);
+
+        world.run_system_once(spawn_stars_from_density);
+        let (_, dynamics) = world.query::<(&Star, &PruDynamics)>().single(&world);
+        let radius = edge.distance(center);
+        let offset = edge - center;
+        let expected_speed = (gravity.g_effective * (core_mass + cell_mass) / radius).sqrt();
+        assert!((dynamics.velocity.length() - expected_speed).abs() < 1e-4);
+        assert!(dynamics.velocity.dot(offset).abs() < 1e-4, "not tangential");
+        assert!(dynamics.velocity.dot(GALAXY_ROTATION_AXIS).abs() < 1e-4);
+        // Prograde about the rotation axis.
+        assert!(offset.cross(dynamics.velocity).dot(GALAXY_ROTATION_AXIS) > 0.0);
+        assert_eq!(dynamics.velocity_at_integer_tick, dynamics.velocity);
     }
 }
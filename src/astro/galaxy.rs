@@ -1,5 +1,10 @@
+use std::collections::HashMap;
+use std::fmt;
+
 use bevy::prelude::*;
 
+use crate::tracked::TrackedEntity;
+
 /// A galaxy as a higher-level structure, linked to a region of the PRU lattice.
 #[derive(Component, Debug, Clone)]
 pub struct Galaxy {
@@ -12,6 +17,184 @@ pub struct Galaxy {
     pub region_key: UVec3,
 }
 
+impl fmt::Display for Galaxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Galaxy#{} m={:.1} r={:.1} *={}",
+            self.id, self.total_mass, self.radius, self.num_stars
+        )
+    }
+}
+
+/// Axis galaxies rotate about when stars are given orbits, matching the default camera pole.
+pub const GALAXY_ROTATION_AXIS: Vec3 = Vec3::Y;
+
+impl Galaxy {
+    /// Whether `position` lies within the galaxy's radius.
+    pub fn contains(&self, position: Vec3) -> bool {
+        position.distance_squared(self.center) < self.radius * self.radius
+    }
+
+    /// Whether a cell at `grid_coords` is one the galaxy scan counts toward this galaxy.
+    ///
+    /// Scans key overdense cells by `grid_coords / region_size`, so a member
+    /// is dense enough and falls in the galaxy's region.
+    pub fn claims(
+        &self,
+        grid_coords: UVec3,
+        local_density: f32,
+        density_threshold: f32,
+        region_size: u32,
+    ) -> bool {
+        local_density >= density_threshold && grid_coords / region_size.max(1) == self.region_key
+    }
+}
+
+/// The galaxy picked in the agents panel or with Tab.
+///
+/// Shared by the galaxy debug overlay, the follow camera, and the cell
+/// inspector. Cleared once the galaxy is gone.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SelectedGalaxy(pub Option<Entity>);
+
+/// Drop the selection once its galaxy has merged away or the universe was reset.
+pub fn clear_stale_galaxy_selection(
+    mut selected: ResMut<SelectedGalaxy>,
+    galaxies: Query<(), With<Galaxy>>,
+) {
+    if selected.0.is_some_and(|entity| !galaxies.contains(entity)) {
+        selected.0 = None;
+    }
+}
+
+/// The galaxy a star belongs to.
+///
+/// Stars join the nearest galaxy whose halo they sit in and keep belonging to
+/// it as they move. Tidal stripping clears the link for good, leaving the
+/// star to drift as a field star.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct StarGalaxyMembership {
+    pub galaxy: TrackedEntity,
+    /// Set once tides pulled the star out; stripped stars never rejoin a galaxy.
+    pub stripped: bool,
+}
+
+impl StarGalaxyMembership {
+    pub fn strip(&mut self) {
+        self.galaxy.clear();
+        self.stripped = true;
+    }
+}
+
+/// Jacobi (tidal) radius of a satellite of `m_small` at distance `d` from a host of `m_large`.
+///
+/// Beyond `d * (m_small / (3 m_large))^(1/3)` from the satellite's center, the
+/// host's tide outpulls the satellite's own gravity.
+pub fn jacobi_radius(distance: f32, m_small: f32, m_large: f32) -> f32 {
+    if m_large <= 0.0 {
+        return f32::INFINITY;
+    }
+    distance * (m_small.max(0.0) / (3.0 * m_large)).cbrt()
+}
+
+/// Velocity of a circular orbit about `center` at `position` for `enclosed_mass`.
+///
+/// The speed is `sqrt(G M / r)` for the mass enclosed within `r`, and the
+/// direction is tangential, prograde about [`GALAXY_ROTATION_AXIS`]. Positions on the axis (or at the center) get no
+/// velocity, since no orbital plane is defined there.
+pub fn circular_orbit_velocity(
+    center: Vec3,
+    position: Vec3,
+    enclosed_mass: f32,
+    g_effective: f32,
+) -> Vec3 {
+    let offset = position - center;
+    // Perpendicular to the offset, so off-plane stars orbit the center on a tilted circle.
+    let tangent = GALAXY_ROTATION_AXIS.cross(offset).normalize_or_zero();
+    let radius = offset.length();
+    if tangent == Vec3::ZERO || radius <= f32::EPSILON {
+        return Vec3::ZERO;
+    }
+    tangent * (g_effective * enclosed_mass.max(0.0) / radius).sqrt()
+}
+
+/// Uniform hash over galaxy halos for point-in-halo lookups.
+///
+/// Buckets are as wide as the largest halo, so a point can only lie inside
+/// halos centered in its own bucket or one of the 26 around it.
+pub struct HaloIndex<T> {
+    bucket_size: f32,
+    halos: Vec<(Vec3, f32, T)>,
+    buckets: HashMap<IVec3, Vec<usize>>,
+}
+
+impl<T> HaloIndex<T> {
+    /// Index `(center, radius, payload)` halos.
+    pub fn new(halos: Vec<(Vec3, f32, T)>) -> Self {
+        let bucket_size = halos
+            .iter()
+            .map(|(_, radius, _)| *radius)
+            .fold(f32::EPSILON, f32::max);
+        let mut buckets: HashMap<IVec3, Vec<usize>> = HashMap::new();
+        for (index, (center, _, _)) in halos.iter().enumerate() {
+            buckets
+                .entry(bucket_of(*center, bucket_size))
+                .or_default()
+                .push(index);
+        }
+        Self {
+            bucket_size,
+            halos,
+            buckets,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.halos.len()
+    }
+
+    /// The indexed halos, in insertion order.
+    pub fn into_halos(self) -> Vec<(Vec3, f32, T)> {
+        self.halos
+    }
+
+    /// Indices of the halos containing `position`, with its squared distance to each center.
+    pub fn containing(&self, position: Vec3) -> impl Iterator<Item = (usize, f32)> + '_ {
+        let bucket = bucket_of(position, self.bucket_size);
+        NEIGHBOR_OFFSETS
+            .iter()
+            .filter_map(move |offset| self.buckets.get(&(bucket + *offset)))
+            .flatten()
+            .filter_map(move |&index| {
+                let (center, radius, _) = &self.halos[index];
+                let distance2 = center.distance_squared(position);
+                (distance2 < radius * radius).then_some((index, distance2))
+            })
+    }
+
+    /// Payload of the halo whose center is nearest among those containing `position`.
+    pub fn nearest_containing(&self, position: Vec3) -> Option<&T> {
+        self.containing(position)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(index, _)| &self.halos[index].2)
+    }
+}
+
+fn bucket_of(position: Vec3, bucket_size: f32) -> IVec3 {
+    (position / bucket_size).floor().as_ivec3()
+}
+
+const NEIGHBOR_OFFSETS: [IVec3; 27] = {
+    let mut offsets = [IVec3::ZERO; 27];
+    let mut i = 0;
+    while i < 27 {
+        offsets[i] = IVec3::new(i as i32 % 3 - 1, i as i32 / 3 % 3 - 1, i as i32 / 9 - 1);
+        i += 1;
+    }
+    offsets
+};
+
 #[derive(Resource, Default)]
 pub struct GalaxyIdCounter {
     pub next_id: u32,
@@ -24,3 +207,35 @@ impl GalaxyIdCounter {
         id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astro::black_hole::BlackHole;
+    use crate::astro::star::Star;
+
+    #[test]
+    fn structures_display_their_key_quantities() {
+        let galaxy = Galaxy {
+            id: 7,
+            total_mass: 1234.56,
+            radius: 5.24,
+            num_stars: 42,
+            center: Vec3::ZERO,
+            region_key: UVec3::ZERO,
+        };
+        assert_eq!(galaxy.to_string(), "Galaxy#7 m=1234.6 r=5.2 *=42");
+
+        let star = Star {
+            mass: 1.0,
+            radius: 0.125,
+            temperature: 5772.4,
+            luminosity: 1.004,
+            metallicity: 0.02,
+        };
+        assert_eq!(star.to_string(), "Star T=5772K L=1.00 R=0.125 Z=0.020");
+
+        let hole = BlackHole::new(12.5, 0.625, -0.3);
+        assert_eq!(hole.to_string(), "BH m=12.50 r=0.625 s=-0.300");
+    }
+}
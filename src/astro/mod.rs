@@ -7,11 +7,15 @@
 
 use bevy::prelude::*;
 
-use crate::pru::universe::compute_derived_fields;
+use crate::pru::igm::apply_igm_pressure;
+use crate::spatial::update_spatial_octree;
 
+pub mod assets;
+pub mod binary;
 pub mod black_hole;
 pub mod formation;
 pub mod galaxy;
+pub mod planet;
 pub mod star;
 
 pub struct AstroPlugin;
@@ -19,19 +23,46 @@ pub struct AstroPlugin;
 impl Plugin for AstroPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<formation::FormationSettings>()
+            .init_resource::<binary::BinarySettings>()
             .init_resource::<formation::FormationSchedule>()
+            .init_resource::<formation::GalaxyScanTask>()
+            .init_resource::<formation::PendingFormation>()
+            .init_resource::<formation::FormationSpatialHash>()
+            .init_resource::<formation::FormationRateMonitor>()
+            .init_resource::<formation::StructureCounts>()
+            .init_resource::<formation::FormationActivity>()
             .init_resource::<galaxy::GalaxyIdCounter>()
+            .init_resource::<galaxy::SelectedGalaxy>()
+            .init_resource::<star::StarEmissive>()
+            .add_event::<binary::CompactBinaryMerger>()
+            .add_event::<formation::SupernovaEvent>()
+            .add_systems(Startup, assets::setup_astro_assets)
             .add_systems(
                 Update,
                 (
+                    formation::rewind_formation_schedule,
+                    formation::bin_formation_rates,
                     formation::spawn_stars_from_density,
                     formation::spawn_black_holes_from_density,
+                    planet::spawn_planets_from_density,
+                    black_hole::black_hole_accretion,
+                    binary::capture_compact_binaries,
+                    binary::evolve_compact_binaries,
+                    star::stellar_enrichment,
+                    star::refresh_star_emissive,
+                    formation::dispatch_galaxy_scan,
                     formation::identify_galaxies,
+                    galaxy::clear_stale_galaxy_selection,
+                    formation::assign_galaxy_membership,
+                    formation::tidal_stripping,
+                    apply_igm_pressure,
+                    planet::orbit_planets,
                     star::animate_stars,
                     black_hole::animate_black_holes,
+                    formation::track_structure_counts,
                 )
                     .chain()
-                    .after(compute_derived_fields),
+                    .after(update_spatial_octree),
             );
     }
 }
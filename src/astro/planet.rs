@@ -0,0 +1,302 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::app::SimulationState;
+use crate::pru::cell::{DerivedFields, PruCell, PruDynamics};
+use crate::pru::gravity::GravityParams;
+use crate::pru::universe::{FieldMetrics, PruUniverse};
+use crate::spatial::SpatialOctree;
+use crate::tracked::TrackedEntity;
+
+use super::assets::AstroAssets;
+use super::formation::{FormationSchedule, FormationSettings, FormationSpatialHash};
+use super::star::Star;
+
+/// Planet mass per unit of source-cell density.
+const PLANET_MASS_PER_DENSITY: f32 = 0.01;
+
+/// A sub-stellar body on a circular orbit about its host star.
+///
+/// Planets are kinematic: they follow their host rather than feeling the
+/// lattice, so they carry no `PruDynamics` and never enter the gravity step.
+#[derive(Component, Debug, Clone)]
+pub struct Planet {
+    pub mass: f32,
+    pub orbital_radius: f32,
+    pub host_star: TrackedEntity,
+    /// Host-to-planet offset at zero phase; its length is `orbital_radius`.
+    pub offset: Vec3,
+    /// Orbit normal, perpendicular to `offset`.
+    pub axis: Vec3,
+    /// Radians per second.
+    pub angular_speed: f32,
+    pub phase: f32,
+}
+
+impl fmt::Display for Planet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Planet m={:.3} a={:.2} ω={:.2}",
+            self.mass, self.orbital_radius, self.angular_speed
+        )
+    }
+}
+
+/// Planets orbiting a star, in formation order.
+#[derive(Component, Debug, Clone, Default)]
+pub struct PlanetarySystem {
+    pub planets: Vec<Entity>,
+}
+
+/// RMS deviation of `velocities` from their mean.
+pub fn velocity_dispersion(velocities: &[Vec3]) -> f32 {
+    if velocities.is_empty() {
+        return 0.0;
+    }
+    let count = velocities.len() as f32;
+    let mean = velocities.iter().copied().sum::<Vec3>() / count;
+    (velocities
+        .iter()
+        .map(|v| v.distance_squared(mean))
+        .sum::<f32>()
+        / count)
+        .sqrt()
+}
+
+/// Form planets around stars from moderately dense, dynamically cold cells.
+///
+/// Runs on the star cadence. A cell qualifies when its density sits between
+/// `planet_density_fraction` of the lattice mean and the star threshold and
+/// the cells around it move together (velocity dispersion below
+/// `planet_max_velocity_dispersion`). The lower bound follows the mean, like
+/// the IGM cut, so it keeps its meaning as the field's density scale drifts.
+/// The planet orbits the nearest star within `planet_host_radius` at the
+/// cell's distance, with the Keplerian speed for the star's mass.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_planets_from_density(
+    mut commands: Commands,
+    universe: Res<PruUniverse>,
+    sim_state: Res<SimulationState>,
+    settings: Res<FormationSettings>,
+    gravity: Res<GravityParams>,
+    metrics: Res<FieldMetrics>,
+    assets: Res<AstroAssets>,
+    octree: Res<SpatialOctree>,
+    mut spatial_hash: ResMut<FormationSpatialHash>,
+    mut schedule: ResMut<FormationSchedule>,
+    cells: Query<(&PruCell, &DerivedFields)>,
+    velocities: Query<&PruDynamics, With<PruCell>>,
+    mut stars: Query<(&Star, &Transform, &mut PlanetarySystem)>,
+    planets: Query<&Transform, With<Planet>>,
+) {
+    if !sim_state.advanced_this_frame()
+        || sim_state.tick.saturating_sub(schedule.last_planet_tick) < settings.star_interval
+    {
+        return;
+    }
+    schedule.last_planet_tick = sim_state.tick;
+    let _span = info_span!("formation_planets").entered();
+
+    let neighborhood = universe.spacing * 1.5;
+    let avoidance_radius = universe.spacing * 0.5;
+    let density_floor = metrics.avg_density * settings.planet_density_fraction;
+    // Spawns are deferred, so this pass's planets are added to the hash as they are queued.
+    spatial_hash.rebuild_planets(
+        universe.spacing,
+        planets.iter().map(|transform| transform.translation),
+    );
+
+    for (cell, derived) in cells.iter() {
+        let density = derived.local_density;
+        if density <= density_floor || density >= settings.star_density_threshold {
+            continue;
+        }
+
+        let neighbors: Vec<Vec3> = octree
+            .query_radius(cell.position, neighborhood)
+            .into_iter()
+            .filter_map(|entity| velocities.get(entity).ok())
+            .map(|dynamics| dynamics.velocity_at_integer_tick)
+            .collect();
+        if velocity_dispersion(&neighbors) >= settings.planet_max_velocity_dispersion {
+            continue;
+        }
+
+        let host = octree
+            .query_radius(cell.position, settings.planet_host_radius)
+            .into_iter()
+            .filter_map(|entity| {
+                let (_, transform, _) = stars.get(entity).ok()?;
+                Some((
+                    entity,
+                    transform.translation.distance_squared(cell.position),
+                ))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(entity, _)| entity);
+        let Some(host) = host else {
+            continue;
+        };
+        let Ok((star, star_transform, mut system)) = stars.get_mut(host) else {
+            continue;
+        };
+        if system.planets.len() >= settings.max_planets_per_star {
+            continue;
+        }
+
+        if spatial_hash.planet_within(cell.position, avoidance_radius) {
+            continue;
+        }
+
+        let offset = cell.position - star_transform.translation;
+        let orbital_radius = offset.length();
+        if orbital_radius <= star.radius {
+            continue;
+        }
+        let axis = (offset / orbital_radius).any_orthonormal_vector();
+        let angular_speed = (gravity.g_effective * star.mass / orbital_radius.powi(3))
+            .max(0.0)
+            .sqrt();
+        let mass = density * PLANET_MASS_PER_DENSITY;
+        let mut host_star = TrackedEntity::default();
+        host_star.set(host);
+
+        let planet = commands
+            .spawn((
+                PbrBundle {
+                    mesh: assets.planet_mesh.clone(),
+                    material: assets.planet_material.clone(),
+                    transform: Transform::from_translation(cell.position)
+                        .with_scale(Vec3::splat((mass * 2.0).clamp(0.05, 0.2))),
+                    ..Default::default()
+                },
+                Planet {
+                    mass,
+                    orbital_radius,
+                    host_star,
+                    offset,
+                    axis,
+                    angular_speed,
+                    phase: 0.0,
+                },
+                Name::new("Planet"),
+            ))
+            .id();
+        system.planets.push(planet);
+        spatial_hash.insert_planet(cell.position);
+    }
+}
+
+/// Advance each planet along its orbit and keep it on its (possibly moving) host.
+///
+/// Planets whose host star is gone are despawned, and host systems drop
+/// entries for planets that no longer exist.
+pub fn orbit_planets(
+    mut commands: Commands,
+    sim_state: Res<SimulationState>,
+    hosts: Query<&Transform, (With<Star>, Without<Planet>)>,
+    mut planets: Query<(Entity, &mut Planet, &mut Transform)>,
+    mut systems: Query<&mut PlanetarySystem>,
+) {
+    if !sim_state.advanced_this_frame() {
+        return;
+    }
+    let dt = sim_state.dt * sim_state.steps_this_frame as f32;
+
+    for (entity, mut planet, mut transform) in planets.iter_mut() {
+        let Some(host) = planet.host_star.get(&hosts).map(|host| host.translation) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        planet.phase = (planet.phase + planet.angular_speed * dt) % std::f32::consts::TAU;
+        transform.translation =
+            host + Quat::from_axis_angle(planet.axis, planet.phase) * planet.offset;
+    }
+
+    for mut system in systems.iter_mut() {
+        if system
+            .planets
+            .iter()
+            .any(|planet| !planets.contains(*planet))
+        {
+            system.planets.retain(|planet| planets.contains(*planet));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::astro::star::SOLAR_METALLICITY;
+
+    #[test]
+    fn cells_below_the_mean_density_form_no_planets() {
+        let settings = FormationSettings::default();
+        let mut world = World::new();
+        world.insert_resource(SimulationState {
+            steps_this_frame: 1,
+            tick: settings.star_interval,
+            ..Default::default()
+        });
+        world.insert_resource(PruUniverse::new(UVec3::splat(8), 1.0));
+        world.init_resource::<FieldMetrics>();
+        world.resource_mut::<FieldMetrics>().avg_density = 20.0;
+        world.insert_resource(AstroAssets {
+            star_mesh: Handle::default(),
+            black_hole_mesh: Handle::default(),
+            black_hole_material: Handle::default(),
+            planet_mesh: Handle::default(),
+            planet_material: Handle::default(),
+            halo_mesh: Handle::default(),
+            halo_material: Handle::default(),
+        });
+        world.init_resource::<GravityParams>();
+        world.init_resource::<FormationSchedule>();
+        world.init_resource::<FormationSpatialHash>();
+        world.insert_resource(settings.clone());
+
+        let star_position = Vec3::splat(4.0);
+        let star = world
+            .spawn((
+                Star {
+                    mass: 1.0,
+                    radius: 0.3,
+                    temperature: 5800.0,
+                    luminosity: 1.0,
+                    metallicity: SOLAR_METALLICITY,
+                },
+                Transform::from_translation(star_position),
+                PlanetarySystem::default(),
+            ))
+            .id();
+        // A quiet cell next to the star, well under the lattice mean.
+        let cell_position = star_position + Vec3::X;
+        let cell = world
+            .spawn((
+                PruCell::new(cell_position, UVec3::ZERO, 1.0, 0.0),
+                PruDynamics::default(),
+                DerivedFields {
+                    local_density: 0.6,
+                    ..Default::default()
+                },
+            ))
+            .id();
+        let mut octree = SpatialOctree::default();
+        octree.rebuild(vec![(star, star_position), (cell, cell_position)]);
+        world.insert_resource(octree);
+
+        world.run_system_once(spawn_planets_from_density);
+        assert_eq!(world.query::<&Planet>().iter(&world).count(), 0);
+
+        // Between the mean and the star threshold, the same cell forms one.
+        world.get_mut::<DerivedFields>(cell).unwrap().local_density = 25.0;
+        world.resource_mut::<SimulationState>().tick += settings.star_interval;
+        world.run_system_once(spawn_planets_from_density);
+        assert_eq!(world.query::<&Planet>().iter(&world).count(), 1);
+        assert_eq!(world.get::<PlanetarySystem>(star).unwrap().planets.len(), 1);
+    }
+}
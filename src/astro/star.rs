@@ -1,5 +1,54 @@
+use std::fmt;
+
 use bevy::prelude::*;
 
+use crate::app::SimulationState;
+use crate::astro::black_hole::BlackHole;
+use crate::astro::formation::FormationSettings;
+use crate::render::animation::{set_scale, AnimationClock, AnimationSettings, ScaleModifier};
+use crate::render::lod::LodState;
+use crate::spatial::SpatialOctree;
+
+/// Metal mass fraction of a newly formed star (solar metallicity).
+pub const SOLAR_METALLICITY: f32 = 0.02;
+/// Metallicity gained per unit `enrichment_yield` by each nearby event.
+const ENRICHMENT_PER_YIELD: f32 = 0.01;
+/// Fractional temperature drop per unit of metallicity gained (opacity cooling).
+const METALLICITY_COOLING: f32 = 2.0;
+/// Coolest temperature enrichment can push a star to.
+const MIN_ENRICHED_TEMPERATURE: f32 = 2500.0;
+/// Metallicity above solar at which a star's tint is fully gold.
+const METAL_RICH_SPAN: f32 = 0.08;
+/// Emissive multiplier every star gets regardless of luminosity.
+pub(crate) const EMISSIVE_FLOOR: f32 = 1.2;
+
+/// Mapping from star luminosity to material emissive strength.
+///
+/// The emissive multiplier is `1.2 + scale * luminosity^exponent`. Lowering
+/// the exponent compresses the bright end, which helps when a low formation
+/// threshold produces many faint stars next to a few very luminous ones.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct StarEmissive {
+    pub scale: f32,
+    pub exponent: f32,
+}
+
+impl Default for StarEmissive {
+    fn default() -> Self {
+        Self {
+            scale: 0.2,
+            exponent: 1.0,
+        }
+    }
+}
+
+impl StarEmissive {
+    /// Emissive multiplier applied to a star's base color.
+    pub fn factor(&self, luminosity: f32) -> f32 {
+        EMISSIVE_FLOOR + self.scale.max(0.0) * luminosity.max(0.0).powf(self.exponent)
+    }
+}
+
 /// A luminous star, emerging from high-density regions.
 #[derive(Component, Debug, Clone)]
 pub struct Star {
@@ -7,14 +56,48 @@ pub struct Star {
     pub radius: f32,
     pub temperature: f32,
     pub luminosity: f32,
+    /// Mass fraction of elements heavier than helium.
+    pub metallicity: f32,
+}
+
+impl Star {
+    /// Base and emissive colors for this star's material.
+    pub fn material_colors(&self, emissive: &StarEmissive) -> (Color, Color) {
+        let color = star_color(self.temperature, self.metallicity);
+        let emissive_scale = emissive.factor(self.luminosity);
+        (color, Color::LinearRgba(color.to_linear() * emissive_scale))
+    }
+}
+
+impl fmt::Display for Star {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Star T={:.0}K L={:.2} R={:.3} Z={:.3}",
+            self.temperature, self.luminosity, self.radius, self.metallicity
+        )
+    }
 }
 
 /// Simple flicker animation to keep stars visually alive.
-pub fn animate_stars(time: Res<Time>, mut query: Query<(&Star, &mut Transform)>) {
-    let phase = time.elapsed_seconds();
-    for (star, mut transform) in query.iter_mut() {
-        let jitter = (phase * 2.3 + star.radius).sin() * 0.02;
-        transform.scale = Vec3::splat((star.radius + jitter).max(0.05));
+pub fn animate_stars(
+    clock: Res<AnimationClock>,
+    animation: Res<AnimationSettings>,
+    mut query: Query<(
+        &Star,
+        Option<&LodState>,
+        Option<&ScaleModifier>,
+        &mut Transform,
+    )>,
+) {
+    let amplitude = animation.amplitude(animation.star_flicker);
+    for (star, lod, modifier, mut transform) in query.iter_mut() {
+        if lod.is_some_and(|lod| !lod.animate) {
+            continue;
+        }
+        let jitter = (clock.time * 2.3 + star.radius).sin() * 0.02 * amplitude;
+        let scale = (star.radius + jitter).max(0.05) * modifier.map_or(1.0, |m| m.0);
+        set_scale(&mut transform, Vec3::splat(scale));
     }
 }
 
@@ -31,3 +114,77 @@ pub fn star_color_from_temperature(temp: f32) -> Color {
         Color::srgb(0.9, 0.45, 0.35)
     }
 }
+
+/// Temperature color, shifted toward gold as metallicity rises above solar.
+pub fn star_color(temp: f32, metallicity: f32) -> Color {
+    let base = star_color_from_temperature(temp).to_linear();
+    let gold = Color::srgb(1.0, 0.78, 0.35).to_linear();
+    let t = ((metallicity - SOLAR_METALLICITY) / METAL_RICH_SPAN).clamp(0.0, 1.0);
+    Color::LinearRgba(base * (1.0 - t) + gold * t)
+}
+
+/// Enrich stars near black holes that accreted since the last pass.
+///
+/// Each accretion event adds `enrichment_yield * 0.01` to the metallicity of
+/// every star within `enrichment_radius` of the hole, cooling it slightly.
+#[allow(clippy::too_many_arguments)]
+pub fn stellar_enrichment(
+    sim_state: Res<SimulationState>,
+    settings: Res<FormationSettings>,
+    emissive: Res<StarEmissive>,
+    octree: Res<SpatialOctree>,
+    mut last_event_tick: Local<u64>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    holes: Query<(&BlackHole, &Transform)>,
+    mut stars: Query<(&mut Star, &Handle<StandardMaterial>)>,
+) {
+    if !sim_state.advanced_this_frame() {
+        return;
+    }
+    // Ticks restart from zero after a reset.
+    if sim_state.tick < *last_event_tick {
+        *last_event_tick = 0;
+    }
+
+    let since = *last_event_tick;
+    let gain = settings.enrichment_yield * ENRICHMENT_PER_YIELD;
+    for (bh, transform) in holes.iter() {
+        if bh.last_accretion_tick <= since || bh.last_accreted <= 0.0 {
+            continue;
+        }
+        *last_event_tick = (*last_event_tick).max(bh.last_accretion_tick);
+
+        for entity in octree.query_radius(transform.translation, settings.enrichment_radius) {
+            let Ok((mut star, material)) = stars.get_mut(entity) else {
+                continue;
+            };
+            star.metallicity += gain;
+            star.temperature = (star.temperature * (1.0 - METALLICITY_COOLING * gain))
+                .max(MIN_ENRICHED_TEMPERATURE);
+
+            if let Some(material) = materials.get_mut(material) {
+                let (base, glow) = star.material_colors(&emissive);
+                material.base_color = base;
+                material.emissive = glow.into();
+            }
+        }
+    }
+}
+
+/// Re-tint every star when the luminosity-to-emissive mapping is changed.
+pub fn refresh_star_emissive(
+    emissive: Res<StarEmissive>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    stars: Query<(&Star, &Handle<StandardMaterial>)>,
+) {
+    if !emissive.is_changed() || emissive.is_added() {
+        return;
+    }
+    for (star, material) in stars.iter() {
+        if let Some(material) = materials.get_mut(material) {
+            let (base, glow) = star.material_colors(&emissive);
+            material.base_color = base;
+            material.emissive = glow.into();
+        }
+    }
+}
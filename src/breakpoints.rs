@@ -0,0 +1,343 @@
+//! Breakpoints: pause the simulation at interesting moments.
+//!
+//! Each trigger in [`BreakpointSettings`] is off, armed once (it disarms after
+//! firing), or repeating. A firing trigger stops the simulation like the pause
+//! button and raises a toast saying why. Tick and energy-drift triggers are
+//! checked right after the fixed step that crosses them, so the simulation
+//! stops on that tick; formation and merger triggers are checked once the
+//! frame's structures have spawned.
+
+use bevy::prelude::*;
+
+use crate::agents::events::{GalaxyMergerEvent, ReportSeverity};
+use crate::app::SimulationState;
+use crate::astro::binary::CompactBinaryMerger;
+use crate::astro::black_hole::BlackHole;
+use crate::astro::star::Star;
+use crate::pru::gravity::{GravitySet, SimulationEnergy};
+use crate::ui::toasts::Toasts;
+
+/// Default ticks between `AtTick` stops, and how far ahead arming it from the UI aims.
+pub const BREAKPOINT_TICK_STEP: u64 = 1000;
+/// Seconds a breakpoint toast stays up.
+const BREAKPOINT_TOAST_SECS: f32 = 5.0;
+
+/// Whether a trigger is off, fires once, or fires every time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TriggerMode {
+    #[default]
+    Off,
+    /// Fire on the next match, then disarm.
+    Once,
+    /// Fire on every match.
+    Repeat,
+}
+
+impl TriggerMode {
+    pub fn next(self) -> Self {
+        match self {
+            TriggerMode::Off => TriggerMode::Once,
+            TriggerMode::Once => TriggerMode::Repeat,
+            TriggerMode::Repeat => TriggerMode::Off,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TriggerMode::Off => "off",
+            TriggerMode::Once => "once",
+            TriggerMode::Repeat => "repeat",
+        }
+    }
+
+    /// Whether the trigger is armed; a `Once` trigger disarms as it fires.
+    pub fn fire(&mut self) -> bool {
+        match *self {
+            TriggerMode::Off => false,
+            TriggerMode::Once => {
+                *self = TriggerMode::Off;
+                true
+            }
+            TriggerMode::Repeat => true,
+        }
+    }
+}
+
+/// One of the conditions [`BreakpointSettings`] can pause on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// A star forms.
+    FirstStar,
+    /// A black hole forms.
+    FirstBlackHole,
+    /// Two galaxies merge, or a compact binary coalesces.
+    Merger,
+    /// `|SimulationEnergy::relative_drift|` rises above the threshold.
+    EnergyDrift,
+    /// The tick counter reaches `BreakpointSettings::at_tick`.
+    AtTick,
+}
+
+impl Breakpoint {
+    pub const ALL: [Breakpoint; 5] = [
+        Breakpoint::FirstStar,
+        Breakpoint::FirstBlackHole,
+        Breakpoint::Merger,
+        Breakpoint::EnergyDrift,
+        Breakpoint::AtTick,
+    ];
+}
+
+/// Which breakpoints are armed, and their thresholds.
+#[derive(Resource, Clone, Debug)]
+pub struct BreakpointSettings {
+    pub first_star: TriggerMode,
+    pub first_black_hole: TriggerMode,
+    pub merger: TriggerMode,
+    pub energy_drift: TriggerMode,
+    /// Relative energy drift, as a fraction, that trips `energy_drift`.
+    pub energy_drift_threshold: f64,
+    pub at_tick: TriggerMode,
+    /// Tick `at_tick` stops on; a repeating stop moves on by `tick_interval`.
+    pub pause_tick: u64,
+    pub tick_interval: u64,
+}
+
+impl Default for BreakpointSettings {
+    fn default() -> Self {
+        Self {
+            first_star: TriggerMode::Off,
+            first_black_hole: TriggerMode::Off,
+            merger: TriggerMode::Off,
+            energy_drift: TriggerMode::Off,
+            energy_drift_threshold: 0.05,
+            at_tick: TriggerMode::Off,
+            pause_tick: BREAKPOINT_TICK_STEP,
+            tick_interval: BREAKPOINT_TICK_STEP,
+        }
+    }
+}
+
+impl BreakpointSettings {
+    pub fn mode(&self, breakpoint: Breakpoint) -> TriggerMode {
+        match breakpoint {
+            Breakpoint::FirstStar => self.first_star,
+            Breakpoint::FirstBlackHole => self.first_black_hole,
+            Breakpoint::Merger => self.merger,
+            Breakpoint::EnergyDrift => self.energy_drift,
+            Breakpoint::AtTick => self.at_tick,
+        }
+    }
+
+    pub fn mode_mut(&mut self, breakpoint: Breakpoint) -> &mut TriggerMode {
+        match breakpoint {
+            Breakpoint::FirstStar => &mut self.first_star,
+            Breakpoint::FirstBlackHole => &mut self.first_black_hole,
+            Breakpoint::Merger => &mut self.merger,
+            Breakpoint::EnergyDrift => &mut self.energy_drift,
+            Breakpoint::AtTick => &mut self.at_tick,
+        }
+    }
+
+    /// Short button label, e.g. `Drift >5%: once`.
+    pub fn label(&self, breakpoint: Breakpoint) -> String {
+        let name = match breakpoint {
+            Breakpoint::FirstStar => "Star".to_string(),
+            Breakpoint::FirstBlackHole => "Black hole".to_string(),
+            Breakpoint::Merger => "Merger".to_string(),
+            Breakpoint::EnergyDrift => {
+                format!("Drift >{:.0}%", self.energy_drift_threshold * 100.0)
+            }
+            Breakpoint::AtTick => format!("Tick {}", self.pause_tick),
+        };
+        format!("{name}: {}", self.mode(breakpoint).label())
+    }
+
+    /// Step `breakpoint` to its next mode; arming `AtTick` aims it past `current_tick`.
+    pub fn cycle(&mut self, breakpoint: Breakpoint, current_tick: u64) {
+        let mode = self.mode_mut(breakpoint);
+        *mode = mode.next();
+        if breakpoint == Breakpoint::AtTick
+            && self.at_tick == TriggerMode::Once
+            && self.pause_tick <= current_tick
+        {
+            self.pause_tick = current_tick + self.tick_interval.max(1);
+        }
+    }
+}
+
+/// Arms the [`BreakpointSettings`] triggers.
+pub struct BreakpointPlugin;
+
+impl Plugin for BreakpointPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BreakpointSettings>()
+            .add_systems(
+                FixedUpdate,
+                check_step_breakpoints.after(GravitySet::Diagnostics),
+            )
+            .add_systems(Update, check_structure_breakpoints);
+    }
+}
+
+/// Stop the simulation and say why.
+fn pause_for(sim_state: &mut SimulationState, toasts: Option<&mut Toasts>, reason: String) {
+    sim_state.running = false;
+    info!("Breakpoint at tick {}: {reason}", sim_state.tick);
+    if let Some(toasts) = toasts {
+        toasts.push(
+            format!("Paused at tick {}: {reason}", sim_state.tick),
+            ReportSeverity::Warning,
+            BREAKPOINT_TOAST_SECS,
+        );
+    }
+}
+
+/// Tick and energy-drift breakpoints, checked after each fixed step.
+pub fn check_step_breakpoints(
+    mut settings: ResMut<BreakpointSettings>,
+    mut sim_state: ResMut<SimulationState>,
+    energy: Res<SimulationEnergy>,
+    mut toasts: Option<ResMut<Toasts>>,
+    mut drift_exceeded: Local<bool>,
+) {
+    // Stepping while paused still trips triggers, so they never fire late on resume.
+    // Only look at the threshold while armed, so arming never fires on an old excess.
+    if settings.energy_drift == TriggerMode::Off {
+        *drift_exceeded = false;
+    } else {
+        let drift = energy.relative_drift.unwrap_or(0.0).abs();
+        let exceeded = drift > settings.energy_drift_threshold;
+        if exceeded && !*drift_exceeded && settings.energy_drift.fire() {
+            let reason = format!(
+                "energy drift {:.2}% above {:.2}%",
+                drift * 100.0,
+                settings.energy_drift_threshold * 100.0
+            );
+            pause_for(&mut sim_state, toasts.as_deref_mut(), reason);
+        }
+        *drift_exceeded = exceeded;
+    }
+
+    if settings.at_tick != TriggerMode::Off && sim_state.tick >= settings.pause_tick {
+        let target = settings.pause_tick;
+        if settings.at_tick.fire() {
+            if settings.at_tick == TriggerMode::Repeat {
+                let interval = settings.tick_interval.max(1);
+                settings.pause_tick =
+                    sim_state.tick + interval - (sim_state.tick - target) % interval;
+            }
+            pause_for(
+                &mut sim_state,
+                toasts.as_deref_mut(),
+                format!("reached tick {target}"),
+            );
+        }
+    }
+}
+
+/// Formation and merger breakpoints, checked once the frame's structures exist.
+pub fn check_structure_breakpoints(
+    mut settings: ResMut<BreakpointSettings>,
+    mut sim_state: ResMut<SimulationState>,
+    mut toasts: Option<ResMut<Toasts>>,
+    new_stars: Query<(), Added<Star>>,
+    new_black_holes: Query<(), Added<BlackHole>>,
+    mut galaxy_mergers: EventReader<GalaxyMergerEvent>,
+    mut compact_mergers: EventReader<CompactBinaryMerger>,
+) {
+    let mut reasons = Vec::new();
+    let stars = new_stars.iter().count();
+    if stars > 0 && settings.first_star.fire() {
+        reasons.push(format!("{stars} star(s) formed"));
+    }
+    let black_holes = new_black_holes.iter().count();
+    if black_holes > 0 && settings.first_black_hole.fire() {
+        reasons.push(format!("{black_holes} black hole(s) formed"));
+    }
+    let mergers = galaxy_mergers.read().count();
+    let coalescences = compact_mergers.read().count();
+    if mergers + coalescences > 0 && settings.merger.fire() {
+        reasons.push(match (mergers, coalescences) {
+            (0, n) => format!("{n} compact binary merger(s)"),
+            (n, 0) => format!("{n} galaxy merger(s)"),
+            (m, n) => format!("{m} galaxy and {n} compact binary merger(s)"),
+        });
+    }
+    if !reasons.is_empty() {
+        pause_for(&mut sim_state, toasts.as_deref_mut(), reasons.join(", "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn breakpoint_world(settings: BreakpointSettings) -> World {
+        let mut world = World::new();
+        world.insert_resource(settings);
+        world.init_resource::<SimulationState>();
+        world.init_resource::<SimulationEnergy>();
+        world.init_resource::<Events<GalaxyMergerEvent>>();
+        world.init_resource::<Events<CompactBinaryMerger>>();
+        world
+    }
+
+    #[test]
+    fn a_galaxy_merger_pauses_once_then_disarms() {
+        let mut world = breakpoint_world(BreakpointSettings {
+            merger: TriggerMode::Once,
+            ..default()
+        });
+        world.run_system_once(check_structure_breakpoints);
+        assert!(world.resource::<SimulationState>().running);
+
+        world.send_event(GalaxyMergerEvent {
+            survivor: 1,
+            absorbed: 2,
+            absorbed_mass: 3.0,
+        });
+        world.run_system_once(check_structure_breakpoints);
+        assert!(!world.resource::<SimulationState>().running);
+        assert_eq!(
+            world.resource::<BreakpointSettings>().merger,
+            TriggerMode::Off
+        );
+
+        world.resource_mut::<SimulationState>().running = true;
+        world.send_event(GalaxyMergerEvent {
+            survivor: 1,
+            absorbed: 4,
+            absorbed_mass: 1.0,
+        });
+        world.run_system_once(check_structure_breakpoints);
+        assert!(world.resource::<SimulationState>().running);
+    }
+
+    #[test]
+    fn crossing_the_drift_threshold_pauses_only_on_the_crossing() {
+        let mut world = breakpoint_world(BreakpointSettings {
+            energy_drift: TriggerMode::Repeat,
+            energy_drift_threshold: 0.05,
+            ..default()
+        });
+        let check = world.register_system(check_step_breakpoints);
+        let step = |world: &mut World, drift: f64| {
+            world.resource_mut::<SimulationState>().running = true;
+            world.resource_mut::<SimulationEnergy>().relative_drift = Some(drift);
+            world.run_system(check).unwrap();
+            !world.resource::<SimulationState>().running
+        };
+
+        assert!(!step(&mut world, 0.01), "below the threshold");
+        assert!(step(&mut world, -0.08), "crossing it in either direction");
+        assert!(!step(&mut world, 0.09), "staying above it");
+        assert!(!step(&mut world, 0.02), "dropping back under");
+        assert!(
+            step(&mut world, 0.06),
+            "a repeat trigger fires on the next crossing"
+        );
+    }
+}
@@ -0,0 +1,398 @@
+//! A/B comparison: a second universe differing in one parameter, run alongside the first.
+//!
+//! Universe B lives in its own [`HeadlessRun`] world, so every simulation
+//! system runs unchanged on each side with its own `GravityParams`,
+//! `FormationSettings`, and metrics. B is built from the app's current
+//! settings (and the same lattice seed) with [`ComparisonSpec`]'s override
+//! applied, then stepped to the app's tick every frame: pausing, stepping,
+//! and time scaling drive both sides. Resetting the universe rebuilds B from
+//! the settings in force at that moment.
+//!
+//! B is drawn as proxy bodies beside the lattice, offset along +X by the
+//! lattice width plus a gap, and the experiment panel lists both sides'
+//! metrics. Start it with `--compare <param>=<value>`, where `<param>` is a
+//! sweep parameter label (`g_effective`, `star_density`, `softening`).
+
+use bevy::prelude::*;
+
+use crate::app::{arg_value, density_color, SimConfig, SimulationState};
+use crate::astro::assets::AstroAssets;
+use crate::astro::black_hole::BlackHole;
+use crate::astro::formation::FormationSettings;
+use crate::astro::galaxy::Galaxy;
+use crate::astro::star::Star;
+use crate::experiment::headless::HeadlessRun;
+use crate::experiment::sweep::SweepParameter;
+use crate::pru::cell::{DerivedFields, PruCell};
+use crate::pru::gravity::{GravityParams, SimulationEnergy};
+use crate::pru::universe::{FieldMetrics, FieldSettings, PruUniverse, UniverseSettings};
+use crate::render::lod::CellMeshes;
+
+/// Gap between the two lattices, in lattice spacings.
+const COMPARISON_GAP_SPACINGS: f32 = 4.0;
+
+/// The one parameter universe B changes relative to the app's universe.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct ComparisonSpec {
+    pub parameter: SweepParameter,
+    pub value: f32,
+}
+
+/// Parse `<param>=<value>`, clamping the value to the parameter's range.
+impl std::str::FromStr for ComparisonSpec {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let expected = || {
+            "expected <param>=<value> with param one of g_effective, star_density, softening"
+                .to_string()
+        };
+        let (label, value) = spec.split_once('=').ok_or_else(expected)?;
+        let parameter = SweepParameter::from_label(label.trim()).ok_or_else(expected)?;
+        let value: f32 = value
+            .trim()
+            .parse()
+            .ok()
+            .filter(|v: &f32| v.is_finite())
+            .ok_or_else(expected)?;
+        let (min, max) = parameter.limits();
+        Ok(Self {
+            parameter,
+            value: value.clamp(min, max),
+        })
+    }
+}
+
+impl ComparisonSpec {
+    /// Short description, e.g. `g_effective=0.500`.
+    pub fn label(&self) -> String {
+        format!("{}={:.3}", self.parameter.label(), self.value)
+    }
+}
+
+/// `--compare <param>=<value>`: run a second universe with one parameter changed.
+pub fn comparison_from_args() -> Option<ComparisonSpec> {
+    arg_value("--compare")
+}
+
+/// Universe B's world, built lazily from the app's settings.
+///
+/// A non-send resource: the headless app is driven from the main thread.
+#[derive(Default)]
+pub struct ComparisonWorld {
+    run: Option<HeadlessRun>,
+    /// `SimulationState::generation` of the app universe B was built alongside.
+    generation: u64,
+}
+
+impl ComparisonWorld {
+    /// Build B afresh from the app's current settings plus `spec`'s override.
+    fn rebuild(&mut self, app_world: &World, spec: ComparisonSpec) {
+        let universe = *app_world.resource::<UniverseSettings>();
+        let field = *app_world.resource::<FieldSettings>();
+        let config = *app_world.resource::<SimConfig>();
+        let gravity = app_world.resource::<GravityParams>().clone();
+        let formation = app_world.resource::<FormationSettings>().clone();
+        self.run = Some(HeadlessRun::new(|world| {
+            world.insert_resource(universe);
+            world.insert_resource(field);
+            world.insert_resource(config);
+            world.insert_resource(gravity);
+            world.insert_resource(formation);
+            spec.parameter.apply(world, spec.value);
+        }));
+        self.generation = app_world.resource::<SimulationState>().generation;
+        info!("Comparison universe built with {}", spec.label());
+    }
+}
+
+/// Headline metrics of one universe.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UniverseSnapshot {
+    pub tick: u64,
+    pub stars: usize,
+    pub black_holes: usize,
+    pub galaxies: usize,
+    pub avg_density: f32,
+    pub energy_drift: Option<f64>,
+    pub momentum: f32,
+}
+
+impl UniverseSnapshot {
+    pub fn of(world: &mut World) -> Self {
+        let metrics = world.resource::<FieldMetrics>();
+        let avg_density = metrics.avg_density;
+        let momentum = metrics.total_linear_momentum.length();
+        Self {
+            tick: world.resource::<SimulationState>().tick,
+            stars: world.query_filtered::<(), With<Star>>().iter(world).count(),
+            black_holes: world
+                .query_filtered::<(), With<BlackHole>>()
+                .iter(world)
+                .count(),
+            galaxies: world
+                .query_filtered::<(), With<Galaxy>>()
+                .iter(world)
+                .count(),
+            avg_density,
+            energy_drift: world.resource::<SimulationEnergy>().relative_drift,
+            momentum,
+        }
+    }
+}
+
+/// Both universes as of this frame: metrics for the panel, and B's bodies to draw.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct ComparisonView {
+    pub label: String,
+    pub a: UniverseSnapshot,
+    pub b: UniverseSnapshot,
+    /// World-space shift from B's coordinates to where its proxies are drawn.
+    pub offset: Vec3,
+    /// B's cells: position and local density.
+    pub cells: Vec<(Vec3, f32)>,
+    /// B's density extent, which its cell colors are normalized against.
+    pub density_bounds: (f32, f32),
+    pub stars: Vec<Transform>,
+    pub black_holes: Vec<Transform>,
+}
+
+/// Which of B's bodies a proxy stands for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProxyKind {
+    Cell,
+    Star,
+    BlackHole,
+}
+
+/// A body drawn on behalf of universe B: the `index`-th of its kind in [`ComparisonView`].
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ComparisonProxy {
+    pub kind: ProxyKind,
+    pub index: usize,
+}
+
+/// Material shared by B's star proxies; per-star colors are left to universe A.
+#[derive(Resource)]
+pub struct ComparisonAssets {
+    pub star_material: Handle<StandardMaterial>,
+}
+
+/// Runs universe B next to the app's universe; see the module docs.
+pub struct ComparisonPlugin {
+    pub spec: ComparisonSpec,
+}
+
+impl Plugin for ComparisonPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.spec)
+            .insert_non_send_resource(ComparisonWorld::default())
+            .add_systems(Startup, setup_comparison_assets)
+            .add_systems(Update, (step_comparison, sync_comparison_proxies).chain());
+    }
+}
+
+pub fn setup_comparison_assets(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let color = Color::srgb(1.0, 0.85, 0.6);
+    commands.insert_resource(ComparisonAssets {
+        star_material: materials.add(StandardMaterial {
+            base_color: color,
+            emissive: color.to_linear() * 4.0,
+            ..Default::default()
+        }),
+    });
+}
+
+/// Bring universe B to the app's tick and publish both sides in [`ComparisonView`].
+///
+/// B is rebuilt when the app's universe was reset since B was built. When
+/// the app's tick goes back (a rewind scrub), B stays at the newest tick it
+/// reached and waits there until the app catches up, rather than being
+/// rebuilt and replayed on every scrub.
+pub fn step_comparison(world: &mut World) {
+    let Some(mut comparison) = world.remove_non_send_resource::<ComparisonWorld>() else {
+        return;
+    };
+    let spec = *world.resource::<ComparisonSpec>();
+    let (tick, generation) = {
+        let state = world.resource::<SimulationState>();
+        (state.tick, state.generation)
+    };
+
+    let stale = match &comparison.run {
+        Some(_) => comparison.generation != generation,
+        None => true,
+    };
+    if stale {
+        comparison.rebuild(world, spec);
+    }
+    let run = comparison
+        .run
+        .as_mut()
+        .expect("comparison universe was just built");
+    let behind = tick.saturating_sub(run.tick());
+    if behind > 0 {
+        let _span = info_span!("comparison_step", ticks = behind).entered();
+        run.step(behind as u32);
+    }
+
+    let universe = world.resource::<PruUniverse>();
+    let offset =
+        Vec3::X * universe.spacing * (universe.grid_dimensions.x as f32 + COMPARISON_GAP_SPACINGS);
+    let a = UniverseSnapshot::of(world);
+
+    let b_world = run.world_mut();
+    let b = UniverseSnapshot::of(b_world);
+    let metrics = b_world.resource::<FieldMetrics>();
+    let density_bounds = (metrics.min_density, metrics.max_density);
+    let cells = b_world
+        .query::<(&PruCell, &DerivedFields)>()
+        .iter(b_world)
+        .map(|(cell, derived)| (cell.position, derived.local_density))
+        .collect();
+    let stars = b_world
+        .query_filtered::<&Transform, With<Star>>()
+        .iter(b_world)
+        .copied()
+        .collect();
+    let black_holes = b_world
+        .query_filtered::<&Transform, With<BlackHole>>()
+        .iter(b_world)
+        .copied()
+        .collect();
+
+    world.insert_resource(ComparisonView {
+        label: spec.label(),
+        a,
+        b,
+        offset,
+        cells,
+        density_bounds,
+        stars,
+        black_holes,
+    });
+    world.insert_non_send_resource(comparison);
+}
+
+/// Spawn, move, recolor, and despawn proxies so they mirror [`ComparisonView`].
+pub fn sync_comparison_proxies(
+    mut commands: Commands,
+    view: Option<Res<ComparisonView>>,
+    cell_meshes: Option<Res<CellMeshes>>,
+    astro_assets: Option<Res<AstroAssets>>,
+    comparison_assets: Option<Res<ComparisonAssets>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut proxies: Query<(
+        Entity,
+        &ComparisonProxy,
+        &mut Transform,
+        &Handle<StandardMaterial>,
+    )>,
+) {
+    let (Some(view), Some(cell_meshes), Some(astro_assets), Some(comparison_assets)) =
+        (view, cell_meshes, astro_assets, comparison_assets)
+    else {
+        return;
+    };
+    let span = view.density_bounds.1 - view.density_bounds.0;
+    let cell_color = |density: f32| {
+        let norm = if span > f32::EPSILON {
+            (density - view.density_bounds.0) / span
+        } else {
+            0.0
+        };
+        density_color(norm)
+    };
+    let proxy_transform = |kind: ProxyKind, index: usize| match kind {
+        ProxyKind::Cell => view
+            .cells
+            .get(index)
+            .map(|(position, _)| Transform::from_translation(*position + view.offset)),
+        ProxyKind::Star | ProxyKind::BlackHole => {
+            let bodies = if kind == ProxyKind::Star {
+                &view.stars
+            } else {
+                &view.black_holes
+            };
+            bodies.get(index).map(|transform| Transform {
+                translation: transform.translation + view.offset,
+                ..*transform
+            })
+        }
+    };
+
+    let mut shown = [
+        vec![false; view.cells.len()],
+        vec![false; view.stars.len()],
+        vec![false; view.black_holes.len()],
+    ];
+    for (entity, proxy, mut transform, material) in proxies.iter_mut() {
+        let Some(target) = proxy_transform(proxy.kind, proxy.index) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        shown[proxy.kind as usize][proxy.index] = true;
+        if *transform != target {
+            *transform = target;
+        }
+        if proxy.kind == ProxyKind::Cell {
+            let color = cell_color(view.cells[proxy.index].1);
+            let stale = materials
+                .get(material)
+                .is_some_and(|material| material.base_color != color);
+            if stale {
+                if let Some(material) = materials.get_mut(material) {
+                    material.base_color = color;
+                }
+            }
+        }
+    }
+
+    for (kind, shown) in [ProxyKind::Cell, ProxyKind::Star, ProxyKind::BlackHole]
+        .into_iter()
+        .zip(shown)
+    {
+        for index in shown
+            .iter()
+            .enumerate()
+            .filter(|(_, shown)| !**shown)
+            .map(|(index, _)| index)
+        {
+            let Some(transform) = proxy_transform(kind, index) else {
+                continue;
+            };
+            let (mesh, material) = match kind {
+                ProxyKind::Cell => (
+                    cell_meshes.full.clone(),
+                    materials.add(StandardMaterial {
+                        base_color: cell_color(view.cells[index].1),
+                        metallic: 0.05,
+                        perceptual_roughness: 0.7,
+                        ..Default::default()
+                    }),
+                ),
+                ProxyKind::Star => (
+                    astro_assets.star_mesh.clone(),
+                    comparison_assets.star_material.clone(),
+                ),
+                ProxyKind::BlackHole => (
+                    astro_assets.black_hole_mesh.clone(),
+                    astro_assets.black_hole_material.clone(),
+                ),
+            };
+            commands.spawn((
+                PbrBundle {
+                    mesh,
+                    material,
+                    transform,
+                    ..Default::default()
+                },
+                ComparisonProxy { kind, index },
+                Name::new("Comparison Proxy"),
+            ));
+        }
+    }
+}
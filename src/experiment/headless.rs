@@ -0,0 +1,161 @@
+//! Windowless simulation runner used by batch experiments.
+//!
+//! A headless run builds the same simulation, formation, and agent plugins as
+//! the interactive app but without rendering or UI, then advances it one fixed
+//! tick per update so results depend only on tick count, never on wall time:
+//! each update feeds the clock exactly one `FixedUpdate` period.
+
+use bevy::ecs::schedule::ExecutorKind;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy::utils::Instant;
+
+use crate::agents::AgentsPlugin;
+use crate::app::{PruSimulationPlugin, SimConfig, SimulationState};
+use crate::astro::star::Star;
+use crate::astro::AstroPlugin;
+use crate::pru::gravity::SimulationEnergy;
+
+/// A self-contained simulation world advanced explicitly tick by tick.
+pub struct HeadlessRun {
+    app: App,
+}
+
+impl HeadlessRun {
+    /// Build a fresh universe, letting `configure` override resources before the first tick.
+    pub fn new(configure: impl FnOnce(&mut World)) -> Self {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .add_plugins((PruSimulationPlugin, AstroPlugin, AgentsPlugin));
+
+        // Runs already execute on their own worker thread; keep each one single-threaded.
+        app.edit_schedule(Update, |schedule| {
+            schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        })
+        .edit_schedule(FixedUpdate, |schedule| {
+            schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        });
+
+        let world = app.world_mut();
+        // Start the clock now so the first update already carries a full period.
+        world
+            .resource_mut::<Time<Real>>()
+            .update_with_instant(Instant::now());
+        world.resource_mut::<SimulationState>().running = false;
+        configure(world);
+
+        Self { app }
+    }
+
+    /// Advance the simulation by exactly `ticks` fixed steps.
+    pub fn step(&mut self, ticks: u32) {
+        for _ in 0..ticks {
+            let world = self.app.world_mut();
+            let time_scale = world.resource::<SimulationState>().time_scale;
+            let period = world.resource::<SimConfig>().tick_period(time_scale);
+            world.insert_resource(TimeUpdateStrategy::ManualDuration(period));
+            world.resource_mut::<SimulationState>().step_once();
+            self.app.update();
+        }
+    }
+
+    /// Ticks simulated so far.
+    pub fn tick(&self) -> u64 {
+        self.app.world().resource::<SimulationState>().tick
+    }
+
+    /// The run's world, for reading results or adjusting resources between steps.
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    /// Number of stars formed so far.
+    pub fn star_count(&mut self) -> usize {
+        let world = self.app.world_mut();
+        world.query_filtered::<(), With<Star>>().iter(world).count()
+    }
+
+    /// Relative drift of total energy from the first measured tick.
+    pub fn energy_drift(&self) -> f64 {
+        self.app
+            .world()
+            .resource::<SimulationEnergy>()
+            .relative_drift
+            .unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::events::AstroReportLog;
+    use crate::app::ResetUniverse;
+    use crate::astro::formation::FormationSettings;
+    use crate::pru::universe::UniverseSettings;
+
+    #[test]
+    fn a_paused_universe_stays_frozen() {
+        let mut run = HeadlessRun::new(|world| {
+            world.resource_mut::<UniverseSettings>().grid_dimensions = UVec3::splat(6);
+            // Every cell qualifies, but a zero budget spawns one star per
+            // frame, leaving a queue that a paused frame must not drain.
+            let mut formation = world.resource_mut::<FormationSettings>();
+            formation.star_density_threshold = 0.0;
+            formation.formation_budget_ms = 0.0;
+        });
+        let interval = run
+            .world_mut()
+            .resource::<FormationSettings>()
+            .star_interval;
+        run.step(interval as u32 + 1);
+        let entities = run.world_mut().entities().len();
+        let stars = run.star_count();
+        let pushed = run.world_mut().resource::<AstroReportLog>().pushed;
+        let tick = run.tick();
+        assert!(stars > 0);
+
+        // Frames keep arriving with a full fixed period each, but nothing may advance.
+        for _ in 0..1000 {
+            let world = run.app.world_mut();
+            let period = world.resource::<SimConfig>().tick_period(1.0);
+            world.insert_resource(TimeUpdateStrategy::ManualDuration(period));
+            run.app.update();
+        }
+        assert_eq!(run.tick(), tick);
+        assert_eq!(run.star_count(), stars);
+        assert_eq!(run.world_mut().entities().len(), entities);
+        assert_eq!(run.world_mut().resource::<AstroReportLog>().pushed, pushed);
+
+        // The queued stars were only waiting for the next tick.
+        run.step(1);
+        assert!(run.star_count() > stars);
+    }
+
+    #[test]
+    fn material_count_stays_bounded_across_formation_and_resets() {
+        let mut run = HeadlessRun::new(|world| {
+            world.resource_mut::<UniverseSettings>().grid_dimensions = UVec3::splat(6);
+            let mut formation = world.resource_mut::<FormationSettings>();
+            formation.star_density_threshold = 0.0;
+            formation.formation_budget_ms = 1000.0;
+        });
+        let interval = run
+            .world_mut()
+            .resource::<FormationSettings>()
+            .star_interval as u32;
+        run.step(1);
+        let materials =
+            |run: &mut HeadlessRun| run.world_mut().resource::<Assets<StandardMaterial>>().len();
+        let baseline = materials(&mut run);
+        // Every reset frees the stars' own materials, leaving cells and shared ones.
+        for _ in 0..4 {
+            run.step(interval + 1);
+            assert!(run.star_count() > 0);
+            run.world_mut().send_event(ResetUniverse::default());
+            run.step(2);
+            assert_eq!(materials(&mut run), baseline);
+        }
+    }
+}
@@ -0,0 +1,21 @@
+//! Experiment management: headless batch runs and parameter sweeps.
+//!
+//! =========================
+//! PHASE 5: TIME CONTROL, PRESETS & EXPERIMENT MANAGEMENT
+//! Status: IN PROGRESS
+//! =========================
+
+use bevy::prelude::*;
+
+pub mod comparison;
+pub mod headless;
+pub mod sweep;
+
+pub struct ExperimentPlugin;
+
+impl Plugin for ExperimentPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<sweep::ParameterSweep>()
+            .add_systems(Update, sweep::poll_parameter_sweep);
+    }
+}
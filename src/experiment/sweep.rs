@@ -0,0 +1,431 @@
+//! Parameter sweeps: run one headless simulation per value on worker threads.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use bevy::prelude::*;
+
+use crate::agents::events::ReportSeverity;
+use crate::astro::formation::FormationSettings;
+use crate::experiment::headless::HeadlessRun;
+use crate::pru::gravity::{GravityParams, G_EFFECTIVE_RANGE, SOFTENING_RANGE};
+use crate::ui::toasts::{Toasts, TOAST_SECS};
+
+/// Upper bound on values in a single sweep, keeping runs and the plot manageable.
+pub const MAX_SWEEP_POINTS: usize = 12;
+/// Ticks simulated per sweep value before metrics are sampled.
+pub const DEFAULT_SWEEP_TICKS: u32 = 240;
+
+/// Simulation input varied across a sweep.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SweepParameter {
+    GravityStrength,
+    StarDensityThreshold,
+    SofteningLength,
+}
+
+impl SweepParameter {
+    pub fn next(self) -> Self {
+        match self {
+            SweepParameter::GravityStrength => SweepParameter::StarDensityThreshold,
+            SweepParameter::StarDensityThreshold => SweepParameter::SofteningLength,
+            SweepParameter::SofteningLength => SweepParameter::GravityStrength,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SweepParameter::GravityStrength => "g_effective",
+            SweepParameter::StarDensityThreshold => "star_density",
+            SweepParameter::SofteningLength => "softening",
+        }
+    }
+
+    /// Parameter named by `label`, as written by [`SweepParameter::label`].
+    pub fn from_label(label: &str) -> Option<Self> {
+        [
+            SweepParameter::GravityStrength,
+            SweepParameter::StarDensityThreshold,
+            SweepParameter::SofteningLength,
+        ]
+        .into_iter()
+        .find(|parameter| parameter.label() == label)
+    }
+
+    /// Increment used by the panel's nudge buttons.
+    pub fn nudge(self) -> f32 {
+        match self {
+            SweepParameter::GravityStrength => 0.05,
+            SweepParameter::StarDensityThreshold => 1.0,
+            SweepParameter::SofteningLength => 0.02,
+        }
+    }
+
+    /// Valid value range, matching the interactive controls where they exist.
+    pub fn limits(self) -> (f32, f32) {
+        match self {
+            SweepParameter::GravityStrength => G_EFFECTIVE_RANGE,
+            SweepParameter::StarDensityThreshold => (1.0, 60.0),
+            SweepParameter::SofteningLength => SOFTENING_RANGE,
+        }
+    }
+
+    /// Initial (start, end, step) bracketing the default setting.
+    fn default_range(self) -> (f32, f32, f32) {
+        match self {
+            SweepParameter::GravityStrength => (0.2, 1.0, 0.2),
+            SweepParameter::StarDensityThreshold => (26.0, 34.0, 2.0),
+            SweepParameter::SofteningLength => (0.1, 0.5, 0.1),
+        }
+    }
+
+    /// Write `value` into the world's corresponding resource.
+    pub fn apply(self, world: &mut World, value: f32) {
+        match self {
+            SweepParameter::GravityStrength => {
+                world.resource_mut::<GravityParams>().set_g(value);
+            }
+            SweepParameter::StarDensityThreshold => {
+                world
+                    .resource_mut::<FormationSettings>()
+                    .star_density_threshold = value;
+            }
+            SweepParameter::SofteningLength => {
+                world.resource_mut::<GravityParams>().set_softening(value);
+            }
+        }
+    }
+}
+
+/// Output metric plotted against the swept parameter.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SweepMetric {
+    StarCount,
+    EnergyDrift,
+}
+
+impl SweepMetric {
+    pub fn next(self) -> Self {
+        match self {
+            SweepMetric::StarCount => SweepMetric::EnergyDrift,
+            SweepMetric::EnergyDrift => SweepMetric::StarCount,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SweepMetric::StarCount => "Final stars",
+            SweepMetric::EnergyDrift => "Final dE/E0",
+        }
+    }
+
+    pub fn sample(self, sample: &SweepSample) -> f64 {
+        match self {
+            SweepMetric::StarCount => sample.star_count as f64,
+            SweepMetric::EnergyDrift => sample.energy_drift,
+        }
+    }
+}
+
+/// Which bound of the sweep range a panel button adjusts.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SweepField {
+    Start,
+    End,
+    Step,
+}
+
+/// A parameter range to sweep, inclusive of both ends.
+#[derive(Clone, Copy, Debug)]
+pub struct SweepSpec {
+    pub parameter: SweepParameter,
+    pub start: f32,
+    pub end: f32,
+    pub step: f32,
+    pub ticks: u32,
+}
+
+impl SweepSpec {
+    pub fn new(parameter: SweepParameter) -> Self {
+        let (start, end, step) = parameter.default_range();
+        Self {
+            parameter,
+            start,
+            end,
+            step,
+            ticks: DEFAULT_SWEEP_TICKS,
+        }
+    }
+
+    /// Values visited by the sweep, from `start` to `end` in `step` increments.
+    pub fn values(&self) -> Vec<f32> {
+        // Tolerate float accumulation so `end` itself is included.
+        let limit = self.end + self.step * 1e-3;
+        (0..MAX_SWEEP_POINTS)
+            .map(|i| self.start + self.step * i as f32)
+            .take_while(|value| *value <= limit)
+            .collect()
+    }
+
+    /// Nudge one bound, keeping the range ordered and inside the parameter limits.
+    pub fn adjust(&mut self, field: SweepField, direction: f32) {
+        let (min, max) = self.parameter.limits();
+        let nudge = self.parameter.nudge();
+        let delta = nudge * direction;
+        match field {
+            SweepField::Start => self.start = (self.start + delta).clamp(min, self.end),
+            SweepField::End => self.end = (self.end + delta).clamp(self.start, max),
+            SweepField::Step => self.step = (self.step + delta).clamp(nudge, max - min),
+        }
+    }
+}
+
+/// Metrics sampled at the end of one sweep run.
+#[derive(Clone, Copy, Debug)]
+pub struct SweepSample {
+    pub value: f32,
+    pub star_count: usize,
+    pub energy_drift: f64,
+}
+
+/// Simulate a fresh universe with `parameter` set to `value` and sample its metrics.
+pub fn run_sweep_point(parameter: SweepParameter, value: f32, ticks: u32) -> SweepSample {
+    let mut run = HeadlessRun::new(|world| {
+        // Sweep points already run side by side; one solver thread each avoids oversubscription.
+        world.resource_mut::<GravityParams>().solver_threads = 1;
+        parameter.apply(world, value);
+    });
+    run.step(ticks);
+
+    SweepSample {
+        value,
+        star_count: run.star_count(),
+        energy_drift: run.energy_drift(),
+    }
+}
+
+/// What a sweep produced: samples from the runs that finished, and the values whose runs panicked.
+#[derive(Clone, Debug, Default)]
+pub struct SweepOutcome {
+    /// Samples in sweep order.
+    pub samples: Vec<SweepSample>,
+    pub failed: Vec<f32>,
+}
+
+/// Run every value in `spec`, in parallel batches, returning samples in sweep order.
+///
+/// A run that panics is recorded in [`SweepOutcome::failed`]; the others still complete.
+pub fn run_sweep(spec: SweepSpec, completed: &AtomicUsize) -> SweepOutcome {
+    run_sweep_with(spec, completed, |value| {
+        run_sweep_point(spec.parameter, value, spec.ticks)
+    })
+}
+
+/// [`run_sweep`] with `run` standing in for [`run_sweep_point`].
+fn run_sweep_with(
+    spec: SweepSpec,
+    completed: &AtomicUsize,
+    run: impl Fn(f32) -> SweepSample + Sync,
+) -> SweepOutcome {
+    let values = spec.values();
+    let batch = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+
+    let mut outcome = SweepOutcome {
+        samples: Vec::with_capacity(values.len()),
+        failed: Vec::new(),
+    };
+    for chunk in values.chunks(batch) {
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = chunk
+                .iter()
+                .map(|&value| {
+                    let run = &run;
+                    let handle = scope.spawn(move || {
+                        let sample = run(value);
+                        completed.fetch_add(1, Ordering::Relaxed);
+                        sample
+                    });
+                    (value, handle)
+                })
+                .collect();
+            for (value, handle) in handles {
+                match handle.join() {
+                    Ok(sample) => outcome.samples.push(sample),
+                    Err(_) => {
+                        warn!("sweep run {} = {value} panicked", spec.parameter.label());
+                        completed.fetch_add(1, Ordering::Relaxed);
+                        outcome.failed.push(value);
+                    }
+                }
+            }
+        });
+    }
+    outcome
+}
+
+/// An in-flight sweep running on a background thread.
+pub struct SweepJob {
+    pub spec: SweepSpec,
+    pub total: usize,
+    pub completed: Arc<AtomicUsize>,
+    handle: JoinHandle<SweepOutcome>,
+}
+
+/// Sweep configuration, progress, and the most recent results.
+#[derive(Resource)]
+pub struct ParameterSweep {
+    pub spec: SweepSpec,
+    pub metric: SweepMetric,
+    pub job: Option<SweepJob>,
+    /// Results of the last completed sweep, along with the spec that produced them.
+    pub results: Option<(SweepSpec, Vec<SweepSample>)>,
+}
+
+impl Default for ParameterSweep {
+    fn default() -> Self {
+        Self {
+            spec: SweepSpec::new(SweepParameter::GravityStrength),
+            metric: SweepMetric::StarCount,
+            job: None,
+            results: None,
+        }
+    }
+}
+
+impl ParameterSweep {
+    /// Switch to the next parameter, resetting the range to its defaults.
+    pub fn cycle_parameter(&mut self) {
+        self.spec = SweepSpec {
+            ticks: self.spec.ticks,
+            ..SweepSpec::new(self.spec.parameter.next())
+        };
+    }
+
+    /// Launch the current spec on a background thread unless a sweep is already running.
+    pub fn start(&mut self) {
+        if self.job.is_some() {
+            return;
+        }
+
+        let spec = self.spec;
+        let completed = Arc::new(AtomicUsize::new(0));
+        let worker_completed = completed.clone();
+        let handle = std::thread::Builder::new()
+            .name("parameter-sweep".to_string())
+            .spawn(move || run_sweep(spec, &worker_completed))
+            .expect("failed to spawn parameter sweep thread");
+
+        self.job = Some(SweepJob {
+            spec,
+            total: spec.values().len(),
+            completed,
+            handle,
+        });
+    }
+
+    /// Runs finished so far and total runs, if a sweep is in flight.
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        self.job
+            .as_ref()
+            .map(|job| (job.completed.load(Ordering::Relaxed), job.total))
+    }
+}
+
+/// Collect a finished sweep's samples without blocking the frame.
+pub fn poll_parameter_sweep(mut sweep: ResMut<ParameterSweep>, mut toasts: Option<ResMut<Toasts>>) {
+    let finished = sweep
+        .job
+        .as_ref()
+        .is_some_and(|job| job.handle.is_finished());
+    if !finished {
+        return;
+    }
+
+    let Some(job) = sweep.job.take() else {
+        return;
+    };
+    let (message, severity) = match job.handle.join() {
+        Ok(SweepOutcome { samples, failed }) if failed.is_empty() => {
+            let message = format!("Parameter sweep finished: {} runs", samples.len());
+            sweep.results = Some((job.spec, samples));
+            (message, ReportSeverity::Info)
+        }
+        Ok(SweepOutcome { samples, failed }) => {
+            let values: Vec<String> = failed.iter().map(|value| format!("{value:.3}")).collect();
+            let message = format!(
+                "Parameter sweep finished: {} runs, {} failed ({} = {})",
+                samples.len(),
+                failed.len(),
+                job.spec.parameter.label(),
+                values.join(", ")
+            );
+            sweep.results = Some((job.spec, samples));
+            (message, ReportSeverity::Warning)
+        }
+        Err(_) => {
+            warn!("parameter sweep worker panicked; discarding results");
+            (
+                "Parameter sweep failed; results discarded".to_string(),
+                ReportSeverity::Critical,
+            )
+        }
+    };
+    if let Some(toasts) = toasts.as_mut() {
+        toasts.push(message, severity, TOAST_SECS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_panicking_run_is_reported_while_the_rest_complete() {
+        let spec = SweepSpec {
+            start: 0.2,
+            end: 1.0,
+            step: 0.2,
+            ..SweepSpec::new(SweepParameter::GravityStrength)
+        };
+        let completed = AtomicUsize::new(0);
+        let outcome = run_sweep_with(spec, &completed, |value| {
+            assert!((value - 0.6).abs() > 1e-4, "simulated solver blow-up");
+            SweepSample {
+                value,
+                star_count: (value * 10.0).round() as usize,
+                energy_drift: 0.0,
+            }
+        });
+
+        assert_eq!(completed.load(Ordering::Relaxed), spec.values().len());
+        assert_eq!(outcome.failed.len(), 1);
+        assert!((outcome.failed[0] - 0.6).abs() < 1e-4);
+        let stars: Vec<usize> = outcome.samples.iter().map(|s| s.star_count).collect();
+        assert_eq!(stars, vec![2, 4, 8, 10]);
+    }
+
+    #[test]
+    fn a_two_point_sweep_runs_each_value_headless() {
+        let spec = SweepSpec {
+            start: 0.5,
+            end: 1.0,
+            step: 0.5,
+            ticks: 2,
+            ..SweepSpec::new(SweepParameter::GravityStrength)
+        };
+        let completed = AtomicUsize::new(0);
+        let outcome = run_sweep(spec, &completed);
+
+        assert_eq!(completed.load(Ordering::Relaxed), 2);
+        assert!(outcome.failed.is_empty());
+        let values: Vec<f32> = outcome.samples.iter().map(|s| s.value).collect();
+        assert_eq!(values, vec![0.5, 1.0]);
+        assert!(outcome
+            .samples
+            .iter()
+            .all(|sample| sample.energy_drift.is_finite()));
+    }
+}
@@ -17,13 +17,21 @@
 // Status: IN PROGRESS
 // =========================
 // PHASE 5: TIME CONTROL, PRESETS & EXPERIMENT MANAGEMENT
-// Status: TODO
+// Status: IN PROGRESS
 
 mod agents;
 mod app;
 mod astro;
+mod breakpoints;
+mod experiment;
+#[cfg(feature = "metrics-endpoint")]
+mod metrics;
+mod profiling;
 mod pru;
 mod render;
+mod shutdown;
+mod spatial;
+mod tracked;
 mod ui;
 
 fn main() {
@@ -0,0 +1,281 @@
+//! Prometheus text-format metrics endpoint for unattended runs.
+//!
+//! Built only with the `metrics-endpoint` feature. A background thread serves
+//! the latest snapshot on every HTTP request; the sim publishes a freshly
+//! formatted body once per tick and skips the update rather than wait if the
+//! server happens to be reading it. The listen address defaults to
+//! [`DEFAULT_METRICS_ADDR`] and can be overridden with `--metrics-addr <addr>`.
+
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write as _};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::app::{arg_value, SimulationState};
+use crate::astro::black_hole::BlackHole;
+use crate::astro::galaxy::Galaxy;
+use crate::astro::star::Star;
+use crate::pru::gravity::SimulationEnergy;
+use crate::pru::universe::FieldMetrics;
+
+/// Loopback address on the port range conventionally used by Prometheus exporters.
+pub const DEFAULT_METRICS_ADDR: &str = "127.0.0.1:9464";
+
+/// Values exported on each scrape.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MetricsSnapshot {
+    pub tick: u64,
+    pub simulation_time: f32,
+    pub avg_density: f32,
+    pub max_density: f32,
+    pub kinetic_energy: f64,
+    pub potential_energy: f64,
+    pub total_energy: f64,
+    pub energy_drift: Option<f64>,
+    pub stars: usize,
+    pub black_holes: usize,
+    pub galaxies: usize,
+}
+
+/// Render `snapshot` in the Prometheus text exposition format.
+///
+/// Energy drift is omitted until an initial energy has been recorded.
+pub fn format_prometheus(snapshot: &MetricsSnapshot) -> String {
+    let mut out = String::new();
+    let mut gauge = |name: &str, help: &str, value: f64| {
+        let _ = writeln!(out, "# HELP pru_{name} {help}");
+        let _ = writeln!(out, "# TYPE pru_{name} gauge");
+        let _ = writeln!(out, "pru_{name} {}", prometheus_value(value));
+    };
+
+    gauge("tick", "Current simulation tick.", snapshot.tick as f64);
+    gauge(
+        "simulation_time_seconds",
+        "Total simulated time.",
+        snapshot.simulation_time as f64,
+    );
+    gauge(
+        "avg_density",
+        "Mean local density across lattice cells.",
+        snapshot.avg_density as f64,
+    );
+    gauge(
+        "max_density",
+        "Maximum local density across lattice cells.",
+        snapshot.max_density as f64,
+    );
+    gauge(
+        "kinetic_energy",
+        "Total kinetic energy of cells and structures.",
+        snapshot.kinetic_energy,
+    );
+    gauge(
+        "potential_energy",
+        "Total pairwise potential energy of cells and structures.",
+        snapshot.potential_energy,
+    );
+    gauge(
+        "total_energy",
+        "Kinetic plus potential energy.",
+        snapshot.total_energy,
+    );
+    if let Some(drift) = snapshot.energy_drift {
+        gauge(
+            "energy_drift_ratio",
+            "Relative drift of total energy since the first sample.",
+            drift,
+        );
+    }
+    gauge("stars", "Stars currently alive.", snapshot.stars as f64);
+    gauge(
+        "black_holes",
+        "Black holes currently alive.",
+        snapshot.black_holes as f64,
+    );
+    gauge(
+        "galaxies",
+        "Galaxies currently identified.",
+        snapshot.galaxies as f64,
+    );
+    out
+}
+
+/// Prometheus spells infinities `+Inf`/`-Inf`, unlike Rust's `inf`.
+fn prometheus_value(value: f64) -> String {
+    match value {
+        f64::INFINITY => "+Inf".to_string(),
+        f64::NEG_INFINITY => "-Inf".to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// Latest formatted body shared with the server thread.
+#[derive(Resource, Clone)]
+pub struct MetricsEndpoint {
+    pub addr: SocketAddr,
+    body: Arc<Mutex<String>>,
+    last_tick: Option<u64>,
+}
+
+pub struct MetricsEndpointPlugin;
+
+impl Plugin for MetricsEndpointPlugin {
+    fn build(&self, app: &mut App) {
+        let addr = metrics_addr_from_args();
+        match start_metrics_server(&addr) {
+            Ok(endpoint) => {
+                info!(
+                    "Serving Prometheus metrics on http://{}/metrics",
+                    endpoint.addr
+                );
+                app.insert_resource(endpoint)
+                    .add_systems(Last, publish_metrics);
+            }
+            Err(err) => warn!("Metrics endpoint disabled: could not bind {addr}: {err}"),
+        }
+    }
+}
+
+/// Parse `--metrics-addr <addr>` or `--metrics-addr=<addr>`, falling back to the default.
+fn metrics_addr_from_args() -> String {
+    arg_value("--metrics-addr").unwrap_or_else(|| DEFAULT_METRICS_ADDR.to_string())
+}
+
+/// Bind `addr` and serve the shared body from a background thread.
+fn start_metrics_server(addr: &str) -> std::io::Result<MetricsEndpoint> {
+    let listener = TcpListener::bind(addr)?;
+    let endpoint = MetricsEndpoint {
+        addr: listener.local_addr()?,
+        body: Arc::new(Mutex::new(format_prometheus(&MetricsSnapshot::default()))),
+        last_tick: None,
+    };
+
+    let body = endpoint.body.clone();
+    std::thread::Builder::new()
+        .name("metrics-endpoint".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        if let Err(err) = serve_scrape(stream, &body) {
+                            debug!("Metrics scrape failed: {err}");
+                        }
+                    }
+                    Err(err) => debug!("Metrics connection failed: {err}"),
+                }
+            }
+        })?;
+    Ok(endpoint)
+}
+
+/// Answer one request with the current body, whatever the requested path.
+fn serve_scrape(mut stream: TcpStream, body: &Mutex<String>) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    // Drain the request headers; the request itself is not inspected.
+    while reader.read_line(&mut line)? > 0 && !line.trim_end().is_empty() {
+        line.clear();
+    }
+
+    let body = body.lock().map(|body| body.clone()).unwrap_or_default();
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    )
+}
+
+/// Refresh the served body once per simulation tick.
+fn publish_metrics(
+    sim_state: Res<SimulationState>,
+    metrics: Res<FieldMetrics>,
+    energy: Res<SimulationEnergy>,
+    mut endpoint: ResMut<MetricsEndpoint>,
+    stars: Query<(), With<Star>>,
+    black_holes: Query<(), With<BlackHole>>,
+    galaxies: Query<(), With<Galaxy>>,
+) {
+    if endpoint.last_tick == Some(sim_state.tick) {
+        return;
+    }
+
+    let snapshot = MetricsSnapshot {
+        tick: sim_state.tick,
+        simulation_time: sim_state.simulation_time,
+        avg_density: metrics.avg_density,
+        max_density: metrics.max_density,
+        kinetic_energy: energy.kinetic,
+        potential_energy: energy.potential,
+        total_energy: energy.total,
+        energy_drift: energy.relative_drift,
+        stars: stars.iter().count(),
+        black_holes: black_holes.iter().count(),
+        galaxies: galaxies.iter().count(),
+    };
+    let formatted = format_prometheus(&snapshot);
+
+    // A scrape in progress only holds the lock for a clone; retry next frame instead of waiting.
+    let Ok(mut body) = endpoint.body.try_lock() else {
+        return;
+    };
+    *body = formatted;
+    drop(body);
+    endpoint.last_tick = Some(sim_state.tick);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exposition_lists_every_gauge_with_a_parseable_value() {
+        let snapshot = MetricsSnapshot {
+            tick: 42,
+            simulation_time: 0.7,
+            avg_density: 1.25,
+            max_density: f32::INFINITY,
+            kinetic_energy: 3.5,
+            potential_energy: -7.25,
+            total_energy: -3.75,
+            energy_drift: Some(1e-4),
+            stars: 12,
+            black_holes: 1,
+            galaxies: 2,
+        };
+        let text = format_prometheus(&snapshot);
+
+        let samples: Vec<(&str, f64)> = text
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(|line| {
+                let (name, value) = line.split_once(' ').unwrap();
+                let value = match value {
+                    "+Inf" => f64::INFINITY,
+                    "-Inf" => f64::NEG_INFINITY,
+                    _ => value.parse().unwrap(),
+                };
+                (name, value)
+            })
+            .collect();
+        let value = |name: &str| samples.iter().find(|(n, _)| *n == name).map(|(_, v)| *v);
+        assert_eq!(value("pru_tick"), Some(42.0));
+        assert_eq!(value("pru_avg_density"), Some(1.25));
+        assert_eq!(value("pru_max_density"), Some(f64::INFINITY));
+        assert_eq!(value("pru_potential_energy"), Some(-7.25));
+        assert_eq!(value("pru_energy_drift_ratio"), Some(1e-4));
+        assert_eq!(value("pru_stars"), Some(12.0));
+        assert_eq!(value("pru_black_holes"), Some(1.0));
+        assert_eq!(value("pru_galaxies"), Some(2.0));
+        for (name, _) in &samples {
+            assert!(text.contains(&format!("# TYPE {name} gauge")));
+        }
+
+        let fresh = format_prometheus(&MetricsSnapshot::default());
+        assert!(!fresh.contains("pru_energy_drift_ratio"));
+    }
+}
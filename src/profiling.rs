@@ -0,0 +1,37 @@
+//! Optional Chrome tracing output, enabled with `--trace <file.json>`.
+//!
+//! Major systems open `info_span!`s with stable names (`gravity_step`,
+//! `relational_kernel`, `naive_forces`, `derived_fields`, `formation_stars`,
+//! `formation_black_holes`, `accretion`, `galaxy_scan_dispatch`,
+//! `galaxy_identify`, `agent_analysis`, `cell_materials`). Without `--trace`
+//! no extra layer is installed and the spans are only seen by the log filter.
+//! Open the resulting file in `chrome://tracing` or Perfetto.
+
+use std::path::PathBuf;
+
+use bevy::log::BoxedLayer;
+use bevy::prelude::*;
+
+use crate::app::arg_value;
+
+/// Destination for the Chrome trace, parsed from the command line.
+#[derive(Resource, Clone)]
+pub struct TraceOutput(pub PathBuf);
+
+/// Parse `--trace <path>` or `--trace=<path>` from the process arguments.
+pub fn trace_output_from_args() -> Option<TraceOutput> {
+    arg_value("--trace").map(TraceOutput)
+}
+
+/// `LogPlugin::custom_layer` hook installing the Chrome layer when `TraceOutput` is set.
+///
+/// The flush guard lives in the world, so the trace file is completed when the app exits.
+pub fn chrome_trace_layer(app: &mut App) -> Option<BoxedLayer> {
+    let output = app.world().get_resource::<TraceOutput>()?.clone();
+    let (layer, guard) = tracing_chrome::ChromeLayerBuilder::new()
+        .file(output.0)
+        .include_args(true)
+        .build();
+    app.insert_non_send_resource(guard);
+    Some(Box::new(layer))
+}
@@ -1,5 +1,22 @@
+use std::fmt;
+
+use bevy::math::DVec3;
 use bevy::prelude::*;
 
+/// Scalar the pairwise solver and integrator work in: `f64` with the `f64-dynamics` feature.
+#[cfg(not(feature = "f64-dynamics"))]
+pub type Real = f32;
+/// Scalar the pairwise solver and integrator work in: `f64` with the `f64-dynamics` feature.
+#[cfg(feature = "f64-dynamics")]
+pub type Real = f64;
+
+/// Vector counterpart of [`Real`].
+#[cfg(not(feature = "f64-dynamics"))]
+pub type RealVec3 = Vec3;
+/// Vector counterpart of [`Real`].
+#[cfg(feature = "f64-dynamics")]
+pub type RealVec3 = DVec3;
+
 /// Component representing a single PRU cell in the simulation lattice.
 ///
 /// UA (mass_lock) and UB (geometry_lock) are simplified scalar placeholders for
@@ -17,6 +34,14 @@ pub struct PruCell {
     pub ub_geom_lock: f64,
 }
 
+/// Lightest mass a lattice cell takes from its UA lock.
+pub const MIN_CELL_MASS: f32 = 0.05;
+
+/// Inertial mass a cell derives from its UA lock.
+pub fn mass_from_ua_lock(ua_mass_lock: f64) -> f32 {
+    (ua_mass_lock as f32).max(MIN_CELL_MASS)
+}
+
 impl PruCell {
     /// Convenience constructor for a new PRU cell.
     pub fn new(position: Vec3, grid_coords: UVec3, ua_mass_lock: f64, ub_geom_lock: f64) -> Self {
@@ -27,6 +52,29 @@ impl PruCell {
             ub_geom_lock,
         }
     }
+
+    /// Set the UA lock and re-derive `dynamics.mass` from it.
+    ///
+    /// Any mass the cell gained or lost since setup (accretion, collapse) is
+    /// replaced by the value the new lock implies.
+    pub fn set_ua_mass_lock(&mut self, value: f64, dynamics: &mut PruDynamics) {
+        self.ua_mass_lock = value.max(0.0);
+        dynamics.mass = mass_from_ua_lock(self.ua_mass_lock);
+    }
+}
+
+impl fmt::Display for PruCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Cell({},{},{}) ua={:.3} ub={:.3}",
+            self.grid_coords.x,
+            self.grid_coords.y,
+            self.grid_coords.z,
+            self.ua_mass_lock,
+            self.ub_geom_lock
+        )
+    }
 }
 
 /// Dynamical properties for a PRU cell used by the macro-gravity integrator.
@@ -34,10 +82,24 @@ impl PruCell {
 pub struct PruDynamics {
     /// Effective inertial mass derived from UA.
     pub mass: f32,
-    /// Current velocity in world units per second.
+    /// Velocity the last drift moved the body with, in world units per second.
+    ///
+    /// The integrator drifts with the integer-tick velocity half-kicked by the
+    /// tick's acceleration, so this belongs half a tick before the position.
     pub velocity: Vec3,
+    /// Velocity at the same tick as the position: `velocity + a·dt/2` with the
+    /// acceleration at the drifted position.
+    ///
+    /// The state the integrator carries from step to step, and what energy
+    /// diagnostics and display read; pairing a half-step velocity with an
+    /// integer-tick position would bias kinetic energy. Change both through
+    /// [`Self::kick`].
+    pub velocity_at_integer_tick: Vec3,
     /// Current acceleration accumulated from gravity or other rules.
     pub acceleration: Vec3,
+    /// Full-precision state behind `velocity` and the position, once the body has been integrated.
+    #[cfg(feature = "f64-dynamics")]
+    pub precise: Option<PrecisePhase>,
 }
 
 impl Default for PruDynamics {
@@ -45,9 +107,116 @@ impl Default for PruDynamics {
         Self {
             mass: 1.0,
             velocity: Vec3::ZERO,
+            velocity_at_integer_tick: Vec3::ZERO,
             acceleration: Vec3::ZERO,
+            #[cfg(feature = "f64-dynamics")]
+            precise: None,
+        }
+    }
+}
+
+impl PruDynamics {
+    /// Change the velocity by `delta_v` outside the gravity step, e.g. a pressure kick.
+    pub fn kick(&mut self, delta_v: Vec3) {
+        self.velocity += delta_v;
+        self.velocity_at_integer_tick += delta_v;
+    }
+
+    /// Position at integrator precision for a body whose `f32` position is `position`.
+    ///
+    /// Falls back to `position` when there is no precise state or something
+    /// moved the body since the integrator last wrote it.
+    pub fn precise_position(&self, position: Vec3) -> DVec3 {
+        #[cfg(feature = "f64-dynamics")]
+        if let Some(phase) = self
+            .precise
+            .filter(|phase| phase.written_position == position)
+        {
+            return phase.position;
+        }
+        position.as_dvec3()
+    }
+
+    /// [`Self::velocity_at_integer_tick`] at integrator precision.
+    pub fn precise_velocity_at_integer_tick(&self) -> DVec3 {
+        #[cfg(feature = "f64-dynamics")]
+        if let Some(phase) = self
+            .precise
+            .filter(|phase| phase.written_velocity_at_integer_tick == self.velocity_at_integer_tick)
+        {
+            return phase.velocity_at_integer_tick;
+        }
+        self.velocity_at_integer_tick.as_dvec3()
+    }
+}
+
+/// `f64` phase-space state the integrator advances in `f64-dynamics` builds.
+///
+/// The `f32` fields stay the interface everything else reads and writes; the
+/// integrator rounds its results into them after every step. Edits made to
+/// them in between (pressure kicks, teleports) are folded back in by
+/// [`PrecisePhase::absorb`] before the next step.
+#[cfg(feature = "f64-dynamics")]
+#[derive(Debug, Clone, Copy)]
+pub struct PrecisePhase {
+    pub position: DVec3,
+    /// Half-step velocity, as in [`PruDynamics::velocity`].
+    pub velocity: DVec3,
+    pub velocity_at_integer_tick: DVec3,
+    written_position: Vec3,
+    written_velocity_at_integer_tick: Vec3,
+}
+
+#[cfg(feature = "f64-dynamics")]
+impl PrecisePhase {
+    pub fn new(position: Vec3, velocity_at_integer_tick: Vec3) -> Self {
+        Self {
+            position: position.as_dvec3(),
+            velocity: velocity_at_integer_tick.as_dvec3(),
+            velocity_at_integer_tick: velocity_at_integer_tick.as_dvec3(),
+            written_position: position,
+            written_velocity_at_integer_tick: velocity_at_integer_tick,
         }
     }
+
+    /// Pick up changes made to the `f32` position or integer-tick velocity since [`Self::written`].
+    ///
+    /// A moved position replaces the precise one; a velocity change is added
+    /// as a delta so the low-order bits survive a small kick.
+    pub fn absorb(&mut self, position: Vec3, velocity_at_integer_tick: Vec3) {
+        if position != self.written_position {
+            self.position = position.as_dvec3();
+        }
+        if velocity_at_integer_tick != self.written_velocity_at_integer_tick {
+            self.velocity_at_integer_tick +=
+                (velocity_at_integer_tick - self.written_velocity_at_integer_tick).as_dvec3();
+        }
+    }
+
+    /// Round the state to `f32`, remembering the values handed out.
+    ///
+    /// Returns `(position, velocity, velocity_at_integer_tick)`.
+    pub fn written(&mut self) -> (Vec3, Vec3, Vec3) {
+        self.written_position = self.position.as_vec3();
+        self.written_velocity_at_integer_tick = self.velocity_at_integer_tick.as_vec3();
+        (
+            self.written_position,
+            self.velocity.as_vec3(),
+            self.written_velocity_at_integer_tick,
+        )
+    }
+}
+
+impl fmt::Display for PruDynamics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Dyn m={:.2} |v|={:.3} |a|={:.3}",
+            self.mass,
+            self.velocity_at_integer_tick.length(),
+            self.acceleration.length()
+        )
+    }
 }
 
 /// Derived scalar fields computed from a cell's locks and local neighborhood.
@@ -57,4 +226,52 @@ pub struct DerivedFields {
     pub local_density: f32,
     /// Curvature-like proxy derived from UB relative to neighbors.
     pub curvature_proxy: f32,
+    /// SPH gradient of the UA mass lock field, the "information pressure" of PRU theory.
+    pub ua_gradient: Vec3,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The numbers after each `=` in a Display string, unit suffixes dropped.
+    fn values(text: &str) -> Vec<f64> {
+        text.split('=')
+            .skip(1)
+            .map(|field| {
+                let number: String = field
+                    .chars()
+                    .take_while(|c| c.is_ascii_digit() || matches!(c, '.' | '-'))
+                    .collect();
+                number.parse().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn display_reads_back_at_its_printed_precision() {
+        let cell = PruCell::new(Vec3::ZERO, UVec3::new(3, 0, 12), 1.23456, -0.5);
+        let text = cell.to_string();
+        assert!(text.starts_with("Cell(3,0,12) "), "{text}");
+        let [ua, ub] = values(&text)[..] else {
+            panic!("{text}");
+        };
+        assert!((ua - cell.ua_mass_lock).abs() <= 5e-4);
+        assert!((ub - cell.ub_geom_lock).abs() <= 5e-4);
+
+        let dynamics = PruDynamics {
+            mass: 2.345,
+            velocity_at_integer_tick: Vec3::new(0.3, 0.0, 0.4),
+            acceleration: Vec3::new(0.0, -1.5, 0.0),
+            ..Default::default()
+        };
+        let text = dynamics.to_string();
+        assert!(text.starts_with("Dyn "), "{text}");
+        let [mass, speed, accel] = values(&text)[..] else {
+            panic!("{text}");
+        };
+        assert!((mass - 2.345).abs() <= 5e-3);
+        assert!((speed - 0.5).abs() <= 5e-4);
+        assert!((accel - 1.5).abs() <= 5e-4);
+    }
 }
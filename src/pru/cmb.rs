@@ -0,0 +1,142 @@
+//! Background radiation pressure: a CMB analog acting on the lattice.
+//!
+//! A photon gas at temperature `T` has pressure `P = (4σ/3c) T⁴`. Here the
+//! constant `4σ/3c` is folded into [`CMB_PRESSURE_UNIT`], the lattice-unit
+//! pressure at today's 2.725 K, and the result is scaled by the mean
+//! `|ub_geom_lock|`: in PRU terms the background is a uniform "information
+//! pressure" carried by the geometry locks. The magnitude is used because the
+//! locks are signed and a balanced lattice averages to about zero.
+//!
+//! The pressure is uniform, so `a = -∇P/ρ` vanishes wherever a cell is
+//! surrounded by others. It is evaluated with the symmetric SPH form
+//! `a_i = -Σ_j m_j (P/ρ_i² + P/ρ_j²) ∇W(r_ij)` over the derived-field kernel,
+//! which cancels in the interior and leaves an outward push on cells at the
+//! edge of the matter distribution. Pairs exchange equal and opposite
+//! forces, so total momentum is unchanged.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::app::SimulationState;
+use crate::pru::cell::{DerivedFields, PruCell, PruDynamics};
+use crate::pru::universe::{FieldSettings, PruUniverse};
+use crate::spatial::SpatialOctree;
+
+/// Present-day CMB temperature, in kelvin.
+pub const CMB_TEMPERATURE_K: f32 = 2.725;
+/// Radiation pressure at [`CMB_TEMPERATURE_K`] per unit mean `|UB|` lock, in lattice units.
+///
+/// Sized so edge cells drift outward by about 1e-5 per tick on the default
+/// lattice, a slow nudge next to gravity.
+pub const CMB_PRESSURE_UNIT: f32 = 1.0;
+
+/// Background radiation pressure settings.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct CmbSettings {
+    pub enabled: bool,
+    /// Background temperature in kelvin; the pressure scales as its fourth power.
+    pub temperature: f32,
+}
+
+impl Default for CmbSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            temperature: CMB_TEMPERATURE_K,
+        }
+    }
+}
+
+impl CmbSettings {
+    /// Radiation pressure in lattice units, given the lattice's mean `|ub_geom_lock|`.
+    pub fn pressure(&self, mean_ub_magnitude: f32) -> f32 {
+        let t = self.temperature.max(0.0) / CMB_TEMPERATURE_K;
+        CMB_PRESSURE_UNIT * t.powi(4) * mean_ub_magnitude.abs()
+    }
+}
+
+/// Kick every cell by the background radiation pressure once per elapsed tick span.
+pub fn apply_cmb_pressure(
+    sim_state: Res<SimulationState>,
+    settings: Res<CmbSettings>,
+    universe: Res<PruUniverse>,
+    field_settings: Res<FieldSettings>,
+    octree: Res<SpatialOctree>,
+    mut last_tick: Local<Option<u64>>,
+    mut cells: Query<(Entity, &PruCell, &DerivedFields, &mut PruDynamics)>,
+) {
+    // Apply once per elapsed tick span; ticks restart from zero after a reset.
+    let steps = match *last_tick {
+        Some(last) if sim_state.tick >= last => sim_state.tick - last,
+        _ => 0,
+    };
+    *last_tick = Some(sim_state.tick);
+    if steps == 0 || !settings.enabled {
+        return;
+    }
+
+    let bodies: HashMap<Entity, (Vec3, f32, f32)> = cells
+        .iter()
+        .filter(|(_, _, derived, _)| derived.local_density > f32::EPSILON)
+        .map(|(entity, cell, derived, dynamics)| {
+            (
+                entity,
+                (cell.position, dynamics.mass, derived.local_density),
+            )
+        })
+        .collect();
+    if bodies.is_empty() {
+        return;
+    }
+    let mean_ub_magnitude = cells
+        .iter()
+        .map(|(_, cell, _, _)| cell.ub_geom_lock.abs() as f32)
+        .sum::<f32>()
+        / cells.iter().len() as f32;
+    let pressure = settings.pressure(mean_ub_magnitude);
+    if pressure <= 0.0 {
+        return;
+    }
+
+    // Same kernel, smoothing length, and volume scaling as `compute_derived_fields`,
+    // so the pressure gradient is on the scale of the densities it divides by.
+    let kernel = field_settings.kernel;
+    let smoothing_length = (universe.spacing * field_settings.smoothing_radius).max(0.0002);
+    let reference_volume =
+        (2.0 * std::f32::consts::PI).powf(1.5) * (0.5 * smoothing_length).powi(3);
+    let support = kernel.support(smoothing_length);
+
+    let dt = sim_state.dt * steps as f32;
+    let mut kicks = Vec::with_capacity(bodies.len());
+    for (entity, _, _, _) in cells.iter() {
+        let Some(&(position, _, density)) = bodies.get(&entity) else {
+            continue;
+        };
+        let own_term = pressure / (density * density);
+        let mut acceleration = Vec3::ZERO;
+        for other in octree.query_radius(position, support) {
+            if other == entity {
+                continue;
+            }
+            let Some(&(other_position, other_mass, other_density)) = bodies.get(&other) else {
+                continue;
+            };
+            let offset = position - other_position;
+            let r = offset.length();
+            if r <= f32::EPSILON {
+                continue;
+            }
+            let slope = kernel.gradient(r, smoothing_length) * reference_volume;
+            acceleration -= offset / r
+                * (other_mass * (own_term + pressure / (other_density * other_density)) * slope);
+        }
+        kicks.push((entity, acceleration * dt));
+    }
+
+    for (entity, delta_v) in kicks {
+        if let Ok((_, _, _, mut dynamics)) = cells.get_mut(entity) {
+            dynamics.kick(delta_v);
+        }
+    }
+}
@@ -0,0 +1,246 @@
+//! Two-point correlation of the density field, computed off the main thread.
+//!
+//! ξ(r) measures how much more (or less) likely two cells a distance `r` apart
+//! are to both be overdense than chance: with density contrast
+//! `δ = ρ / ρ̄ - 1`, each bin averages `δ_i δ_j` over the cell pairs whose
+//! separation falls in it. Clustering shows up as positive ξ at small `r`;
+//! voids between clumps pull it negative further out. Every cell is paired
+//! with every other, so the estimate runs on the async compute pool from a
+//! snapshot of the field and lands in [`TwoPointCorrelation`] a frame or more
+//! later.
+
+use bevy::prelude::*;
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool, Task};
+
+use crate::app::SimulationState;
+use crate::pru::cell::{DerivedFields, PruCell};
+use crate::pru::universe::PruUniverse;
+
+/// When and how finely the correlation function is measured.
+#[derive(Resource, Clone, Copy)]
+pub struct CorrelationSettings {
+    pub enabled: bool,
+    /// Ticks between measurements while the simulation runs.
+    pub interval_ticks: u64,
+    /// Number of separation bins.
+    pub bins: usize,
+    /// Largest separation measured, in lattice spacings.
+    pub max_separation: f32,
+}
+
+impl Default for CorrelationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_ticks: 120,
+            bins: 12,
+            max_separation: 6.0,
+        }
+    }
+}
+
+/// Latest ξ(r) measurement.
+#[derive(Resource, Default)]
+pub struct TwoPointCorrelation {
+    /// ξ per separation bin; bin `i` covers `[i, i + 1) * bin_width`.
+    pub xi: Vec<f32>,
+    /// World-space width of each bin.
+    pub bin_width: f32,
+    /// Tick the measured snapshot was taken at; `None` before the first result.
+    pub tick: Option<u64>,
+}
+
+/// Background measurement result, tagged with the world generation it was taken from.
+pub struct CorrelationResult {
+    pub generation: u64,
+    pub tick: u64,
+    pub bin_width: f32,
+    pub xi: Vec<f32>,
+}
+
+/// Correlation measurement currently running on the async compute pool, if any.
+#[derive(Resource, Default)]
+pub struct CorrelationTask {
+    pub task: Option<Task<CorrelationResult>>,
+}
+
+/// Two-point correlation of `(position, density)` samples in `bins` bins of `bin_width`.
+///
+/// Bins without any pairs, and every bin when the mean density is not
+/// positive, report zero.
+pub fn two_point_correlation(samples: &[(Vec3, f32)], bin_width: f32, bins: usize) -> Vec<f32> {
+    let mut sums = vec![0.0f64; bins];
+    let mut counts = vec![0u64; bins];
+    let mean = samples
+        .iter()
+        .map(|(_, density)| *density as f64)
+        .sum::<f64>()
+        / samples.len().max(1) as f64;
+    if mean <= 0.0 || bin_width <= 0.0 {
+        return vec![0.0; bins];
+    }
+
+    let contrast: Vec<(Vec3, f64)> = samples
+        .iter()
+        .map(|(position, density)| (*position, *density as f64 / mean - 1.0))
+        .collect();
+    for (i, (position_a, delta_a)) in contrast.iter().enumerate() {
+        for (position_b, delta_b) in &contrast[i + 1..] {
+            let bin = (position_a.distance(*position_b) / bin_width) as usize;
+            if bin < bins {
+                sums[bin] += delta_a * delta_b;
+                counts[bin] += 1;
+            }
+        }
+    }
+
+    sums.iter()
+        .zip(&counts)
+        .map(|(sum, count)| {
+            if *count > 0 {
+                (sum / *count as f64) as f32
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// Snapshot the density field and launch a correlation measurement when one is due.
+///
+/// A new measurement is never launched while another is still in flight.
+pub fn dispatch_correlation(
+    sim_state: Res<SimulationState>,
+    universe: Option<Res<PruUniverse>>,
+    settings: Res<CorrelationSettings>,
+    mut correlation_task: ResMut<CorrelationTask>,
+    mut last_tick: Local<Option<u64>>,
+    cells: Query<(&PruCell, &DerivedFields)>,
+) {
+    if !settings.enabled || correlation_task.task.is_some() {
+        return;
+    }
+    let Some(universe) = universe else {
+        return;
+    };
+    // Ticks running backwards means the world was reset.
+    let due = last_tick.is_none_or(|last| {
+        sim_state.tick < last || sim_state.tick - last >= settings.interval_ticks.max(1)
+    });
+    if !due && !settings.is_changed() {
+        return;
+    }
+    *last_tick = Some(sim_state.tick);
+    let _span = info_span!("correlation_dispatch").entered();
+
+    let samples: Vec<(Vec3, f32)> = cells
+        .iter()
+        .map(|(cell, derived)| (cell.position, derived.local_density))
+        .collect();
+    let bins = settings.bins.max(1);
+    let bin_width = settings.max_separation.max(0.0) * universe.spacing / bins as f32;
+    let generation = sim_state.generation;
+    let tick = sim_state.tick;
+
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        CorrelationResult {
+            generation,
+            tick,
+            bin_width,
+            xi: two_point_correlation(&samples, bin_width, bins),
+        }
+    });
+    correlation_task.task = Some(task);
+}
+
+/// Store a finished correlation measurement.
+///
+/// Results from an earlier world generation (before a reset) are discarded.
+pub fn collect_correlation(
+    sim_state: Res<SimulationState>,
+    mut correlation_task: ResMut<CorrelationTask>,
+    mut correlation: ResMut<TwoPointCorrelation>,
+) {
+    let Some(task) = correlation_task.task.as_mut() else {
+        return;
+    };
+    let Some(result) = block_on(poll_once(task)) else {
+        return;
+    };
+    correlation_task.task = None;
+
+    if result.generation != sim_state.generation {
+        return;
+    }
+    correlation.xi = result.xi;
+    correlation.bin_width = result.bin_width;
+    correlation.tick = Some(result.tick);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_background_measurement_eventually_lands_in_the_resource() {
+        AsyncComputeTaskPool::get_or_init(Default::default);
+        let mut world = World::new();
+        world.insert_resource(SimulationState {
+            tick: 10,
+            ..Default::default()
+        });
+        world.insert_resource(PruUniverse::new(UVec3::splat(4), 1.0));
+        world.insert_resource(CorrelationSettings {
+            enabled: true,
+            ..Default::default()
+        });
+        world.init_resource::<CorrelationTask>();
+        world.init_resource::<TwoPointCorrelation>();
+        // Slabs alternating between densities 1 and 3 along x.
+        let mut samples = Vec::new();
+        for i in 0..64u32 {
+            let site = UVec3::new(i % 4, (i / 4) % 4, i / 16);
+            let position = site.as_vec3();
+            let density = if site.x.is_multiple_of(2) { 1.0 } else { 3.0 };
+            samples.push((position, density));
+            world.spawn((
+                PruCell::new(position, site, 1.0, 0.0),
+                DerivedFields {
+                    local_density: density,
+                    ..Default::default()
+                },
+            ));
+        }
+        let mut schedule = Schedule::default();
+        schedule.add_systems((collect_correlation, dispatch_correlation).chain());
+
+        schedule.run(&mut world);
+        assert!(world.resource::<CorrelationTask>().task.is_some());
+        assert_eq!(world.resource::<TwoPointCorrelation>().tick, None);
+        let started = std::time::Instant::now();
+        while world.resource::<TwoPointCorrelation>().tick.is_none() {
+            assert!(
+                started.elapsed().as_secs() < 10,
+                "measurement never finished"
+            );
+            std::thread::yield_now();
+            schedule.run(&mut world);
+        }
+
+        let settings = CorrelationSettings::default();
+        let bin_width = settings.max_separation / settings.bins as f32;
+        let correlation = world.resource::<TwoPointCorrelation>();
+        assert_eq!(correlation.tick, Some(10));
+        assert_eq!(correlation.bin_width, bin_width);
+        assert_eq!(
+            correlation.xi,
+            two_point_correlation(&samples, bin_width, settings.bins)
+        );
+        // Every pair √3 apart sits in neighboring slabs, one dense and one sparse.
+        assert!(
+            (correlation.xi[3] + 0.25).abs() < 1e-6,
+            "{:?}",
+            correlation.xi
+        );
+    }
+}
@@ -1,9 +1,34 @@
+//! Macro-gravity solvers, the fixed-step integrator, and energy diagnostics.
+//!
+//! Invariant: a gravity step must be bit-for-bit deterministic for a given
+//! input, independent of thread count. Replays and state hashes depend on it.
+//! Parallel force accumulation therefore gives each body's sum to exactly one
+//! thread, which adds the other bodies' pulls in ascending body order. Future
+//! optimizations must preserve this ordering rather than accumulate into
+//! shared state.
+//!
+//! With the `f64-dynamics` feature the integrator and the pairwise solver keep
+//! positions and velocities in `f64` (see [`PrecisePhase`]) and round to `f32`
+//! only for `PruCell::position`, `Transform`, and the other readers. The
+//! relational lattice solver stays in `f32`: its accelerations are rebuilt from
+//! the mass field every tick, so nothing accumulates in them.
+
+use bevy::math::DVec3;
 use bevy::prelude::*;
+use bevy::tasks::{ComputeTaskPool, TaskPool};
 
+use crate::agents::events::{AstroReport, AstroReportLog, ReportSeverity};
 use crate::app::SimulationState;
-use crate::pru::cell::{PruCell, PruDynamics};
-use crate::pru::gravity_relational::{apply_relational_gravity, RelationalKernel};
-use crate::pru::universe::PruUniverse;
+#[cfg(feature = "f64-dynamics")]
+use crate::pru::cell::PrecisePhase;
+use crate::pru::cell::{PruCell, PruDynamics, Real, RealVec3};
+use crate::pru::gravity_relational::{
+    apply_relational_gravity, initialize_relational_kernel, ActiveGravityKernel,
+};
+use crate::pru::separation::{separation_accelerations, MinSeparation};
+use crate::pru::supercell::aggregate_blocks;
+use crate::pru::universe::{setup_universe, FieldMetrics, LatticePeriods, PruUniverse};
+use crate::ui::toasts::Toasts;
 
 // =========================
 // PHASE 3: MACRO GRAVITY & LARGE-SCALE STRUCTURE
@@ -19,6 +44,86 @@ pub enum GravityMode {
     RelationalLattice,
 }
 
+impl GravityMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            GravityMode::NaiveNBody => "Naive N-Body",
+            GravityMode::RelationalLattice => "Relational Lattice",
+        }
+    }
+}
+
+/// Which bodies the gravity step integrates.
+///
+/// Bodies outside the selection stay fixed. In the relational solver they
+/// still source the lattice mass field; the naive solver only pairs
+/// integrated bodies, which is where the savings on large lattices come from.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GravityTargets {
+    /// Every lattice cell moves, and so do stars and black holes.
+    AllCells,
+    /// Only stars and black holes move, against a static lattice.
+    StructuresOnly,
+    /// Cells and structures whose mass reaches `target_mass_threshold` move.
+    AboveMassThreshold,
+}
+
+impl GravityTargets {
+    pub fn next(self) -> Self {
+        match self {
+            GravityTargets::AllCells => GravityTargets::StructuresOnly,
+            GravityTargets::StructuresOnly => GravityTargets::AboveMassThreshold,
+            GravityTargets::AboveMassThreshold => GravityTargets::AllCells,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GravityTargets::AllCells => "All cells",
+            GravityTargets::StructuresOnly => "Structures only",
+            GravityTargets::AboveMassThreshold => "Above mass threshold",
+        }
+    }
+}
+
+/// How the integrator drains velocity each step.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DampingModel {
+    /// No damping; the symplectic integrator conserves energy.
+    None,
+    /// Drag proportional to velocity: `dv/dt = -damping · v`.
+    Linear,
+    /// Drag proportional to speed squared, opposing the velocity:
+    /// `dv/dt = -damping · |v| · v`, as in a viscous medium.
+    Quadratic,
+}
+
+impl DampingModel {
+    pub fn next(self) -> Self {
+        match self {
+            DampingModel::None => DampingModel::Linear,
+            DampingModel::Linear => DampingModel::Quadratic,
+            DampingModel::Quadratic => DampingModel::None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DampingModel::None => "None",
+            DampingModel::Linear => "Linear",
+            DampingModel::Quadratic => "Quadratic",
+        }
+    }
+}
+
+/// Holds a body in place regardless of [`GravityTargets`].
+///
+/// The gravity step never moves a pinned body, but it stays a force source:
+/// the naive solver pairs it with the movers even when the targets would leave
+/// it out, which makes a fixed central mass or potential probe a one-key setup.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Pinned;
+
 /// Tunable parameters controlling the effective gravity model.
 #[derive(Resource, Clone)]
 pub struct GravityParams {
@@ -27,13 +132,260 @@ pub struct GravityParams {
     /// Softening length to avoid singularities at tiny separations or to tame the relational kernel gain.
     pub softening_length: f32,
     /// Simple velocity damping to keep the naive integrator stable.
+    ///
+    /// The drag coefficient of [`damping_model`](Self::damping_model).
     pub damping: f32,
+    /// Drag law `damping` feeds.
+    pub damping_model: DampingModel,
     /// Clamp extremely large accelerations that would destabilize the scene.
     pub max_acceleration: f32,
     /// Whether gravity forces are applied (integration still runs for inertial motion).
+    ///
+    /// The single switch for macro-gravity: the UI, keyboard, and solver all read it here.
     pub enabled: bool,
     /// Active solver controlling how accelerations are computed.
     pub mode: GravityMode,
+    /// Tasks pairwise force accumulation is split into (0 = one per compute pool thread).
+    pub solver_threads: usize,
+    /// Bodies integrated by the gravity step.
+    pub targets: GravityTargets,
+    /// Minimum mass for a body to move under [`GravityTargets::AboveMassThreshold`].
+    pub target_mass_threshold: f32,
+    /// Yukawa screening length `λ`; `None` keeps plain Newtonian gravity.
+    ///
+    /// Screened pairs interact through the potential `-G m_a m_b e^{-r/λ} / r`,
+    /// so beyond a few `λ` gravity effectively vanishes. Both solvers and the
+    /// energy diagnostics use the same screened form.
+    pub screening_length: Option<f32>,
+    /// Cells per side of the super-cells the pairwise solver works on; `None` keeps every cell.
+    ///
+    /// Blocks of `n³` cells act as one body with their summed mass at their
+    /// center of mass (see [`crate::pru::supercell`]), and each member feels
+    /// its block's acceleration, so the pairwise cost drops by `n⁶` while
+    /// forces inside a block are ignored. The relational solver already runs
+    /// in linear time and does not use it.
+    pub macro_block_size: Option<u32>,
+    /// Factor on `g_effective` that only the relational solver applies.
+    ///
+    /// The two solvers' force laws differ, so the same `g_effective` gives
+    /// them different strengths; calibration (see
+    /// [`relational_g_scale`](crate::pru::gravity_relational::relational_g_scale))
+    /// sets this so they agree without touching the shared constant.
+    pub relational_g_scale: f32,
+}
+
+/// Valid `g_effective` range; 0 turns attraction off without disabling integration.
+pub const G_EFFECTIVE_RANGE: (f32, f32) = (0.0, 5.0);
+/// Valid `damping` range (fraction of velocity removed per second).
+pub const DAMPING_RANGE: (f32, f32) = (0.0, 1.0);
+/// Valid `softening_length` range; zero would let close pairs blow up.
+pub const SOFTENING_RANGE: (f32, f32) = (0.01, 3.0);
+/// Valid `max_acceleration` range.
+pub const MAX_ACCELERATION_RANGE: (f32, f32) = (1.0, 1000.0);
+/// Valid `screening_length` range when screening is on.
+pub const SCREENING_RANGE: (f32, f32) = (0.05, 1000.0);
+/// Valid `macro_block_size` range when macro mode is on.
+pub const MACRO_BLOCK_RANGE: (u32, u32) = (2, 16);
+/// Block size macro mode starts with.
+pub const DEFAULT_MACRO_BLOCK_SIZE: u32 = 4;
+
+/// A `GravityParams` field that is non-finite or outside its documented range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GravityParamsError {
+    pub field: &'static str,
+    pub value: f32,
+    pub range: (f32, f32),
+}
+
+impl std::fmt::Display for GravityParamsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.value.is_finite() {
+            return write!(
+                f,
+                "gravity parameter `{}` must be finite, got {}",
+                self.field, self.value
+            );
+        }
+        write!(
+            f,
+            "gravity parameter `{}` = {} is outside {}..={}",
+            self.field, self.value, self.range.0, self.range.1
+        )
+    }
+}
+
+impl std::error::Error for GravityParamsError {}
+
+/// Clamp `value` into `range`, keeping `current` when `value` is NaN or infinite.
+fn clamped_or(current: f32, value: f32, range: (f32, f32)) -> f32 {
+    if value.is_finite() {
+        value.clamp(range.0, range.1)
+    } else {
+        current
+    }
+}
+
+impl GravityParams {
+    /// The gravitational constant the active solver applies.
+    pub fn effective_g(&self) -> f32 {
+        match self.mode {
+            GravityMode::NaiveNBody => self.g_effective,
+            GravityMode::RelationalLattice => self.g_effective * self.relational_g_scale,
+        }
+    }
+
+    /// Set `g_effective`, clamped to [`G_EFFECTIVE_RANGE`]. Non-finite values are ignored.
+    pub fn set_g(&mut self, value: f32) {
+        self.g_effective = clamped_or(self.g_effective, value, G_EFFECTIVE_RANGE);
+    }
+
+    /// Set `damping`, clamped to [`DAMPING_RANGE`]. Non-finite values are ignored.
+    pub fn set_damping(&mut self, value: f32) {
+        self.damping = clamped_or(self.damping, value, DAMPING_RANGE);
+    }
+
+    /// Set `softening_length`, clamped to [`SOFTENING_RANGE`]. Non-finite values are ignored.
+    pub fn set_softening(&mut self, value: f32) {
+        self.softening_length = clamped_or(self.softening_length, value, SOFTENING_RANGE);
+    }
+
+    /// Set `macro_block_size`, clamped to [`MACRO_BLOCK_RANGE`]; `None` turns macro mode off.
+    pub fn set_macro_block_size(&mut self, value: Option<u32>) {
+        self.macro_block_size =
+            value.map(|size| size.clamp(MACRO_BLOCK_RANGE.0, MACRO_BLOCK_RANGE.1));
+    }
+
+    /// Set `max_acceleration`, clamped to [`MAX_ACCELERATION_RANGE`]. Non-finite values are ignored.
+    pub fn set_max_accel(&mut self, value: f32) {
+        self.max_acceleration = clamped_or(self.max_acceleration, value, MAX_ACCELERATION_RANGE);
+    }
+
+    /// Check every tunable against its range without changing anything.
+    ///
+    /// [`validate_gravity_params`] runs this on every change, so a typo in
+    /// hand-set parameters is reported instead of silently reaching the solver.
+    pub fn validate(&self) -> Result<(), GravityParamsError> {
+        let checks = [
+            ("g_effective", self.g_effective, G_EFFECTIVE_RANGE),
+            ("damping", self.damping, DAMPING_RANGE),
+            ("softening_length", self.softening_length, SOFTENING_RANGE),
+            (
+                "max_acceleration",
+                self.max_acceleration,
+                MAX_ACCELERATION_RANGE,
+            ),
+            (
+                "target_mass_threshold",
+                self.target_mass_threshold,
+                (0.0, f32::MAX),
+            ),
+        ];
+        let screening = self
+            .screening_length
+            .map(|value| ("screening_length", value, SCREENING_RANGE));
+        let macro_block = self.macro_block_size.map(|value| {
+            (
+                "macro_block_size",
+                value as f32,
+                (MACRO_BLOCK_RANGE.0 as f32, MACRO_BLOCK_RANGE.1 as f32),
+            )
+        });
+        for (field, value, range) in checks.into_iter().chain(screening).chain(macro_block) {
+            if !value.is_finite() || value < range.0 || value > range.1 {
+                return Err(GravityParamsError {
+                    field,
+                    value,
+                    range,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Pull every tunable back into its range, resetting non-finite values to the defaults.
+    ///
+    /// The fallback for params that fail [`Self::validate`] after being set
+    /// directly rather than through the setters.
+    pub fn repair(&mut self) {
+        let defaults = Self::default();
+        let (g, damping, softening, max_accel) = (
+            self.g_effective,
+            self.damping,
+            self.softening_length,
+            self.max_acceleration,
+        );
+        self.g_effective = defaults.g_effective;
+        self.damping = defaults.damping;
+        self.softening_length = defaults.softening_length;
+        self.max_acceleration = defaults.max_acceleration;
+        self.set_g(g);
+        self.set_damping(damping);
+        self.set_softening(softening);
+        self.set_max_accel(max_accel);
+        if !(self.target_mass_threshold.is_finite() && self.target_mass_threshold >= 0.0) {
+            self.target_mass_threshold = defaults.target_mass_threshold;
+        }
+        self.screening_length = self.screening_length.and_then(|lambda| {
+            lambda
+                .is_finite()
+                .then(|| lambda.clamp(SCREENING_RANGE.0, SCREENING_RANGE.1))
+        });
+        self.set_macro_block_size(self.macro_block_size);
+    }
+
+    /// Factor the Yukawa screening applies to the Newtonian pair force at distance `r`.
+    ///
+    /// The derivative of the screened potential gives `(1 + r/λ) e^{-r/λ}`
+    /// rather than the bare exponential, which keeps forces and the reported
+    /// potential energy consistent.
+    pub fn screened_force_factor(&self, r: f32) -> f32 {
+        match self.screening_length {
+            Some(lambda) => {
+                let x = r / lambda.max(SCREENING_RANGE.0);
+                (1.0 + x) * (-x).exp()
+            }
+            None => 1.0,
+        }
+    }
+
+    /// Factor the Yukawa screening applies to the Newtonian pair potential at distance `r`.
+    pub fn screened_potential_factor(&self, r: f32) -> f32 {
+        match self.screening_length {
+            Some(lambda) => (-r / lambda.max(SCREENING_RANGE.0)).exp(),
+            None => 1.0,
+        }
+    }
+
+    /// Factor a velocity of `speed` is scaled by after a step of `dt` under the damping model.
+    ///
+    /// Quadratic drag uses the exact solution of `dv/dt = -c v²`,
+    /// `v / (1 + c v dt)`, which never reverses the velocity however fast
+    /// the body is moving.
+    pub fn damping_factor(&self, speed: f64, dt: f64) -> f64 {
+        let damping = self.damping as f64;
+        match self.damping_model {
+            DampingModel::None => 1.0,
+            DampingModel::Linear => 1.0 - damping * dt,
+            DampingModel::Quadratic => 1.0 / (1.0 + damping * speed * dt),
+        }
+    }
+
+    /// Whether a lattice cell of `mass` is integrated under the current targets.
+    pub fn integrates_cell(&self, mass: f32) -> bool {
+        match self.targets {
+            GravityTargets::AllCells => true,
+            GravityTargets::StructuresOnly => false,
+            GravityTargets::AboveMassThreshold => mass >= self.target_mass_threshold,
+        }
+    }
+
+    /// Whether a star or black hole of `mass` is integrated under the current targets.
+    pub fn integrates_structure(&self, mass: f32) -> bool {
+        match self.targets {
+            GravityTargets::AllCells | GravityTargets::StructuresOnly => true,
+            GravityTargets::AboveMassThreshold => mass >= self.target_mass_threshold,
+        }
+    }
 }
 
 impl Default for GravityParams {
@@ -42,9 +394,16 @@ impl Default for GravityParams {
             g_effective: 0.6,
             softening_length: 0.25,
             damping: 0.01,
+            damping_model: DampingModel::Linear,
             max_acceleration: 120.0,
             enabled: true,
             mode: GravityMode::RelationalLattice,
+            solver_threads: 0,
+            targets: GravityTargets::AllCells,
+            target_mass_threshold: 1.2,
+            screening_length: None,
+            macro_block_size: None,
+            relational_g_scale: 1.0,
         }
     }
 }
@@ -57,129 +416,912 @@ pub struct SimulationEnergy {
     pub total: f64,
     pub initial_total: Option<f64>,
     pub relative_drift: Option<f64>,
+    /// Power radiated by black hole accretion, an energy injection outside the N-body budget.
+    pub accretion_power: f64,
+    /// Gravity settings `initial_total` was captured under.
+    pub baseline: Option<EnergyFingerprint>,
+    /// Total mass of the bodies gravity acts on, cells and structures alike.
+    pub total_mass: f64,
+    /// Mass-weighted mean position of those bodies.
+    pub center_of_mass: Vec3,
+    /// Mass-weighted mean velocity of those bodies.
+    pub center_of_mass_velocity: Vec3,
+    /// Total linear momentum when `initial_total` was captured.
+    pub initial_momentum: Option<Vec3>,
+    /// `|p − p₀|` over the gross momentum `Σ mᵢ|vᵢ|`.
+    ///
+    /// Pairwise forces cancel, so this stays near zero without damping;
+    /// growth points at an asymmetric force kernel.
+    pub momentum_drift: Option<f32>,
 }
 
-/// Simulate pending fixed steps using a naive O(N^2) pairwise gravity rule.
+impl SimulationEnergy {
+    /// Whether a body is gravitationally bound to the whole lattice.
+    ///
+    /// Treats the lattice as a point mass at its center of mass and compares
+    /// the body's speed relative to the bulk flow with the local escape speed:
+    /// bound when `v² < 2GM/r`, with `r` softened and screened like the pair potential.
+    pub fn is_bound(&self, position: Vec3, velocity: Vec3, params: &GravityParams) -> bool {
+        let r2 = position.distance_squared(self.center_of_mass) as f64;
+        let r = (r2 + (params.softening_length * params.softening_length) as f64).sqrt();
+        let v2 = (velocity - self.center_of_mass_velocity).length_squared() as f64;
+        let screening = params.screened_potential_factor(r as f32) as f64;
+        r > 0.0 && v2 < 2.0 * params.effective_g() as f64 * self.total_mass * screening / r
+    }
+}
+
+/// The `GravityParams` fields that define which physical system the energy describes.
+///
+/// When any of them changes, drift against the old baseline is meaningless.
+#[derive(Clone, Copy, PartialEq)]
+pub struct EnergyFingerprint {
+    /// The active solver's G, [`GravityParams::effective_g`].
+    pub g_effective: f32,
+    pub softening_length: f32,
+    pub enabled: bool,
+    pub mode: GravityMode,
+    pub screening_length: Option<f32>,
+    pub macro_block_size: Option<u32>,
+}
+
+impl EnergyFingerprint {
+    pub fn of(params: &GravityParams) -> Self {
+        Self {
+            g_effective: params.effective_g(),
+            softening_length: params.softening_length,
+            enabled: params.enabled,
+            mode: params.mode,
+            screening_length: params.screening_length,
+            macro_block_size: params.macro_block_size,
+        }
+    }
+
+    /// Comma-separated list of the fields that differ from `previous`, e.g. `G changed 0.6→0.8`.
+    pub fn describe_change(&self, previous: &Self) -> String {
+        let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+        let mut changes = Vec::new();
+        if self.g_effective != previous.g_effective {
+            changes.push(format!(
+                "G changed {}→{}",
+                previous.g_effective, self.g_effective
+            ));
+        }
+        if self.softening_length != previous.softening_length {
+            changes.push(format!(
+                "softening changed {}→{}",
+                previous.softening_length, self.softening_length
+            ));
+        }
+        if self.enabled != previous.enabled {
+            changes.push(format!(
+                "gravity {}→{}",
+                on_off(previous.enabled),
+                on_off(self.enabled)
+            ));
+        }
+        if self.mode != previous.mode {
+            changes.push(format!(
+                "mode {}→{}",
+                previous.mode.label(),
+                self.mode.label()
+            ));
+        }
+        if self.screening_length != previous.screening_length {
+            let label = |lambda: Option<f32>| lambda.map_or("off".to_string(), |l| l.to_string());
+            changes.push(format!(
+                "screening {}→{}",
+                label(previous.screening_length),
+                label(self.screening_length)
+            ));
+        }
+        if self.macro_block_size != previous.macro_block_size {
+            let label = |size: Option<u32>| size.map_or("off".to_string(), |n| format!("{n}³"));
+            changes.push(format!(
+                "macro blocks {}→{}",
+                label(previous.macro_block_size),
+                label(self.macro_block_size)
+            ));
+        }
+        changes.join(", ")
+    }
+}
+
+/// Debug guards that catch numerical blow-ups before they spread through the lattice.
+#[derive(Resource, Clone, Copy)]
+pub struct NumericalGuards {
+    /// Scan every body for non-finite position or velocity after each gravity step.
+    pub check_non_finite: bool,
+    /// Pause the simulation when a non-finite body is found so the state can be inspected.
+    pub freeze_on_non_finite: bool,
+}
+
+impl Default for NumericalGuards {
+    fn default() -> Self {
+        Self {
+            check_non_finite: cfg!(debug_assertions),
+            freeze_on_non_finite: true,
+        }
+    }
+}
+
+/// Gravity-layer system sets, in execution order within a `FixedUpdate` run.
+///
+/// Physics systems that read post-step positions (the derived fields) order
+/// themselves after these sets. `Update` runs after every `FixedUpdate` run of
+/// the frame, so the octree, formation, and agents see stepped state without
+/// any ordering.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GravitySet {
+    /// Integrate this frame's pending ticks: forces, separation, and motion.
+    Step,
+    /// Inspect the stepped state: non-finite guards and energy metrics.
+    Diagnostics,
+}
+
+/// Macro-gravity resources, the relational kernel, and the fixed-rate step.
+///
+/// The kernel is built at startup once `setup_universe` has inserted the
+/// universe. The owning app orders [`GravitySet::Step`] after its tick advance.
+pub struct GravityPlugin;
+
+impl Plugin for GravityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GravityParams>()
+            .init_resource::<SimulationEnergy>()
+            .init_resource::<NumericalGuards>()
+            .init_resource::<MinSeparation>()
+            .configure_sets(
+                FixedUpdate,
+                GravitySet::Step.before(GravitySet::Diagnostics),
+            )
+            .add_systems(Startup, initialize_relational_kernel.after(setup_universe))
+            .add_systems(
+                FixedUpdate,
+                (
+                    validate_gravity_params
+                        .run_if(resource_changed::<GravityParams>)
+                        .before(GravitySet::Step),
+                    simulate_gravity_step.in_set(GravitySet::Step),
+                    (detect_non_finite_bodies, compute_energy_metrics)
+                        .in_set(GravitySet::Diagnostics),
+                ),
+            );
+    }
+}
+
+/// Reject out-of-range gravity params before the solver sees them.
+///
+/// Runs whenever the resource changes, so presets, command-line flags, copied
+/// settings, and UI edits all pass through the same check; a failure is
+/// logged and the params are repaired rather than left to poison the step.
+pub fn validate_gravity_params(mut params: ResMut<GravityParams>) {
+    if let Err(err) = params.validate() {
+        error!("{err}; clamping gravity parameters back into range");
+        params.repair();
+    }
+}
+
+/// Simulate pending fixed steps under the active gravity solver.
 ///
 /// The implementation keeps the logic in one place so future grid/octree-based
 /// accelerators can swap in while preserving the integrator and UI plumbing.
+/// Stars and black holes carry `PruDynamics` without a `PruCell`; they are
+/// integrated alongside cells when `GravityParams::targets` selects them.
+///
+/// Each step is a kick-drift-kick leapfrog: half a kick with the tick's
+/// acceleration, a drift, then forces at the new positions close the step
+/// with the other half kick. Those forces open the next step, so a step costs
+/// one force evaluation; they are only re-evaluated up front when something
+/// may have changed them since (a reset or rewind, new or unpinned bodies, settings).
+#[allow(clippy::too_many_arguments)]
 pub fn simulate_gravity_step(
     params: Res<GravityParams>,
+    separation: Res<MinSeparation>,
     universe: Res<PruUniverse>,
-    kernel: Option<Res<RelationalKernel>>,
+    kernel: Option<Res<ActiveGravityKernel>>,
     mut sim_state: ResMut<SimulationState>,
-    cell_data_query: Query<(&PruCell, &PruDynamics)>,
-    mut bodies: Query<(&mut PruCell, &mut PruDynamics, &mut Transform)>,
+    mut evaluated_at: Local<Option<(u64, u64)>>,
+    pinned: Query<(), Added<Pinned>>,
+    mut unpinned: RemovedComponents<Pinned>,
+    mut bodies: Query<(&mut PruCell, &mut PruDynamics, &mut Transform, Has<Pinned>)>,
+    mut structures: Query<(&mut PruDynamics, &mut Transform, Has<Pinned>), Without<PruCell>>,
 ) {
+    let unpinned = unpinned.read().count() > 0;
     let steps = sim_state.take_pending_steps();
     if steps == 0 {
         return;
     }
+    let _span = info_span!("gravity_step", steps, bodies = bodies.iter().len()).entered();
 
     let dt = sim_state.dt;
-    let softening2 = params.softening_length * params.softening_length;
+    let first_tick = sim_state.tick.saturating_sub(steps as u64);
+    let forces_current = *evaluated_at == Some((first_tick, sim_state.generation))
+        && !params.is_changed()
+        && !separation.is_changed()
+        && !kernel.as_ref().is_some_and(|kernel| kernel.is_changed())
+        && pinned.is_empty()
+        && !unpinned
+        && !bodies
+            .iter_mut()
+            .any(|(_, dyn_state, _, _)| dyn_state.is_added())
+        && !structures
+            .iter_mut()
+            .any(|(dyn_state, _, _)| dyn_state.is_added());
+    let kernel = kernel.as_deref();
+    if !forces_current {
+        accumulate_accelerations(
+            &params,
+            &separation,
+            &universe,
+            kernel,
+            &mut bodies,
+            &mut structures,
+        );
+    }
 
     for _ in 0..steps {
-        // Reset accelerations before accumulating forces for this fixed step.
-        for (_, mut dyn_state, _) in bodies.iter_mut() {
-            dyn_state.acceleration = Vec3::ZERO;
-        }
-
-        if params.enabled {
-            match params.mode {
-                GravityMode::NaiveNBody => {
-                    // Pairwise force accumulation using Bevy's combination iterator.
-                    let mut combos = bodies.iter_combinations_mut();
-                    while let Some([(cell_a, mut dyn_a, _), (cell_b, mut dyn_b, _)]) =
-                        combos.fetch_next()
-                    {
-                        let displacement = cell_b.position - cell_a.position;
-                        let dist2 = displacement.length_squared() + softening2;
-                        if dist2 <= 0.0 {
-                            continue;
-                        }
+        // Open the step: half kick and drift; fixed and pinned bodies are held at rest.
+        for (mut cell, mut dyn_state, mut transform, pinned) in bodies.iter_mut() {
+            if pinned || !params.integrates_cell(dyn_state.mass) {
+                hold_at_rest(&mut dyn_state);
+                continue;
+            }
+            kick_drift(&params, dt, &mut dyn_state, &mut cell.position);
+            confine_body(&universe, &mut dyn_state, &mut cell.position);
+            transform.translation = cell.position;
+        }
+        for (mut dyn_state, mut transform, pinned) in structures.iter_mut() {
+            if pinned || !params.integrates_structure(dyn_state.mass) {
+                hold_at_rest(&mut dyn_state);
+                continue;
+            }
+            kick_drift(&params, dt, &mut dyn_state, &mut transform.translation);
+            confine_body(&universe, &mut dyn_state, &mut transform.translation);
+        }
 
-                        let inv_dist = dist2.sqrt().recip();
-                        let inv_dist3 = inv_dist * inv_dist * inv_dist;
-                        let mass_product = dyn_a.mass * dyn_b.mass;
-                        if mass_product <= 0.0 {
-                            continue;
-                        }
+        accumulate_accelerations(
+            &params,
+            &separation,
+            &universe,
+            kernel,
+            &mut bodies,
+            &mut structures,
+        );
 
-                        let force_mag = params.g_effective * mass_product * inv_dist3;
-                        let direction = displacement * inv_dist;
+        // Close the step with the accelerations at the drifted positions.
+        for (_, mut dyn_state, _, pinned) in bodies.iter_mut() {
+            if !pinned && params.integrates_cell(dyn_state.mass) {
+                close_kick(dt, &mut dyn_state);
+            }
+        }
+        for (mut dyn_state, _, pinned) in structures.iter_mut() {
+            if !pinned && params.integrates_structure(dyn_state.mass) {
+                close_kick(dt, &mut dyn_state);
+            }
+        }
+    }
+    *evaluated_at = Some((sim_state.tick, sim_state.generation));
+}
 
-                        let accel_a = direction * (force_mag / dyn_a.mass);
-                        let accel_b = direction * (force_mag / dyn_b.mass);
+/// Zero a held body's velocities, writing only when it was moving.
+fn hold_at_rest(dyn_state: &mut Mut<PruDynamics>) {
+    if dyn_state.velocity != Vec3::ZERO || dyn_state.velocity_at_integer_tick != Vec3::ZERO {
+        dyn_state.velocity = Vec3::ZERO;
+        dyn_state.velocity_at_integer_tick = Vec3::ZERO;
+    }
+}
 
-                        dyn_a.acceleration += accel_a;
-                        dyn_b.acceleration -= accel_b;
+/// Replace every body's acceleration with the forces at its current position.
+///
+/// Gravity from the active solver, then the soft-core separation push, with
+/// the total clamped to `max_acceleration`.
+fn accumulate_accelerations(
+    params: &GravityParams,
+    separation: &MinSeparation,
+    universe: &PruUniverse,
+    kernel: Option<&ActiveGravityKernel>,
+    bodies: &mut Query<(&mut PruCell, &mut PruDynamics, &mut Transform, Has<Pinned>)>,
+    structures: &mut Query<(&mut PruDynamics, &mut Transform, Has<Pinned>), Without<PruCell>>,
+) {
+    for (_, mut dyn_state, _, _) in bodies.iter_mut() {
+        dyn_state.acceleration = Vec3::ZERO;
+    }
+    for (mut dyn_state, _, _) in structures.iter_mut() {
+        dyn_state.acceleration = Vec3::ZERO;
+    }
+
+    if params.enabled {
+        match params.mode {
+            GravityMode::NaiveNBody => {
+                // Fixed bodies are left out entirely, so the pairwise cost scales with movers
+                // (and pinned sources) only.
+                let movers = integrated_bodies(params, bodies, structures);
+                let softening2 = params.softening_length * params.softening_length;
+                let accelerations = match params.macro_block_size {
+                    Some(block_size) => {
+                        macro_accelerations(params, universe, &movers, block_size, softening2)
                     }
-                }
-                GravityMode::RelationalLattice => {
-                    if let Some(kernel) = kernel.as_ref() {
-                        // Snapshot the lattice masses so we can feed a dense lookup table to the
-                        // relational kernel. This keeps runtime work to neighbor lookups instead
-                        // of all-pairs force evaluation.
-                        let cell_data: Vec<(UVec3, f32)> = cell_data_query
-                            .iter()
-                            .map(|(cell, dyn_state)| (cell.grid_coords, dyn_state.mass))
-                            .collect();
-                        apply_relational_gravity(
-                            &params,
-                            &universe,
-                            kernel,
-                            &cell_data,
-                            &mut bodies,
-                        );
+                    None => naive_accelerations(
+                        movers.solver_positions(),
+                        &movers.masses,
+                        params.g_effective,
+                        softening2,
+                        params.screening_length,
+                        universe.periods(),
+                        solver_threads(params.solver_threads),
+                    ),
+                };
+                #[cfg(feature = "f64-dynamics")]
+                let accelerations = accelerations.iter().map(DVec3::as_vec3).collect();
+                add_integrated_accelerations(params, bodies, structures, accelerations);
+            }
+            GravityMode::RelationalLattice => {
+                if let Some(kernel) = kernel {
+                    // Snapshot the lattice masses so we can feed a dense lookup table to the
+                    // relational kernel. This keeps runtime work to neighbor lookups instead
+                    // of all-pairs force evaluation.
+                    let cell_data: Vec<(UVec3, f32)> = bodies
+                        .iter()
+                        .map(|(cell, dyn_state, _, _)| (cell.grid_coords, dyn_state.mass))
+                        .collect();
+                    let params = &GravityParams {
+                        g_effective: params.effective_g(),
+                        ..params.clone()
+                    };
+                    let field = apply_relational_gravity(
+                        params,
+                        universe,
+                        kernel.0.as_ref(),
+                        &cell_data,
+                        bodies,
+                    );
+                    // Structures sample the field at the nearest lattice site.
+                    for (mut dyn_state, transform, pinned) in structures.iter_mut() {
+                        if !pinned && params.integrates_structure(dyn_state.mass) {
+                            let coords = universe.nearest_grid_coords(transform.translation);
+                            dyn_state.acceleration =
+                                kernel.0.acceleration_at(&field, params, coords);
+                        }
                     }
                 }
             }
         }
+    }
+
+    // Soft-core repulsion acts on top of gravity, only between bodies that move.
+    if separation.enabled {
+        let movers = integrated_bodies(params, bodies, structures);
+        let pushes = separation_accelerations(
+            &movers.positions,
+            &movers.velocities,
+            &movers.masses,
+            separation,
+        );
+        add_integrated_accelerations(params, bodies, structures, pushes);
+    }
 
-        // Integrate motion (semi-implicit Euler).
-        for (mut cell, mut dyn_state, mut transform) in bodies.iter_mut() {
-            if dyn_state.acceleration.length_squared()
-                > params.max_acceleration * params.max_acceleration
-            {
-                dyn_state.acceleration = dyn_state
-                    .acceleration
-                    .clamp_length_max(params.max_acceleration);
+    let max2 = params.max_acceleration * params.max_acceleration;
+    let clamp = |dyn_state: &mut PruDynamics| {
+        if dyn_state.acceleration.length_squared() > max2 {
+            dyn_state.acceleration = dyn_state
+                .acceleration
+                .clamp_length_max(params.max_acceleration);
+        }
+    };
+    for (_, mut dyn_state, _, _) in bodies.iter_mut() {
+        clamp(&mut dyn_state);
+    }
+    for (mut dyn_state, _, _) in structures.iter_mut() {
+        clamp(&mut dyn_state);
+    }
+}
+
+/// Snapshot of the bodies the current targets integrate, plus pinned bodies.
+///
+/// Cells come first, then structures, each in query order; this is the order
+/// [`add_integrated_accelerations`] expects.
+#[derive(Default)]
+struct IntegratedBodies {
+    positions: Vec<Vec3>,
+    velocities: Vec<Vec3>,
+    masses: Vec<f32>,
+    /// Lattice coordinates of the leading cell entries.
+    cell_coords: Vec<UVec3>,
+    /// Integrator-precision positions for the pairwise solver.
+    #[cfg(feature = "f64-dynamics")]
+    precise_positions: Vec<DVec3>,
+}
+
+impl IntegratedBodies {
+    fn push(&mut self, position: Vec3, dyn_state: &PruDynamics) {
+        #[cfg(feature = "f64-dynamics")]
+        self.precise_positions
+            .push(dyn_state.precise_position(position));
+        self.positions.push(position);
+        self.velocities.push(dyn_state.velocity);
+        self.masses.push(dyn_state.mass);
+    }
+
+    /// Positions in the precision [`naive_accelerations`] runs at.
+    fn solver_positions(&self) -> &[RealVec3] {
+        #[cfg(feature = "f64-dynamics")]
+        return &self.precise_positions;
+        #[cfg(not(feature = "f64-dynamics"))]
+        return &self.positions;
+    }
+
+    /// Position `index` in `f64`, at full integrator precision when there is one.
+    fn precise_position(&self, index: usize) -> DVec3 {
+        #[cfg(feature = "f64-dynamics")]
+        return self.precise_positions[index];
+        #[cfg(not(feature = "f64-dynamics"))]
+        return self.positions[index].as_dvec3();
+    }
+}
+
+fn integrated_bodies(
+    params: &GravityParams,
+    bodies: &Query<(&mut PruCell, &mut PruDynamics, &mut Transform, Has<Pinned>)>,
+    structures: &Query<(&mut PruDynamics, &mut Transform, Has<Pinned>), Without<PruCell>>,
+) -> IntegratedBodies {
+    let mut movers = IntegratedBodies::default();
+    for (cell, dyn_state, _, pinned) in bodies.iter() {
+        if pinned || params.integrates_cell(dyn_state.mass) {
+            movers.push(cell.position, dyn_state);
+            movers.cell_coords.push(cell.grid_coords);
+        }
+    }
+    for (dyn_state, transform, pinned) in structures.iter() {
+        if pinned || params.integrates_structure(dyn_state.mass) {
+            movers.push(transform.translation, dyn_state);
+        }
+    }
+    movers
+}
+
+/// Pairwise accelerations with the cells of `movers` collapsed into super-cells.
+///
+/// Super-cells and then the structures go through [`naive_accelerations`];
+/// every cell takes its block's acceleration. The result is laid out like
+/// `movers`, one entry per body.
+fn macro_accelerations(
+    params: &GravityParams,
+    universe: &PruUniverse,
+    movers: &IntegratedBodies,
+    block_size: u32,
+    softening2: f32,
+) -> Vec<RealVec3> {
+    let cell_count = movers.cell_coords.len();
+    let solver_positions = movers.solver_positions();
+    let blocks = aggregate_blocks(
+        (0..cell_count).map(|i| {
+            (
+                movers.cell_coords[i],
+                movers.precise_position(i),
+                movers.masses[i],
+            )
+        }),
+        block_size,
+    );
+
+    #[cfg(feature = "f64-dynamics")]
+    let block_positions = blocks.cells.iter().map(|block| block.position);
+    #[cfg(not(feature = "f64-dynamics"))]
+    let block_positions = blocks.cells.iter().map(|block| block.position.as_vec3());
+    let positions: Vec<RealVec3> = block_positions
+        .chain(solver_positions[cell_count..].iter().copied())
+        .collect();
+    let masses: Vec<f32> = blocks
+        .cells
+        .iter()
+        .map(|block| block.mass)
+        .chain(movers.masses[cell_count..].iter().copied())
+        .collect();
+    let block_accelerations = naive_accelerations(
+        &positions,
+        &masses,
+        params.g_effective,
+        softening2,
+        params.screening_length,
+        universe.periods(),
+        solver_threads(params.solver_threads),
+    );
+
+    let structures = &block_accelerations[blocks.cells.len()..];
+    blocks
+        .membership
+        .iter()
+        .map(|&block| block_accelerations[block])
+        .chain(structures.iter().copied())
+        .collect()
+}
+
+/// Add per-body accelerations laid out as by [`integrated_bodies`]; pinned bodies' entries are dropped.
+fn add_integrated_accelerations(
+    params: &GravityParams,
+    bodies: &mut Query<(&mut PruCell, &mut PruDynamics, &mut Transform, Has<Pinned>)>,
+    structures: &mut Query<(&mut PruDynamics, &mut Transform, Has<Pinned>), Without<PruCell>>,
+    accelerations: Vec<Vec3>,
+) {
+    let mut accelerations = accelerations.into_iter();
+    for (_, mut dyn_state, _, pinned) in bodies.iter_mut() {
+        if pinned || params.integrates_cell(dyn_state.mass) {
+            let accel = accelerations.next().unwrap_or_default();
+            if !pinned {
+                dyn_state.acceleration += accel;
             }
+        }
+    }
+    for (mut dyn_state, _, pinned) in structures.iter_mut() {
+        if pinned || params.integrates_structure(dyn_state.mass) {
+            let accel = accelerations.next().unwrap_or_default();
+            if !pinned {
+                dyn_state.acceleration += accel;
+            }
+        }
+    }
+}
 
-            let accel = dyn_state.acceleration;
-            dyn_state.velocity += accel * dt;
-            dyn_state.velocity *= 1.0 - params.damping * dt;
-            cell.position += dyn_state.velocity * dt;
-            transform.translation = cell.position;
+/// Open a step: half-kick the integer-tick velocity with the body's acceleration, damp, and drift.
+#[cfg(not(feature = "f64-dynamics"))]
+fn kick_drift(params: &GravityParams, dt: f32, dyn_state: &mut PruDynamics, position: &mut Vec3) {
+    let mut velocity = dyn_state.velocity_at_integer_tick + dyn_state.acceleration * (0.5 * dt);
+    velocity *= params.damping_factor(velocity.length() as f64, dt as f64) as f32;
+    dyn_state.velocity = velocity;
+    *position += velocity * dt;
+}
+
+/// Wrap, reflect, or stop a drifted body at the lattice faces (see [`PruUniverse::confine`]).
+#[cfg(not(feature = "f64-dynamics"))]
+fn confine_body(universe: &PruUniverse, dyn_state: &mut PruDynamics, position: &mut Vec3) {
+    let (mut precise_position, mut velocity) = (position.as_dvec3(), dyn_state.velocity.as_dvec3());
+    if universe.confine(&mut precise_position, &mut velocity) {
+        *position = precise_position.as_vec3();
+        dyn_state.velocity = velocity.as_vec3();
+    }
+}
+
+/// Close a step: the other half kick, with the acceleration at the drifted position.
+#[cfg(not(feature = "f64-dynamics"))]
+fn close_kick(dt: f32, dyn_state: &mut PruDynamics) {
+    dyn_state.velocity_at_integer_tick = dyn_state.velocity + dyn_state.acceleration * (0.5 * dt);
+}
+
+/// [`kick_drift`] in `f64`; only the rounded results are written to `position` and the velocities.
+#[cfg(feature = "f64-dynamics")]
+fn kick_drift(params: &GravityParams, dt: f32, dyn_state: &mut PruDynamics, position: &mut Vec3) {
+    let mut phase = dyn_state
+        .precise
+        .unwrap_or_else(|| PrecisePhase::new(*position, dyn_state.velocity_at_integer_tick));
+    phase.absorb(*position, dyn_state.velocity_at_integer_tick);
+
+    let dt = dt as f64;
+    phase.velocity =
+        phase.velocity_at_integer_tick + dyn_state.acceleration.as_dvec3() * (0.5 * dt);
+    phase.velocity *= params.damping_factor(phase.velocity.length(), dt);
+    phase.position += phase.velocity * dt;
+
+    (*position, dyn_state.velocity, _) = phase.written();
+    dyn_state.precise = Some(phase);
+}
+
+/// [`confine_body`] on the `f64` state.
+#[cfg(feature = "f64-dynamics")]
+fn confine_body(universe: &PruUniverse, dyn_state: &mut PruDynamics, position: &mut Vec3) {
+    let Some(mut phase) = dyn_state.precise else {
+        return;
+    };
+    if universe.confine(&mut phase.position, &mut phase.velocity) {
+        (*position, dyn_state.velocity, _) = phase.written();
+        dyn_state.precise = Some(phase);
+    }
+}
+
+/// [`close_kick`] in `f64`.
+#[cfg(feature = "f64-dynamics")]
+fn close_kick(dt: f32, dyn_state: &mut PruDynamics) {
+    let Some(mut phase) = dyn_state.precise else {
+        return;
+    };
+    phase.velocity_at_integer_tick =
+        phase.velocity + dyn_state.acceleration.as_dvec3() * (0.5 * dt as f64);
+    (_, _, dyn_state.velocity_at_integer_tick) = phase.written();
+    dyn_state.precise = Some(phase);
+}
+
+/// Bodies at or below this mass neither exert nor feel pairwise gravity.
+///
+/// Accretion can drain a cell completely, and dividing a force by a zero mass
+/// would poison every accumulated sum with NaN.
+pub const MIN_BODY_MASS: f32 = 1e-6;
+
+/// Rows of the pairwise interaction matrix handled by one accumulation block.
+///
+/// Tasks are handed whole blocks, so a task always has enough rows to be
+/// worth spawning; the summation order within a row never depends on it.
+pub const FORCE_BLOCK_ROWS: usize = 64;
+
+fn solver_threads(requested: usize) -> usize {
+    if requested > 0 {
+        return requested;
+    }
+    ComputeTaskPool::get_or_init(TaskPool::default).thread_num()
+}
+
+/// Pairwise O(N^2) accelerations, accumulated in parallel yet deterministically.
+///
+/// Body rows are split into fixed blocks of [`FORCE_BLOCK_ROWS`], and each
+/// block sums the pulls on its own rows from every other body in ascending
+/// body order, writing only those rows. The blocks are shared out among
+/// `threads` tasks on Bevy's [`ComputeTaskPool`]. No sum is ever split across
+/// tasks, so the result is bit-identical for any `threads` value. Runs in [`Real`]
+/// precision. With a `screening_length`, each pair force carries the Yukawa
+/// factor of [`GravityParams::screened_force_factor`]. Along the periodic axes
+/// of `periods`, each pair interacts through its nearest image.
+pub fn naive_accelerations(
+    positions: &[RealVec3],
+    masses: &[f32],
+    g_effective: f32,
+    softening2: f32,
+    screening_length: Option<f32>,
+    periods: LatticePeriods,
+    threads: usize,
+) -> Vec<RealVec3> {
+    let n = positions.len();
+    let _span = info_span!("naive_forces", bodies = n, threads).entered();
+    let blocks = n.div_ceil(FORCE_BLOCK_ROWS);
+    let mut accelerations = vec![RealVec3::ZERO; n];
+    let threads = threads.clamp(1, blocks.max(1));
+    let rows_per_thread = blocks.div_ceil(threads) * FORCE_BLOCK_ROWS;
+    let law = PairLaw {
+        g_effective: g_effective as Real,
+        softening2: softening2 as Real,
+        screening_length: screening_length.map(|lambda| lambda.max(SCREENING_RANGE.0) as Real),
+        periods,
+    };
+
+    if threads == 1 {
+        accumulate_force_rows(&law, 0, &mut accelerations, positions, masses);
+        return accelerations;
+    }
+    ComputeTaskPool::get_or_init(TaskPool::default).scope(|scope| {
+        for (chunk_index, rows) in accelerations.chunks_mut(rows_per_thread.max(1)).enumerate() {
+            let first_row = chunk_index * rows_per_thread;
+            scope.spawn(async move {
+                accumulate_force_rows(&law, first_row, rows, positions, masses);
+            });
+        }
+    });
+    accelerations
+}
+
+/// The pair force [`naive_accelerations`] sums, in [`Real`] precision.
+#[derive(Clone, Copy)]
+struct PairLaw {
+    g_effective: Real,
+    softening2: Real,
+    screening_length: Option<Real>,
+    periods: LatticePeriods,
+}
+
+/// Sum the pulls of every other body on rows `first_row..first_row + rows.len()`.
+fn accumulate_force_rows(
+    law: &PairLaw,
+    first_row: usize,
+    rows: &mut [RealVec3],
+    positions: &[RealVec3],
+    masses: &[f32],
+) {
+    for (offset, accel) in rows.iter_mut().enumerate() {
+        let a = first_row + offset;
+        if masses[a] <= MIN_BODY_MASS {
+            continue;
+        }
+
+        for b in 0..positions.len() {
+            if b == a || masses[b] <= MIN_BODY_MASS {
+                continue;
+            }
+
+            let displacement = law.periods.minimum_image(positions[b] - positions[a]);
+            let dist2 = displacement.length_squared() + law.softening2;
+            if dist2 <= 0.0 {
+                continue;
+            }
+
+            let inv_dist = dist2.sqrt().recip();
+            let inv_dist3 = inv_dist * inv_dist * inv_dist;
+            let mut pull = law.g_effective * masses[b] as Real * inv_dist3;
+            if let Some(lambda) = law.screening_length {
+                let x = dist2.sqrt() / lambda;
+                pull *= (1.0 + x) * (-x).exp();
+            }
+            *accel += displacement * pull;
         }
     }
 }
 
+/// Report bodies whose position or velocity has become NaN or infinite.
+///
+/// A single bad value reaches every other body through the force sums within a
+/// tick, so the first offender is named while it is still identifiable.
+pub fn detect_non_finite_bodies(
+    guards: Res<NumericalGuards>,
+    mut sim_state: ResMut<SimulationState>,
+    mut last_reported_tick: Local<Option<u64>>,
+    mut toasts: Option<ResMut<Toasts>>,
+    bodies: Query<(Entity, Option<&Name>, &PruCell, &PruDynamics)>,
+) {
+    // Only inspect ticks that have not been reported yet, so a frozen sim doesn't spam the log.
+    if !guards.check_non_finite || *last_reported_tick == Some(sim_state.tick) {
+        return;
+    }
+
+    let mut offenders = bodies.iter().filter(|(_, _, cell, dyn_state)| {
+        !cell.position.is_finite() || !dyn_state.velocity.is_finite()
+    });
+    let Some((entity, name, cell, dyn_state)) = offenders.next() else {
+        return;
+    };
+    let others = offenders.count();
+    *last_reported_tick = Some(sim_state.tick);
+
+    error!(
+        "Non-finite state at tick {} in {} ({:?}): position {:?}, velocity {:?}, mass {} ({} other bodies affected)",
+        sim_state.tick,
+        name.map(Name::as_str).unwrap_or("unnamed body"),
+        entity,
+        cell.position,
+        dyn_state.velocity,
+        dyn_state.mass,
+        others
+    );
+    let paused = guards.freeze_on_non_finite && sim_state.running;
+    if let Some(toasts) = toasts.as_mut() {
+        let action = if paused { "; paused" } else { "" };
+        toasts.push(
+            format!(
+                "Non-finite state at tick {} in {} bodies{action}",
+                sim_state.tick,
+                others + 1
+            ),
+            ReportSeverity::Critical,
+            6.0,
+        );
+    }
+
+    if paused {
+        sim_state.running = false;
+        warn!("Simulation paused after non-finite state; inspect and resume or reset");
+    }
+}
+
 /// Compute kinetic and potential energy for diagnostics shown in the HUD.
+///
+/// Sums over the body set the naive solver integrates: cells and structures
+/// selected by `GravityParams::targets`, plus pinned bodies acting as sources.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn compute_energy_metrics(
+    sim_state: Res<SimulationState>,
     params: Res<GravityParams>,
+    universe: Res<PruUniverse>,
     mut energy: ResMut<SimulationEnergy>,
-    bodies: Query<(&PruCell, &PruDynamics)>,
+    mut metrics: ResMut<FieldMetrics>,
+    mut reports: Option<ResMut<AstroReportLog>>,
+    bodies: Query<(&PruCell, &PruDynamics, Has<Pinned>)>,
+    structures: Query<(&PruDynamics, &Transform, Has<Pinned>), Without<PruCell>>,
 ) {
+    // A baseline from different gravity settings describes another system; re-capture it.
+    let fingerprint = EnergyFingerprint::of(&params);
+    if let Some(previous) = energy.baseline.filter(|previous| *previous != fingerprint) {
+        energy.initial_total = None;
+        energy.relative_drift = None;
+        if let Some(reports) = reports.as_mut() {
+            reports.push(AstroReport {
+                tick: sim_state.tick,
+                severity: ReportSeverity::Info,
+                summary: format!(
+                    "energy baseline reset: {}",
+                    fingerprint.describe_change(&previous)
+                ),
+                position: None,
+            });
+        }
+    }
+    energy.baseline = Some(fingerprint);
+
+    // The bodies the solver sums over: integrated cells and structures, plus pinned sources.
+    let cells: Vec<(UVec3, DVec3, &PruDynamics)> = bodies
+        .iter()
+        .filter(|(_, dyn_state, pinned)| *pinned || params.integrates_cell(dyn_state.mass))
+        .map(|(cell, dyn_state, _)| {
+            (
+                cell.grid_coords,
+                dyn_state.precise_position(cell.position),
+                dyn_state,
+            )
+        })
+        .collect();
+    let structures: Vec<(DVec3, &PruDynamics)> = structures
+        .iter()
+        .filter(|(dyn_state, _, pinned)| *pinned || params.integrates_structure(dyn_state.mass))
+        .map(|(dyn_state, transform, _)| {
+            (dyn_state.precise_position(transform.translation), dyn_state)
+        })
+        .collect();
+    let all_bodies = || {
+        cells
+            .iter()
+            .map(|(_, position, dyn_state)| (*position, *dyn_state))
+            .chain(structures.iter().copied())
+    };
+
     let mut kinetic = 0.0f64;
-    for (_cell, dyn_state) in bodies.iter() {
-        kinetic += 0.5 * dyn_state.mass as f64 * dyn_state.velocity.length_squared() as f64;
+    let mut total_mass = 0.0f64;
+    let mut weighted_position = DVec3::ZERO;
+    let mut momentum = DVec3::ZERO;
+    let mut gross_momentum = 0.0f64;
+    for (position, dyn_state) in all_bodies() {
+        let mass = dyn_state.mass as f64;
+        let velocity = dyn_state.precise_velocity_at_integer_tick();
+        kinetic += 0.5 * mass * velocity.length_squared();
+        total_mass += mass;
+        weighted_position += position * mass;
+        momentum += velocity * mass;
+        gross_momentum += mass * velocity.length();
+    }
+    energy.total_mass = total_mass;
+    metrics.total_linear_momentum = momentum.as_vec3();
+    if total_mass > 0.0 {
+        energy.center_of_mass = (weighted_position / total_mass).as_vec3();
+        energy.center_of_mass_velocity = (momentum / total_mass).as_vec3();
+    } else {
+        energy.center_of_mass = Vec3::ZERO;
+        energy.center_of_mass_velocity = Vec3::ZERO;
     }
 
+    // Pairs are measured through the nearest periodic image, as the solver does.
+    let periods = universe.periods();
+    let pair_potential = |position_a: DVec3, mass_a: f32, position_b: DVec3, mass_b: f32| {
+        let displacement = periods.minimum_image_f64(position_b - position_a);
+        let distance = (displacement.length_squared()
+            + (params.softening_length * params.softening_length) as f64)
+            .sqrt();
+        if distance > 0.0 {
+            let screening = params.screened_potential_factor(distance as f32) as f64;
+            -params.effective_g() as f64 * mass_a as f64 * mass_b as f64 * screening / distance
+        } else {
+            0.0
+        }
+    };
+    // The same sources the solver sees: macro mode collapses the cells into super-cells.
+    let sources: Vec<(DVec3, f32)> = match params.macro_block_size {
+        Some(block_size) => {
+            let blocks = aggregate_blocks(
+                cells
+                    .iter()
+                    .map(|(coords, position, dyn_state)| (*coords, *position, dyn_state.mass)),
+                block_size,
+            );
+            blocks
+                .cells
+                .iter()
+                .map(|block| (block.position, block.mass))
+                .chain(
+                    structures
+                        .iter()
+                        .map(|(position, dyn_state)| (*position, dyn_state.mass)),
+                )
+                .collect()
+        }
+        None => all_bodies()
+            .map(|(position, dyn_state)| (position, dyn_state.mass))
+            .collect(),
+    };
     let mut potential = 0.0f64;
-    {
-        let mut combos = bodies.iter_combinations();
-        while let Some([(cell_a, dyn_a), (cell_b, dyn_b)]) = combos.fetch_next() {
-            let displacement = cell_b.position - cell_a.position;
-            let distance = (displacement.length_squared()
-                + params.softening_length * params.softening_length)
-                .sqrt();
-            if distance > 0.0 {
-                let term = -params.g_effective as f64 * dyn_a.mass as f64 * dyn_b.mass as f64
-                    / distance as f64;
-                potential += term;
-            }
+    for (i, (position_a, mass_a)) in sources.iter().enumerate() {
+        for (position_b, mass_b) in &sources[i + 1..] {
+            potential += pair_potential(*position_a, *mass_a, *position_b, *mass_b);
         }
     }
 
@@ -190,10 +1332,669 @@ pub fn compute_energy_metrics(
     if energy.initial_total.is_none() && energy.total.abs() > 1e-9 {
         energy.initial_total = Some(energy.total);
     }
+    // Momentum does not depend on the gravity settings, so a baseline reset keeps it.
+    if energy.initial_momentum.is_none() && energy.initial_total.is_some() {
+        energy.initial_momentum = Some(momentum.as_vec3());
+    }
 
     if let Some(initial) = energy.initial_total {
         if initial.abs() > 1e-9 {
             energy.relative_drift = Some((energy.total - initial) / initial);
         }
     }
+    if let Some(initial) = energy.initial_momentum {
+        if gross_momentum > 1e-9 {
+            let change = momentum - initial.as_dvec3();
+            energy.momentum_drift = Some((change.length() / gross_momentum) as f32);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astro::black_hole::BlackHole;
+    use crate::experiment::headless::HeadlessRun;
+    use crate::pru::universe::UniverseSettings;
+    #[cfg(feature = "f64-dynamics")]
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::utils::HashMap;
+
+    #[test]
+    fn integer_tick_velocity_follows_a_constant_force() {
+        let params = GravityParams {
+            damping_model: DampingModel::None,
+            ..Default::default()
+        };
+        let dt = 1.0 / 60.0;
+        let v0 = Vec3::new(0.5, -0.25, 0.0);
+        let a = Vec3::new(0.0, -2.0, 1.0);
+        let mut dyn_state = PruDynamics {
+            velocity: v0,
+            velocity_at_integer_tick: v0,
+            acceleration: a,
+            ..Default::default()
+        };
+        let mut position = Vec3::ZERO;
+        for step in 1..=120 {
+            kick_drift(&params, dt, &mut dyn_state, &mut position);
+            dyn_state.acceleration = a;
+            close_kick(dt, &mut dyn_state);
+            let t = step as f32 * dt;
+            let expected = v0 + a * t;
+            assert!(
+                dyn_state.velocity_at_integer_tick.distance(expected) < 1e-5,
+                "step {step}: {} != {expected}",
+                dyn_state.velocity_at_integer_tick
+            );
+            let expected_position = v0 * t + 0.5 * a * t * t;
+            assert!(position.distance(expected_position) < 1e-4);
+        }
+    }
+
+    #[test]
+    fn naive_accelerations_match_bitwise_across_thread_counts() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let count = 3 * FORCE_BLOCK_ROWS + 17;
+        let positions: Vec<RealVec3> = (0..count)
+            .map(|_| {
+                RealVec3::new(
+                    rng.gen_range(-2.0..2.0),
+                    rng.gen_range(-2.0..2.0),
+                    rng.gen_range(-2.0..2.0),
+                )
+            })
+            .collect();
+        let masses: Vec<f32> = (0..count).map(|_| rng.gen_range(0.1..2.0)).collect();
+
+        let reference = naive_accelerations(
+            &positions,
+            &masses,
+            1.0,
+            0.01,
+            Some(1.5),
+            LatticePeriods::default(),
+            1,
+        );
+        for threads in [2, 3, 4, 8] {
+            let accelerations = naive_accelerations(
+                &positions,
+                &masses,
+                1.0,
+                0.01,
+                Some(1.5),
+                LatticePeriods::default(),
+                threads,
+            );
+            let identical = reference
+                .iter()
+                .zip(&accelerations)
+                .all(|(a, b)| a.to_array().map(Real::to_bits) == b.to_array().map(Real::to_bits));
+            assert!(identical, "{threads} threads diverged from one");
+        }
+    }
+
+    #[test]
+    fn massless_bodies_neither_pull_nor_get_pulled() {
+        // The drained body sits right on top of a massive one with no softening.
+        let positions = [RealVec3::ZERO, RealVec3::ZERO, RealVec3::X];
+        let masses = [0.0, 1.0, 2.0];
+        let accelerations = naive_accelerations(
+            &positions,
+            &masses,
+            1.0,
+            0.0,
+            None,
+            LatticePeriods::default(),
+            1,
+        );
+
+        assert!(accelerations.iter().all(|a| a.is_finite()));
+        assert_eq!(accelerations[0], RealVec3::ZERO);
+        assert!((accelerations[1].x - 2.0).abs() < 1e-6);
+        assert!((accelerations[2].x + 1.0).abs() < 1e-6);
+    }
+
+    /// World with the naive solver and energy diagnostics, stepped by the returned schedule.
+    #[test]
+    fn setters_clamp_and_ignore_non_finite_values() {
+        let mut params = GravityParams::default();
+        params.set_g(9.0);
+        params.set_damping(-1.0);
+        params.set_softening(0.0);
+        params.set_max_accel(f32::INFINITY);
+        assert_eq!(params.g_effective, G_EFFECTIVE_RANGE.1);
+        assert_eq!(params.damping, DAMPING_RANGE.0);
+        assert_eq!(params.softening_length, SOFTENING_RANGE.0);
+        assert_eq!(
+            params.max_acceleration,
+            GravityParams::default().max_acceleration
+        );
+
+        params.set_g(f32::NAN);
+        assert_eq!(params.g_effective, G_EFFECTIVE_RANGE.1);
+        assert!(params.validate().is_ok());
+    }
+
+    #[test]
+    fn invalid_params_are_rejected_and_repaired_before_the_step() {
+        let (mut world, mut schedule) = gravity_world(GravityParams::default());
+        schedule.add_systems(
+            validate_gravity_params
+                .run_if(resource_changed::<GravityParams>)
+                .before(simulate_gravity_step),
+        );
+        spawn_cell(&mut world, Vec3::new(4.0, 8.0, 8.0), Vec3::ZERO, 1.0);
+        spawn_cell(&mut world, Vec3::new(12.0, 8.0, 8.0), Vec3::ZERO, 1.0);
+
+        {
+            let mut params = world.resource_mut::<GravityParams>();
+            params.g_effective = f32::NAN;
+            params.softening_length = 0.0;
+            params.max_acceleration = -5.0;
+        }
+        let err = world.resource::<GravityParams>().validate().unwrap_err();
+        assert_eq!(err.field, "g_effective");
+        assert!(err.to_string().contains("must be finite"), "{err}");
+
+        step(&mut world, &mut schedule, 3);
+        let params = world.resource::<GravityParams>();
+        assert!(params.validate().is_ok());
+        assert_eq!(params.g_effective, GravityParams::default().g_effective);
+        assert_eq!(params.softening_length, SOFTENING_RANGE.0);
+        assert_eq!(params.max_acceleration, MAX_ACCELERATION_RANGE.0);
+        let mut cells = world.query::<(&Transform, &PruDynamics)>();
+        assert!(cells
+            .iter(&world)
+            .all(|(transform, dyn_state)| transform.translation.is_finite()
+                && dyn_state.velocity.is_finite()));
+    }
+
+    fn gravity_world(params: GravityParams) -> (World, Schedule) {
+        let mut world = World::new();
+        world.insert_resource(GravityParams {
+            mode: GravityMode::NaiveNBody,
+            damping_model: DampingModel::None,
+            ..params
+        });
+        world.init_resource::<MinSeparation>();
+        world.insert_resource(PruUniverse::new(UVec3::splat(16), 1.0));
+        world.init_resource::<SimulationState>();
+        world.init_resource::<SimulationEnergy>();
+        world.init_resource::<FieldMetrics>();
+        let mut schedule = Schedule::default();
+        schedule.add_systems((simulate_gravity_step, compute_energy_metrics).chain());
+        (world, schedule)
+    }
+
+    fn step(world: &mut World, schedule: &mut Schedule, ticks: u32) {
+        for _ in 0..ticks {
+            world.resource_mut::<SimulationState>().step_once();
+            schedule.run(world);
+        }
+    }
+
+    fn spawn_cell(world: &mut World, position: Vec3, velocity: Vec3, mass: f32) -> Entity {
+        world
+            .spawn((
+                PruCell::new(position, UVec3::ZERO, 1.0, 0.0),
+                PruDynamics {
+                    mass,
+                    velocity,
+                    velocity_at_integer_tick: velocity,
+                    ..Default::default()
+                },
+                Transform::from_translation(position),
+            ))
+            .id()
+    }
+
+    fn spawn_structure(world: &mut World, position: Vec3, velocity: Vec3, mass: f32) -> Entity {
+        world
+            .spawn((
+                BlackHole::new(mass, 0.2, 0.0),
+                PruDynamics {
+                    mass,
+                    velocity,
+                    velocity_at_integer_tick: velocity,
+                    ..Default::default()
+                },
+                Transform::from_translation(position),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn energy_of_a_cell_and_a_black_hole_is_conserved() {
+        let (mut world, mut schedule) = gravity_world(GravityParams::default());
+        // A bound, eccentric pair: the hole is heavier and carries half the energy budget.
+        spawn_cell(
+            &mut world,
+            Vec3::new(-1.0, 0.0, 0.0),
+            Vec3::new(0.0, -0.6, 0.0),
+            1.0,
+        );
+        spawn_structure(
+            &mut world,
+            Vec3::new(0.5, 0.0, 0.0),
+            Vec3::new(0.0, 0.3, 0.0),
+            2.0,
+        );
+
+        step(&mut world, &mut schedule, 1);
+        let start = *world.resource::<SimulationEnergy>();
+        assert_eq!(start.total_mass, 3.0);
+        assert!(start.potential < 0.0);
+        assert!(start.total < 0.0, "the pair should be bound");
+
+        let mut kinetic_range = (f64::MAX, f64::MIN);
+        for _ in 0..600 {
+            step(&mut world, &mut schedule, 1);
+            let kinetic = world.resource::<SimulationEnergy>().kinetic;
+            kinetic_range = (kinetic_range.0.min(kinetic), kinetic_range.1.max(kinetic));
+        }
+        let energy = world.resource::<SimulationEnergy>();
+        // Energy trades between kinetic and potential along the orbit, but the total holds.
+        assert!(kinetic_range.1 > kinetic_range.0 * 1.5);
+        let drift = energy.relative_drift.unwrap();
+        assert!(drift.abs() < 1e-3, "relative drift {drift}");
+        assert!(energy.center_of_mass.length() < 1e-3);
+    }
+
+    #[test]
+    fn structures_only_targets_leave_plain_cells_in_place() {
+        let (mut world, mut schedule) = gravity_world(GravityParams {
+            targets: GravityTargets::StructuresOnly,
+            ..Default::default()
+        });
+        let cell = spawn_cell(&mut world, Vec3::new(-1.0, 0.0, 0.0), Vec3::ZERO, 5.0);
+        let star = spawn_structure(&mut world, Vec3::new(1.0, 0.0, 0.0), Vec3::ZERO, 1.0);
+        let other = spawn_structure(&mut world, Vec3::new(1.0, 1.0, 0.0), Vec3::ZERO, 1.0);
+        step(&mut world, &mut schedule, 30);
+
+        let position = |world: &World, entity| world.get::<Transform>(entity).unwrap().translation;
+        assert_eq!(position(&world, cell), Vec3::new(-1.0, 0.0, 0.0));
+        assert_eq!(world.get::<PruCell>(cell).unwrap().position.x, -1.0);
+        assert!(
+            position(&world, star).y > 0.0,
+            "the structures attract each other"
+        );
+        assert!(position(&world, other).y < 1.0);
+    }
+
+    #[test]
+    fn toggling_gravity_params_switches_the_solver_forces() {
+        let (mut world, mut schedule) = gravity_world(GravityParams::default());
+        let a = spawn_cell(&mut world, Vec3::new(-1.0, 0.0, 0.0), Vec3::ZERO, 1.0);
+        let b = spawn_cell(&mut world, Vec3::new(1.0, 0.0, 0.0), Vec3::ZERO, 1.0);
+        step(&mut world, &mut schedule, 1);
+        let pull = world.get::<PruDynamics>(a).unwrap().acceleration;
+        assert!(pull.x > 0.0);
+
+        // Switched off, the forces drop out right away and the pair coasts.
+        world.resource_mut::<GravityParams>().enabled = false;
+        step(&mut world, &mut schedule, 1);
+        let coasting = *world.get::<PruDynamics>(b).unwrap();
+        assert_eq!(coasting.acceleration, Vec3::ZERO);
+        step(&mut world, &mut schedule, 10);
+        let later = world.get::<PruDynamics>(b).unwrap();
+        assert_eq!(
+            later.velocity_at_integer_tick,
+            coasting.velocity_at_integer_tick
+        );
+
+        world.resource_mut::<GravityParams>().enabled = true;
+        step(&mut world, &mut schedule, 1);
+        assert!(world.get::<PruDynamics>(a).unwrap().acceleration.x > 0.0);
+    }
+
+    #[test]
+    fn changing_g_mid_run_recaptures_the_energy_baseline() {
+        let (mut world, mut schedule) = gravity_world(GravityParams::default());
+        world.init_resource::<AstroReportLog>();
+        spawn_cell(&mut world, Vec3::new(-1.0, 0.0, 0.0), Vec3::ZERO, 1.0);
+        spawn_cell(&mut world, Vec3::new(1.0, 0.0, 0.0), Vec3::ZERO, 1.0);
+        step(&mut world, &mut schedule, 5);
+        let before = world.resource::<SimulationEnergy>().initial_total.unwrap();
+
+        let g = world.resource::<GravityParams>().g_effective;
+        world.resource_mut::<GravityParams>().g_effective = g * 2.0;
+        step(&mut world, &mut schedule, 1);
+        let energy = *world.resource::<SimulationEnergy>();
+        let after = energy.initial_total.unwrap();
+        // Doubling G doubles the potential of a pair that has barely moved.
+        assert!((after / before - 2.0).abs() < 0.05, "{before} -> {after}");
+        assert!(energy.relative_drift.unwrap().abs() < 1e-6);
+        let reports = &world.resource::<AstroReportLog>().reports;
+        assert_eq!(reports.len(), 1);
+        assert!(
+            reports[0]
+                .summary
+                .starts_with("energy baseline reset: G changed"),
+            "{}",
+            reports[0].summary
+        );
+
+        // An untouched setting keeps the baseline.
+        step(&mut world, &mut schedule, 5);
+        assert_eq!(
+            world.resource::<SimulationEnergy>().initial_total,
+            Some(after)
+        );
+        assert_eq!(world.resource::<AstroReportLog>().reports.len(), 1);
+    }
+
+    #[test]
+    fn cells_move_after_ten_headless_ticks_with_gravity_enabled() {
+        let mut run = HeadlessRun::new(|world| {
+            world.resource_mut::<UniverseSettings>().grid_dimensions = UVec3::splat(6);
+            world.resource_mut::<GravityParams>().enabled = true;
+        });
+        run.step(1);
+        let world = run.world_mut();
+        let mut cells = world.query_filtered::<(Entity, &Transform), With<PruCell>>();
+        let start: HashMap<Entity, Vec3> = cells
+            .iter(world)
+            .map(|(entity, transform)| (entity, transform.translation))
+            .collect();
+        assert!(!start.is_empty());
+
+        run.step(10);
+        let world = run.world_mut();
+        let moved = cells
+            .iter(world)
+            .filter(|(entity, transform)| {
+                start
+                    .get(entity)
+                    .is_some_and(|&p| p.distance(transform.translation) > 1e-6)
+            })
+            .count();
+        assert!(
+            moved > start.len() / 2,
+            "{moved} of {} cells moved",
+            start.len()
+        );
+    }
+
+    #[test]
+    fn slow_nearby_bodies_are_bound_and_fast_distant_ones_escape() {
+        let (mut world, mut schedule) = gravity_world(GravityParams::default());
+        let center = Vec3::splat(8.0);
+        spawn_cell(&mut world, center, Vec3::ZERO, 50.0);
+        let near = spawn_cell(&mut world, center + Vec3::X, Vec3::Y * 0.1, 0.1);
+        let far = spawn_cell(&mut world, center + Vec3::Z * 6.0, Vec3::Z * 10.0, 0.1);
+        step(&mut world, &mut schedule, 1);
+
+        let energy = *world.resource::<SimulationEnergy>();
+        assert!((energy.total_mass - 50.2).abs() < 1e-4);
+        assert!(energy.center_of_mass.distance(center) < 0.05);
+        let params = world.resource::<GravityParams>().clone();
+        let state = |entity| {
+            let transform = world.get::<Transform>(entity).unwrap();
+            let dyn_state = world.get::<PruDynamics>(entity).unwrap();
+            (transform.translation, dyn_state.velocity_at_integer_tick)
+        };
+        let (position, velocity) = state(near);
+        assert!(energy.is_bound(position, velocity, &params));
+        let (position, velocity) = state(far);
+        assert!(!energy.is_bound(position, velocity, &params));
+    }
+
+    #[test]
+    fn a_pinned_mass_holds_still_while_a_satellite_orbits_it() {
+        let (mut world, mut schedule) = gravity_world(GravityParams::default());
+        let center = Vec3::splat(8.0);
+        let (mass, radius) = (40.0, 3.0);
+        let g = world.resource::<GravityParams>().g_effective;
+        let speed = (g * mass / radius).sqrt();
+        let anchor = spawn_cell(&mut world, center, Vec3::ZERO, mass);
+        world.entity_mut(anchor).insert(Pinned);
+        let satellite = spawn_cell(&mut world, center + Vec3::X * radius, Vec3::Y * speed, 0.01);
+
+        let dt = world.resource::<SimulationState>().dt;
+        let quarter = (std::f32::consts::FRAC_PI_2 * radius / speed / dt).round() as u32;
+        let mut angle = 0.0;
+        let mut previous = Vec3::X;
+        for _ in 0..4 {
+            step(&mut world, &mut schedule, quarter);
+            let position = world.get::<Transform>(satellite).unwrap().translation;
+            let offset = position - center;
+            // The satellite keeps to its circle and sweeps a quarter turn each time.
+            assert!((offset.length() - radius).abs() < 0.05 * radius, "{offset}");
+            angle += previous.angle_between(offset);
+            previous = offset;
+            let anchor_state = world.get::<PruDynamics>(anchor).unwrap();
+            assert_eq!(world.get::<Transform>(anchor).unwrap().translation, center);
+            assert_eq!(anchor_state.velocity, Vec3::ZERO);
+        }
+        assert!((angle - std::f32::consts::TAU).abs() < 0.2, "swept {angle}");
+    }
+
+    #[test]
+    fn quadratic_drag_bleeds_fast_bodies_harder_and_no_damping_bleeds_nothing() {
+        // Fraction of a lone body's kinetic energy left after 20 ticks.
+        let kept = |damping_model: DampingModel, speed: f32| {
+            let (mut world, mut schedule) = gravity_world(GravityParams {
+                damping: 0.5,
+                ..Default::default()
+            });
+            world.resource_mut::<GravityParams>().damping_model = damping_model;
+            spawn_cell(&mut world, Vec3::splat(8.0), Vec3::X * speed, 1.0);
+            step(&mut world, &mut schedule, 1);
+            let start = world.resource::<SimulationEnergy>().kinetic;
+            step(&mut world, &mut schedule, 20);
+            world.resource::<SimulationEnergy>().kinetic / start
+        };
+
+        let fast = kept(DampingModel::Quadratic, 2.0);
+        let slow = kept(DampingModel::Quadratic, 0.1);
+        assert!(fast < slow, "fast kept {fast}, slow kept {slow}");
+        assert!(slow < 1.0);
+        for speed in [0.1, 2.0] {
+            let kept = kept(DampingModel::None, speed);
+            assert!((kept - 1.0).abs() < 1e-6, "undamped body kept {kept}");
+        }
+    }
+
+    #[test]
+    fn a_short_screening_length_suppresses_distant_pulls() {
+        // Pull on a body `separation` away from a unit mass after one tick.
+        let pull = |screening_length: Option<f32>, separation: f32| {
+            let (mut world, mut schedule) = gravity_world(GravityParams {
+                screening_length,
+                ..Default::default()
+            });
+            let probe = spawn_cell(&mut world, Vec3::splat(4.0), Vec3::ZERO, 1.0);
+            spawn_cell(
+                &mut world,
+                Vec3::splat(4.0) + Vec3::X * separation,
+                Vec3::ZERO,
+                1.0,
+            );
+            step(&mut world, &mut schedule, 1);
+            world
+                .get::<PruDynamics>(probe)
+                .unwrap()
+                .acceleration
+                .length()
+        };
+
+        let lambda = Some(1.0);
+        let far = pull(lambda, 8.0) / pull(None, 8.0);
+        let near = pull(lambda, 0.5) / pull(None, 0.5);
+        // (1 + 8) e^-8 ≈ 0.003 against (1 + 0.5) e^-0.5 ≈ 0.91.
+        assert!(far < 0.01, "distant pull kept {far} of Newtonian");
+        assert!(near > 0.8, "close pull kept only {near} of Newtonian");
+    }
+
+    #[test]
+    fn a_custom_kernel_drives_the_integrated_acceleration() {
+        use crate::pru::gravity_relational::{GravityKernel, RelationalMassField};
+
+        /// The same pull everywhere, whatever the mass field.
+        struct ConstantKernel(Vec3);
+
+        impl GravityKernel for ConstantKernel {
+            fn acceleration_at(
+                &self,
+                _field: &RelationalMassField,
+                _params: &GravityParams,
+                _coords: UVec3,
+            ) -> Vec3 {
+                self.0
+            }
+        }
+
+        let (mut world, mut schedule) = gravity_world(GravityParams::default());
+        world.resource_mut::<GravityParams>().mode = GravityMode::RelationalLattice;
+        let pull = Vec3::new(0.0, -1.5, 0.5);
+        world.insert_resource(ActiveGravityKernel::new(ConstantKernel(pull)));
+        let start = Vec3::splat(8.0);
+        let cell = spawn_cell(&mut world, start, Vec3::ZERO, 1.0);
+
+        let ticks = 60;
+        step(&mut world, &mut schedule, ticks);
+        let t = ticks as f32 * world.resource::<SimulationState>().dt;
+        let dyn_state = world.get::<PruDynamics>(cell).unwrap();
+        assert!(dyn_state.acceleration.distance(pull) < 1e-6);
+        assert!(dyn_state.velocity_at_integer_tick.distance(pull * t) < 1e-4);
+        let position = world.get::<Transform>(cell).unwrap().translation;
+        assert!(
+            position.distance(start + 0.5 * pull * t * t) < 1e-4,
+            "{position}"
+        );
+    }
+
+    #[cfg(feature = "f64-dynamics")]
+    fn precise_state(world: &mut World) -> Vec<[u64; 6]> {
+        world
+            .query::<&PruDynamics>()
+            .iter(world)
+            .map(|dyn_state| {
+                let phase = dyn_state
+                    .precise
+                    .expect("integrated bodies carry f64 state");
+                let [a, b, c] = phase.position.to_array();
+                let [d, e, f] = phase.velocity_at_integer_tick.to_array();
+                [a, b, c, d, e, f].map(f64::to_bits)
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "f64-dynamics")]
+    #[test]
+    fn rewinding_restores_the_f64_state_bit_for_bit() {
+        use crate::pru::rewind::{record_rewind_snapshot, restore_rewind_snapshot, RewindBuffer};
+
+        let (mut world, _) = gravity_world(GravityParams::default());
+        world.init_resource::<RewindBuffer>();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(
+            (
+                simulate_gravity_step,
+                compute_energy_metrics,
+                record_rewind_snapshot,
+            )
+                .chain(),
+        );
+        spawn_cell(&mut world, Vec3::new(6.0, 8.0, 8.0), Vec3::Y * 0.3, 2.0);
+        spawn_cell(&mut world, Vec3::new(10.0, 8.0, 8.0), -Vec3::Y * 0.3, 2.0);
+
+        step(&mut world, &mut schedule, 10);
+        let at_ten = precise_state(&mut world);
+        step(&mut world, &mut schedule, 10);
+        let at_twenty = precise_state(&mut world);
+        assert_ne!(at_ten, at_twenty);
+
+        world.resource_mut::<RewindBuffer>().scrub_by(-10);
+        world.run_system_once(restore_rewind_snapshot);
+        assert_eq!(world.resource::<SimulationState>().tick, 10);
+        assert_eq!(precise_state(&mut world), at_ten);
+
+        // Replaying from the restored state retraces the same f64 trajectory.
+        step(&mut world, &mut schedule, 10);
+        assert_eq!(precise_state(&mut world), at_twenty);
+    }
+
+    #[cfg(feature = "f64-dynamics")]
+    #[test]
+    fn slow_drifts_accumulate_without_f32_quantization() {
+        let (mut world, mut schedule) = gravity_world(GravityParams::default());
+        let start = Vec3::new(12.0, 8.0, 8.0);
+        // Each tick moves the body 1.7e-7, under half an f32 ulp at x = 12
+        // (9.5e-7), so f32 positions would never leave the start.
+        let speed = 1.0e-5;
+        let body = spawn_cell(&mut world, start, Vec3::X * speed, 1.0);
+        let ticks = 60_000;
+        step(&mut world, &mut schedule, ticks);
+
+        let dt = world.resource::<SimulationState>().dt as f64;
+        let expected = start.x as f64 + speed as f64 * dt * ticks as f64;
+        let dyn_state = world.get::<PruDynamics>(body).unwrap();
+        let precise = dyn_state.precise.unwrap().position;
+        assert!(
+            (precise.x - expected).abs() < 1e-9,
+            "{} vs {expected}",
+            precise.x
+        );
+        let rendered = world.get::<Transform>(body).unwrap().translation.x;
+        assert!(rendered > start.x);
+        assert!((rendered as f64 - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn an_unequal_pair_conserves_total_momentum() {
+        let mut world = World::new();
+        world.insert_resource(GravityParams {
+            mode: GravityMode::NaiveNBody,
+            damping_model: DampingModel::None,
+            ..Default::default()
+        });
+        world.insert_resource(PruUniverse::new(UVec3::splat(16), 1.0));
+        world.init_resource::<MinSeparation>();
+        world.init_resource::<SimulationState>();
+        world.init_resource::<SimulationEnergy>();
+        world.init_resource::<FieldMetrics>();
+        // Unequal masses with a net drift, so momentum is neither zero nor symmetric.
+        for (x, velocity, mass) in [
+            (7.0, Vec3::new(0.2, -0.6, 0.1), 1.0),
+            (8.5, Vec3::new(0.0, 0.25, 0.0), 3.0),
+        ] {
+            let position = Vec3::new(x, 8.0, 8.0);
+            world.spawn((
+                PruCell::new(position, UVec3::ZERO, 1.0, 0.0),
+                PruDynamics {
+                    mass,
+                    velocity,
+                    velocity_at_integer_tick: velocity,
+                    ..Default::default()
+                },
+                Transform::from_translation(position),
+            ));
+        }
+        let mut schedule = Schedule::default();
+        schedule.add_systems((simulate_gravity_step, compute_energy_metrics).chain());
+        let mut step = |world: &mut World| {
+            world.resource_mut::<SimulationState>().step_once();
+            schedule.run(world);
+            world.resource::<FieldMetrics>().total_linear_momentum
+        };
+
+        let start = step(&mut world);
+        assert!(
+            (start - Vec3::new(0.2, 0.15, 0.1)).length() < 1e-5,
+            "{start}"
+        );
+        for _ in 0..400 {
+            let momentum = step(&mut world);
+            assert!(
+                (momentum - start).length() < 1e-4,
+                "momentum {momentum} from {start}"
+            );
+        }
+        let drift = world.resource::<SimulationEnergy>().momentum_drift.unwrap();
+        assert!(drift < 1e-4, "momentum drift {drift}");
+    }
 }
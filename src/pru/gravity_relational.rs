@@ -1,8 +1,8 @@
 use bevy::prelude::*;
 
-use crate::pru::cell::{PruCell, PruDynamics};
-use crate::pru::gravity::GravityParams;
-use crate::pru::universe::PruUniverse;
+use crate::pru::cell::{PruCell, PruDynamics, Real, RealVec3};
+use crate::pru::gravity::{naive_accelerations, GravityParams, Pinned};
+use crate::pru::universe::{wrap_lattice_coords, AxisBoundary, LatticePeriods, PruUniverse};
 
 /// Precomputed neighbor offsets describing the fixed PRU lattice connectivity.
 ///
@@ -19,22 +19,70 @@ pub const NEIGHBOR_OFFSETS: [IVec3; 6] = [
     IVec3::new(0, 0, -1),
 ];
 
+/// A relational force law: the acceleration a lattice site feels from the mass field.
+///
+/// The gravity step calls the kernel in [`ActiveGravityKernel`] for every
+/// integrated cell (and for stars and black holes at their nearest site), so a
+/// new force law (screened, anisotropic, distance cut-off, ...) only needs an
+/// implementation of this trait, registered in place of the default
+/// [`RelationalKernel`].
+pub trait GravityKernel: Send + Sync + 'static {
+    /// Acceleration at lattice site `coords` given the current mass field.
+    fn acceleration_at(
+        &self,
+        field: &RelationalMassField,
+        params: &GravityParams,
+        coords: UVec3,
+    ) -> Vec3;
+
+    /// Lattice offsets the kernel couples and their weights, for visualization.
+    ///
+    /// Kernels without a fixed stencil return nothing.
+    fn edges(&self) -> Vec<(IVec3, Vec3)> {
+        Vec::new()
+    }
+
+    /// The same force law rebuilt for a lattice of `spacing`, when it depends on it.
+    ///
+    /// Called when the universe is rebuilt; kernels that return `None` are kept as they are.
+    fn for_spacing(&self, _spacing: f32) -> Option<Box<dyn GravityKernel>> {
+        None
+    }
+}
+
+/// The force law the relational gravity mode runs.
+///
+/// Inserted with a [`RelationalKernel`] at startup unless a kernel was
+/// registered first, and rebuilt through [`GravityKernel::for_spacing`] when
+/// the universe is reset; replace the resource to swap force laws at runtime.
+#[derive(Resource)]
+pub struct ActiveGravityKernel(pub Box<dyn GravityKernel>);
+
+impl ActiveGravityKernel {
+    pub fn new(kernel: impl GravityKernel) -> Self {
+        Self(Box::new(kernel))
+    }
+}
+
 /// Lookup-table weights derived once from the lattice spacing.
 ///
 /// Each entry is a directionally-oriented weight that approximates
 /// (1 / r^3) * r_hat for the offset measured in lattice units. The weights are
 /// precomputed so runtime updates only perform cheap multiplications against the
 /// local mass density field.
-#[derive(Resource, Clone)]
+#[derive(Clone)]
 pub struct RelationalKernel {
     pub offsets: Vec<IVec3>,
     pub weights: Vec<Vec3>,
+    /// World-space length of each offset, for distance-dependent screening.
+    pub distances: Vec<f32>,
 }
 
 impl RelationalKernel {
     pub fn new(spacing: f32) -> Self {
         let mut offsets = Vec::with_capacity(NEIGHBOR_OFFSETS.len());
         let mut weights = Vec::with_capacity(NEIGHBOR_OFFSETS.len());
+        let mut distances = Vec::with_capacity(NEIGHBOR_OFFSETS.len());
 
         for offset in NEIGHBOR_OFFSETS.iter() {
             let world_offset = offset.as_vec3() * spacing;
@@ -44,9 +92,58 @@ impl RelationalKernel {
 
             offsets.push(*offset);
             weights.push(direction * inv_r3);
+            distances.push(world_offset.length());
         }
 
-        Self { offsets, weights }
+        Self {
+            offsets,
+            weights,
+            distances,
+        }
+    }
+}
+
+impl GravityKernel for RelationalKernel {
+    fn acceleration_at(
+        &self,
+        field: &RelationalMassField,
+        params: &GravityParams,
+        coords: UVec3,
+    ) -> Vec3 {
+        // Optional softening acts as a damped gain on the kernel to avoid
+        // runaway accelerations when the lattice is tightly packed.
+        let softened_gain = 1.0 / (1.0 + params.softening_length.max(0.0));
+        let mut accel = Vec3::ZERO;
+
+        // A neighbor across a periodic edge is reached through the same unit
+        // offset, so each weight already points along its minimum-image
+        // displacement and no wrapped positions are needed.
+        for ((offset, weight), distance) in self
+            .offsets
+            .iter()
+            .zip(self.weights.iter())
+            .zip(self.distances.iter())
+        {
+            let Some(neighbor_mass) = field.mass_at(coords.as_ivec3() + *offset) else {
+                continue;
+            };
+            let screening = params.screened_force_factor(*distance);
+            accel += *weight * (params.g_effective * neighbor_mass * softened_gain * screening);
+        }
+
+        accel
+    }
+
+    fn for_spacing(&self, spacing: f32) -> Option<Box<dyn GravityKernel>> {
+        Some(Box::new(RelationalKernel::new(spacing)))
+    }
+
+    fn edges(&self) -> Vec<(IVec3, Vec3)> {
+        self.offsets
+            .iter()
+            .copied()
+            .zip(self.weights.iter().copied())
+            .collect()
     }
 }
 
@@ -54,10 +151,133 @@ impl RelationalKernel {
 ///
 /// This system keeps the kernel in a resource so the gravity step can run with
 /// only neighbor lookups and table reads. It mirrors the PRU thesis idea of a
-/// precomputed interaction graph instead of a per-frame all-to-all solve.
-pub fn initialize_relational_kernel(mut commands: Commands, universe: Res<PruUniverse>) {
-    let kernel = RelationalKernel::new(universe.spacing);
-    commands.insert_resource(kernel);
+/// precomputed interaction graph instead of a per-frame all-to-all solve. A
+/// kernel registered before startup is left in place.
+pub fn initialize_relational_kernel(
+    mut commands: Commands,
+    universe: Res<PruUniverse>,
+    registered: Option<Res<ActiveGravityKernel>>,
+) {
+    if registered.is_none() {
+        commands.insert_resource(ActiveGravityKernel::new(RelationalKernel::new(
+            universe.spacing,
+        )));
+    }
+}
+
+/// Rebuild the active kernel for the current lattice spacing.
+///
+/// Runs after a reset, whose new lattice may have a different spacing than the
+/// one the kernel's weights were derived from.
+pub fn rebuild_relational_kernel(
+    universe: Res<PruUniverse>,
+    kernel: Option<ResMut<ActiveGravityKernel>>,
+) {
+    let Some(mut kernel) = kernel else {
+        return;
+    };
+    if let Some(rebuilt) = kernel.0.for_spacing(universe.spacing) {
+        kernel.0 = rebuilt;
+    }
+}
+
+/// The [`GravityParams::relational_g_scale`] at which `kernel` pulls a
+/// reference pair as hard as the naive solver does under `params`.
+///
+/// `g_effective`, masses, and spacing are all lattice units, and the two
+/// solvers use different force laws (a fixed stencil gain versus a softened
+/// pairwise sum), so the same `g_effective` gives them different strengths.
+/// The pair, of `masses`, sits on adjacent lattice sites `separation` apart,
+/// the distance the relational stencil couples; pass the spacing `kernel`
+/// was built for. Both solvers are evaluated at `G = 1` with the rest of
+/// `params` (softening, screening), so the scale depends on neither
+/// `g_effective` nor a previous scale, and recalibrating changes nothing.
+///
+/// Returns `None` when the kernel exerts no force on the pair.
+pub fn relational_g_scale(
+    kernel: &dyn GravityKernel,
+    params: &GravityParams,
+    separation: f32,
+    masses: (f32, f32),
+) -> Option<f32> {
+    let unit_params = GravityParams {
+        g_effective: 1.0,
+        ..params.clone()
+    };
+    let field = RelationalMassField::new(
+        UVec3::new(2, 1, 1),
+        &[(UVec3::ZERO, masses.0), (UVec3::X, masses.1)],
+    );
+    let relational = kernel
+        .acceleration_at(&field, &unit_params, UVec3::ZERO)
+        .length();
+
+    let naive = naive_accelerations(
+        &[RealVec3::ZERO, RealVec3::X * separation as Real],
+        &[masses.0, masses.1],
+        1.0,
+        params.softening_length * params.softening_length,
+        params.screening_length,
+        LatticePeriods::default(),
+        1,
+    );
+    #[cfg(feature = "f64-dynamics")]
+    let naive = naive[0].as_vec3().length();
+    #[cfg(not(feature = "f64-dynamics"))]
+    let naive = naive[0].length();
+
+    let scale = naive / relational;
+    (relational > f32::EPSILON && scale.is_finite()).then_some(scale)
+}
+
+/// Dense lattice mass buffer indexed by grid coordinates.
+///
+/// Every cell writes its mass here, whether or not it is integrated, so the
+/// static background still sources the relational kernel.
+pub struct RelationalMassField {
+    dims: UVec3,
+    boundaries: [AxisBoundary; 3],
+    mass: Vec<f32>,
+}
+
+impl RelationalMassField {
+    /// Cells whose coordinates fall outside `dims` are left out of the field.
+    pub fn new(dims: UVec3, cell_data: &[(UVec3, f32)]) -> Self {
+        let volume = dims.x as usize * dims.y as usize * dims.z as usize;
+        let mut field = Self {
+            dims,
+            boundaries: [AxisBoundary::Open; 3],
+            mass: vec![0.0f32; volume],
+        };
+        for (coords, mass) in cell_data.iter() {
+            if let Some(index) = field.index(*coords) {
+                field.mass[index] = *mass;
+            }
+        }
+        field
+    }
+
+    /// Buffer slot for `coord`, or `None` outside the lattice (always, for an empty one).
+    fn index(&self, coord: UVec3) -> Option<usize> {
+        if coord.cmpge(self.dims).any() {
+            return None;
+        }
+        let (x, y, z) = (coord.x as usize, coord.y as usize, coord.z as usize);
+        let (dy, dz) = (self.dims.y as usize, self.dims.z as usize);
+        Some((x * dy + y) * dz + z)
+    }
+
+    /// The field with per-axis boundaries, so lookups wrap along periodic axes.
+    pub fn with_boundaries(mut self, boundaries: [AxisBoundary; 3]) -> Self {
+        self.boundaries = boundaries;
+        self
+    }
+
+    /// Mass at lattice site `coords`, wrapped along periodic axes, or `None` past an open edge.
+    pub fn mass_at(&self, coords: IVec3) -> Option<f32> {
+        let coords = wrap_lattice_coords(coords, self.dims, self.boundaries)?;
+        self.index(coords).map(|index| self.mass[index])
+    }
 }
 
 /// Compute gravity using the precomputed kernel and the current mass density
@@ -66,56 +286,83 @@ pub fn initialize_relational_kernel(mut commands: Commands, universe: Res<PruUni
 /// The algorithm:
 /// 1. Build a dense mass buffer indexed by lattice coordinates (a pure lookup
 ///    table with the same shape as the universe).
-/// 2. For each cell, walk the fixed neighbor offsets and accumulate the
-///    contributions using the cached kernel weights.
+/// 2. For each integrated cell, ask the active [`GravityKernel`] for its
+///    acceleration; the default walks the fixed neighbor offsets and
+///    accumulates the contributions using the cached kernel weights.
 /// 3. Write the resulting acceleration into `PruDynamics` so the integrator can
 ///    update velocities/positions.
 ///
 /// This keeps per-tick complexity at O(N * neighbors) and emphasizes local,
-/// relational updates instead of a global all-pairs loop.
+/// relational updates instead of a global all-pairs loop. The mass field is
+/// returned so non-lattice bodies can sample it as well.
 pub fn apply_relational_gravity(
     params: &GravityParams,
     universe: &PruUniverse,
-    kernel: &RelationalKernel,
+    kernel: &dyn GravityKernel,
     cell_data: &[(UVec3, f32)],
-    bodies: &mut Query<(&mut PruCell, &mut PruDynamics, &mut Transform)>,
-) {
-    let dims = universe.grid_dimensions;
-    let volume = (dims.x * dims.y * dims.z) as usize;
-    let mut mass_field = vec![0.0f32; volume];
-
-    let idx = |coord: UVec3| -> usize {
-        (coord.x * dims.y * dims.z + coord.y * dims.z + coord.z) as usize
-    };
+    bodies: &mut Query<(&mut PruCell, &mut PruDynamics, &mut Transform, Has<Pinned>)>,
+) -> RelationalMassField {
+    let _span = info_span!("relational_kernel", cells = cell_data.len()).entered();
+    let field = RelationalMassField::new(universe.grid_dimensions, cell_data)
+        .with_boundaries(universe.boundaries());
 
-    for (coords, mass) in cell_data.iter() {
-        mass_field[idx(*coords)] = *mass;
+    for (cell, mut dynamics, _, pinned) in bodies.iter_mut() {
+        if pinned || !params.integrates_cell(dynamics.mass) {
+            continue;
+        }
+        dynamics.acceleration = kernel.acceleration_at(&field, params, cell.grid_coords);
     }
 
-    for (cell, mut dynamics, _) in bodies.iter_mut() {
-        let mut accel = Vec3::ZERO;
-
-        for (offset, weight) in kernel.offsets.iter().zip(kernel.weights.iter()) {
-            let neighbor = cell.grid_coords.as_ivec3() + *offset;
-            if neighbor.x < 0
-                || neighbor.y < 0
-                || neighbor.z < 0
-                || neighbor.x >= dims.x as i32
-                || neighbor.y >= dims.y as i32
-                || neighbor.z >= dims.z as i32
-            {
-                continue;
-            }
+    field
+}
 
-            let neighbor_coords = neighbor.as_uvec3();
-            let neighbor_mass = mass_field[idx(neighbor_coords)];
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            // Optional softening acts as a damped gain on the kernel to avoid
-            // runaway accelerations when the lattice is tightly packed.
-            let softened_gain = 1.0 / (1.0 + params.softening_length.max(0.0));
-            accel += *weight * (params.g_effective * neighbor_mass * softened_gain);
-        }
+    #[test]
+    fn calibrated_scale_matches_the_naive_pull_and_is_idempotent() {
+        let spacing = 0.8;
+        let kernel = RelationalKernel::new(spacing);
+        let masses = (1.3, 1.3);
+        let mut params = GravityParams {
+            g_effective: 0.6,
+            ..Default::default()
+        };
+        let scale = relational_g_scale(&kernel, &params, spacing, masses).unwrap();
+        params.relational_g_scale = scale;
+        assert_eq!(
+            relational_g_scale(&kernel, &params, spacing, masses),
+            Some(scale)
+        );
 
-        dynamics.acceleration = accel;
+        let relational_params = GravityParams {
+            g_effective: params.effective_g(),
+            ..params.clone()
+        };
+        let field = RelationalMassField::new(
+            UVec3::new(2, 1, 1),
+            &[(UVec3::ZERO, masses.0), (UVec3::X, masses.1)],
+        );
+        let relational = kernel
+            .acceleration_at(&field, &relational_params, UVec3::ZERO)
+            .length();
+        let naive = naive_accelerations(
+            &[RealVec3::ZERO, RealVec3::X * spacing as Real],
+            &[masses.0, masses.1],
+            params.g_effective,
+            params.softening_length * params.softening_length,
+            params.screening_length,
+            LatticePeriods::default(),
+            1,
+        );
+        #[cfg(feature = "f64-dynamics")]
+        let naive = naive[0].as_vec3().length();
+        #[cfg(not(feature = "f64-dynamics"))]
+        let naive = naive[0].length();
+        assert!(
+            (relational - naive).abs() <= 1e-5 * naive,
+            "relational {relational} != naive {naive}"
+        );
     }
 }
@@ -0,0 +1,315 @@
+//! Runtime lattice growth: add a layer of cells where mass crowds a boundary face.
+//!
+//! Collapsing or expanding setups push cells toward the lattice edge, where
+//! the relational kernel's stencil is clipped. While growth is enabled, each
+//! tick measures the share of total mass within one spacing of every open
+//! face. A face holding more than [`LatticeGrowthSettings::boundary_mass_fraction`]
+//! gets a new layer of low-mass cells past it, up to
+//! [`LatticeGrowthSettings::max_dimensions`].
+//!
+//! Existing cells keep their rest positions: the universe's `origin_shift`
+//! absorbs the change in `center_offset`, so the lattice drifts off center as
+//! it grows. A layer on a low face renumbers the existing cells one step along
+//! that axis and renames them to match. Galaxy region keys are recomputed
+//! with the scan's region size, an in-flight galaxy scan is dropped, and the
+//! rewind buffer is cleared, since its snapshots hold the old numbering. A
+//! universe reset returns to `UniverseSettings`'s size.
+
+use bevy::prelude::*;
+
+use crate::agents::events::{AstroReport, AstroReportLog, ReportSeverity};
+use crate::app::SimulationState;
+use crate::astro::formation::{FormationSettings, GalaxyScanTask};
+use crate::astro::galaxy::Galaxy;
+use crate::pru::cell::{PruCell, PruDynamics, MIN_CELL_MASS};
+use crate::pru::rewind::RewindBuffer;
+use crate::pru::universe::{cell_name, spawn_cell, AxisBoundary, PruUniverse};
+use crate::render::lod::CellMeshes;
+
+/// When and how far the lattice grows.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct LatticeGrowthSettings {
+    pub enabled: bool,
+    /// Share of total mass within one spacing of a face that makes it grow.
+    ///
+    /// Above the 1/10 an edge layer of the default 10-cell lattice holds at rest.
+    pub boundary_mass_fraction: f32,
+    /// Largest lattice growth can produce, per axis.
+    pub max_dimensions: UVec3,
+}
+
+impl Default for LatticeGrowthSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            boundary_mass_fraction: 0.25,
+            max_dimensions: UVec3::splat(24),
+        }
+    }
+}
+
+/// Share of `bodies`' total mass within one spacing of each face, as `[axis][low, high]`.
+///
+/// Faces sit half a spacing outside the outermost sites, where the lattice's
+/// volume ends, so at rest only the outer layer counts. Bodies that drifted
+/// past a face count toward it.
+pub fn boundary_mass_fractions(
+    universe: &PruUniverse,
+    bodies: impl IntoIterator<Item = (Vec3, f32)>,
+) -> [[f32; 2]; 3] {
+    let (low, high) = universe.bounds();
+    let mut near = [[0.0f32; 2]; 3];
+    let mut total = 0.0f32;
+    for (position, mass) in bodies {
+        total += mass;
+        for axis in 0..3 {
+            if position[axis] < low[axis] + universe.spacing {
+                near[axis][0] += mass;
+            }
+            if position[axis] > high[axis] - universe.spacing {
+                near[axis][1] += mass;
+            }
+        }
+    }
+    if total > 0.0 {
+        for faces in near.iter_mut() {
+            for share in faces.iter_mut() {
+                *share /= total;
+            }
+        }
+    }
+    near
+}
+
+/// Grow the lattice past every crowded open face, once per elapsed tick span.
+#[allow(clippy::too_many_arguments)]
+pub fn grow_lattice(
+    mut commands: Commands,
+    sim_state: Res<SimulationState>,
+    settings: Res<LatticeGrowthSettings>,
+    mut universe: ResMut<PruUniverse>,
+    cell_meshes: Res<CellMeshes>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut reports: Option<ResMut<AstroReportLog>>,
+    mut rewind: Option<ResMut<RewindBuffer>>,
+    mut scan_task: Option<ResMut<GalaxyScanTask>>,
+    formation: Option<Res<FormationSettings>>,
+    mut cells: Query<(&mut PruCell, &PruDynamics, Option<&mut Name>)>,
+    mut galaxies: Query<&mut Galaxy>,
+) {
+    if !settings.enabled || !sim_state.advanced_this_frame() {
+        return;
+    }
+    let fractions = boundary_mass_fractions(
+        &universe,
+        cells
+            .iter()
+            .map(|(cell, dynamics, _)| (cell.position, dynamics.mass)),
+    );
+
+    // Sites of the new layers; spawned once every face is done, so later
+    // low-face growth renumbers them along with the existing cells.
+    let mut new_sites: Vec<UVec3> = Vec::new();
+    let mut shift = UVec3::ZERO;
+    for (axis, faces) in fractions.iter().enumerate() {
+        if universe.boundaries()[axis] != AxisBoundary::Open {
+            continue;
+        }
+        for (face, share) in faces.iter().enumerate() {
+            if *share <= settings.boundary_mass_fraction
+                || universe.grid_dimensions[axis] >= settings.max_dimensions[axis]
+            {
+                continue;
+            }
+            let high_face = face == 1;
+            let offset = universe.grow(axis, high_face);
+            shift += offset;
+            for site in new_sites.iter_mut() {
+                *site += offset;
+            }
+            let dims = universe.grid_dimensions;
+            let layer = if high_face { dims[axis] - 1 } else { 0 };
+            let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+            for i in 0..dims[a] {
+                for j in 0..dims[b] {
+                    let mut site = UVec3::ZERO;
+                    site[axis] = layer;
+                    site[a] = i;
+                    site[b] = j;
+                    new_sites.push(site);
+                }
+            }
+        }
+    }
+    if new_sites.is_empty() {
+        return;
+    }
+
+    if shift != UVec3::ZERO {
+        for (mut cell, _, name) in cells.iter_mut() {
+            cell.grid_coords += shift;
+            if let Some(mut name) = name {
+                *name = cell_name(cell.grid_coords);
+            }
+        }
+        if let Some(scan_task) = scan_task.as_mut() {
+            // A running scan keyed its regions with the old numbering.
+            scan_task.task = None;
+            if let Some(formation) = formation.as_deref() {
+                let region_size = scan_task.region_size(formation);
+                for mut galaxy in galaxies.iter_mut() {
+                    let key = universe.nearest_grid_coords(galaxy.center) / region_size;
+                    if galaxy.region_key != key {
+                        galaxy.region_key = key;
+                    }
+                }
+            }
+        }
+    }
+    for site in &new_sites {
+        spawn_cell(
+            &mut commands,
+            &mut materials,
+            &cell_meshes.full,
+            PruCell::new(
+                universe.rest_position(*site),
+                *site,
+                MIN_CELL_MASS as f64,
+                0.0,
+            ),
+            Vec3::ZERO,
+        );
+    }
+    universe.total_cells += new_sites.len();
+    if let Some(rewind) = rewind.as_mut() {
+        rewind.clear();
+    }
+
+    let dims = universe.grid_dimensions;
+    let summary = format!(
+        "Universe grew to {}×{}×{} at tick {}",
+        dims.x, dims.y, dims.z, sim_state.tick
+    );
+    info!("{summary} ({} new cells)", new_sites.len());
+    if let Some(reports) = reports.as_mut() {
+        reports.push(AstroReport {
+            tick: sim_state.tick,
+            severity: ReportSeverity::Info,
+            summary,
+            position: None,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn boundary_shares_count_the_outer_layer_and_escaped_bodies() {
+        let universe = PruUniverse::new(UVec3::splat(4), 1.0);
+        let lattice: Vec<(Vec3, f32)> = (0..64)
+            .map(|i| UVec3::new(i % 4, (i / 4) % 4, i / 16))
+            .map(|site| (universe.rest_position(site), 1.0))
+            .collect();
+        for faces in boundary_mass_fractions(&universe, lattice.iter().copied()) {
+            assert_eq!(faces, [0.25, 0.25]);
+        }
+
+        // A body as heavy as the lattice, drifted past the low x face.
+        let escaped = (Vec3::new(-10.0, 0.0, 0.0), 64.0);
+        let fractions = boundary_mass_fractions(&universe, lattice.into_iter().chain([escaped]));
+        assert_eq!(fractions[0], [(16.0 + 64.0) / 128.0, 16.0 / 128.0]);
+        assert_eq!(fractions[1], [16.0 / 128.0; 2]);
+        assert_eq!(boundary_mass_fractions(&universe, []), [[0.0; 2]; 3]);
+    }
+
+    #[test]
+    fn growing_a_low_face_renumbers_cells_and_re_keys_galaxies() {
+        let mut world = World::new();
+        world.insert_resource(SimulationState {
+            steps_this_frame: 1,
+            ..Default::default()
+        });
+        world.insert_resource(LatticeGrowthSettings {
+            enabled: true,
+            boundary_mass_fraction: 0.2,
+            ..Default::default()
+        });
+        let universe = PruUniverse::new(UVec3::splat(4), 1.0);
+        world.insert_resource(universe.clone());
+        world.insert_resource(CellMeshes {
+            full: Handle::default(),
+            reduced: Handle::default(),
+        });
+        world.init_resource::<Assets<StandardMaterial>>();
+        world.init_resource::<FormationSettings>();
+        world.insert_resource(GalaxyScanTask {
+            task: None,
+            keyed_region_size: Some(2),
+        });
+
+        // A heavy cell on the low x face crowds it; no other face passes 0.2.
+        let cells: Vec<(Entity, UVec3, Vec3)> = (0..64)
+            .map(|i| UVec3::new(i % 4, (i / 4) % 4, i / 16))
+            .map(|site| {
+                let position = universe.rest_position(site);
+                let mass = if site == UVec3::new(0, 1, 1) {
+                    64.0
+                } else {
+                    1.0
+                };
+                let entity = world
+                    .spawn((
+                        PruCell::new(position, site, mass as f64, 0.0),
+                        PruDynamics {
+                            mass,
+                            ..Default::default()
+                        },
+                        cell_name(site),
+                    ))
+                    .id();
+                (entity, site, position)
+            })
+            .collect();
+        let center = universe.rest_position(UVec3::ONE);
+        let galaxy = world
+            .spawn(Galaxy {
+                id: 0,
+                total_mass: 100.0,
+                radius: 1.0,
+                num_stars: 1,
+                center,
+                region_key: UVec3::ZERO,
+            })
+            .id();
+
+        world.run_system_once(grow_lattice);
+        let grown = world.resource::<PruUniverse>().clone();
+        assert_eq!(grown.grid_dimensions, UVec3::new(5, 4, 4));
+        for (entity, site, position) in cells {
+            let cell = world.get::<PruCell>(entity).unwrap();
+            assert_eq!(cell.grid_coords, site + UVec3::X);
+            assert_eq!(cell.position, position);
+            assert!(grown.rest_position(cell.grid_coords).distance(position) < 1e-5);
+            assert_eq!(
+                *world.get::<Name>(entity).unwrap(),
+                cell_name(cell.grid_coords)
+            );
+        }
+        let new_layer: Vec<UVec3> = world
+            .query::<&PruCell>()
+            .iter(&world)
+            .filter(|cell| cell.grid_coords.x == 0)
+            .map(|cell| cell.grid_coords)
+            .collect();
+        assert_eq!(new_layer.len(), 16);
+        // The galaxy sits at the same place, now two sites in along x.
+        assert_eq!(
+            world.get::<Galaxy>(galaxy).unwrap().region_key,
+            UVec3::new(1, 0, 0)
+        );
+    }
+}
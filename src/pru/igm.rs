@@ -0,0 +1,166 @@
+//! Diffuse inter-galactic medium (IGM) in the emptiest lattice cells.
+//!
+//! Cells whose local density falls well below the lattice mean are labeled
+//! [`Igm`] and treated as a thin ideal gas: pressure is density times
+//! temperature, where temperature is a photo-heated floor plus the cell's
+//! kinetic term. Galaxies feel the net pressure of the IGM around them, so a
+//! galaxy with dense, hot gas on one side drifts toward the thinner side, a
+//! weak stand-in for ram-pressure stripping and feedback.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::app::SimulationState;
+use crate::astro::galaxy::{Galaxy, StarGalaxyMembership};
+use crate::astro::star::Star;
+use crate::pru::cell::{DerivedFields, PruCell, PruDynamics};
+use crate::pru::universe::FieldMetrics;
+use crate::spatial::SpatialOctree;
+
+/// Gas state of a cell labeled as inter-galactic medium.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Igm {
+    pub density: f32,
+    pub temperature: f32,
+}
+
+impl Igm {
+    /// Ideal-gas pressure in lattice units.
+    pub fn pressure(&self) -> f32 {
+        self.density * self.temperature
+    }
+}
+
+/// Tunables for IGM labeling and its pressure on galaxies.
+#[derive(Resource, Clone, Copy)]
+pub struct IgmSettings {
+    /// Cells below this fraction of the mean local density are labeled IGM.
+    ///
+    /// Relative rather than absolute because the density kernel sums
+    /// neighboring masses, so absolute values scale with lattice spacing.
+    pub density_fraction: f32,
+    /// Temperature floor from the ionizing background.
+    pub base_temperature: f32,
+    /// Scale of the pressure acceleration on galaxies; small so it nudges rather than disrupts.
+    pub pressure_strength: f32,
+    /// Distance beyond a galaxy's radius within which IGM cells press on it.
+    pub pressure_range: f32,
+}
+
+impl Default for IgmSettings {
+    fn default() -> Self {
+        Self {
+            density_fraction: 0.5,
+            base_temperature: 1.0,
+            pressure_strength: 0.02,
+            pressure_range: 3.0,
+        }
+    }
+}
+
+/// Label low-density cells as IGM, refresh their gas state, and record the IGM fraction.
+pub fn label_igm_cells(
+    mut commands: Commands,
+    settings: Res<IgmSettings>,
+    mut metrics: ResMut<FieldMetrics>,
+    mut cells: Query<(Entity, &DerivedFields, &PruDynamics, Option<&mut Igm>), With<PruCell>>,
+) {
+    let threshold = metrics.avg_density * settings.density_fraction;
+    let mut total = 0usize;
+    let mut labeled = 0usize;
+    for (entity, derived, dynamics, igm) in cells.iter_mut() {
+        total += 1;
+        if derived.local_density >= threshold {
+            if igm.is_some() {
+                commands.entity(entity).remove::<Igm>();
+            }
+            continue;
+        }
+
+        labeled += 1;
+        // Kinetic temperature: m <v²> / 3 per degree of freedom, on top of the floor.
+        let state = Igm {
+            density: derived.local_density,
+            temperature: settings.base_temperature
+                + dynamics.mass * dynamics.velocity.length_squared() / 3.0,
+        };
+        match igm {
+            Some(mut igm) => *igm = state,
+            None => {
+                commands.entity(entity).insert(state);
+            }
+        }
+    }
+    metrics.igm_fraction = if total > 0 {
+        labeled as f32 / total as f32
+    } else {
+        0.0
+    };
+}
+
+/// Push galaxy member stars along the net IGM pressure force on their galaxy.
+///
+/// Only stars whose [`StarGalaxyMembership`] names the galaxy are pushed, so
+/// tidally stripped stars drift on their own.
+///
+/// The force sums each nearby IGM cell's pressure along its direction toward
+/// the galaxy center, a discrete version of -∮P dA over the galaxy's
+/// surroundings, so uniform gas cancels and only pressure gradients act.
+/// Stars only move under it when the gravity step integrates structures.
+pub fn apply_igm_pressure(
+    sim_state: Res<SimulationState>,
+    settings: Res<IgmSettings>,
+    octree: Res<SpatialOctree>,
+    mut last_tick: Local<Option<u64>>,
+    igm_cells: Query<(&PruCell, &Igm)>,
+    galaxies: Query<(Entity, &Galaxy)>,
+    mut stars: Query<(&mut PruDynamics, &mut StarGalaxyMembership), With<Star>>,
+) {
+    // Apply once per elapsed tick span; ticks restart from zero after a reset.
+    let steps = match *last_tick {
+        Some(last) if sim_state.tick >= last => sim_state.tick - last,
+        _ => 0,
+    };
+    *last_tick = Some(sim_state.tick);
+    if steps == 0 || settings.pressure_strength <= 0.0 {
+        return;
+    }
+
+    let dt = sim_state.dt * steps as f32;
+    let mut kicks: HashMap<Entity, Vec3> = HashMap::new();
+    for (entity, galaxy) in galaxies.iter() {
+        if galaxy.total_mass <= 0.0 {
+            continue;
+        }
+
+        let mut force = Vec3::ZERO;
+        for entity in octree.query_radius(galaxy.center, galaxy.radius + settings.pressure_range) {
+            let Ok((cell, igm)) = igm_cells.get(entity) else {
+                continue;
+            };
+            let inward = (galaxy.center - cell.position).normalize_or_zero();
+            force += inward * igm.pressure();
+        }
+        if force == Vec3::ZERO {
+            continue;
+        }
+
+        kicks.insert(
+            entity,
+            force * (settings.pressure_strength / galaxy.total_mass) * dt,
+        );
+    }
+    if kicks.is_empty() {
+        return;
+    }
+
+    for (mut dynamics, mut membership) in stars.iter_mut() {
+        let Some((entity, _)) = membership.galaxy.get(&galaxies) else {
+            continue;
+        };
+        if let Some(delta_v) = kicks.get(&entity) {
+            dynamics.kick(*delta_v);
+        }
+    }
+}
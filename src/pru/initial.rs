@@ -0,0 +1,172 @@
+//! Initial conditions for a freshly built lattice.
+//!
+//! Cells always sit on lattice sites, so neighbor stencils and grid lookups
+//! keep working; an initial condition only shapes the UA locks (and with them
+//! the masses) and the starting velocities.
+
+use bevy::prelude::*;
+
+use crate::astro::galaxy::circular_orbit_velocity;
+use crate::pru::universe::UniverseSettings;
+
+/// Spiral arm density contrast `A` in the disk profile.
+const SPIRAL_ARM_AMPLITUDE: f32 = 0.5;
+/// Bar density relative to the disk's central density.
+const BAR_AMPLITUDE: f32 = 1.0;
+/// Bar half-width across its major axis, as a fraction of its half-length.
+const BAR_ASPECT: f32 = 0.3;
+/// Disk scale length `r_s`, as a fraction of the disk radius.
+const DISK_SCALE_FRACTION: f32 = 0.3;
+/// Exponential scale height of the disk, in lattice spacings.
+const DISK_SCALE_HEIGHT: f32 = 0.5;
+
+/// How `setup_universe` seeds the lattice.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum InitialConditions {
+    /// UA locks drawn uniformly from `0.4..1.6`, with small random velocities.
+    #[default]
+    Uniform,
+    /// A rotating disk galaxy in the lattice's y = 0 mid-plane, normal to
+    /// [`GALAXY_ROTATION_AXIS`](crate::astro::galaxy::GALAXY_ROTATION_AXIS), with logarithmic spiral arms and a central bar.
+    SpiralDisk {
+        arms: u32,
+        /// Angle between the arms and the circles of constant radius, in radians.
+        pitch_angle: f32,
+        /// Half-length of the bar along the x axis, in world units; 0 for no bar.
+        bar_half_length: f32,
+    },
+}
+
+impl InitialConditions {
+    /// A two-armed disk with 20° arms and a bar two spacings long on each side.
+    pub fn spiral_disk(spacing: f32) -> Self {
+        InitialConditions::SpiralDisk {
+            arms: 2,
+            pitch_angle: 20f32.to_radians(),
+            bar_half_length: 2.0 * spacing,
+        }
+    }
+}
+
+impl std::str::FromStr for InitialConditions {
+    type Err = String;
+
+    /// `uniform` or `spiral-disk`, the latter shaped for the default lattice spacing.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "uniform" => Ok(InitialConditions::Uniform),
+            "spiral-disk" => Ok(InitialConditions::spiral_disk(
+                UniverseSettings::default().spacing,
+            )),
+            _ => Err("expected uniform or spiral-disk".to_string()),
+        }
+    }
+}
+
+/// Surface density of the spiral disk at mid-plane position `(x, z)`, before normalization.
+///
+/// `ρ(r, φ) = ρ₀ e^{-r/r_s} (1 + A cos(m (φ − ln(r/r_s) / tan p)))`, plus a
+/// bar along x that fades past `bar_half_length` and across `BAR_ASPECT` of it.
+pub fn spiral_disk_density(
+    x: f32,
+    z: f32,
+    scale_length: f32,
+    arms: u32,
+    pitch_angle: f32,
+    bar_half_length: f32,
+) -> f32 {
+    let r = (x * x + z * z).sqrt();
+    let scale_length = scale_length.max(f32::EPSILON);
+    let disk = (-r / scale_length).exp();
+    let arm_phase = if r > f32::EPSILON {
+        let phi = z.atan2(x);
+        let winding = (r / scale_length).ln() / pitch_angle.tan().max(1e-3);
+        (arms as f32 * (phi - winding)).cos()
+    } else {
+        0.0
+    };
+    let bar = if bar_half_length > 0.0 {
+        let width = bar_half_length * BAR_ASPECT;
+        BAR_AMPLITUDE * (-(x / bar_half_length).powi(4) - (z / width).powi(2)).exp()
+    } else {
+        0.0
+    };
+    disk * (1.0 + SPIRAL_ARM_AMPLITUDE * arm_phase) + bar
+}
+
+/// A lattice cell as seeded, before it is spawned.
+#[derive(Clone, Copy, Debug)]
+pub struct SeedCell {
+    pub grid_coords: UVec3,
+    pub position: Vec3,
+    pub ua_mass_lock: f64,
+    pub ub_geom_lock: f64,
+    pub velocity: Vec3,
+}
+
+/// Reshape seeded cells into a spiral disk and set them on circular orbits.
+///
+/// UA locks follow [`spiral_disk_density`], falling off exponentially away
+/// from the mid-plane, and are rescaled so the lattice keeps the mean lock
+/// (and so roughly the total mass) of the uniform seed. Each cell then moves
+/// at `v_c = sqrt(G M(<r) / r)` for the mass inside its cylindrical radius.
+pub fn apply_spiral_disk(
+    cells: &mut [SeedCell],
+    arms: u32,
+    pitch_angle: f32,
+    bar_half_length: f32,
+    spacing: f32,
+    g_effective: f32,
+    mass_from_lock: impl Fn(f64) -> f32,
+) {
+    if cells.is_empty() {
+        return;
+    }
+    let in_plane = |position: Vec3| position.xz().length();
+    let disk_radius = cells
+        .iter()
+        .map(|cell| in_plane(cell.position))
+        .fold(0.0, f32::max);
+    let scale_length = (disk_radius * DISK_SCALE_FRACTION).max(spacing);
+    let scale_height = DISK_SCALE_HEIGHT * spacing;
+
+    let densities: Vec<f32> = cells
+        .iter()
+        .map(|cell| {
+            let height = cell.position.y.abs();
+            spiral_disk_density(
+                cell.position.x,
+                cell.position.z,
+                scale_length,
+                arms,
+                pitch_angle,
+                bar_half_length,
+            ) * (-height / scale_height).exp()
+        })
+        .collect();
+    let mean_lock = cells.iter().map(|cell| cell.ua_mass_lock).sum::<f64>() / cells.len() as f64;
+    let mean_density = densities.iter().sum::<f32>() as f64 / cells.len() as f64;
+    let normalization = if mean_density > 0.0 {
+        mean_lock / mean_density
+    } else {
+        0.0
+    };
+    for (cell, density) in cells.iter_mut().zip(&densities) {
+        cell.ua_mass_lock = *density as f64 * normalization;
+    }
+
+    // Enclosed mass by cylindrical radius, accumulated in order of radius.
+    let mut by_radius: Vec<(f32, usize)> = cells
+        .iter()
+        .enumerate()
+        .map(|(index, cell)| (in_plane(cell.position), index))
+        .collect();
+    by_radius.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut enclosed = 0.0;
+    for (_, index) in by_radius {
+        let cell = &mut cells[index];
+        let center = Vec3::Y * cell.position.y;
+        cell.velocity = circular_orbit_velocity(center, cell.position, enclosed, g_effective);
+        enclosed += mass_from_lock(cell.ua_mass_lock);
+    }
+}
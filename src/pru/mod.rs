@@ -6,7 +6,15 @@
 //! enabling deterministic, reproducible simulations.
 
 pub mod cell;
+pub mod cmb;
+pub mod correlation;
 pub mod gravity;
 pub mod gravity_relational;
+pub mod growth;
+pub mod igm;
+pub mod initial;
+pub mod rewind;
 pub mod rules;
+pub mod separation;
+pub mod supercell;
 pub mod universe;
@@ -0,0 +1,277 @@
+//! Short-term rewind: an in-memory ring buffer of the last few seconds of lattice state.
+//!
+//! After every tick the cells' `PruCell` and `PruDynamics` are copied into
+//! [`RewindBuffer`], keeping at most [`RewindBuffer::capacity`] ticks. Scrubbing
+//! pauses the simulation and writes a past snapshot back onto the cells, tick
+//! counter included, so what is shown is exactly the state that tick had.
+//! Resuming or stepping from there re-enters live simulation: the next tick
+//! recorded replaces the snapshots after it, discarding that future.
+//!
+//! Only the lattice rewinds. Stars, black holes, galaxies, and planets formed
+//! since the scrubbed tick stay where they are, and a universe reset clears
+//! the buffer.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::app::SimulationState;
+use crate::pru::cell::{PruCell, PruDynamics};
+use crate::pru::gravity::GravitySet;
+use crate::render::interpolation::TickInterpolation;
+
+/// Ticks kept by default: five seconds at the default 60 Hz.
+pub const DEFAULT_REWIND_TICKS: usize = 300;
+
+/// Lattice state at the end of one tick.
+#[derive(Clone, Debug)]
+pub struct TickSnapshot {
+    pub tick: u64,
+    pub simulation_time: f32,
+    pub cells: Vec<(Entity, PruCell, PruDynamics)>,
+}
+
+/// Recent lattice snapshots, oldest first, and which one is being shown.
+#[derive(Resource, Debug)]
+pub struct RewindBuffer {
+    /// Most snapshots kept; older ones are dropped as new ticks arrive.
+    pub capacity: usize,
+    snapshots: VecDeque<TickSnapshot>,
+    /// Snapshots back from the newest that the lattice currently shows; 0 is live.
+    offset: usize,
+    /// Set by scrubbing until the shown snapshot has been written to the cells.
+    restore_pending: bool,
+    /// Tick of the state the cells hold, recorded or restored.
+    last_tick: Option<u64>,
+    /// `SimulationState::generation` the snapshots belong to.
+    generation: u64,
+}
+
+impl Default for RewindBuffer {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_REWIND_TICKS)
+    }
+}
+
+impl RewindBuffer {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            snapshots: VecDeque::new(),
+            offset: 0,
+            restore_pending: false,
+            last_tick: None,
+            generation: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Snapshots back from the newest being shown; 0 while live.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Furthest back a scrub can reach.
+    pub fn max_offset(&self) -> usize {
+        self.snapshots.len().saturating_sub(1)
+    }
+
+    /// The snapshot being shown, or the newest one while live.
+    pub fn shown(&self) -> Option<&TickSnapshot> {
+        self.snapshots
+            .len()
+            .checked_sub(1 + self.offset)
+            .and_then(|index| self.snapshots.get(index))
+    }
+
+    /// Store the state after a tick, dropping any snapshots it supersedes.
+    ///
+    /// Snapshots at or after `snapshot.tick` belong to a future abandoned by
+    /// resuming from a scrubbed tick, so they go; the buffer is live again.
+    pub fn record(&mut self, snapshot: TickSnapshot) {
+        while self
+            .snapshots
+            .back()
+            .is_some_and(|newest| newest.tick >= snapshot.tick)
+        {
+            self.snapshots.pop_back();
+        }
+        self.last_tick = Some(snapshot.tick);
+        self.snapshots.push_back(snapshot);
+        while self.snapshots.len() > self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.offset = 0;
+        self.restore_pending = false;
+    }
+
+    /// Show the snapshot `offset` ticks back, clamped to the buffer.
+    pub fn scrub_to(&mut self, offset: usize) {
+        let offset = offset.min(self.max_offset());
+        if offset != self.offset {
+            self.offset = offset;
+            self.restore_pending = true;
+        }
+    }
+
+    /// Move the shown snapshot by `delta` ticks; negative goes back in time.
+    pub fn scrub_by(&mut self, delta: i64) {
+        let offset = self.offset as i64 - delta;
+        self.scrub_to(offset.max(0) as usize);
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.offset = 0;
+        self.restore_pending = false;
+        self.last_tick = None;
+    }
+}
+
+/// Records the lattice every tick and restores it when scrubbed.
+pub struct RewindPlugin;
+
+impl Plugin for RewindPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RewindBuffer>()
+            .add_systems(
+                FixedUpdate,
+                record_rewind_snapshot.after(GravitySet::Diagnostics),
+            )
+            .add_systems(Update, restore_rewind_snapshot);
+    }
+}
+
+/// Copy the lattice into the buffer whenever the tick counter moved.
+pub fn record_rewind_snapshot(
+    sim_state: Res<SimulationState>,
+    mut buffer: ResMut<RewindBuffer>,
+    cells: Query<(Entity, &PruCell, &PruDynamics)>,
+) {
+    if buffer.generation != sim_state.generation {
+        buffer.clear();
+        buffer.generation = sim_state.generation;
+    }
+    if buffer.last_tick == Some(sim_state.tick) {
+        return;
+    }
+    let cells = cells
+        .iter()
+        .map(|(entity, cell, dynamics)| (entity, *cell, *dynamics))
+        .collect();
+    buffer.record(TickSnapshot {
+        tick: sim_state.tick,
+        simulation_time: sim_state.simulation_time,
+        cells,
+    });
+}
+
+/// Write the scrubbed-to snapshot onto the cells and pause there.
+pub fn restore_rewind_snapshot(
+    mut buffer: ResMut<RewindBuffer>,
+    mut sim_state: ResMut<SimulationState>,
+    mut cells: Query<(
+        &mut PruCell,
+        &mut PruDynamics,
+        &mut Transform,
+        Option<&mut TickInterpolation>,
+    )>,
+) {
+    if !buffer.restore_pending {
+        return;
+    }
+    buffer.restore_pending = false;
+    let Some(snapshot) = buffer.shown() else {
+        return;
+    };
+    let (tick, simulation_time) = (snapshot.tick, snapshot.simulation_time);
+    // Cells despawned since are skipped; the snapshot cannot bring them back.
+    for (entity, cell, dynamics) in &snapshot.cells {
+        let Ok((mut live_cell, mut live_dynamics, mut transform, interpolation)) =
+            cells.get_mut(*entity)
+        else {
+            continue;
+        };
+        *live_cell = *cell;
+        *live_dynamics = *dynamics;
+        transform.translation = cell.position;
+        // Otherwise interpolation would draw the cell back at its live position.
+        if let Some(mut interpolation) = interpolation {
+            *interpolation = TickInterpolation::new(cell.position);
+        }
+    }
+
+    sim_state.running = false;
+    sim_state.pending_steps = 0;
+    sim_state.tick = tick;
+    sim_state.simulation_time = simulation_time;
+    buffer.last_tick = Some(tick);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn lattice_state(world: &mut World) -> Vec<(Entity, [u32; 9])> {
+        let mut state: Vec<(Entity, [u32; 9])> = world
+            .query::<(Entity, &PruCell, &PruDynamics)>()
+            .iter(world)
+            .map(|(entity, cell, dynamics)| {
+                let [a, b, c] = cell.position.to_array();
+                let [d, e, f] = dynamics.velocity.to_array();
+                let [g, h, i] = dynamics.velocity_at_integer_tick.to_array();
+                (entity, [a, b, c, d, e, f, g, h, i].map(f32::to_bits))
+            })
+            .collect();
+        state.sort_by_key(|(entity, _)| *entity);
+        state
+    }
+
+    #[test]
+    fn scrubbing_back_restores_the_exact_lattice_state() {
+        let mut world = World::new();
+        world.init_resource::<SimulationState>();
+        world.init_resource::<RewindBuffer>();
+        for i in 0..4 {
+            let position = Vec3::new(i as f32 * 0.7, 0.1, -0.3);
+            world.spawn((
+                PruCell::new(position, UVec3::new(i, 0, 0), 1.0, 0.5),
+                PruDynamics::default(),
+                Transform::from_translation(position),
+            ));
+        }
+
+        let (total, back) = (10u64, 6usize);
+        let mut expected = None;
+        for tick in 1..=total {
+            world.resource_mut::<SimulationState>().tick = tick;
+            for (mut cell, mut dynamics) in world
+                .query::<(&mut PruCell, &mut PruDynamics)>()
+                .iter_mut(&mut world)
+            {
+                dynamics.velocity += Vec3::new(0.013, -0.007, 0.1 / tick as f32);
+                dynamics.velocity_at_integer_tick = dynamics.velocity * 1.01;
+                cell.position += dynamics.velocity / 60.0;
+            }
+            world.run_system_once(record_rewind_snapshot);
+            if tick == total - back as u64 {
+                expected = Some(lattice_state(&mut world));
+            }
+        }
+
+        world
+            .resource_mut::<RewindBuffer>()
+            .scrub_by(-(back as i64));
+        world.run_system_once(restore_rewind_snapshot);
+
+        assert_eq!(Some(lattice_state(&mut world)), expected);
+        let sim_state = world.resource::<SimulationState>();
+        assert_eq!(sim_state.tick, total - back as u64);
+        assert!(!sim_state.running);
+    }
+}
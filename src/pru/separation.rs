@@ -0,0 +1,144 @@
+//! Optional soft-core repulsion keeping bodies from interpenetrating.
+//!
+//! This is a visual/stability aid rather than a pressure model: the push only
+//! switches on inside `MinSeparation::distance`, grows linearly with the
+//! overlap, and is damped along the pair axis so overlapping bodies ease apart
+//! instead of flying off. Pairs are found with a sorted grid binned at the
+//! separation distance and visited in a fixed order, so the result stays
+//! deterministic (see the determinism invariant in `gravity`).
+
+use bevy::prelude::*;
+
+use crate::pru::gravity::MIN_BODY_MASS;
+
+/// Minimum-separation settings applied by the gravity step.
+#[derive(Resource, Clone, Copy)]
+pub struct MinSeparation {
+    /// Whether the repulsion runs at all.
+    pub enabled: bool,
+    /// Distance below which two bodies are pushed apart.
+    pub distance: f32,
+    /// Acceleration per unit overlap, scaled by the pair's reduced mass.
+    pub stiffness: f32,
+}
+
+impl Default for MinSeparation {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            distance: 0.3,
+            stiffness: 200.0,
+        }
+    }
+}
+
+/// Repulsive accelerations for every pair closer than `settings.distance`.
+///
+/// Each pair exchanges equal and opposite forces, so the total momentum of
+/// the bodies is unchanged.
+pub fn separation_accelerations(
+    positions: &[Vec3],
+    velocities: &[Vec3],
+    masses: &[f32],
+    settings: &MinSeparation,
+) -> Vec<Vec3> {
+    let mut accelerations = vec![Vec3::ZERO; positions.len()];
+    let min_distance = settings.distance;
+    if min_distance <= 0.0 || positions.len() < 2 {
+        return accelerations;
+    }
+
+    // Bin bodies by grid cell; only bodies in adjacent cells can be in range.
+    let key = |p: Vec3| (p / min_distance).floor().as_ivec3();
+    let mut binned: Vec<(IVec3, usize)> = positions
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (key(*p), i))
+        .collect();
+    binned.sort_unstable_by_key(|(cell, i)| (cell.to_array(), *i));
+
+    let min_distance2 = min_distance * min_distance;
+    // Damping ratio 0.5: separating pairs coast just past the minimum instead of creeping toward it.
+    let damping = settings.stiffness.max(0.0).sqrt();
+    for a in 0..positions.len() {
+        if masses[a] <= MIN_BODY_MASS {
+            continue;
+        }
+        let home = key(positions[a]);
+        for dz in -1..=1 {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let cell = home + IVec3::new(dx, dy, dz);
+                    let start = binned.partition_point(|(c, _)| c.to_array() < cell.to_array());
+                    for &(c, b) in binned[start..].iter() {
+                        if c != cell {
+                            break;
+                        }
+                        if b <= a || masses[b] <= MIN_BODY_MASS {
+                            continue;
+                        }
+
+                        let displacement = positions[b] - positions[a];
+                        let dist2 = displacement.length_squared();
+                        if dist2 >= min_distance2 {
+                            continue;
+                        }
+
+                        let dist = dist2.sqrt();
+                        // Coincident bodies get a fixed direction so they still separate.
+                        let direction = if dist > 1e-6 {
+                            displacement / dist
+                        } else {
+                            Vec3::X
+                        };
+                        let reduced_mass = masses[a] * masses[b] / (masses[a] + masses[b]);
+                        let closing_speed = (velocities[a] - velocities[b]).dot(direction);
+                        let push =
+                            settings.stiffness * (min_distance - dist) + damping * closing_speed;
+                        let force = direction * (reduced_mass * push);
+                        accelerations[a] -= force / masses[a];
+                        accelerations[b] += force / masses[b];
+                    }
+                }
+            }
+        }
+    }
+    accelerations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_bodies_separate_without_net_momentum() {
+        let settings = MinSeparation {
+            enabled: true,
+            ..Default::default()
+        };
+        let masses = [1.0, 3.0];
+        let mut positions = [Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.04, 0.03, 0.0)];
+        let mut velocities = [Vec3::ZERO; 2];
+        let dt = 1.0 / 60.0;
+        for _ in 0..30 {
+            let accelerations =
+                separation_accelerations(&positions, &velocities, &masses, &settings);
+            for i in 0..2 {
+                velocities[i] += accelerations[i] * dt;
+                positions[i] += velocities[i] * dt;
+            }
+        }
+
+        let separation = positions[0].distance(positions[1]);
+        assert!(separation >= settings.distance, "only {separation} apart");
+        let momentum: Vec3 = velocities.iter().zip(masses).map(|(v, m)| *v * m).sum();
+        assert!(momentum.length() < 1e-5, "net momentum {momentum}");
+        // The push acts along the line joining the pair.
+        assert!(
+            (positions[1] - positions[0])
+                .normalize()
+                .distance(Vec3::new(0.8, 0.6, 0.0))
+                < 1e-4
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Block aggregation of the lattice into super-cells for large grids.
+//!
+//! Cells are grouped by their rest `grid_coords` into cubes of `block_size`
+//! cells per side, so a block keeps its members as they drift. Each
+//! super-cell carries the block's summed mass at its center of mass, which is
+//! all the pairwise solver and the macro view need; the fine cells stay
+//! around for derived fields and everything else that reads the lattice.
+//! Blocks come out in lattice order (`x`, then `y`, then `z`), independent
+//! of the order cells are fed in, so solvers built on them stay deterministic.
+
+use std::collections::BTreeMap;
+
+use bevy::math::DVec3;
+use bevy::prelude::*;
+
+/// One block of cells collapsed into a single body.
+#[derive(Clone, Copy, Debug)]
+pub struct SuperCell {
+    /// Block coordinates: member `grid_coords` divided by the block size.
+    pub block: UVec3,
+    /// Summed mass of the members.
+    pub mass: f32,
+    /// Mass-weighted mean position; the plain mean when the block is massless.
+    pub position: DVec3,
+    /// Number of cells in the block.
+    pub members: u32,
+}
+
+/// Super-cells of one aggregation pass, with each input cell's block.
+#[derive(Clone, Debug, Default)]
+pub struct SuperCells {
+    pub cells: Vec<SuperCell>,
+    /// Index into `cells` of every input cell, in input order.
+    pub membership: Vec<usize>,
+}
+
+#[derive(Default)]
+struct BlockSums {
+    mass: f64,
+    weighted_position: DVec3,
+    position: DVec3,
+    members: u32,
+}
+
+/// Collapse `(grid_coords, position, mass)` cells into blocks of `block_size`³.
+///
+/// A `block_size` of 0 is treated as 1, which leaves every cell on its own.
+pub fn aggregate_blocks(
+    cells: impl IntoIterator<Item = (UVec3, DVec3, f32)>,
+    block_size: u32,
+) -> SuperCells {
+    let block_size = block_size.max(1);
+    let mut sums: BTreeMap<(u32, u32, u32), BlockSums> = BTreeMap::new();
+    let mut keys = Vec::new();
+    for (grid_coords, position, mass) in cells {
+        let block = grid_coords / block_size;
+        let key = (block.z, block.y, block.x);
+        let sum = sums.entry(key).or_default();
+        let mass = mass.max(0.0) as f64;
+        sum.mass += mass;
+        sum.weighted_position += position * mass;
+        sum.position += position;
+        sum.members += 1;
+        keys.push(key);
+    }
+
+    let mut index = BTreeMap::new();
+    let cells = sums
+        .into_iter()
+        .enumerate()
+        .map(|(i, ((z, y, x), sum))| {
+            index.insert((z, y, x), i);
+            let position = if sum.mass > 0.0 {
+                sum.weighted_position / sum.mass
+            } else {
+                sum.position / sum.members as f64
+            };
+            SuperCell {
+                block: UVec3::new(x, y, z),
+                mass: sum.mass as f32,
+                position,
+                members: sum.members,
+            }
+        })
+        .collect();
+    let membership = keys.iter().map(|key| index[key]).collect();
+    SuperCells { cells, membership }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_four_cubed_block_collapses_to_its_summed_mass_at_its_center_of_mass() {
+        let mut cells = Vec::new();
+        for z in 0..4 {
+            for y in 0..4 {
+                for x in 0..4 {
+                    let coords = UVec3::new(x, y, z);
+                    // Heavier toward +x, so the center of mass sits off the geometric center.
+                    cells.push((coords, coords.as_dvec3() * 1.5, 1.0 + x as f32));
+                }
+            }
+        }
+        let mass: f64 = cells.iter().map(|(_, _, m)| *m as f64).sum();
+        let center = cells
+            .iter()
+            .map(|(_, position, m)| *position * *m as f64)
+            .sum::<DVec3>()
+            / mass;
+        // A lone cell one block over stays its own super-cell.
+        cells.push((UVec3::new(4, 0, 0), DVec3::new(6.0, 0.0, 0.0), 2.0));
+
+        let blocks = aggregate_blocks(cells, 4);
+        assert_eq!(blocks.cells.len(), 2);
+        let block = blocks.cells[0];
+        assert_eq!(block.block, UVec3::ZERO);
+        assert_eq!(block.members, 64);
+        assert!(
+            (block.mass as f64 - mass).abs() < 1e-4,
+            "mass {}",
+            block.mass
+        );
+        assert!(block.position.distance(center) < 1e-9, "{}", block.position);
+        assert!(block.position.x > 1.5 * 1.5, "pulled toward the heavy side");
+        assert_eq!(blocks.cells[1].block, UVec3::X);
+        assert_eq!(blocks.membership[..64], [0; 64]);
+        assert_eq!(blocks.membership[64], 1);
+    }
+}
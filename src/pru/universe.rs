@@ -1,11 +1,124 @@
-use bevy::math::primitives::Sphere;
+use bevy::math::DVec3;
 use bevy::prelude::*;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
 use rand::{rngs::StdRng, Rng, SeedableRng};
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
 
-use crate::app::SimulationState;
-use crate::pru::cell::{DerivedFields, PruCell, PruDynamics};
+use crate::app::{AppliedCellColor, CellColorState, SimConfig, SimulationState};
+use crate::pru::cell::{mass_from_ua_lock, DerivedFields, PruCell, PruDynamics, Real, RealVec3};
 use crate::pru::gravity::GravityParams;
+use crate::pru::gravity_relational::NEIGHBOR_OFFSETS;
+use crate::pru::initial::{apply_spiral_disk, InitialConditions, SeedCell};
+use crate::render::colors::lock_color;
+use crate::render::lod::{CellMeshes, LodState, MeshQuality};
+
+/// How the lattice connects across its edges along one axis, and what bodies do there.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum AxisBoundary {
+    /// The lattice ends at the edge: sites past it have no neighbors, and
+    /// bodies may drift past it freely.
+    #[default]
+    Open,
+    /// The axis wraps, so the last site neighbors the first, and a body
+    /// leaving through one face re-enters through the other.
+    Periodic,
+    /// The lattice ends at the edge, and bodies bounce off it elastically.
+    Reflecting,
+    /// The lattice ends at the edge, and bodies reaching it stop there,
+    /// losing their velocity along the axis.
+    Absorbing,
+}
+
+impl AxisBoundary {
+    /// Site index along an axis of `len` sites for `coord`, or `None` past a non-periodic edge.
+    pub fn wrap(self, coord: i32, len: u32) -> Option<u32> {
+        match self {
+            AxisBoundary::Periodic if len > 0 => Some(coord.rem_euclid(len as i32) as u32),
+            AxisBoundary::Periodic => None,
+            _ => (0..len as i32).contains(&coord).then_some(coord as u32),
+        }
+    }
+
+    /// `offset` along an axis `length` long, reduced to its nearest periodic image.
+    pub fn minimum_image(self, offset: f32, length: f32) -> f32 {
+        match self {
+            AxisBoundary::Periodic if length > 0.0 => offset - length * (offset / length).round(),
+            _ => offset,
+        }
+    }
+
+    /// Bring a body at `x` moving at `v` back within `low..high` as this boundary dictates.
+    ///
+    /// Returns whether anything changed. Open axes leave the body alone.
+    pub fn confine(self, x: &mut f64, v: &mut f64, low: f64, high: f64) -> bool {
+        let length = high - low;
+        if (low..high).contains(x) || length <= 0.0 {
+            return false;
+        }
+        match self {
+            AxisBoundary::Open => return false,
+            AxisBoundary::Periodic => *x = low + (*x - low).rem_euclid(length),
+            AxisBoundary::Reflecting if *x < low => {
+                *x = (2.0 * low - *x).min(high);
+                *v = v.abs();
+            }
+            AxisBoundary::Reflecting => {
+                *x = (2.0 * high - *x).max(low);
+                *v = -v.abs();
+            }
+            AxisBoundary::Absorbing => {
+                *x = x.clamp(low, high);
+                *v = 0.0;
+            }
+        }
+        true
+    }
+}
+
+/// Lattice topology presets, expanded into per-axis [`AxisBoundary`] values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum BoundaryCondition {
+    /// Every axis open.
+    #[default]
+    Open,
+    /// X and Z wrap: a flat torus for disk simulations in the y = 0 plane.
+    Torus2D {
+        /// What the faces above and below the disk do; open, reflecting, or absorbing.
+        y: AxisBoundary,
+    },
+}
+
+impl BoundaryCondition {
+    /// Boundaries along x, y, and z.
+    pub fn axes(self) -> [AxisBoundary; 3] {
+        match self {
+            BoundaryCondition::Open => [AxisBoundary::Open; 3],
+            BoundaryCondition::Torus2D { y } => [AxisBoundary::Periodic, y, AxisBoundary::Periodic],
+        }
+    }
+}
+
+impl std::str::FromStr for BoundaryCondition {
+    type Err = String;
+
+    /// `open` or `torus-2d`, with `-reflect` or `-absorb` for the y faces.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let y = match value {
+            "open" => return Ok(BoundaryCondition::Open),
+            "torus-2d" => AxisBoundary::Open,
+            "torus-2d-reflect" => AxisBoundary::Reflecting,
+            "torus-2d-absorb" => AxisBoundary::Absorbing,
+            _ => {
+                return Err(
+                    "expected open, torus-2d, torus-2d-reflect, or torus-2d-absorb".to_string(),
+                )
+            }
+        };
+        Ok(BoundaryCondition::Torus2D { y })
+    }
+}
 
 /// Resource describing the high-level PRU universe configuration.
 #[derive(Resource, Clone)]
@@ -16,18 +129,397 @@ pub struct PruUniverse {
     pub spacing: f32,
     /// Aggregate count of spawned cells.
     pub total_cells: usize,
-    /// Whether macro-gravity is enabled for dynamic motion.
-    pub gravity_enabled: bool,
+    pub x_boundary: AxisBoundary,
+    pub y_boundary: AxisBoundary,
+    pub z_boundary: AxisBoundary,
+    /// How far runtime growth has moved the lattice off center (see [`PruUniverse::grow`]).
+    pub origin_shift: Vec3,
 }
 
 impl PruUniverse {
-    /// Construct a new universe description with zeroed counters.
+    /// Construct a new universe description with zeroed counters and open edges.
     pub fn new(grid_dimensions: UVec3, spacing: f32) -> Self {
         Self {
             grid_dimensions,
             spacing,
             total_cells: 0,
-            gravity_enabled: true,
+            x_boundary: AxisBoundary::Open,
+            y_boundary: AxisBoundary::Open,
+            z_boundary: AxisBoundary::Open,
+            origin_shift: Vec3::ZERO,
+        }
+    }
+
+    /// Set every axis boundary from a topology preset.
+    pub fn with_boundary(mut self, boundary: BoundaryCondition) -> Self {
+        [self.x_boundary, self.y_boundary, self.z_boundary] = boundary.axes();
+        self
+    }
+
+    /// Boundaries along x, y, and z.
+    pub fn boundaries(&self) -> [AxisBoundary; 3] {
+        [self.x_boundary, self.y_boundary, self.z_boundary]
+    }
+
+    /// Offset subtracted from scaled grid coordinates to center the lattice on the origin.
+    ///
+    /// After runtime growth the lattice is no longer centered; the offset then
+    /// keeps every site at the rest position it had before.
+    pub fn center_offset(&self) -> Vec3 {
+        (self.grid_dimensions.as_vec3() - Vec3::ONE) * 0.5 * self.spacing - self.origin_shift
+    }
+
+    /// World-space rest position of the site at `coords`.
+    pub fn rest_position(&self, coords: UVec3) -> Vec3 {
+        coords.as_vec3() * self.spacing - self.center_offset()
+    }
+
+    /// Add a layer of sites past one face of `axis` (0 = x), leaving every existing site where it is.
+    ///
+    /// The new layer on the high face takes the next index along `axis`. On
+    /// the low face it takes index 0 and existing sites move up by one; the
+    /// returned offset is what their `grid_coords` need adding.
+    pub fn grow(&mut self, axis: usize, high_face: bool) -> UVec3 {
+        self.grid_dimensions[axis] += 1;
+        let half_step = 0.5 * self.spacing;
+        if high_face {
+            self.origin_shift[axis] += half_step;
+            UVec3::ZERO
+        } else {
+            self.origin_shift[axis] -= half_step;
+            UVec3::AXES[axis]
+        }
+    }
+
+    /// Lattice coordinates of the cell rest position closest to `position`.
+    pub fn nearest_grid_coords(&self, position: Vec3) -> UVec3 {
+        let max = self.grid_dimensions.saturating_sub(UVec3::ONE).as_vec3();
+        ((position + self.center_offset()) / self.spacing)
+            .round()
+            .clamp(Vec3::ZERO, max)
+            .as_uvec3()
+    }
+
+    /// The site at `coords`, wrapped along periodic axes; `None` past an open edge.
+    pub fn wrap_coords(&self, coords: IVec3) -> Option<UVec3> {
+        wrap_lattice_coords(coords, self.grid_dimensions, self.boundaries())
+    }
+
+    /// Face neighbors of `coords` across each axis's boundary, skipping the site itself.
+    ///
+    /// Along a periodic axis of two sites, both offsets reach the same
+    /// neighbor, and it is yielded twice, as the relational kernel sees it.
+    pub fn neighbors(&self, coords: UVec3) -> impl Iterator<Item = UVec3> + '_ {
+        NEIGHBOR_OFFSETS.iter().filter_map(move |offset| {
+            self.wrap_coords(coords.as_ivec3() + *offset)
+                .filter(|neighbor| *neighbor != coords)
+        })
+    }
+
+    /// World-space `displacement` reduced to its nearest image along the periodic axes.
+    pub fn minimum_image(&self, displacement: Vec3) -> Vec3 {
+        let lengths = self.grid_dimensions.as_vec3() * self.spacing;
+        let [x, y, z] = self.boundaries();
+        Vec3::new(
+            x.minimum_image(displacement.x, lengths.x),
+            y.minimum_image(displacement.y, lengths.y),
+            z.minimum_image(displacement.z, lengths.z),
+        )
+    }
+
+    /// Lengths of the periodic axes, for minimum-image displacements away from the ECS.
+    pub fn periods(&self) -> LatticePeriods {
+        let lengths = self.grid_dimensions.as_vec3() * self.spacing;
+        let boundaries = self.boundaries();
+        LatticePeriods(std::array::from_fn(|axis| {
+            (boundaries[axis] == AxisBoundary::Periodic).then_some(lengths[axis])
+        }))
+    }
+
+    /// World-space box the lattice fills: half a spacing past the outermost sites.
+    ///
+    /// Periodic axes repeat with exactly this length.
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        let half_step = Vec3::splat(0.5 * self.spacing);
+        let last = self.grid_dimensions.saturating_sub(UVec3::ONE);
+        (
+            self.rest_position(UVec3::ZERO) - half_step,
+            self.rest_position(last) + half_step,
+        )
+    }
+
+    /// Apply each axis's boundary to a body at `position` moving at `velocity`.
+    ///
+    /// Returns whether the body was wrapped, reflected, or stopped.
+    pub fn confine(&self, position: &mut DVec3, velocity: &mut DVec3) -> bool {
+        let (low, high) = self.bounds();
+        let mut changed = false;
+        for (axis, boundary) in self.boundaries().into_iter().enumerate() {
+            changed |= boundary.confine(
+                &mut position[axis],
+                &mut velocity[axis],
+                low[axis] as f64,
+                high[axis] as f64,
+            );
+        }
+        changed
+    }
+}
+
+/// Lengths of the periodic lattice axes; `None` for axes that do not wrap.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LatticePeriods(pub [Option<f32>; 3]);
+
+impl LatticePeriods {
+    /// `displacement` reduced to its nearest image along the periodic axes.
+    pub fn minimum_image(&self, displacement: RealVec3) -> RealVec3 {
+        let mut components = displacement.to_array();
+        for (component, period) in components.iter_mut().zip(self.0) {
+            if let Some(length) = period.filter(|length| *length > 0.0) {
+                let length = length as Real;
+                *component -= length * (*component / length).round();
+            }
+        }
+        RealVec3::from_array(components)
+    }
+
+    /// [`minimum_image`](Self::minimum_image) in `f64` whatever `Real` is, for diagnostics.
+    pub fn minimum_image_f64(&self, displacement: DVec3) -> DVec3 {
+        let mut components = displacement.to_array();
+        for (component, period) in components.iter_mut().zip(self.0) {
+            if let Some(length) = period.filter(|length| *length > 0.0) {
+                let length = length as f64;
+                *component -= length * (*component / length).round();
+            }
+        }
+        DVec3::from_array(components)
+    }
+}
+
+/// `coords` wrapped into a `dims` lattice with the given per-axis boundaries.
+pub fn wrap_lattice_coords(
+    coords: IVec3,
+    dims: UVec3,
+    boundaries: [AxisBoundary; 3],
+) -> Option<UVec3> {
+    Some(UVec3::new(
+        boundaries[0].wrap(coords.x, dims.x)?,
+        boundaries[1].wrap(coords.y, dims.y)?,
+        boundaries[2].wrap(coords.z, dims.z)?,
+    ))
+}
+
+/// Smallest lattice extent along any axis outside single-cell mode.
+pub const MIN_CELLS_PER_AXIS: u32 = 2;
+
+/// Lattice shape requested for the next universe build.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct UniverseSettings {
+    pub grid_dimensions: UVec3,
+    pub spacing: f32,
+    /// Build a lone cell, ignoring `grid_dimensions`.
+    ///
+    /// Without it, every axis is raised to [`MIN_CELLS_PER_AXIS`] so neighbor
+    /// stencils and gradients always have something to work with.
+    pub single_cell: bool,
+    /// How the lattice's locks and velocities are seeded.
+    pub initial_conditions: InitialConditions,
+    /// Which lattice axes wrap around.
+    pub boundary: BoundaryCondition,
+}
+
+impl Default for UniverseSettings {
+    fn default() -> Self {
+        Self {
+            grid_dimensions: UVec3::new(10, 10, 10),
+            spacing: 1.4,
+            single_cell: false,
+            initial_conditions: InitialConditions::Uniform,
+            boundary: BoundaryCondition::Open,
+        }
+    }
+}
+
+impl UniverseSettings {
+    /// Settings with degenerate dimensions and spacing replaced by the nearest usable ones.
+    pub fn validated(&self) -> Self {
+        let mut valid = *self;
+        if self.single_cell {
+            valid.grid_dimensions = UVec3::ONE;
+        } else {
+            valid.grid_dimensions = self.grid_dimensions.max(UVec3::splat(MIN_CELLS_PER_AXIS));
+            if valid.grid_dimensions != self.grid_dimensions {
+                warn!(
+                    "Grid {} needs at least {MIN_CELLS_PER_AXIS} cells per axis; using {} (set single_cell for a lone cell)",
+                    self.grid_dimensions, valid.grid_dimensions
+                );
+            }
+        }
+        if !(self.spacing.is_finite() && self.spacing > 0.0) {
+            valid.spacing = Self::default().spacing;
+            warn!(
+                "Lattice spacing {} is not positive; using {}",
+                self.spacing, valid.spacing
+            );
+        }
+        valid
+    }
+}
+
+/// Smoothing kernel used to build the derived density and curvature fields.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SphKernelType {
+    /// Gaussian with standard deviation h/2; smooth, but its tail never reaches zero.
+    #[default]
+    Gaussian,
+    /// Monaghan's M4 cubic spline, zero beyond r = h.
+    CubicSpline,
+    /// Wendland C4, zero beyond r = h; resists pairing instabilities at high neighbor counts.
+    Wendland4,
+    /// Uniform weight inside r = h: a plain neighbor average with a sharp edge.
+    TopHat,
+}
+
+impl SphKernelType {
+    pub fn next(self) -> Self {
+        match self {
+            SphKernelType::Gaussian => SphKernelType::CubicSpline,
+            SphKernelType::CubicSpline => SphKernelType::Wendland4,
+            SphKernelType::Wendland4 => SphKernelType::TopHat,
+            SphKernelType::TopHat => SphKernelType::Gaussian,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SphKernelType::Gaussian => "Gaussian",
+            SphKernelType::CubicSpline => "Cubic spline",
+            SphKernelType::Wendland4 => "Wendland C4",
+            SphKernelType::TopHat => "Top-hat",
+        }
+    }
+
+    /// Kernel value W(r, h), normalized so that ∫W dV = 1.
+    pub fn weight(self, r: f32, h: f32) -> f32 {
+        use std::f32::consts::PI;
+
+        let q = r / h;
+        match self {
+            SphKernelType::Gaussian => {
+                let sigma = 0.5 * h;
+                (-0.5 * (r / sigma).powi(2)).exp() / ((2.0 * PI).powf(1.5) * sigma.powi(3))
+            }
+            SphKernelType::CubicSpline => {
+                let shape = if q <= 0.5 {
+                    1.0 - 6.0 * q * q + 6.0 * q * q * q
+                } else if q <= 1.0 {
+                    2.0 * (1.0 - q).powi(3)
+                } else {
+                    0.0
+                };
+                8.0 / (PI * h.powi(3)) * shape
+            }
+            SphKernelType::Wendland4 => {
+                if q >= 1.0 {
+                    return 0.0;
+                }
+                let shape = (1.0 - q).powi(6) * (1.0 + 6.0 * q + 35.0 / 3.0 * q * q);
+                495.0 / (32.0 * PI * h.powi(3)) * shape
+            }
+            SphKernelType::TopHat => {
+                if q >= 1.0 {
+                    return 0.0;
+                }
+                3.0 / (4.0 * PI * h.powi(3))
+            }
+        }
+    }
+
+    /// Radial derivative dW/dr of [`SphKernelType::weight`]; zero or negative everywhere.
+    ///
+    /// The top-hat is flat inside its edge, so it has no usable derivative;
+    /// it borrows the Gaussian's instead.
+    pub fn gradient(self, r: f32, h: f32) -> f32 {
+        use std::f32::consts::PI;
+
+        let q = r / h;
+        match self {
+            SphKernelType::Gaussian => {
+                let sigma = 0.5 * h;
+                -r / (sigma * sigma) * self.weight(r, h)
+            }
+            SphKernelType::CubicSpline => {
+                let slope = if q <= 0.5 {
+                    -12.0 * q + 18.0 * q * q
+                } else if q <= 1.0 {
+                    -6.0 * (1.0 - q).powi(2)
+                } else {
+                    0.0
+                };
+                8.0 / (PI * h.powi(4)) * slope
+            }
+            SphKernelType::Wendland4 => {
+                if q >= 1.0 {
+                    return 0.0;
+                }
+                let slope = -56.0 / 3.0 * q * (1.0 + 5.0 * q) * (1.0 - q).powi(5);
+                495.0 / (32.0 * PI * h.powi(4)) * slope
+            }
+            SphKernelType::TopHat => SphKernelType::Gaussian.gradient(r, h),
+        }
+    }
+
+    /// Distance beyond which [`SphKernelType::gradient`] is negligible.
+    ///
+    /// The compact kernels end at `h`; the Gaussian is cut at four standard
+    /// deviations, where its weight has fallen to about 3e-4 of the peak.
+    pub fn support(self, h: f32) -> f32 {
+        match self {
+            SphKernelType::Gaussian | SphKernelType::TopHat => 2.0 * h,
+            SphKernelType::CubicSpline | SphKernelType::Wendland4 => h,
+        }
+    }
+}
+
+/// Valid `FieldSettings::smoothing_radius` range, in lattice spacings.
+pub const SMOOTHING_RADIUS_RANGE: (f32, f32) = (0.5, 6.0);
+
+/// Options for the derived-field kernels.
+///
+/// `compute_derived_fields` recomputes when these change, so a change shows up
+/// in the fields on the next physics run, even while paused.
+#[derive(Resource, Clone, Copy)]
+pub struct FieldSettings {
+    /// Count a cell's own mass (the kernel weight at r = 0) toward its local density.
+    ///
+    /// Off by default so density, like the curvature proxy, describes the
+    /// neighborhood only, and formation thresholds compare environments rather
+    /// than being dominated by each cell's own mass.
+    pub include_self_density: bool,
+    /// Smoothing kernel for density and the curvature proxy.
+    pub kernel: SphKernelType,
+    /// Smoothing length `h`, in lattice spacings.
+    ///
+    /// Densities grow with the volume the kernel covers, so formation
+    /// thresholds only mean something relative to this radius.
+    pub smoothing_radius: f32,
+}
+
+impl Default for FieldSettings {
+    fn default() -> Self {
+        Self {
+            include_self_density: false,
+            kernel: SphKernelType::default(),
+            smoothing_radius: 2.5,
+        }
+    }
+}
+
+impl FieldSettings {
+    /// Set `smoothing_radius`, clamped to [`SMOOTHING_RADIUS_RANGE`]. Non-finite values are ignored.
+    pub fn set_smoothing_radius(&mut self, value: f32) {
+        if value.is_finite() {
+            self.smoothing_radius = value.clamp(SMOOTHING_RADIUS_RANGE.0, SMOOTHING_RADIUS_RANGE.1);
         }
     }
 }
@@ -39,8 +531,40 @@ pub struct FieldMetrics {
     pub min_density: f32,
     pub max_density: f32,
     pub avg_curvature: f32,
+    pub min_curvature: f32,
+    pub max_curvature: f32,
+    /// 5th/95th percentile densities, a range that resists outliers.
+    pub density_percentiles: (f32, f32),
+    /// 5th/95th percentile curvature proxies.
+    pub curvature_percentiles: (f32, f32),
+    /// Largest |curvature proxy|, the symmetric extent of the curvature overlay.
+    pub max_abs_curvature: f32,
+    /// 95th percentile of |curvature proxy|.
+    pub abs_curvature_percentile: f32,
+    /// Mean |∇UA|: how much information contrast the lattice currently holds.
+    pub mean_ua_gradient_magnitude: f32,
+    pub max_ua_gradient_magnitude: f32,
+    /// 5th/95th percentile |∇UA|.
+    pub ua_gradient_percentiles: (f32, f32),
+    pub min_speed: f32,
+    pub max_speed: f32,
+    /// 5th/95th percentile cell speeds.
+    pub speed_percentiles: (f32, f32),
+    /// Extent of [`log_acceleration`] over the cells.
+    pub min_log_acceleration: f32,
+    pub max_log_acceleration: f32,
+    /// 5th/95th percentile of [`log_acceleration`].
+    pub log_acceleration_percentiles: (f32, f32),
     pub density_history: VecDeque<f32>,
     pub max_history: usize,
+    /// Frames between recorded density samples; doubles along with `max_history`.
+    pub history_stride: u32,
+    /// Frames seen since the last recorded density sample.
+    history_skipped: u32,
+    /// Fraction of cells currently labeled as inter-galactic medium.
+    pub igm_fraction: f32,
+    /// `Σ mᵢ vᵢ` over the lattice, updated by `compute_energy_metrics`.
+    pub total_linear_momentum: Vec3,
 }
 
 impl Default for FieldMetrics {
@@ -50,133 +574,425 @@ impl Default for FieldMetrics {
             min_density: 0.0,
             max_density: 0.0,
             avg_curvature: 0.0,
+            min_curvature: 0.0,
+            max_curvature: 0.0,
+            density_percentiles: (0.0, 0.0),
+            curvature_percentiles: (0.0, 0.0),
+            max_abs_curvature: 0.0,
+            abs_curvature_percentile: 0.0,
+            mean_ua_gradient_magnitude: 0.0,
+            max_ua_gradient_magnitude: 0.0,
+            ua_gradient_percentiles: (0.0, 0.0),
+            min_speed: 0.0,
+            max_speed: 0.0,
+            speed_percentiles: (0.0, 0.0),
+            min_log_acceleration: 0.0,
+            max_log_acceleration: 0.0,
+            log_acceleration_percentiles: (0.0, 0.0),
             density_history: VecDeque::from(vec![0.0; 32]),
             max_history: 64,
+            history_stride: 1,
+            history_skipped: 0,
+            igm_fraction: 0.0,
+            total_linear_momentum: Vec3::ZERO,
+        }
+    }
+}
+
+/// Radius of the shared cell sphere mesh before per-cell scaling.
+const CELL_RADIUS: f32 = 0.12;
+
+/// Largest density history the adaptive buffer grows to.
+pub const MAX_DENSITY_HISTORY: usize = 4096;
+/// Ticks per history entry after which the buffer doubles.
+const HISTORY_GROWTH_TICKS: u64 = 10;
+
+impl FieldMetrics {
+    /// Zero the per-frame field statistics, as when there are no cells to measure.
+    ///
+    /// The density history is kept; it describes earlier frames.
+    pub fn clear_field_stats(&mut self) {
+        *self = Self {
+            density_history: std::mem::take(&mut self.density_history),
+            max_history: self.max_history,
+            history_stride: self.history_stride,
+            history_skipped: self.history_skipped,
+            ..Self::default()
+        };
+    }
+
+    /// Append an average-density sample, growing the window on long runs.
+    ///
+    /// Once `tick` passes `max_history * 10`, the buffer doubles (up to
+    /// [`MAX_DENSITY_HISTORY`]) and the existing samples are thinned to every
+    /// other entry. The sampling stride doubles too, so the history keeps a
+    /// uniform time axis while covering ever longer spans.
+    pub fn record_density(&mut self, density: f32, tick: u64) {
+        while self.max_history < MAX_DENSITY_HISTORY
+            && tick > self.max_history as u64 * HISTORY_GROWTH_TICKS
+        {
+            self.max_history = (self.max_history * 2).min(MAX_DENSITY_HISTORY);
+            self.history_stride *= 2;
+            // Keep the newest sample so the plot stays current.
+            let offset = (self.density_history.len() + 1) % 2;
+            self.density_history = self
+                .density_history
+                .iter()
+                .skip(offset)
+                .step_by(2)
+                .copied()
+                .collect();
+        }
+
+        self.history_skipped += 1;
+        if self.history_skipped < self.history_stride {
+            return;
+        }
+        self.history_skipped = 0;
+
+        self.density_history.push_back(density);
+        while self.density_history.len() > self.max_history {
+            self.density_history.pop_front();
         }
     }
+
+    /// Speed extent for adaptive velocity coloring, capped at a multiple of the
+    /// 95th percentile so a few fast escapers don't wash out the flow field.
+    pub fn capped_speed_extent(&self) -> (f32, f32) {
+        let cap = self.speed_percentiles.1 * SPEED_ESCAPER_CAP;
+        (self.min_speed, self.max_speed.min(cap.max(self.min_speed)))
+    }
+}
+
+/// Multiple of the 95th-percentile speed beyond which speeds saturate the velocity overlay.
+const SPEED_ESCAPER_CAP: f32 = 2.0;
+
+/// Smallest acceleration the acceleration overlay tells apart; weaker ones share its color.
+pub const MIN_OVERLAY_ACCELERATION: f32 = 1e-6;
+
+/// `log10 |a|`, the value the acceleration overlay colors by.
+///
+/// Accelerations near `GravityParams::max_acceleration` are orders of
+/// magnitude above typical ones, so a linear scale would leave most cells at
+/// the bottom of the colormap.
+pub fn log_acceleration(acceleration: Vec3) -> f32 {
+    acceleration.length().max(MIN_OVERLAY_ACCELERATION).log10()
+}
+
+/// Ticks between lattice complexity samples.
+pub const COMPLEXITY_INTERVAL_TICKS: u64 = 500;
+/// Complexity samples kept for plotting.
+const COMPLEXITY_HISTORY_LEN: usize = 64;
+
+/// Kolmogorov complexity proxy: DEFLATE-compressed size of the UA lock field.
+///
+/// Random lock values compress poorly; ordered, repetitive structure compresses
+/// well, so a falling size means the lattice is becoming more structured.
+#[derive(Resource, Default)]
+pub struct LatticeComplexity {
+    /// Compressed size of the latest sample in bytes (0 before the first sample).
+    pub compressed_size: usize,
+    /// Recent compressed sizes, oldest first.
+    pub complexity_history: VecDeque<usize>,
+    /// Tick of the latest sample.
+    pub last_tick: Option<u64>,
+}
+
+/// Compressed size of `ua_mass_lock` values after rounding to two decimals.
+///
+/// Values are serialized as little-endian `f32`s in the given order, so
+/// callers should pass them in a stable (lattice) order.
+pub fn compressed_lock_size(locks: impl IntoIterator<Item = f64>) -> usize {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    for lock in locks {
+        let rounded = ((lock * 100.0).round() / 100.0) as f32;
+        // Writing into a Vec cannot fail.
+        let _ = encoder.write_all(&rounded.to_le_bytes());
+    }
+    encoder.finish().map(|bytes| bytes.len()).unwrap_or(0)
 }
 
-/// Startup system: build a small 3D lattice of PRU cells with random lock values.
+/// Sample the lattice complexity every [`COMPLEXITY_INTERVAL_TICKS`] ticks.
+pub fn compute_complexity(
+    sim_state: Res<SimulationState>,
+    mut complexity: ResMut<LatticeComplexity>,
+    cells: Query<&PruCell>,
+) {
+    // A tick behind the last sample means the world was reset; sample right away.
+    let due = complexity.last_tick.is_none_or(|last| {
+        sim_state.tick.saturating_sub(last) >= COMPLEXITY_INTERVAL_TICKS || sim_state.tick < last
+    });
+    if !due || cells.is_empty() {
+        return;
+    }
+
+    let mut locks: Vec<(UVec3, f64)> = cells
+        .iter()
+        .map(|cell| (cell.grid_coords, cell.ua_mass_lock))
+        .collect();
+    locks.sort_unstable_by_key(|(coords, _)| (coords.z, coords.y, coords.x));
+    let size = compressed_lock_size(locks.into_iter().map(|(_, lock)| lock));
+
+    complexity.compressed_size = size;
+    complexity.complexity_history.push_back(size);
+    while complexity.complexity_history.len() > COMPLEXITY_HISTORY_LEN {
+        complexity.complexity_history.pop_front();
+    }
+    complexity.last_tick = Some(sim_state.tick);
+}
+
+/// Startup system: build the (validated) `UniverseSettings` lattice of PRU cells with random lock values.
+///
+/// Every cell draws its locks and velocity from the same seeded stream, and
+/// `UniverseSettings::initial_conditions` then reshapes them (see
+/// [`InitialConditions`]).
+#[allow(clippy::too_many_arguments)]
 pub fn setup_universe(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    mut gravity: ResMut<GravityParams>,
     mut sim_state: ResMut<SimulationState>,
+    config: Res<SimConfig>,
+    gravity: Res<GravityParams>,
+    settings: Res<UniverseSettings>,
+    mesh_quality: Option<Res<MeshQuality>>,
 ) {
-    // Configure a modest grid that is fast to render while showcasing the lattice.
-    let grid_dimensions = UVec3::new(10, 10, 10);
-    let spacing = 1.4;
-    let base_dt = 1.0 / 60.0;
+    let UniverseSettings {
+        grid_dimensions,
+        spacing,
+        initial_conditions,
+        boundary,
+        ..
+    } = settings.validated();
 
-    let mut universe = PruUniverse::new(grid_dimensions, spacing);
+    let mut universe = PruUniverse::new(grid_dimensions, spacing).with_boundary(boundary);
     commands.insert_resource(universe.clone());
-    sim_state.dt = base_dt;
-    gravity.enabled = universe.gravity_enabled;
+    sim_state.dt = config.dt();
 
     let mut rng = StdRng::seed_from_u64(42);
-    let cell_mesh = meshes.add(Mesh::from(Sphere { radius: 0.12 }));
+    let quality = mesh_quality.as_deref().copied().unwrap_or_default();
+    let cell_mesh = meshes.add(quality.sphere(CELL_RADIUS));
+    // A coarser icosphere for distant cells: ~80 triangles or fewer.
+    let reduced_cell_mesh = meshes.add(quality.reduced_sphere(CELL_RADIUS));
+    commands.insert_resource(CellMeshes {
+        full: cell_mesh.clone(),
+        reduced: reduced_cell_mesh,
+    });
 
-    let center_offset = (grid_dimensions.as_vec3() - Vec3::ONE) * 0.5 * spacing;
+    let center_offset = universe.center_offset();
 
+    let mut seeds = Vec::with_capacity(grid_dimensions.element_product() as usize);
     for x in 0..grid_dimensions.x {
         for y in 0..grid_dimensions.y {
             for z in 0..grid_dimensions.z {
                 let position = Vec3::new(x as f32, y as f32, z as f32) * spacing - center_offset;
                 let ua_mass_lock: f64 = rng.gen_range(0.4..1.6);
                 let ub_geom_lock: f64 = rng.gen_range(-1.0..1.0);
-
-                let grid_coords = UVec3::new(x, y, z);
-                let cell = PruCell::new(position, grid_coords, ua_mass_lock, ub_geom_lock);
-                let mass = (ua_mass_lock as f32).max(0.05);
                 let velocity = Vec3::new(
                     rng.gen_range(-0.05..0.05),
                     rng.gen_range(-0.05..0.05),
                     rng.gen_range(-0.05..0.05),
                 );
-                let dynamics = PruDynamics {
-                    mass,
+                seeds.push(SeedCell {
+                    grid_coords: UVec3::new(x, y, z),
+                    position,
+                    ua_mass_lock,
+                    ub_geom_lock,
                     velocity,
-                    ..Default::default()
-                };
-
-                let material_color = color_from_locks(ua_mass_lock, ub_geom_lock);
-                let material = materials.add(StandardMaterial {
-                    base_color: material_color,
-                    metallic: 0.05,
-                    perceptual_roughness: 0.7,
-                    ..Default::default()
                 });
-
-                commands.spawn((
-                    PbrBundle {
-                        mesh: cell_mesh.clone(),
-                        material,
-                        transform: Transform::from_translation(position),
-                        ..Default::default()
-                    },
-                    cell,
-                    DerivedFields::default(),
-                    Name::new(format!("PRU Cell ({x}, {y}, {z})")),
-                    dynamics,
-                ));
-
-                universe.total_cells += 1;
             }
         }
     }
 
+    if let InitialConditions::SpiralDisk {
+        arms,
+        pitch_angle,
+        bar_half_length,
+    } = initial_conditions
+    {
+        apply_spiral_disk(
+            &mut seeds,
+            arms,
+            pitch_angle,
+            bar_half_length,
+            spacing,
+            gravity.g_effective,
+            mass_from_ua_lock,
+        );
+    }
+
+    for seed in seeds {
+        let SeedCell {
+            grid_coords,
+            position,
+            ua_mass_lock,
+            ub_geom_lock,
+            velocity,
+        } = seed;
+        spawn_cell(
+            &mut commands,
+            &mut materials,
+            &cell_mesh,
+            PruCell::new(position, grid_coords, ua_mass_lock, ub_geom_lock),
+            velocity,
+        );
+        universe.total_cells += 1;
+    }
+
     // Update the resource with the final cell count.
     commands.insert_resource(universe);
 }
 
-fn color_from_locks(ua: f64, ub: f64) -> Color {
-    let mass = (ua as f32).clamp(0.0, 2.0);
-    let geom = ((ub as f32) + 1.0) * 0.5; // map -1..1 to 0..1
+/// Spawn a lattice cell entity with its own lock-colored material.
+pub fn spawn_cell(
+    commands: &mut Commands,
+    materials: &mut Assets<StandardMaterial>,
+    mesh: &Handle<Mesh>,
+    cell: PruCell,
+    velocity: Vec3,
+) -> Entity {
+    let dynamics = PruDynamics {
+        mass: mass_from_ua_lock(cell.ua_mass_lock),
+        velocity,
+        velocity_at_integer_tick: velocity,
+        ..Default::default()
+    };
 
-    let r = 0.2 + 0.6 * geom;
-    let g = 0.2 + 0.6 * (1.0 - geom);
-    let b = 0.4 + 0.5 * (1.0 - mass * 0.5);
-    Color::srgb(r.min(1.0), g.min(1.0), b.min(1.0))
+    let material_color = lock_color(cell.ua_mass_lock, cell.ub_geom_lock);
+    let material = materials.add(StandardMaterial {
+        base_color: material_color,
+        metallic: 0.05,
+        perceptual_roughness: 0.7,
+        ..Default::default()
+    });
+
+    let grid_coords = cell.grid_coords;
+    commands
+        .spawn((
+            PbrBundle {
+                mesh: mesh.clone(),
+                material,
+                transform: Transform::from_translation(cell.position),
+                ..Default::default()
+            },
+            cell,
+            DerivedFields::default(),
+            AppliedCellColor::new(material_color),
+            CellColorState::new(material_color),
+            LodState::new(CELL_RADIUS),
+            cell_name(grid_coords),
+            dynamics,
+        ))
+        .id()
+}
+
+/// Debug name of the cell at `grid_coords`.
+pub fn cell_name(grid_coords: UVec3) -> Name {
+    Name::new(format!(
+        "PRU Cell ({}, {}, {})",
+        grid_coords.x, grid_coords.y, grid_coords.z
+    ))
 }
 
 /// Compute per-cell derived fields (density & curvature proxies) and update rolling metrics.
+///
+/// Density and curvature share the `FieldSettings::kernel` weights with a
+/// smoothing length of `FieldSettings::smoothing_radius` lattice spacings. Both skip the cell itself (matched by entity, not by distance), except
+/// that `FieldSettings::include_self_density` adds the cell's own mass to its density.
+///
+/// A second pass takes the SPH gradient of the UA lock field,
+/// `∇UA_i = Σ_j m_j/ρ_j (UA_j − UA_i) ∇W(r_ij)`, using the densities from the first.
+/// The sum is renormalized by `L_i = Σ_j m_j/ρ_j ∇W(r_ij) ⊗ (x_j − x_i)` so linear
+/// fields come out exact, even on the lattice boundary.
+///
+/// Runs in `FixedUpdate` but only does the work on the frame's last run, and
+/// only when a tick was stepped or the cells, settings, or lattice changed
+/// since the fields were last computed; a paused universe is not recomputed.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn compute_derived_fields(
     universe: Res<PruUniverse>,
-    cell_query: Query<(&PruCell, &PruDynamics)>,
-    mut derived_query: Query<(&PruCell, &mut DerivedFields)>,
+    sim_state: Res<SimulationState>,
+    field_settings: Res<FieldSettings>,
+    fixed_time: Res<Time<Fixed>>,
+    edited: Query<(), Or<(Changed<PruCell>, Changed<PruDynamics>)>>,
+    mut stale: Local<bool>,
+    mut computed_at: Local<Option<(u64, u64)>>,
+    cell_query: Query<(Entity, &PruCell, &PruDynamics)>,
+    mut derived_query: Query<(Entity, &PruCell, &mut DerivedFields)>,
     mut metrics: ResMut<FieldMetrics>,
 ) {
-    let smoothing_radius = universe.spacing * 2.5;
-    let smoothing_inv = 1.0 / (smoothing_radius * 0.5).max(0.0001);
+    // Changes are latched, since they only show once and an earlier run this
+    // frame may have seen them.
+    let stamp = (sim_state.tick, sim_state.generation);
+    *stale |= *computed_at != Some(stamp)
+        || field_settings.is_changed()
+        || universe.is_changed()
+        || !edited.is_empty();
+    let more_runs_this_frame = fixed_time.overstep() >= fixed_time.timestep();
+    if !*stale || more_runs_this_frame {
+        return;
+    }
+    *stale = false;
+    *computed_at = Some(stamp);
+
+    let smoothing_length = (universe.spacing * field_settings.smoothing_radius).max(0.0002);
+    let kernel = field_settings.kernel;
+    // Weights are scaled by the Gaussian's effective volume, so the Gaussian
+    // peaks at 1 and every kernel yields densities on the same scale.
+    let reference_volume =
+        (2.0 * std::f32::consts::PI).powf(1.5) * (0.5 * smoothing_length).powi(3);
+    let self_weight = kernel.weight(0.0, smoothing_length) * reference_volume;
 
-    let cell_data: Vec<(Vec3, f32, f32)> = cell_query
+    let cell_data: Vec<(Entity, Vec3, f32, f32, f32)> = cell_query
         .iter()
-        .map(|(cell, dyn_state)| (cell.position, dyn_state.mass, cell.ub_geom_lock as f32))
+        .map(|(entity, cell, dyn_state)| {
+            (
+                entity,
+                cell.position,
+                dyn_state.mass,
+                cell.ub_geom_lock as f32,
+                cell.ua_mass_lock as f32,
+            )
+        })
         .collect();
 
     if cell_data.is_empty() {
+        metrics.clear_field_stats();
         return;
     }
+    let _span = info_span!("derived_fields", cells = cell_data.len()).entered();
 
     let mut density_sum = 0.0;
     let mut curvature_sum = 0.0;
-    let mut min_density = f32::MAX;
-    let mut max_density = f32::MIN;
+    let mut densities = Vec::with_capacity(cell_data.len());
+    let mut curvatures = Vec::with_capacity(cell_data.len());
+    let mut speeds: Vec<f32> = cell_query
+        .iter()
+        .map(|(_, _, dyn_state)| dyn_state.velocity_at_integer_tick.length())
+        .collect();
+    let mut accelerations: Vec<f32> = cell_query
+        .iter()
+        .map(|(_, _, dyn_state)| log_acceleration(dyn_state.acceleration))
+        .collect();
 
-    for (cell, mut derived) in derived_query.iter_mut() {
+    for (entity, cell, mut derived) in derived_query.iter_mut() {
         let mut density = 0.0f32;
         let mut ub_weighted = 0.0f32;
         let mut ub_weight_sum = 0.0f32;
 
-        for (pos, mass, ub) in cell_data.iter() {
-            let r = (*pos - cell.position).length();
-            let weight = (-0.5 * (r * smoothing_inv).powi(2)).exp();
-            density += *mass * weight;
-            if r > 0.0 {
-                ub_weighted += *ub * weight;
-                ub_weight_sum += weight;
+        for (other, pos, mass, ub, _) in cell_data.iter() {
+            if *other == entity {
+                if field_settings.include_self_density {
+                    density += *mass * self_weight;
+                }
+                continue;
             }
+
+            let r = universe.minimum_image(*pos - cell.position).length();
+            let weight = kernel.weight(r, smoothing_length) * reference_volume;
+            density += *mass * weight;
+            ub_weighted += *ub * weight;
+            ub_weight_sum += weight;
         }
 
         derived.local_density = density.max(0.0);
@@ -188,21 +1004,497 @@ pub fn compute_derived_fields(
 
         density_sum += derived.local_density;
         curvature_sum += derived.curvature_proxy.abs();
-        min_density = min_density.min(derived.local_density);
-        max_density = max_density.max(derived.local_density);
+        densities.push(derived.local_density);
+        curvatures.push(derived.curvature_proxy);
     }
 
-    let total_cells = derived_query.iter().count() as f32;
+    // Neighbor volumes m_j / ρ_j; cells with no density around them carry no volume.
+    let densities_by_cell: HashMap<Entity, f32> = derived_query
+        .iter()
+        .map(|(entity, _, derived)| (entity, derived.local_density))
+        .collect();
+    let volumes: Vec<f32> = cell_data
+        .iter()
+        .map(
+            |(entity, _, mass, _, _)| match densities_by_cell.get(entity) {
+                Some(&density) if density > f32::EPSILON => *mass / density,
+                _ => 0.0,
+            },
+        )
+        .collect();
+    let mut gradient_sum = 0.0;
+    let mut gradients = Vec::with_capacity(cell_data.len());
+    for (entity, cell, mut derived) in derived_query.iter_mut() {
+        let ua = cell.ua_mass_lock as f32;
+        let mut gradient = Vec3::ZERO;
+        let mut correction = Mat3::ZERO;
+        for ((other, pos, _, _, other_ua), volume) in cell_data.iter().zip(volumes.iter()) {
+            if *other == entity || *volume == 0.0 {
+                continue;
+            }
+            let offset = universe.minimum_image(cell.position - *pos);
+            let r = offset.length();
+            if r <= f32::EPSILON {
+                continue;
+            }
+            let slope = kernel.gradient(r, smoothing_length) * reference_volume;
+            let weight = offset * (volume * slope / r);
+            gradient += weight * (other_ua - ua);
+            correction +=
+                Mat3::from_cols(weight * -offset.x, weight * -offset.y, weight * -offset.z);
+        }
+        // Fall back to the raw sum where too few neighbors make `L_i` singular.
+        if correction.determinant().abs() > f32::EPSILON {
+            gradient = correction.inverse() * gradient;
+        }
+        derived.ua_gradient = gradient;
+        gradient_sum += gradient.length();
+        gradients.push(gradient.length());
+    }
+
+    let total_cells = densities.len() as f32;
     if total_cells > 0.0 {
+        densities.sort_by(f32::total_cmp);
+        curvatures.sort_by(f32::total_cmp);
+        speeds.sort_by(f32::total_cmp);
+
         metrics.avg_density = density_sum / total_cells;
-        metrics.min_density = min_density;
-        metrics.max_density = max_density;
+        metrics.min_density = densities[0];
+        metrics.max_density = densities[densities.len() - 1];
         metrics.avg_curvature = curvature_sum / total_cells;
+        metrics.min_curvature = curvatures[0];
+        metrics.max_curvature = curvatures[curvatures.len() - 1];
+        metrics.density_percentiles = (percentile(&densities, 0.05), percentile(&densities, 0.95));
+        metrics.curvature_percentiles =
+            (percentile(&curvatures, 0.05), percentile(&curvatures, 0.95));
+        metrics.max_abs_curvature = metrics.min_curvature.abs().max(metrics.max_curvature.abs());
+        let mut abs_curvatures: Vec<f32> = curvatures.iter().map(|c| c.abs()).collect();
+        abs_curvatures.sort_by(f32::total_cmp);
+        metrics.abs_curvature_percentile = percentile(&abs_curvatures, 0.95);
+        gradients.sort_by(f32::total_cmp);
+        metrics.mean_ua_gradient_magnitude = gradient_sum / total_cells;
+        metrics.max_ua_gradient_magnitude = gradients[gradients.len() - 1];
+        metrics.ua_gradient_percentiles =
+            (percentile(&gradients, 0.05), percentile(&gradients, 0.95));
+        metrics.min_speed = speeds[0];
+        metrics.max_speed = speeds[speeds.len() - 1];
+        metrics.speed_percentiles = (percentile(&speeds, 0.05), percentile(&speeds, 0.95));
+        accelerations.sort_by(f32::total_cmp);
+        metrics.min_log_acceleration = accelerations[0];
+        metrics.max_log_acceleration = accelerations[accelerations.len() - 1];
+        metrics.log_acceleration_percentiles = (
+            percentile(&accelerations, 0.05),
+            percentile(&accelerations, 0.95),
+        );
 
         let avg_density = metrics.avg_density;
-        metrics.density_history.push_back(avg_density);
-        while metrics.density_history.len() > metrics.max_history {
-            metrics.density_history.pop_front();
+        metrics.record_density(avg_density, sim_state.tick);
+    }
+}
+
+/// Axis-aligned box whose cells the metrics panel summarizes alongside the whole grid.
+#[derive(Resource, Clone, Copy, Default)]
+pub struct RegionOfInterest {
+    /// World-space `(min, max)` corners; `None` turns ROI metrics off.
+    pub bounds: Option<(Vec3, Vec3)>,
+    /// Keep `bounds` on the halo of the galaxy the camera is following.
+    pub track_followed_galaxy: bool,
+}
+
+/// One cell's contribution to [`RoiMetrics`].
+#[derive(Clone, Copy)]
+pub struct RoiSample {
+    pub position: Vec3,
+    pub density: f32,
+    pub curvature: f32,
+    pub mass: f32,
+    pub velocity: Vec3,
+}
+
+/// Field metrics restricted to the region of interest.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct RoiMetrics {
+    pub cells: usize,
+    pub avg_density: f32,
+    /// Mean |curvature proxy|, matching `FieldMetrics::avg_curvature`.
+    pub avg_curvature: f32,
+    pub kinetic_energy: f64,
+}
+
+impl RoiMetrics {
+    /// Summarize the samples inside `[min, max]` (inclusive).
+    ///
+    /// An empty region reports all zeros rather than dividing by zero.
+    pub fn measure(min: Vec3, max: Vec3, samples: impl IntoIterator<Item = RoiSample>) -> Self {
+        let mut metrics = Self::default();
+        let mut density_sum = 0.0f64;
+        let mut curvature_sum = 0.0f64;
+        for sample in samples {
+            if sample.position.cmplt(min).any() || sample.position.cmpgt(max).any() {
+                continue;
+            }
+            metrics.cells += 1;
+            density_sum += sample.density as f64;
+            curvature_sum += sample.curvature.abs() as f64;
+            metrics.kinetic_energy +=
+                0.5 * sample.mass as f64 * sample.velocity.length_squared() as f64;
+        }
+        if metrics.cells > 0 {
+            metrics.avg_density = (density_sum / metrics.cells as f64) as f32;
+            metrics.avg_curvature = (curvature_sum / metrics.cells as f64) as f32;
+        }
+        metrics
+    }
+}
+
+/// Recompute the ROI metrics from the latest derived fields.
+pub fn compute_roi_metrics(
+    roi: Res<RegionOfInterest>,
+    mut roi_metrics: ResMut<RoiMetrics>,
+    cells: Query<(&PruCell, &DerivedFields, &PruDynamics)>,
+) {
+    let Some((min, max)) = roi.bounds else {
+        if *roi_metrics != RoiMetrics::default() {
+            *roi_metrics = RoiMetrics::default();
+        }
+        return;
+    };
+    *roi_metrics = RoiMetrics::measure(
+        min,
+        max,
+        cells.iter().map(|(cell, derived, dynamics)| RoiSample {
+            position: cell.position,
+            density: derived.local_density,
+            curvature: derived.curvature_proxy,
+            mass: dynamics.mass,
+            velocity: dynamics.velocity_at_integer_tick,
+        }),
+    );
+}
+
+/// Nearest-rank percentile of an ascending-sorted, non-empty slice.
+fn percentile(sorted: &[f32], q: f32) -> f32 {
+    let rank = (q.clamp(0.0, 1.0) * (sorted.len() - 1) as f32).round() as usize;
+    sorted[rank]
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::experiment::headless::HeadlessRun;
+    use crate::pru::gravity::{
+        compute_energy_metrics, simulate_gravity_step, DampingModel, GravityMode, SimulationEnergy,
+    };
+    use crate::pru::separation::MinSeparation;
+
+    #[test]
+    fn every_kernel_integrates_to_one_over_a_uniform_grid() {
+        let h = 2.0;
+        let spacing = h / 8.0;
+        // Three h covers the Gaussian's tail as well as every compact kernel.
+        let reach = (3.0 * h / spacing) as i32;
+        for kernel in [
+            SphKernelType::Gaussian,
+            SphKernelType::CubicSpline,
+            SphKernelType::Wendland4,
+            SphKernelType::TopHat,
+        ] {
+            let mut integral = 0.0f64;
+            for x in -reach..=reach {
+                for y in -reach..=reach {
+                    for z in -reach..=reach {
+                        let r = IVec3::new(x, y, z).as_vec3().length() * spacing;
+                        integral += kernel.weight(r, h) as f64;
+                    }
+                }
+            }
+            integral *= (spacing as f64).powi(3);
+            // The top-hat's sharp edge cuts through grid points, so it converges slowest.
+            let tolerance = if kernel == SphKernelType::TopHat {
+                0.03
+            } else {
+                1e-3
+            };
+            assert!(
+                (integral - 1.0).abs() < tolerance,
+                "{}: ∫W dV = {integral}",
+                kernel.label()
+            );
+        }
+    }
+
+    #[test]
+    fn periodic_x_edge_cells_neighbor_each_other() {
+        let universe =
+            PruUniverse::new(UVec3::new(8, 10, 6), 1.0).with_boundary(BoundaryCondition::Torus2D {
+                y: AxisBoundary::Open,
+            });
+        let neighbors: Vec<UVec3> = universe.neighbors(UVec3::new(0, 5, 3)).collect();
+        assert!(neighbors.contains(&UVec3::new(7, 5, 3)));
+        assert!(neighbors.contains(&UVec3::new(1, 5, 3)));
+        assert_eq!(neighbors.len(), 6);
+
+        let open = PruUniverse::new(UVec3::new(8, 10, 6), 1.0);
+        assert!(!open.neighbors(UVec3::new(0, 5, 3)).any(|n| n.x == 7));
+    }
+
+    #[test]
+    fn growing_keeps_every_existing_site_in_place() {
+        let mut universe = PruUniverse::new(UVec3::new(4, 3, 2), 1.5);
+        let sites: Vec<(UVec3, Vec3)> = (0..24)
+            .map(|i| UVec3::new(i % 4, (i / 4) % 3, i / 12))
+            .map(|site| (site, universe.rest_position(site)))
+            .collect();
+
+        assert_eq!(universe.grow(0, false), UVec3::X);
+        assert_eq!(universe.grow(2, true), UVec3::ZERO);
+        assert_eq!(universe.grid_dimensions, UVec3::new(5, 3, 3));
+        for (site, position) in &sites {
+            let moved = universe.rest_position(*site + UVec3::X);
+            assert!(moved.distance(*position) < 1e-5, "{site} moved to {moved}");
+        }
+        let first = universe.rest_position(UVec3::ZERO);
+        assert!((first.x - (sites[0].1.x - 1.5)).abs() < 1e-5);
+        let top = universe.rest_position(UVec3::new(1, 0, 2));
+        assert!((top.z - (sites[0].1.z + 3.0)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn bodies_wrap_reflect_or_stop_at_the_faces() {
+        let universe = |y| {
+            PruUniverse::new(UVec3::new(4, 4, 4), 1.0)
+                .with_boundary(BoundaryCondition::Torus2D { y })
+        };
+        // The lattice spans -2..2 on every axis.
+        let mut position = DVec3::new(2.5, 0.0, -2.25);
+        let mut velocity = DVec3::new(1.0, 0.0, -1.0);
+        assert!(universe(AxisBoundary::Open).confine(&mut position, &mut velocity));
+        assert_eq!(position, DVec3::new(-1.5, 0.0, 1.75));
+        assert_eq!(velocity, DVec3::new(1.0, 0.0, -1.0));
+
+        let mut position = DVec3::new(0.0, 2.5, 0.0);
+        let mut velocity = DVec3::new(0.0, 1.0, 0.0);
+        let (mut open_position, mut open_velocity) = (position, velocity);
+        assert!(!universe(AxisBoundary::Open).confine(&mut open_position, &mut open_velocity));
+        assert!(universe(AxisBoundary::Reflecting).confine(&mut position, &mut velocity));
+        assert_eq!((position.y, velocity.y), (1.5, -1.0));
+
+        let mut position = DVec3::new(0.0, -2.5, 0.0);
+        let mut velocity = DVec3::new(0.0, -1.0, 0.0);
+        assert!(universe(AxisBoundary::Absorbing).confine(&mut position, &mut velocity));
+        assert_eq!((position.y, velocity.y), (-2.0, 0.0));
+
+        // A close pair drifting through the x face: the leader wraps while the
+        // trailer has not, yet the energy sees the same short separation throughout.
+        let mut world = World::new();
+        world.insert_resource(GravityParams {
+            mode: GravityMode::NaiveNBody,
+            damping_model: DampingModel::None,
+            ..Default::default()
+        });
+        world.insert_resource(universe(AxisBoundary::Open));
+        world.init_resource::<MinSeparation>();
+        world.init_resource::<SimulationState>();
+        world.init_resource::<SimulationEnergy>();
+        world.init_resource::<FieldMetrics>();
+        let pair = [1.2, 1.6].map(|x| {
+            let position = Vec3::new(x, 0.0, 0.0);
+            world
+                .spawn((
+                    PruCell::new(position, UVec3::ZERO, 1.0, 0.0),
+                    PruDynamics {
+                        mass: 1.0,
+                        velocity: Vec3::X * 3.0,
+                        velocity_at_integer_tick: Vec3::X * 3.0,
+                        ..Default::default()
+                    },
+                    Transform::from_translation(position),
+                ))
+                .id()
+        });
+        let mut schedule = Schedule::default();
+        schedule.add_systems((simulate_gravity_step, compute_energy_metrics).chain());
+        let mut totals = Vec::new();
+        let mut straddled = false;
+        for _ in 0..40 {
+            world.resource_mut::<SimulationState>().step_once();
+            schedule.run(&mut world);
+            totals.push(world.resource::<SimulationEnergy>().total);
+            let [trailer, leader] = pair.map(|body| world.get::<PruCell>(body).unwrap().position.x);
+            straddled |= leader < trailer;
+        }
+        assert!(
+            straddled,
+            "the leader should have wrapped ahead of the trailer"
+        );
+        for step in totals.windows(2) {
+            let jump = (step[1] - step[0]).abs() / step[0].abs();
+            assert!(jump < 1e-3, "energy jumped {} -> {}", step[0], step[1]);
+        }
+    }
+
+    #[test]
+    fn roi_metrics_average_only_the_chosen_sub_box() {
+        let mut world = World::new();
+        for site in (0..64).map(|i| UVec3::new(i % 4, (i / 4) % 4, i / 16)) {
+            let position = site.as_vec3();
+            world.spawn((
+                PruCell::new(position, site, 1.0, 0.0),
+                DerivedFields {
+                    local_density: site.x as f32 + 1.0,
+                    curvature_proxy: -(site.z as f32),
+                    ..Default::default()
+                },
+                PruDynamics {
+                    mass: 2.0,
+                    velocity_at_integer_tick: Vec3::X * site.y as f32,
+                    ..Default::default()
+                },
+            ));
+        }
+        world.init_resource::<RoiMetrics>();
+        let mut measure = |bounds| {
+            world.insert_resource(RegionOfInterest {
+                bounds,
+                track_followed_galaxy: false,
+            });
+            world.run_system_once(compute_roi_metrics);
+            *world.resource::<RoiMetrics>()
+        };
+
+        let global = measure(Some((Vec3::ZERO, Vec3::splat(3.0))));
+        assert_eq!(global.cells, 64);
+        assert!((global.avg_density - 2.5).abs() < 1e-6);
+
+        // x ∈ {0, 1}, y = 3, z ∈ {2, 3}: four cells.
+        let sub_box = measure(Some((Vec3::new(-0.5, 2.5, 1.5), Vec3::new(1.5, 3.5, 3.5))));
+        assert_eq!(sub_box.cells, 4);
+        assert!((sub_box.avg_density - 1.5).abs() < 1e-6);
+        assert!((sub_box.avg_curvature - 2.5).abs() < 1e-6);
+        // ½ · 2 · 3² per cell.
+        assert!((sub_box.kinetic_energy - 4.0 * 9.0).abs() < 1e-9);
+        assert_ne!(sub_box.avg_density, global.avg_density);
+
+        let empty = measure(Some((Vec3::splat(10.0), Vec3::splat(11.0))));
+        assert_eq!(empty, RoiMetrics::default());
+        assert!(empty.avg_density.is_finite());
+        assert_eq!(measure(None), RoiMetrics::default());
+    }
+
+    #[test]
+    fn degenerate_universes_step_a_hundred_ticks() {
+        let cell_count = |run: &mut HeadlessRun| {
+            let world = run.world_mut();
+            world.query::<&PruCell>().iter(world).count()
+        };
+        let metrics_are_finite = |run: &mut HeadlessRun| {
+            let metrics = run.world_mut().resource::<FieldMetrics>();
+            [
+                metrics.avg_density,
+                metrics.min_density,
+                metrics.max_density,
+                metrics.avg_curvature,
+                metrics.max_abs_curvature,
+            ]
+            .iter()
+            .all(|value| value.is_finite())
+        };
+
+        // A lone cell, and the two-per-axis floor that zero-sized grids are raised to.
+        for (dimensions, single_cell, cells) in [
+            (UVec3::ZERO, false, 8),
+            (UVec3::new(2, 1, 0), false, 8),
+            (UVec3::ZERO, true, 1),
+        ] {
+            for mode in [GravityMode::NaiveNBody, GravityMode::RelationalLattice] {
+                let mut run = HeadlessRun::new(|world| {
+                    let mut settings = world.resource_mut::<UniverseSettings>();
+                    settings.grid_dimensions = dimensions;
+                    settings.single_cell = single_cell;
+                    world.resource_mut::<GravityParams>().mode = mode;
+                });
+                run.step(100);
+                assert_eq!(cell_count(&mut run), cells, "{dimensions} {single_cell}");
+                assert!(metrics_are_finite(&mut run));
+            }
+        }
+
+        // With every cell gone, the per-cell systems idle and metrics go neutral.
+        let mut run = HeadlessRun::new(|world| {
+            world.resource_mut::<UniverseSettings>().single_cell = true;
+        });
+        run.step(1);
+        let world = run.world_mut();
+        let cells: Vec<Entity> = world
+            .query_filtered::<Entity, With<PruCell>>()
+            .iter(world)
+            .collect();
+        for cell in cells {
+            world.despawn(cell);
         }
+        run.step(100);
+        assert_eq!(cell_count(&mut run), 0);
+        assert!(metrics_are_finite(&mut run));
+        assert_eq!(run.world_mut().resource::<FieldMetrics>().avg_density, 0.0);
+    }
+
+    /// Run [`compute_derived_fields`] once over unit-mass cells at `sites` and return their fields.
+    fn derived_fields(settings: FieldSettings, sites: &[UVec3]) -> Vec<DerivedFields> {
+        let universe = PruUniverse::new(UVec3::splat(3), 1.0);
+        let mut world = World::new();
+        let cells: Vec<Entity> = sites
+            .iter()
+            .map(|site| {
+                let position = universe.rest_position(*site);
+                world
+                    .spawn((
+                        PruCell::new(position, *site, 1.0, 0.0),
+                        PruDynamics::default(),
+                        DerivedFields::default(),
+                    ))
+                    .id()
+            })
+            .collect();
+        world.insert_resource(universe);
+        world.insert_resource(settings);
+        world.init_resource::<SimulationState>();
+        world.init_resource::<Time<Fixed>>();
+        world.init_resource::<FieldMetrics>();
+        world.run_system_once(compute_derived_fields);
+        cells
+            .iter()
+            .map(|cell| *world.get::<DerivedFields>(*cell).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn derived_density_matches_hand_computed_gaussian_sums() {
+        // With h = one spacing, the scaled Gaussian weight is exp(-2 r² / h²).
+        let settings = FieldSettings {
+            kernel: SphKernelType::Gaussian,
+            smoothing_radius: 1.0,
+            ..Default::default()
+        };
+        let with_self = FieldSettings {
+            include_self_density: true,
+            ..settings
+        };
+
+        let lone = [UVec3::ONE];
+        assert_eq!(derived_fields(settings, &lone)[0].local_density, 0.0);
+        assert!((derived_fields(with_self, &lone)[0].local_density - 1.0).abs() < 1e-6);
+
+        // The middle of a 3x3x3 block: 6 face, 12 edge, and 8 corner neighbors.
+        let block: Vec<UVec3> = (0..27)
+            .map(|i| UVec3::new(i % 3, (i / 3) % 3, i / 9))
+            .collect();
+        let neighbors = 6.0 * (-2.0f32).exp() + 12.0 * (-4.0f32).exp() + 8.0 * (-6.0f32).exp();
+        let center = derived_fields(settings, &block)[13];
+        assert!((center.local_density - neighbors).abs() < 1e-5);
+        assert_eq!(center.curvature_proxy, 0.0);
+        let center = derived_fields(with_self, &block)[13];
+        assert!((center.local_density - (1.0 + neighbors)).abs() < 1e-5);
     }
 }
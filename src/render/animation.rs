@@ -0,0 +1,96 @@
+//! Idle animations (cell pulse, star flicker, black hole wobble) on the simulation clock.
+//!
+//! The animations read [`AnimationClock`] rather than wall-clock time, so
+//! they freeze when the simulation pauses and speed up with its time scale.
+//! They only ever write `Transform::scale`, folding in any [`ScaleModifier`]
+//! so other visual features can enlarge a body without being overwritten.
+
+use bevy::prelude::*;
+
+use crate::app::SimulationState;
+
+/// Amplitude controls for the idle animations.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct AnimationSettings {
+    /// Master switch; off holds every body at its rest scale (for screenshots).
+    pub enabled: bool,
+    /// Keep animating, slowed to `idle_rate`, while the simulation is paused.
+    pub animate_while_paused: bool,
+    /// Speed of the paused idle animation relative to real time.
+    pub idle_rate: f32,
+    /// Multiplier on the cell pulse amplitude.
+    pub cell_pulse: f32,
+    /// Multiplier on the star flicker amplitude.
+    pub star_flicker: f32,
+    /// Multiplier on the black hole wobble amplitude.
+    pub black_hole_wobble: f32,
+}
+
+impl Default for AnimationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            animate_while_paused: false,
+            idle_rate: 0.25,
+            cell_pulse: 1.0,
+            star_flicker: 1.0,
+            black_hole_wobble: 1.0,
+        }
+    }
+}
+
+impl AnimationSettings {
+    /// Effective multiplier for an animation whose own amplitude control is `amplitude`.
+    pub fn amplitude(&self, amplitude: f32) -> f32 {
+        if self.enabled {
+            amplitude.max(0.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Phase source for the idle animations.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct AnimationClock {
+    /// Simulated seconds plus any idle time accumulated while paused.
+    pub time: f32,
+    /// Real seconds, scaled by `idle_rate`, spent animating while paused.
+    idle_time: f32,
+}
+
+/// Extra scale factor owned by another visual feature, such as a selection highlight.
+///
+/// The animations multiply it into the scale they write instead of replacing
+/// whatever the feature set.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ScaleModifier(pub f32);
+
+impl Default for ScaleModifier {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Follow the simulation clock, adding slowed real time while paused if enabled.
+pub fn advance_animation_clock(
+    time: Res<Time>,
+    sim_state: Res<SimulationState>,
+    settings: Res<AnimationSettings>,
+    mut clock: ResMut<AnimationClock>,
+) {
+    if !sim_state.running && settings.animate_while_paused {
+        clock.idle_time += time.delta_seconds() * settings.idle_rate.max(0.0);
+    }
+    let time = sim_state.simulation_time + clock.idle_time;
+    if clock.time != time {
+        clock.time = time;
+    }
+}
+
+/// Write `scale` unless it is already current, keeping `Changed<Transform>` quiet.
+pub fn set_scale(transform: &mut Mut<Transform>, scale: Vec3) {
+    if transform.scale != scale {
+        transform.scale = scale;
+    }
+}
@@ -0,0 +1,184 @@
+//! Event horizon look for black holes: a shadow, a photon ring, and a lensing glow.
+//!
+//! Each black hole gets a child quad drawn with [`BlackHoleMaterial`], whose
+//! shader turns it to face the camera and paints the shadow of radius
+//! [`SHADOW_RADIUS_FACTOR`] times the horizon, a thin ring at its edge, and a
+//! glow fading outside it. Spin brightens the approaching side of the ring.
+//! The background is not actually distorted; the glow stands in for it.
+//!
+//! The dark horizon sphere stays underneath, so black holes fall back to it
+//! wherever the effect cannot draw: the plugin does nothing without a render
+//! app (headless runs), and a device that cannot build the shader's pipeline
+//! simply skips the quad.
+
+use bevy::asset::load_internal_asset;
+use bevy::pbr::{NotShadowCaster, NotShadowReceiver};
+use bevy::prelude::*;
+use bevy::render::render_resource::{AsBindGroup, ShaderRef};
+use bevy::render::view::NoFrustumCulling;
+use bevy::render::RenderApp;
+
+use crate::astro::assets::BLACK_HOLE_MESH_RADIUS;
+use crate::astro::black_hole::BlackHole;
+
+const BLACK_HOLE_LENSING_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x5b1d_6c2e_94a7_4f03_b8e1_72d0_c4a9_1e36);
+
+/// Shadow radius as a multiple of the horizon: the photon capture radius `√27/2 r_s`.
+pub const SHADOW_RADIUS_FACTOR: f32 = 2.6;
+/// Half-width of the effect's quad, in shadow radii.
+const LENSING_EXTENT: f32 = 2.0;
+/// Photon ring brightness; above 1 so it blooms under HDR.
+const RING_INTENSITY: f32 = 3.0;
+const RING_COLOR: Color = Color::srgb(1.0, 0.78, 0.45);
+
+/// Shadow and photon ring material, one per black hole.
+///
+/// The fields share one uniform, laid out as `BlackHoleLensing` in `black_hole_lensing.wgsl`.
+#[derive(Asset, TypePath, AsBindGroup, Clone, Debug)]
+pub struct BlackHoleMaterial {
+    #[uniform(0)]
+    pub ring_color: LinearRgba,
+    /// World-space radius of the opaque shadow.
+    #[uniform(0)]
+    pub shadow_radius: f32,
+    /// World-space half-width of the quad.
+    #[uniform(0)]
+    pub extent: f32,
+    /// Dimensionless spin, `0..=1`.
+    #[uniform(0)]
+    pub spin: f32,
+    #[uniform(0)]
+    pub ring_intensity: f32,
+}
+
+impl BlackHoleMaterial {
+    /// Material sized to the black hole's horizon sphere and tinted by its spin.
+    pub fn for_black_hole(black_hole: &BlackHole) -> Self {
+        let shadow_radius = BLACK_HOLE_MESH_RADIUS * black_hole.radius * SHADOW_RADIUS_FACTOR;
+        Self {
+            ring_color: RING_COLOR.to_linear(),
+            shadow_radius,
+            extent: shadow_radius * LENSING_EXTENT,
+            spin: black_hole.spin.clamp(0.0, 1.0),
+            ring_intensity: RING_INTENSITY,
+        }
+    }
+}
+
+impl Material for BlackHoleMaterial {
+    fn vertex_shader() -> ShaderRef {
+        BLACK_HOLE_LENSING_SHADER_HANDLE.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        BLACK_HOLE_LENSING_SHADER_HANDLE.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Blend
+    }
+}
+
+/// Quad every lensing child is drawn on; the shader sizes and turns it.
+#[derive(Resource)]
+pub struct BlackHoleLensingMesh(pub Handle<Mesh>);
+
+/// Marker for a black hole's shadow and photon ring quad.
+#[derive(Component)]
+pub struct BlackHoleLensingQuad;
+
+/// Draws the shadow and photon ring on black holes when a renderer is present.
+pub struct BlackHoleLensingPlugin;
+
+impl Plugin for BlackHoleLensingPlugin {
+    fn build(&self, app: &mut App) {
+        // Headless apps have no shaders to load; black holes keep their dark sphere.
+        if app.get_sub_app(RenderApp).is_none() {
+            return;
+        }
+        load_internal_asset!(
+            app,
+            BLACK_HOLE_LENSING_SHADER_HANDLE,
+            "black_hole_lensing.wgsl",
+            Shader::from_wgsl
+        );
+        app.add_plugins(MaterialPlugin::<BlackHoleMaterial>::default())
+            .add_systems(Startup, setup_black_hole_lensing)
+            .add_systems(Update, attach_black_hole_lensing);
+    }
+}
+
+pub fn setup_black_hole_lensing(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(BlackHoleLensingMesh(meshes.add(Rectangle::new(2.0, 2.0))));
+}
+
+/// Give each new black hole its lensing quad.
+///
+/// The material handle is held only by the quad, so it is freed when the
+/// black hole despawns with its children.
+pub fn attach_black_hole_lensing(
+    mut commands: Commands,
+    mesh: Option<Res<BlackHoleLensingMesh>>,
+    mut materials: ResMut<Assets<BlackHoleMaterial>>,
+    black_holes: Query<(Entity, &BlackHole), Added<BlackHole>>,
+) {
+    let Some(mesh) = mesh else {
+        return;
+    };
+    for (entity, black_hole) in black_holes.iter() {
+        let quad = commands
+            .spawn((
+                MaterialMeshBundle {
+                    mesh: mesh.0.clone(),
+                    material: materials.add(BlackHoleMaterial::for_black_hole(black_hole)),
+                    ..Default::default()
+                },
+                BlackHoleLensingQuad,
+                // The shader moves the quad away from its mesh bounds.
+                NoFrustumCulling,
+                NotShadowCaster,
+                NotShadowReceiver,
+                Name::new("Black Hole Lensing"),
+            ))
+            .id();
+        commands.entity(entity).add_child(quad);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn black_holes_get_a_lensing_material_and_headless_apps_keep_the_sphere() {
+        let mut world = World::new();
+        world.init_resource::<Assets<BlackHoleMaterial>>();
+        world.insert_resource(BlackHoleLensingMesh(Handle::default()));
+        let hole = world.spawn(BlackHole::new(20.0, 1.0, 0.5)).id();
+        world.run_system_once(attach_black_hole_lensing);
+
+        let children = world.get::<Children>(hole).expect("the quad is a child");
+        assert_eq!(children.len(), 1);
+        let quad = children[0];
+        assert!(world.get::<BlackHoleLensingQuad>(quad).is_some());
+        let handle = world.get::<Handle<BlackHoleMaterial>>(quad).unwrap();
+        let material = world
+            .resource::<Assets<BlackHoleMaterial>>()
+            .get(handle)
+            .expect("the material asset exists");
+        assert_eq!(
+            material.shadow_radius,
+            BLACK_HOLE_MESH_RADIUS * SHADOW_RADIUS_FACTOR
+        );
+        assert_eq!(material.spin, 0.5);
+
+        // Without a renderer the plugin stays out of the way.
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, BlackHoleLensingPlugin));
+        app.update();
+        assert!(!app.world().contains_resource::<Assets<BlackHoleMaterial>>());
+    }
+}
@@ -0,0 +1,80 @@
+// Black hole shadow and photon ring, drawn on a camera-facing quad.
+//
+// The quad is expanded around the black hole's center in the vertex stage,
+// so it always faces the camera whatever the parent's scale or rotation, and
+// pulled toward the camera so the dark horizon sphere never clips it. The
+// fragment stage draws, by impact parameter: an opaque shadow, a thin bright
+// ring at its edge, and a faint lensing glow falling off outside it. Spin
+// beams one side of the ring brighter and shifts the shadow toward it.
+
+#import bevy_pbr::{
+    mesh_functions,
+    mesh_view_bindings::view,
+    view_transformations::position_world_to_clip,
+}
+
+struct BlackHoleLensing {
+    ring_color: vec4<f32>,
+    shadow_radius: f32,
+    extent: f32,
+    spin: f32,
+    ring_intensity: f32,
+}
+
+@group(2) @binding(0) var<uniform> lensing: BlackHoleLensing;
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    // Quad coordinates in -1..1 across the effect's extent.
+    @location(0) offset: vec2<f32>,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    let world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
+    let center = world_from_local[3].xyz;
+    let right = view.world_from_view[0].xyz;
+    let up = view.world_from_view[1].xyz;
+    let toward_camera = normalize(view.world_position - center);
+
+    let corner = (right * vertex.position.x + up * vertex.position.y) * lensing.extent;
+    let world_position = center + toward_camera * lensing.shadow_radius + corner;
+
+    var out: VertexOutput;
+    out.clip_position = position_world_to_clip(world_position);
+    out.offset = vertex.position.xy;
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let shadow = lensing.shadow_radius;
+    // A spinning hole's shadow is shifted toward its approaching side.
+    let position = in.offset * lensing.extent - vec2(lensing.spin * 0.08 * shadow, 0.0);
+    let r = length(position);
+    if r >= lensing.extent {
+        discard;
+    }
+
+    let side = position.x / max(r, 1e-4);
+    let beaming = clamp(1.0 + 0.8 * lensing.spin * side, 0.2, 1.8);
+
+    let ring_width = shadow * 0.06;
+    let ring = exp(-pow((r - shadow * 1.03) / ring_width, 2.0));
+    let outside = max(r - shadow, 0.0);
+    let edge_fade = 1.0 - smoothstep(0.7, 1.0, r / lensing.extent);
+    let glow = 0.3 * exp(-outside / (shadow * 0.35)) * edge_fade * step(shadow, r);
+    let inside = 1.0 - smoothstep(shadow * 0.97, shadow, r);
+
+    let light = (ring * lensing.ring_intensity + glow) * beaming;
+    let alpha = clamp(max(inside, ring + glow), 0.0, 1.0);
+    if alpha < 0.003 {
+        discard;
+    }
+    return vec4(lensing.ring_color.rgb * light / max(alpha, 1e-4), alpha);
+}
@@ -1,5 +1,23 @@
+use std::f32::consts::{FRAC_PI_4, FRAC_PI_6, FRAC_PI_8};
+
+use bevy::core_pipeline::tonemapping::Tonemapping;
 use bevy::input::mouse::{MouseMotion, MouseWheel};
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::agents::events::ReportSeverity;
+use crate::astro::galaxy::{Galaxy, SelectedGalaxy};
+use crate::tracked::TrackedEntity;
+use crate::ui::toasts::{Toasts, TOAST_SECS};
+
+/// Largest pitch magnitude, keeping the camera just short of the orbit pole.
+const PITCH_LIMIT: f32 = 1.5;
+/// Closest and farthest orbit radius the scroll wheel reaches.
+const ZOOM_RANGE: (f32, f32) = (2.0, 200.0);
+/// Vertical field of view without auto-FOV.
+const DEFAULT_FOV: f32 = FRAC_PI_4;
+/// Auto-FOV at the closest and farthest zoom: 30° telephoto to 67.5° panoramic.
+const AUTO_FOV_RANGE: (f32, f32) = (FRAC_PI_6, 3.0 * FRAC_PI_8);
 
 /// Resource containing orbit camera parameters.
 #[derive(Resource)]
@@ -11,6 +29,43 @@ pub struct OrbitCameraSettings {
     pub rotate_sensitivity: f32,
     pub pan_sensitivity: f32,
     pub zoom_sensitivity: f32,
+    /// Axis the camera orbits around and keeps upright against.
+    pub up_axis: Vec3,
+    /// Entity the focus follows; cleared when it despawns.
+    pub follow: TrackedEntity,
+    /// Widen the field of view as the camera zooms out (see [`Self::fov`]).
+    pub fov_auto: bool,
+}
+
+impl OrbitCameraSettings {
+    /// Rotation taking the default Y-up orbit frame onto `up_axis`.
+    fn pole_rotation(&self) -> Quat {
+        Quat::from_rotation_arc(Vec3::Y, self.up_axis.normalize_or(Vec3::Y))
+    }
+
+    /// Vertical field of view for the current zoom.
+    ///
+    /// With `fov_auto` it runs linearly from [`AUTO_FOV_RANGE`]`.0` at the
+    /// closest zoom to `.1` at the farthest; otherwise it stays at 45°.
+    pub fn fov(&self) -> f32 {
+        if !self.fov_auto {
+            return DEFAULT_FOV;
+        }
+        let zoom = ((self.radius - ZOOM_RANGE.0) / (ZOOM_RANGE.1 - ZOOM_RANGE.0)).clamp(0.0, 1.0);
+        AUTO_FOV_RANGE.0 + (AUTO_FOV_RANGE.1 - AUTO_FOV_RANGE.0) * zoom
+    }
+
+    /// Switch to the next orbit pole in the Y → Z → X cycle.
+    pub fn cycle_up_axis(&mut self) {
+        self.up_axis = if self.up_axis == Vec3::Y {
+            Vec3::Z
+        } else if self.up_axis == Vec3::Z {
+            Vec3::X
+        } else {
+            Vec3::Y
+        };
+        self.pitch = self.pitch.clamp(-PITCH_LIMIT, PITCH_LIMIT);
+    }
 }
 
 impl Default for OrbitCameraSettings {
@@ -23,6 +78,69 @@ impl Default for OrbitCameraSettings {
             rotate_sensitivity: 0.005,
             pan_sensitivity: 0.015,
             zoom_sensitivity: 1.2,
+            up_axis: Vec3::Y,
+            follow: TrackedEntity::default(),
+            fov_auto: true,
+        }
+    }
+}
+
+/// Tone-mapping curves usable without Bevy's LUT-based tonemappers.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ToneMappingCurve {
+    Reinhard,
+    ReinhardLuminance,
+    AcesFitted,
+    SomewhatBoringDisplayTransform,
+}
+
+impl ToneMappingCurve {
+    pub fn next(self) -> Self {
+        match self {
+            ToneMappingCurve::Reinhard => ToneMappingCurve::ReinhardLuminance,
+            ToneMappingCurve::ReinhardLuminance => ToneMappingCurve::AcesFitted,
+            ToneMappingCurve::AcesFitted => ToneMappingCurve::SomewhatBoringDisplayTransform,
+            ToneMappingCurve::SomewhatBoringDisplayTransform => ToneMappingCurve::Reinhard,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ToneMappingCurve::Reinhard => "Reinhard",
+            ToneMappingCurve::ReinhardLuminance => "Reinhard (luminance)",
+            ToneMappingCurve::AcesFitted => "ACES",
+            ToneMappingCurve::SomewhatBoringDisplayTransform => "Somewhat Boring",
+        }
+    }
+
+    fn tonemapping(self) -> Tonemapping {
+        match self {
+            ToneMappingCurve::Reinhard => Tonemapping::Reinhard,
+            ToneMappingCurve::ReinhardLuminance => Tonemapping::ReinhardLuminance,
+            ToneMappingCurve::AcesFitted => Tonemapping::AcesFitted,
+            ToneMappingCurve::SomewhatBoringDisplayTransform => {
+                Tonemapping::SomewhatBoringDisplayTransform
+            }
+        }
+    }
+}
+
+/// HDR rendering and the curve that rolls bright emissives off toward white.
+///
+/// With HDR off the camera keeps its default tonemapper and bright stars
+/// clip; with HDR on, `curve` compresses them instead.
+#[derive(Resource, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToneMappingSettings {
+    pub hdr: bool,
+    pub curve: ToneMappingCurve,
+}
+
+impl Default for ToneMappingSettings {
+    fn default() -> Self {
+        Self {
+            hdr: false,
+            curve: ToneMappingCurve::AcesFitted,
         }
     }
 }
@@ -37,24 +155,31 @@ pub struct OrbitCameraPlugin;
 impl Plugin for OrbitCameraPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<OrbitCameraSettings>()
+            .init_resource::<ToneMappingSettings>()
             .add_systems(Startup, setup_camera)
             .add_systems(
                 Update,
-                (camera_input, apply_camera_transform.after(camera_input)),
+                (
+                    camera_input,
+                    follow_target.after(camera_input),
+                    apply_camera_transform.after(follow_target),
+                    apply_tone_mapping,
+                ),
             );
     }
 }
 
 fn setup_camera(mut commands: Commands, settings: Res<OrbitCameraSettings>) {
-    let mut transform = Transform::default();
-    transform.translation = settings.focus + Vec3::new(0.0, settings.radius * 0.4, settings.radius);
-    transform.look_at(settings.focus, Vec3::Y);
+    let transform = Transform::from_translation(
+        settings.focus + Vec3::new(0.0, settings.radius * 0.4, settings.radius),
+    )
+    .looking_at(settings.focus, Vec3::Y);
 
     commands.spawn((
         Camera3dBundle {
             transform,
             projection: Projection::Perspective(PerspectiveProjection {
-                fov: std::f32::consts::FRAC_PI_4,
+                fov: settings.fov(),
                 near: 0.1,
                 far: 5000.0,
                 ..Default::default()
@@ -65,6 +190,7 @@ fn setup_camera(mut commands: Commands, settings: Res<OrbitCameraSettings>) {
     ));
 }
 
+#[allow(clippy::too_many_arguments)]
 fn camera_input(
     time: Res<Time>,
     mut settings: ResMut<OrbitCameraSettings>,
@@ -72,22 +198,39 @@ fn camera_input(
     mut mouse_wheel_events: EventReader<MouseWheel>,
     mouse_buttons: Res<ButtonInput<MouseButton>>,
     keyboard: Res<ButtonInput<KeyCode>>,
+    selected: Res<SelectedGalaxy>,
+    galaxies: Query<(Entity, &Galaxy)>,
 ) {
     let delta_time = time.delta_seconds();
 
+    if keyboard.just_pressed(KeyCode::KeyU) {
+        settings.cycle_up_axis();
+    }
+    if keyboard.just_pressed(KeyCode::KeyL) {
+        if settings.follow.is_set() {
+            settings.follow.clear();
+        } else if let Some(entity) = selected
+            .0
+            .filter(|entity| galaxies.contains(*entity))
+            .or_else(|| galaxy_at(settings.focus, &galaxies))
+        {
+            settings.follow.set(entity);
+        }
+    }
+
     for ev in mouse_motion_events.read() {
         if mouse_buttons.pressed(MouseButton::Right) {
             settings.yaw -= ev.delta.x * settings.rotate_sensitivity;
             settings.pitch += ev.delta.y * settings.rotate_sensitivity;
-            settings.pitch = settings.pitch.clamp(-1.5, 1.5);
+            settings.pitch = settings.pitch.clamp(-PITCH_LIMIT, PITCH_LIMIT);
         }
 
         let panning = mouse_buttons.pressed(MouseButton::Middle)
             || (keyboard.pressed(KeyCode::ShiftLeft) && mouse_buttons.pressed(MouseButton::Left));
         if panning {
-            let yaw_rotation = Quat::from_rotation_y(settings.yaw);
+            let yaw_rotation = settings.pole_rotation() * Quat::from_rotation_y(settings.yaw);
             let right = yaw_rotation * Vec3::X;
-            let up = Vec3::Y;
+            let up = settings.up_axis;
             let pan_multiplier = settings.radius * settings.pan_sensitivity * delta_time * 60.0;
             settings.focus -= right * ev.delta.x * pan_multiplier;
             settings.focus += up * ev.delta.y * pan_multiplier;
@@ -97,21 +240,90 @@ fn camera_input(
     for ev in mouse_wheel_events.read() {
         let scroll_amount = ev.y + ev.x;
         settings.radius -= scroll_amount * settings.zoom_sensitivity;
-        settings.radius = settings.radius.clamp(2.0, 200.0);
+        settings.radius = settings.radius.clamp(ZOOM_RANGE.0, ZOOM_RANGE.1);
+    }
+}
+
+/// Galaxy whose halo contains `point`, nearest center first.
+fn galaxy_at(point: Vec3, galaxies: &Query<(Entity, &Galaxy)>) -> Option<Entity> {
+    galaxies
+        .iter()
+        .filter(|(_, galaxy)| galaxy.contains(point))
+        .min_by(|(_, a), (_, b)| {
+            a.center
+                .distance_squared(point)
+                .total_cmp(&b.center.distance_squared(point))
+        })
+        .map(|(entity, _)| entity)
+}
+
+/// Keep the focus on the followed entity, leaving follow mode once it is gone.
+pub fn follow_target(
+    mut settings: ResMut<OrbitCameraSettings>,
+    targets: Query<&Transform, Without<OrbitCamera>>,
+    toasts: Option<ResMut<Toasts>>,
+) {
+    if !settings.follow.is_set() {
+        return;
+    }
+    // Look up without flagging a change, so the camera only moves when the target does.
+    let Some(target) = settings
+        .bypass_change_detection()
+        .follow
+        .get(&targets)
+        .map(|transform| transform.translation)
+    else {
+        info!("Follow target is gone; leaving follow mode");
+        if let Some(mut toasts) = toasts {
+            toasts.push("Follow target is gone", ReportSeverity::Warning, TOAST_SECS);
+        }
+        settings.set_changed();
+        return;
+    };
+    if settings.focus != target {
+        settings.focus = target;
     }
 }
 
+/// Place the camera on its orbit and match its field of view to the zoom.
 fn apply_camera_transform(
     settings: Res<OrbitCameraSettings>,
-    mut query: Query<&mut Transform, With<OrbitCamera>>,
+    mut query: Query<(&mut Transform, &mut Projection), With<OrbitCamera>>,
 ) {
     if settings.is_changed() {
-        let rot = Quat::from_euler(EulerRot::YXZ, settings.yaw, settings.pitch, 0.0);
+        // Orbit in the Y-up frame, then tilt that frame onto the chosen pole.
+        let rot = settings.pole_rotation()
+            * Quat::from_euler(EulerRot::YXZ, settings.yaw, settings.pitch, 0.0);
         let dir = rot * Vec3::new(0.0, 0.0, 1.0);
         let focus = settings.focus;
-        for mut transform in query.iter_mut() {
+        let fov = settings.fov();
+        for (mut transform, mut projection) in query.iter_mut() {
             transform.translation = focus + dir * settings.radius;
-            transform.look_at(focus, Vec3::Y);
+            transform.look_at(focus, settings.up_axis);
+            if let Projection::Perspective(perspective) = projection.as_mut() {
+                if perspective.fov != fov {
+                    perspective.fov = fov;
+                }
+            }
         }
     }
 }
+
+/// Push HDR and tone-mapping changes onto the camera.
+fn apply_tone_mapping(
+    settings: Res<ToneMappingSettings>,
+    mut query: Query<(&mut Camera, &mut Tonemapping), With<OrbitCamera>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let tonemapping = if settings.hdr {
+        settings.curve.tonemapping()
+    } else {
+        Tonemapping::default()
+    };
+    for (mut camera, mut camera_tonemapping) in query.iter_mut() {
+        camera.hdr = settings.hdr;
+        *camera_tonemapping = tonemapping;
+    }
+}
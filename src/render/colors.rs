@@ -0,0 +1,92 @@
+//! Cell colors derived from the UA/UB locks, shared by setup and the seed view.
+//!
+//! Cells with equal locks get equal colors, which hides structure in regions
+//! of similar locks. The seed view can therefore rotate each cell's hue by a
+//! small offset hashed from its `grid_coords`: it depends only on the
+//! coordinates, never on the RNG seed or the physics, so a cell keeps its
+//! offset across runs and resets.
+
+use bevy::prelude::*;
+
+/// Largest hue rotation, in degrees, the per-cell offset applies either way.
+pub const SEED_HUE_OFFSET_DEGREES: f32 = 15.0;
+
+/// Base color for a cell's locks: UB runs green → red, and heavier UA lowers the blue.
+pub fn lock_color(ua: f64, ub: f64) -> Color {
+    let mass = (ua as f32).clamp(0.0, 2.0);
+    let geom = ((ub as f32) + 1.0) * 0.5; // map -1..1 to 0..1
+
+    let r = 0.2 + 0.6 * geom;
+    let g = 0.2 + 0.6 * (1.0 - geom);
+    let b = 0.4 + 0.5 * (1.0 - mass * 0.5);
+    Color::srgb(r.min(1.0), g.min(1.0), b.min(1.0))
+}
+
+/// Deterministic offset in `-1..=1` for a lattice site.
+///
+/// The coordinates are mixed by odd multipliers and a constant (so the
+/// origin is not pinned to an end of the range), then by the MurmurHash3
+/// finalizer, so adjacent sites land far apart.
+pub fn grid_hue_offset(grid_coords: UVec3) -> f32 {
+    let mut hash = 0x9e37_79b9
+        ^ grid_coords.x.wrapping_mul(0x8da6_b343)
+        ^ grid_coords.y.wrapping_mul(0xd816_3841)
+        ^ grid_coords.z.wrapping_mul(0xcb1a_b31f);
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2_ae35);
+    hash ^= hash >> 16;
+    hash as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+/// Seed-view color: [`lock_color`], hue-shifted by [`grid_hue_offset`] when `hue_offset` is on.
+pub fn seed_color(ua: f64, ub: f64, grid_coords: UVec3, hue_offset: bool) -> Color {
+    let color = lock_color(ua, ub);
+    if !hue_offset {
+        return color;
+    }
+    let degrees = grid_hue_offset(grid_coords) * SEED_HUE_OFFSET_DEGREES;
+    Color::Srgba(Hsla::from(color).rotate_hue(degrees).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_colors_match_the_old_formula_and_hue_offsets_are_stable_per_site() {
+        // Colors the per-file copies produced before they were merged.
+        for (ua, ub, expected) in [
+            (0.5, 0.0, Color::srgb(0.5, 0.5, 0.775)),
+            (3.0, 1.0, Color::srgb(0.8, 0.2, 0.4)),
+            (0.0, -1.0, Color::srgb(0.2, 0.8, 0.9)),
+        ] {
+            let color = lock_color(ua, ub).to_srgba();
+            let expected = expected.to_srgba();
+            for (got, want) in color.to_f32_array().iter().zip(expected.to_f32_array()) {
+                assert!((got - want).abs() < 1e-6, "locks ({ua}, {ub}): {color:?}");
+            }
+            assert_eq!(
+                seed_color(ua, ub, UVec3::new(3, 1, 4), false),
+                lock_color(ua, ub)
+            );
+        }
+
+        let site = UVec3::new(5, 2, 7);
+        let offset = grid_hue_offset(site);
+        assert_eq!(grid_hue_offset(site), offset);
+        assert_eq!(
+            seed_color(0.5, 0.0, site, true),
+            seed_color(0.5, 0.0, site, true)
+        );
+        let offsets: Vec<f32> = (0..8)
+            .map(|x| grid_hue_offset(UVec3::new(x, 0, 0)))
+            .collect();
+        assert!(offsets.iter().all(|offset| (-1.0..=1.0).contains(offset)));
+        assert!(
+            offsets.windows(2).all(|pair| pair[0] != pair[1]),
+            "neighbors share an offset: {offsets:?}"
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! Galaxy debug overlay: the selected galaxy's halo, region, and member cells.
+//!
+//! A galaxy is selected by clicking its row in the agents panel or by cycling
+//! with Tab (Shift+Tab backwards) in id order. While
+//! `VisualModeSettings::show_galaxy_debug` is on (F4), the selected galaxy's
+//! halo is drawn as a sphere of `Galaxy::radius` around its center, the
+//! coarse lattice region its `region_key` names is drawn as a box, and the
+//! cells the galaxy scan counts toward it glow with [`MEMBER_TINT`].
+//!
+//! The tint replaces a member's emissive after `update_cell_materials` has
+//! written the overlay colors; cells leaving the set, or the overlay being
+//! turned off, get their overlay emissive back.
+
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+
+use crate::app::{update_cell_materials, AppliedCellColor};
+use crate::astro::formation::{FormationSettings, GalaxyScanTask};
+use crate::astro::galaxy::{Galaxy, SelectedGalaxy};
+use crate::pru::cell::{DerivedFields, PruCell};
+use crate::pru::universe::PruUniverse;
+use crate::ui::controls::VisualModeSettings;
+
+/// Emissive given to the selected galaxy's member cells.
+pub const MEMBER_TINT: LinearRgba = LinearRgba::rgb(0.55, 0.2, 0.8);
+const HALO_COLOR: Color = Color::srgb(0.85, 0.45, 1.0);
+const REGION_COLOR: Color = Color::srgb(0.45, 0.8, 1.0);
+
+/// Selects galaxies and draws the galaxy debug overlay.
+pub struct GalaxyDebugPlugin;
+
+impl Plugin for GalaxyDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                galaxy_debug_input,
+                tint_selected_galaxy_cells
+                    .after(galaxy_debug_input)
+                    .after(update_cell_materials),
+                draw_selected_galaxy_bounds.after(galaxy_debug_input),
+            ),
+        );
+    }
+}
+
+/// World-space corners of the lattice region `key` covers with regions of `region_size` sites.
+///
+/// The box reaches half a spacing past the outermost sites, so neighboring
+/// regions share faces. Regions cut short by the lattice edge stop there.
+pub fn region_bounds(universe: &PruUniverse, key: UVec3, region_size: u32) -> (Vec3, Vec3) {
+    let last = universe.grid_dimensions.saturating_sub(UVec3::ONE);
+    let first_site = (key * region_size).min(last);
+    let last_site = ((key + UVec3::ONE) * region_size)
+        .saturating_sub(UVec3::ONE)
+        .min(last);
+    let half_step = Vec3::splat(0.5 * universe.spacing);
+    (
+        universe.rest_position(first_site) - half_step,
+        universe.rest_position(last_site) + half_step,
+    )
+}
+
+/// F4 toggles the overlay; Tab and Shift+Tab step the selection through the galaxies by id.
+pub fn galaxy_debug_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut modes: ResMut<VisualModeSettings>,
+    mut selected: ResMut<SelectedGalaxy>,
+    galaxies: Query<(Entity, &Galaxy)>,
+) {
+    if keys.just_pressed(KeyCode::F4) {
+        modes.show_galaxy_debug = !modes.show_galaxy_debug;
+    }
+    if !keys.just_pressed(KeyCode::Tab) {
+        return;
+    }
+    let mut ordered: Vec<(u32, Entity)> = galaxies
+        .iter()
+        .map(|(entity, galaxy)| (galaxy.id, entity))
+        .collect();
+    if ordered.is_empty() {
+        return;
+    }
+    ordered.sort_unstable();
+    let backwards = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let current = selected
+        .0
+        .and_then(|entity| ordered.iter().position(|(_, other)| *other == entity));
+    let next = match (current, backwards) {
+        (Some(index), false) => (index + 1) % ordered.len(),
+        (Some(index), true) => (index + ordered.len() - 1) % ordered.len(),
+        (None, false) => 0,
+        (None, true) => ordered.len() - 1,
+    };
+    let (id, entity) = ordered[next];
+    selected.0 = Some(entity);
+    info!("Selected Galaxy #{id}");
+}
+
+/// Tint the selected galaxy's member cells, restoring cells that stopped being members.
+#[allow(clippy::too_many_arguments)]
+pub fn tint_selected_galaxy_cells(
+    modes: Res<VisualModeSettings>,
+    selected: Res<SelectedGalaxy>,
+    settings: Res<FormationSettings>,
+    scan_task: Res<GalaxyScanTask>,
+    galaxies: Query<&Galaxy>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut tinted: Local<HashSet<Entity>>,
+    cells: Query<(
+        Entity,
+        &PruCell,
+        &DerivedFields,
+        &Handle<StandardMaterial>,
+        &AppliedCellColor,
+    )>,
+) {
+    let galaxy = selected
+        .0
+        .filter(|_| modes.show_galaxy_debug)
+        .and_then(|entity| galaxies.get(entity).ok());
+    let mut members = HashSet::new();
+    if let Some(galaxy) = galaxy {
+        let region_size = scan_task.region_size(&settings);
+        for (entity, cell, derived, material_handle, _) in cells.iter() {
+            if !galaxy.claims(
+                cell.grid_coords,
+                derived.local_density,
+                settings.galaxy_density_threshold,
+                region_size,
+            ) {
+                continue;
+            }
+            members.insert(entity);
+            let needs_tint = materials
+                .get(material_handle)
+                .is_some_and(|material| material.emissive != MEMBER_TINT);
+            if needs_tint {
+                if let Some(material) = materials.get_mut(material_handle) {
+                    material.emissive = MEMBER_TINT;
+                }
+            }
+        }
+    }
+
+    for entity in tinted.difference(&members) {
+        let Ok((_, _, _, material_handle, applied)) = cells.get(*entity) else {
+            continue;
+        };
+        if let Some(material) = materials.get_mut(material_handle) {
+            material.emissive = applied.emissive;
+        }
+    }
+    *tinted = members;
+}
+
+/// Draw the selected galaxy's halo sphere and region box.
+pub fn draw_selected_galaxy_bounds(
+    modes: Res<VisualModeSettings>,
+    selected: Res<SelectedGalaxy>,
+    universe: Option<Res<PruUniverse>>,
+    settings: Res<FormationSettings>,
+    scan_task: Res<GalaxyScanTask>,
+    galaxies: Query<&Galaxy>,
+    mut gizmos: Gizmos,
+) {
+    if !modes.show_galaxy_debug {
+        return;
+    }
+    let Some(galaxy) = selected.0.and_then(|entity| galaxies.get(entity).ok()) else {
+        return;
+    };
+    gizmos.sphere(galaxy.center, Quat::IDENTITY, galaxy.radius, HALO_COLOR);
+
+    let Some(universe) = universe else {
+        return;
+    };
+    let (min, max) = region_bounds(
+        &universe,
+        galaxy.region_key,
+        scan_task.region_size(&settings),
+    );
+    gizmos.cuboid(
+        Transform::from_translation((min + max) * 0.5).with_scale(max - min),
+        REGION_COLOR,
+    );
+}
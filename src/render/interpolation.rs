@@ -0,0 +1,247 @@
+//! Smooth rendering between fixed simulation ticks.
+//!
+//! Physics only moves bodies on tick boundaries, so at low time scales or
+//! while single-stepping motion looks steppy. With interpolation on, each
+//! body's `Transform` is drawn between its position before and after the
+//! latest gravity step, by the fraction of the next tick already accumulated.
+//!
+//! Stars and black holes keep their physics position in `Transform`, so the
+//! blended translation is only in place from `PostUpdate` until the next
+//! frame's `PreUpdate`, where the tick position is restored before any
+//! simulation system reads it.
+//!
+//! A body that wrapped through a periodic face is blended along its short
+//! path across the face, not back through the whole lattice.
+
+use bevy::prelude::*;
+
+use crate::app::SimulationState;
+use crate::pru::cell::{PruCell, PruDynamics};
+use crate::pru::universe::PruUniverse;
+
+/// Whether rendering interpolates between ticks or snaps to the latest one.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct RenderInterpolation {
+    pub enabled: bool,
+}
+
+/// A body's positions on either side of the latest gravity step.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct TickInterpolation {
+    /// Position before the latest gravity step, as its periodic image nearest `current`.
+    pub previous: Vec3,
+    /// Position after it.
+    pub current: Vec3,
+    /// Ticks the step covered; the blend spans all of them.
+    span: u32,
+    /// Translation last written for rendering, to be undone next frame.
+    rendered: Option<Vec3>,
+}
+
+impl TickInterpolation {
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            previous: position,
+            current: position,
+            span: 1,
+            rendered: None,
+        }
+    }
+
+    /// Rendered position with `fraction` of the next tick accumulated.
+    ///
+    /// A frame that ran several ticks blends across all of them, so the
+    /// result lags the latest tick by one tick less `fraction`.
+    pub fn blended(&self, fraction: f32) -> Vec3 {
+        let span = self.span.max(1) as f32;
+        let t = ((span - 1.0 + fraction.clamp(0.0, 1.0)) / span).clamp(0.0, 1.0);
+        self.previous.lerp(self.current, t)
+    }
+}
+
+/// Where the integrator keeps a body's position: the cell for lattice cells, else the transform.
+fn tick_position(cell: Option<&PruCell>, transform: &Transform) -> Vec3 {
+    cell.map_or(transform.translation, |cell| cell.position)
+}
+
+/// Put tick positions back before the simulation reads any `Transform`.
+///
+/// A translation that no longer matches what was rendered was moved by
+/// something else since, and is kept as the body's new position.
+pub fn restore_tick_positions(mut bodies: Query<(&mut TickInterpolation, &mut Transform)>) {
+    for (mut interpolation, mut transform) in bodies.iter_mut() {
+        let Some(rendered) = interpolation.rendered.take() else {
+            continue;
+        };
+        if transform.translation == rendered {
+            transform.translation = interpolation.current;
+        } else {
+            interpolation.previous = transform.translation;
+            interpolation.current = transform.translation;
+        }
+    }
+}
+
+/// Shift in the positions the gravity step just produced, and start tracking new bodies.
+///
+/// The position recorded after the previous step is where this one started,
+/// so it becomes `previous`, and the span is the ticks the clock moved since,
+/// the same ticks `overstep_fraction` counts toward. It is taken through the
+/// minimum image, so a body that wrapped across a periodic face starts from
+/// just outside the face it left through. A clock that jumped backwards (a
+/// reset or a rewind) snaps to the new position instead.
+#[allow(clippy::type_complexity)]
+pub fn record_tick_positions(
+    mut commands: Commands,
+    sim_state: Res<SimulationState>,
+    universe: Res<PruUniverse>,
+    mut recorded_at: Local<Option<(u64, u64)>>,
+    mut bodies: Query<(&mut TickInterpolation, Option<&PruCell>, &Transform)>,
+    untracked: Query<
+        (Entity, Option<&PruCell>, &Transform),
+        (With<PruDynamics>, Without<TickInterpolation>),
+    >,
+) {
+    let stamp = (sim_state.tick, sim_state.generation);
+    if *recorded_at != Some(stamp) {
+        let span = match *recorded_at {
+            Some((tick, generation))
+                if generation == sim_state.generation && tick < sim_state.tick =>
+            {
+                Some((sim_state.tick - tick).min(u32::MAX as u64) as u32)
+            }
+            _ => None,
+        };
+        for (mut interpolation, cell, transform) in bodies.iter_mut() {
+            let position = tick_position(cell, transform);
+            interpolation.previous = match span {
+                Some(_) => position - universe.minimum_image(position - interpolation.current),
+                None => position,
+            };
+            interpolation.current = position;
+            interpolation.span = span.unwrap_or(1);
+        }
+        *recorded_at = Some(stamp);
+    }
+    for (entity, cell, transform) in untracked.iter() {
+        commands
+            .entity(entity)
+            .insert(TickInterpolation::new(tick_position(cell, transform)));
+    }
+}
+
+/// Draw each body between its last two tick positions.
+pub fn interpolate_render_positions(
+    settings: Res<RenderInterpolation>,
+    sim_state: Res<SimulationState>,
+    fixed_time: Res<Time<Fixed>>,
+    mut bodies: Query<(&mut TickInterpolation, &mut Transform)>,
+) {
+    if !settings.enabled {
+        return;
+    }
+    let fraction = if sim_state.running {
+        fixed_time.overstep_fraction()
+    } else {
+        1.0
+    };
+    for (mut interpolation, mut transform) in bodies.iter_mut() {
+        let blended = interpolation.blended(fraction);
+        if transform.translation != blended {
+            transform.translation = blended;
+        }
+        interpolation.rendered = Some(blended);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+    use crate::pru::universe::{AxisBoundary, BoundaryCondition};
+
+    #[test]
+    fn half_a_tick_accumulated_draws_bodies_halfway() {
+        // Let the fixed clock accumulate half a timestep past its last tick.
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        let timestep = app.world().resource::<Time<Fixed>>().timestep();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep / 2));
+        app.update();
+        app.update();
+        let world = app.world_mut();
+        let fraction = world.resource::<Time<Fixed>>().overstep_fraction();
+        assert!((fraction - 0.5).abs() < 1e-4, "{fraction}");
+
+        world.init_resource::<SimulationState>();
+        world.insert_resource(PruUniverse::new(UVec3::splat(8), 1.0));
+        world.insert_resource(RenderInterpolation { enabled: true });
+        let (previous, current) = (Vec3::new(1.0, 2.0, 3.0), Vec3::new(2.0, 0.0, 3.5));
+        let body = world
+            .spawn((
+                PruDynamics::default(),
+                Transform::from_translation(previous),
+            ))
+            .id();
+        // Registered once so it remembers the tick it last recorded.
+        let record = world.register_system(record_tick_positions);
+        world.run_system(record).unwrap();
+
+        // One gravity step moves the body.
+        world.resource_mut::<SimulationState>().tick = 1;
+        world.get_mut::<Transform>(body).unwrap().translation = current;
+        world.run_system(record).unwrap();
+
+        world.run_system_once(interpolate_render_positions);
+        let rendered = world.get::<Transform>(body).unwrap().translation;
+        assert!(rendered.distance(previous.lerp(current, fraction)) < 1e-6);
+
+        // The next frame hands the simulation its tick position back.
+        world.run_system_once(restore_tick_positions);
+        assert_eq!(world.get::<Transform>(body).unwrap().translation, current);
+
+        // Snapping instead shows the latest tick as is.
+        world.resource_mut::<RenderInterpolation>().enabled = false;
+        world.run_system_once(interpolate_render_positions);
+        assert_eq!(world.get::<Transform>(body).unwrap().translation, current);
+    }
+
+    #[test]
+    fn bodies_wrapping_through_a_periodic_face_blend_across_it() {
+        let mut world = World::new();
+        world.init_resource::<SimulationState>();
+        world.insert_resource(PruUniverse::new(UVec3::splat(8), 1.0).with_boundary(
+            BoundaryCondition::Torus2D {
+                y: AxisBoundary::Open,
+            },
+        ));
+        let (previous, current) = (Vec3::new(7.8, 2.0, 3.0), Vec3::new(0.2, 2.5, 3.0));
+        let body = world
+            .spawn((
+                PruDynamics::default(),
+                Transform::from_translation(previous),
+            ))
+            .id();
+        let record = world.register_system(record_tick_positions);
+        world.run_system(record).unwrap();
+
+        // One step carries the body 0.4 along +x, through the face at x = 8.
+        world.resource_mut::<SimulationState>().tick = 1;
+        world.get_mut::<Transform>(body).unwrap().translation = current;
+        world.run_system(record).unwrap();
+
+        let interpolation = world.get::<TickInterpolation>(body).unwrap();
+        // Halfway, it sits on the face it crossed, not mid-lattice.
+        assert!(
+            interpolation
+                .blended(0.5)
+                .distance(Vec3::new(0.0, 2.25, 3.0))
+                < 1e-5
+        );
+        assert_eq!(interpolation.blended(1.0), current);
+        // The open y axis still blends straight.
+        assert!((interpolation.blended(0.25).y - 2.125).abs() < 1e-6);
+    }
+}
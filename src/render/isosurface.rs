@@ -0,0 +1,349 @@
+//! Translucent density isosurface showing the cosmic web as a continuous shape.
+//!
+//! Each cell's `local_density` is sampled at its `grid_coords` and the grid is
+//! contoured with marching cubes. Every cube is split into six tetrahedra
+//! around its main diagonal (the marching-tetrahedra variant), which needs only
+//! a handful of cases, has no ambiguous configurations, and matches up across
+//! neighboring cubes, so the surface is watertight. The grid is padded with a
+//! below-threshold shell so surfaces touching the lattice edge still close.
+//! Rebuilding is throttled to every `refresh_interval_ticks` ticks.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+
+use crate::app::SimulationState;
+use crate::pru::cell::{DerivedFields, PruCell};
+use crate::pru::universe::PruUniverse;
+
+/// Isosurface display options.
+#[derive(Resource, Clone, Copy)]
+pub struct IsosurfaceSettings {
+    pub enabled: bool,
+    /// Local density the surface is drawn at.
+    pub iso_level: f32,
+    /// Ticks between mesh rebuilds while the simulation runs.
+    pub refresh_interval_ticks: u64,
+}
+
+impl Default for IsosurfaceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            iso_level: 30.0,
+            refresh_interval_ticks: 30,
+        }
+    }
+}
+
+/// Marker for the entity carrying the isosurface mesh.
+#[derive(Component)]
+pub struct DensityIsosurface;
+
+/// Triangle mesh produced by [`extract_isosurface`].
+#[derive(Default, Clone)]
+pub struct IsosurfaceMesh {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    /// Triangles wound counter-clockwise seen from the low-density side.
+    pub indices: Vec<u32>,
+}
+
+impl IsosurfaceMesh {
+    pub fn into_mesh(self) -> Mesh {
+        let positions: Vec<[f32; 3]> = self.positions.iter().map(|p| p.to_array()).collect();
+        let normals: Vec<[f32; 3]> = self.normals.iter().map(|n| n.to_array()).collect();
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::RENDER_WORLD,
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_indices(Indices::U32(self.indices))
+    }
+}
+
+/// Cube corners as bit offsets: bit 0 = +x, bit 1 = +y, bit 2 = +z.
+const CUBE_CORNERS: [UVec3; 8] = [
+    UVec3::new(0, 0, 0),
+    UVec3::new(1, 0, 0),
+    UVec3::new(0, 1, 0),
+    UVec3::new(1, 1, 0),
+    UVec3::new(0, 0, 1),
+    UVec3::new(1, 0, 1),
+    UVec3::new(0, 1, 1),
+    UVec3::new(1, 1, 1),
+];
+
+/// The six tetrahedra sharing the cube diagonal 0→7, one per axis ordering.
+const CUBE_TETRAHEDRA: [[usize; 4]; 6] = [
+    [0, 1, 3, 7],
+    [0, 1, 5, 7],
+    [0, 2, 3, 7],
+    [0, 2, 6, 7],
+    [0, 4, 5, 7],
+    [0, 4, 6, 7],
+];
+
+/// Scalar grid padded by one below-threshold sample on every side.
+struct PaddedGrid {
+    dims: UVec3,
+    values: Vec<f32>,
+}
+
+impl PaddedGrid {
+    fn new(field: &[f32], dims: UVec3, iso_level: f32) -> Self {
+        let floor = field.iter().copied().fold(iso_level, f32::min) - 1.0;
+        let padded = dims + UVec3::splat(2);
+        let mut values = vec![floor; (padded.x * padded.y * padded.z) as usize];
+        for z in 0..dims.z {
+            for y in 0..dims.y {
+                for x in 0..dims.x {
+                    let source = (x + dims.x * (y + dims.y * z)) as usize;
+                    let target = (x + 1) + padded.x * ((y + 1) + padded.y * (z + 1));
+                    values[target as usize] = field[source];
+                }
+            }
+        }
+        Self {
+            dims: padded,
+            values,
+        }
+    }
+
+    fn index(&self, p: UVec3) -> u32 {
+        p.x + self.dims.x * (p.y + self.dims.y * p.z)
+    }
+
+    fn value(&self, p: UVec3) -> f32 {
+        self.values[self.index(p) as usize]
+    }
+
+    /// Central-difference gradient, one-sided at the edges, in grid units.
+    fn gradient(&self, p: UVec3) -> Vec3 {
+        let axis = |unit: UVec3, coord: u32, extent: u32| {
+            let lo = if coord > 0 { p - unit } else { p };
+            let hi = if coord + 1 < extent { p + unit } else { p };
+            let span = (hi - lo).max_element() as f32;
+            if span == 0.0 {
+                0.0
+            } else {
+                (self.value(hi) - self.value(lo)) / span
+            }
+        };
+        Vec3::new(
+            axis(UVec3::X, p.x, self.dims.x),
+            axis(UVec3::Y, p.y, self.dims.y),
+            axis(UVec3::Z, p.z, self.dims.z),
+        )
+    }
+}
+
+/// Contour `field` (x-fastest, `dims` samples) at `iso_level`.
+///
+/// Sample `(x, y, z)` sits at `origin + (x, y, z) * spacing`. Values above the
+/// level are inside the surface. Vertices are shared between triangles, and
+/// normals point down the density gradient, away from the overdensity.
+pub fn extract_isosurface(
+    field: &[f32],
+    dims: UVec3,
+    iso_level: f32,
+    origin: Vec3,
+    spacing: f32,
+) -> IsosurfaceMesh {
+    let mut mesh = IsosurfaceMesh::default();
+    if dims.cmpeq(UVec3::ZERO).any() || field.len() < (dims.x * dims.y * dims.z) as usize {
+        return mesh;
+    }
+
+    let grid = PaddedGrid::new(field, dims, iso_level);
+    let padded_origin = origin - Vec3::splat(spacing);
+    // One vertex per crossed grid edge, keyed by its endpoint indices.
+    let mut edge_vertices: HashMap<(u32, u32), u32> = HashMap::new();
+    let mut edge_vertex = |mesh: &mut IsosurfaceMesh, a: UVec3, b: UVec3| -> u32 {
+        let (ia, ib) = (grid.index(a), grid.index(b));
+        let key = (ia.min(ib), ia.max(ib));
+        *edge_vertices.entry(key).or_insert_with(|| {
+            let (va, vb) = (grid.value(a), grid.value(b));
+            let t = ((iso_level - va) / (vb - va)).clamp(0.0, 1.0);
+            let position = a.as_vec3().lerp(b.as_vec3(), t);
+            let gradient = grid.gradient(a).lerp(grid.gradient(b), t);
+            mesh.positions.push(padded_origin + position * spacing);
+            mesh.normals.push((-gradient).normalize_or_zero());
+            (mesh.positions.len() - 1) as u32
+        })
+    };
+
+    for z in 0..grid.dims.z - 1 {
+        for y in 0..grid.dims.y - 1 {
+            for x in 0..grid.dims.x - 1 {
+                let base = UVec3::new(x, y, z);
+                for tetrahedron in CUBE_TETRAHEDRA {
+                    let corners = tetrahedron.map(|corner| base + CUBE_CORNERS[corner]);
+                    let (inside, outside): (Vec<UVec3>, Vec<UVec3>) = corners
+                        .iter()
+                        .partition(|corner| grid.value(**corner) > iso_level);
+
+                    let polygon = match inside.len() {
+                        1 => vec![
+                            edge_vertex(&mut mesh, inside[0], outside[0]),
+                            edge_vertex(&mut mesh, inside[0], outside[1]),
+                            edge_vertex(&mut mesh, inside[0], outside[2]),
+                        ],
+                        2 => vec![
+                            edge_vertex(&mut mesh, inside[0], outside[0]),
+                            edge_vertex(&mut mesh, inside[0], outside[1]),
+                            edge_vertex(&mut mesh, inside[1], outside[1]),
+                            edge_vertex(&mut mesh, inside[1], outside[0]),
+                        ],
+                        3 => vec![
+                            edge_vertex(&mut mesh, outside[0], inside[0]),
+                            edge_vertex(&mut mesh, outside[0], inside[1]),
+                            edge_vertex(&mut mesh, outside[0], inside[2]),
+                        ],
+                        _ => continue,
+                    };
+
+                    // Wind triangles to face from the inside corners toward the outside ones.
+                    let centroid = |points: &[UVec3]| {
+                        points.iter().map(|p| p.as_vec3()).sum::<Vec3>() / points.len() as f32
+                    };
+                    let outward = centroid(&outside) - centroid(&inside);
+                    for fan in 1..polygon.len() - 1 {
+                        let (a, mut b, mut c) = (polygon[0], polygon[fan], polygon[fan + 1]);
+                        let [pa, pb, pc] = [a, b, c].map(|i| mesh.positions[i as usize]);
+                        if (pb - pa).cross(pc - pa).dot(outward) < 0.0 {
+                            std::mem::swap(&mut b, &mut c);
+                        }
+                        mesh.indices.extend([a, b, c]);
+                    }
+                }
+            }
+        }
+    }
+    mesh
+}
+
+/// Spawn the (initially hidden) isosurface entity with a translucent material.
+pub fn setup_isosurface(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(IsosurfaceMesh::default().into_mesh()),
+            material: materials.add(StandardMaterial {
+                base_color: Color::srgba(0.45, 0.7, 1.0, 0.25),
+                emissive: LinearRgba::rgb(0.05, 0.1, 0.2),
+                alpha_mode: AlphaMode::Blend,
+                double_sided: true,
+                cull_mode: None,
+                ..Default::default()
+            }),
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        },
+        DensityIsosurface,
+    ));
+}
+
+/// Rebuild the isosurface when it is shown, the level changes, or the refresh interval elapses.
+pub fn update_isosurface(
+    sim_state: Res<SimulationState>,
+    settings: Res<IsosurfaceSettings>,
+    universe: Option<Res<PruUniverse>>,
+    mut last_build_tick: Local<Option<u64>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    cells: Query<(&PruCell, &DerivedFields)>,
+    mut surface: Query<(&Handle<Mesh>, &mut Visibility), With<DensityIsosurface>>,
+) {
+    let Ok((mesh_handle, mut visibility)) = surface.get_single_mut() else {
+        return;
+    };
+    let target_visibility = if settings.enabled {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    if *visibility != target_visibility {
+        *visibility = target_visibility;
+    }
+    if !settings.enabled {
+        *last_build_tick = None;
+        return;
+    }
+    let Some(universe) = universe else {
+        return;
+    };
+
+    // Ticks running backwards means the world was reset.
+    let due = last_build_tick.is_none_or(|last| {
+        sim_state.tick < last || sim_state.tick - last >= settings.refresh_interval_ticks.max(1)
+    });
+    if !due && !settings.is_changed() {
+        return;
+    }
+    *last_build_tick = Some(sim_state.tick);
+
+    let dims = universe.grid_dimensions;
+    let mut field = vec![0.0; (dims.x * dims.y * dims.z) as usize];
+    for (cell, derived) in cells.iter() {
+        let c = cell.grid_coords.min(dims - UVec3::ONE);
+        field[(c.x + dims.x * (c.y + dims.y * c.z)) as usize] = derived.local_density;
+    }
+
+    let surface = extract_isosurface(
+        &field,
+        dims,
+        settings.iso_level,
+        -universe.center_offset(),
+        universe.spacing,
+    );
+    if let Some(mesh) = meshes.get_mut(mesh_handle) {
+        *mesh = surface.into_mesh();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_spherical_overdensity_yields_a_closed_surface_of_the_expected_radius() {
+        let dims = UVec3::splat(16);
+        let center = Vec3::splat(7.5);
+        let radius = 5.0;
+        let mut field = Vec::new();
+        for z in 0..dims.z {
+            for y in 0..dims.y {
+                for x in 0..dims.x {
+                    let r = UVec3::new(x, y, z).as_vec3().distance(center);
+                    field.push(radius - r);
+                }
+            }
+        }
+
+        let mesh = extract_isosurface(&field, dims, 0.0, Vec3::ZERO, 1.0);
+        assert!(!mesh.indices.is_empty());
+        for position in &mesh.positions {
+            let r = position.distance(center);
+            assert!((r - radius).abs() < 0.5, "vertex at radius {r}");
+        }
+
+        // Closed and consistently wound: every edge borders exactly two
+        // triangles, which traverse it in opposite directions.
+        let mut edges: HashMap<(u32, u32), (usize, i32)> = HashMap::new();
+        for triangle in mesh.indices.chunks(3) {
+            for (a, b) in [(0, 1), (1, 2), (2, 0)] {
+                let (a, b) = (triangle[a], triangle[b]);
+                let edge = edges.entry((a.min(b), a.max(b))).or_default();
+                edge.0 += 1;
+                edge.1 += if a < b { 1 } else { -1 };
+            }
+        }
+        assert!(edges.values().all(|&edge| edge == (2, 0)));
+    }
+}
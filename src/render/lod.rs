@@ -0,0 +1,217 @@
+use bevy::prelude::*;
+
+use crate::pru::cell::PruCell;
+use crate::render::camera::{OrbitCamera, OrbitCameraSettings};
+
+/// Tunables for distance-based level of detail.
+#[derive(Resource, Clone, Copy)]
+pub struct LodSettings {
+    /// Camera distance beyond which cells swap to the reduced mesh.
+    pub reduced_mesh_distance: f32,
+    /// Bodies whose projected diameter falls below this many pixels stop animating.
+    pub min_animated_pixels: f32,
+    /// Frames between per-entity camera distance refreshes.
+    pub refresh_interval: u32,
+    /// Relative change in orbit radius that forces an immediate refresh.
+    pub zoom_refresh_fraction: f32,
+}
+
+impl Default for LodSettings {
+    fn default() -> Self {
+        Self {
+            reduced_mesh_distance: 45.0,
+            min_animated_pixels: 1.5,
+            refresh_interval: 10,
+            zoom_refresh_fraction: 0.1,
+        }
+    }
+}
+
+/// Tessellation of the shared cell, star, and black hole spheres.
+///
+/// Read once when the meshes are built, at startup and on each universe
+/// reset. Each level is an icosphere; `High` matches Bevy's default sphere.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MeshQuality {
+    Low,
+    Medium,
+    #[default]
+    High,
+}
+
+impl MeshQuality {
+    pub fn label(self) -> &'static str {
+        match self {
+            MeshQuality::Low => "low",
+            MeshQuality::Medium => "medium",
+            MeshQuality::High => "high",
+        }
+    }
+
+    /// Icosphere subdivisions of the full-detail meshes.
+    pub fn subdivisions(self) -> usize {
+        match self {
+            MeshQuality::Low => 1,
+            MeshQuality::Medium => 3,
+            MeshQuality::High => 5,
+        }
+    }
+
+    /// Icosphere subdivisions of the reduced cell mesh used past `LodSettings::reduced_mesh_distance`.
+    pub fn reduced_subdivisions(self) -> usize {
+        match self {
+            MeshQuality::Low => 0,
+            MeshQuality::Medium | MeshQuality::High => 1,
+        }
+    }
+
+    /// Full-detail sphere of `radius`.
+    pub fn sphere(self, radius: f32) -> Mesh {
+        icosphere(radius, self.subdivisions())
+    }
+
+    /// Reduced-detail sphere of `radius`.
+    pub fn reduced_sphere(self, radius: f32) -> Mesh {
+        icosphere(radius, self.reduced_subdivisions())
+    }
+}
+
+impl std::str::FromStr for MeshQuality {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "low" => Ok(MeshQuality::Low),
+            "medium" => Ok(MeshQuality::Medium),
+            "high" => Ok(MeshQuality::High),
+            _ => Err("expected low, medium, or high".to_string()),
+        }
+    }
+}
+
+fn icosphere(radius: f32, subdivisions: usize) -> Mesh {
+    Sphere::new(radius)
+        .mesh()
+        .ico(subdivisions)
+        .expect("at most five subdivisions are always valid")
+}
+
+/// Shared cell meshes for each detail level.
+#[derive(Resource, Clone)]
+pub struct CellMeshes {
+    pub full: Handle<Mesh>,
+    pub reduced: Handle<Mesh>,
+}
+
+/// Cached per-entity detail decisions, refreshed every few frames.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct LodState {
+    /// Radius of the entity's mesh before its transform scale is applied.
+    pub base_radius: f32,
+    /// Camera distance at the last refresh.
+    pub camera_distance: f32,
+    /// Whether the reduced mesh is in use.
+    pub reduced: bool,
+    /// Whether the entity is large enough on screen to be worth animating.
+    pub animate: bool,
+}
+
+impl LodState {
+    pub fn new(base_radius: f32) -> Self {
+        Self {
+            base_radius,
+            camera_distance: 0.0,
+            reduced: false,
+            animate: true,
+        }
+    }
+}
+
+/// Refresh detail levels from the camera distance every `refresh_interval` frames.
+///
+/// Zooming past `zoom_refresh_fraction` of the last orbit radius refreshes
+/// immediately so detail returns as soon as the camera moves in.
+pub fn update_level_of_detail(
+    mut frames_since_refresh: Local<u32>,
+    mut refreshed_radius: Local<f32>,
+    settings: Res<LodSettings>,
+    camera_settings: Res<OrbitCameraSettings>,
+    cell_meshes: Option<Res<CellMeshes>>,
+    camera: Query<(&Transform, &Camera, &Projection), With<OrbitCamera>>,
+    mut bodies: Query<(&Transform, &mut LodState, &mut Handle<Mesh>, Has<PruCell>)>,
+) {
+    *frames_since_refresh += 1;
+    let zoomed = (camera_settings.radius - *refreshed_radius).abs()
+        > *refreshed_radius * settings.zoom_refresh_fraction;
+    if *frames_since_refresh < settings.refresh_interval && !zoomed {
+        return;
+    }
+
+    let Ok((camera_transform, camera, projection)) = camera.get_single() else {
+        return;
+    };
+    *frames_since_refresh = 0;
+    *refreshed_radius = camera_settings.radius;
+
+    // Pixels covered by one world unit at unit distance from the camera.
+    let viewport_height = camera
+        .logical_viewport_size()
+        .map(|size| size.y)
+        .unwrap_or(720.0);
+    let fov = match projection {
+        Projection::Perspective(perspective) => perspective.fov,
+        Projection::Orthographic(_) => std::f32::consts::FRAC_PI_4,
+    };
+    let pixels_per_unit = viewport_height / (2.0 * (fov * 0.5).tan());
+
+    for (transform, mut lod, mut mesh, is_cell) in bodies.iter_mut() {
+        let distance = (transform.translation - camera_transform.translation)
+            .length()
+            .max(0.001);
+        let diameter = 2.0 * lod.base_radius * transform.scale.max_element();
+        lod.camera_distance = distance;
+        lod.animate = diameter * pixels_per_unit / distance >= settings.min_animated_pixels;
+
+        // Only lattice cells share a mesh with a reduced variant.
+        let reduced = is_cell && distance > settings.reduced_mesh_distance;
+        if reduced != lod.reduced {
+            if let Some(cell_meshes) = cell_meshes.as_ref() {
+                *mesh = if reduced {
+                    cell_meshes.reduced.clone()
+                } else {
+                    cell_meshes.full.clone()
+                };
+                lod.reduced = reduced;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lower_mesh_quality_builds_sparser_spheres() {
+        // An icosphere with n subdivisions has 10 (n + 1)² + 2 vertices.
+        let expected = |subdivisions: usize| 10 * (subdivisions + 1).pow(2) + 2;
+        let qualities = [MeshQuality::Low, MeshQuality::Medium, MeshQuality::High];
+        for quality in qualities {
+            assert_eq!(
+                quality.sphere(0.12).count_vertices(),
+                expected(quality.subdivisions())
+            );
+            assert_eq!(
+                quality.reduced_sphere(0.12).count_vertices(),
+                expected(quality.reduced_subdivisions())
+            );
+        }
+
+        let [low, medium, high] = qualities.map(|quality| quality.sphere(0.12).count_vertices());
+        assert_eq!((low, medium, high), (42, 162, 362));
+        assert!(
+            high > 8 * low,
+            "low quality should cut most of the vertices"
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! Macro view: draw super-cells in place of the fine lattice.
+//!
+//! While `GravityParams::macro_block_size` is set, every lattice cell is
+//! hidden and each block is drawn as one sphere at its center of mass, sized
+//! so its volume follows the block's mass and colored by its mean cell mass
+//! on the density ramp. Blocks are rebuilt every frame from the rendered
+//! (interpolated) cell positions, so the view moves as smoothly as the cells
+//! would.
+
+use bevy::prelude::*;
+
+use crate::app::density_color;
+use crate::pru::cell::{PruCell, PruDynamics};
+use crate::pru::gravity::GravityParams;
+use crate::pru::supercell::aggregate_blocks;
+use crate::render::lod::CellMeshes;
+
+/// Marker for a super-cell sphere drawn in macro mode.
+#[derive(Component)]
+pub struct SuperCellVisual;
+
+/// Hide the lattice and draw its super-cells while macro mode is on.
+pub fn update_macro_view(
+    mut commands: Commands,
+    params: Res<GravityParams>,
+    cell_meshes: Option<Res<CellMeshes>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cells: Query<
+        (&PruCell, &PruDynamics, &Transform, &mut Visibility),
+        Without<SuperCellVisual>,
+    >,
+    mut visuals: Query<(Entity, &mut Transform, &Handle<StandardMaterial>), With<SuperCellVisual>>,
+) {
+    let cell_visibility = if params.macro_block_size.is_some() {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    };
+    for (_, _, _, mut visibility) in cells.iter_mut() {
+        if *visibility != cell_visibility {
+            *visibility = cell_visibility;
+        }
+    }
+
+    let (Some(block_size), Some(cell_meshes)) = (params.macro_block_size, cell_meshes) else {
+        for (entity, _, _) in visuals.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    };
+
+    let blocks = aggregate_blocks(
+        cells.iter().map(|(cell, dyn_state, transform, _)| {
+            (
+                cell.grid_coords,
+                transform.translation.as_dvec3(),
+                dyn_state.mass,
+            )
+        }),
+        block_size,
+    );
+    let total_mass: f32 = blocks.cells.iter().map(|block| block.mass).sum();
+    let mean_cell_mass = total_mass / blocks.membership.len().max(1) as f32;
+    if mean_cell_mass <= 0.0 {
+        return;
+    }
+
+    let mut existing = visuals.iter_mut();
+    for block in &blocks.cells {
+        let translation = block.position.as_vec3();
+        let scale = Vec3::splat((block.mass / mean_cell_mass).cbrt());
+        // A block averaging twice the mean cell mass saturates the ramp.
+        let color = density_color(block.mass / block.members as f32 / mean_cell_mass * 0.5);
+        match existing.next() {
+            Some((_, mut transform, material)) => {
+                transform.translation = translation;
+                transform.scale = scale;
+                // `get_mut` flags the asset for re-upload, so only take it for a new color.
+                let recolor = materials
+                    .get(material)
+                    .is_some_and(|material| material.base_color != color);
+                if recolor {
+                    if let Some(material) = materials.get_mut(material) {
+                        material.base_color = color;
+                    }
+                }
+            }
+            None => {
+                let material = materials.add(StandardMaterial {
+                    base_color: color,
+                    metallic: 0.05,
+                    perceptual_roughness: 0.7,
+                    ..Default::default()
+                });
+                commands.spawn((
+                    PbrBundle {
+                        mesh: cell_meshes.full.clone(),
+                        material,
+                        transform: Transform::from_translation(translation).with_scale(scale),
+                        ..Default::default()
+                    },
+                    SuperCellVisual,
+                    Name::new(format!(
+                        "Super-cell ({}, {}, {})",
+                        block.block.x, block.block.y, block.block.z
+                    )),
+                ));
+            }
+        }
+    }
+    for (entity, _, _) in existing {
+        commands.entity(entity).despawn();
+    }
+}
@@ -2,10 +2,24 @@
 
 use bevy::prelude::*;
 
+use crate::render::black_hole_lensing::BlackHoleLensingPlugin;
 use crate::render::camera::OrbitCameraPlugin;
+use crate::render::galaxy_debug::GalaxyDebugPlugin;
+use crate::render::streamlines::StreamlinePlugin;
+use crate::render::stress::StressVisualizationPlugin;
 use crate::render::visuals::SceneVisualsPlugin;
 
+pub mod animation;
+pub mod black_hole_lensing;
 pub mod camera;
+pub mod colors;
+pub mod galaxy_debug;
+pub mod interpolation;
+pub mod isosurface;
+pub mod lod;
+pub mod macro_view;
+pub mod streamlines;
+pub mod stress;
 pub mod visuals;
 
 /// Bundles all rendering-related plugins for the simulation.
@@ -13,6 +27,27 @@ pub struct RenderPlugin;
 
 impl Plugin for RenderPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((OrbitCameraPlugin, SceneVisualsPlugin));
+        app.init_resource::<lod::LodSettings>()
+            .init_resource::<lod::MeshQuality>()
+            .init_resource::<isosurface::IsosurfaceSettings>()
+            .add_plugins((
+                OrbitCameraPlugin,
+                SceneVisualsPlugin,
+                StressVisualizationPlugin,
+                StreamlinePlugin,
+                BlackHoleLensingPlugin,
+                GalaxyDebugPlugin,
+            ))
+            .add_systems(Startup, isosurface::setup_isosurface)
+            .add_systems(
+                Update,
+                (lod::update_level_of_detail, isosurface::update_isosurface),
+            )
+            .add_systems(
+                PostUpdate,
+                macro_view::update_macro_view
+                    .after(interpolation::interpolate_render_positions)
+                    .before(TransformSystem::TransformPropagate),
+            );
     }
 }
@@ -0,0 +1,283 @@
+//! Streamline overlay: paths traced through the lattice's velocity field.
+//!
+//! Each tick the cells' velocities are deposited onto the lattice's rest grid
+//! with cloud-in-cell (trilinear) weights, mass-weighted, giving a field that
+//! can be sampled anywhere inside the lattice with the same weights. From
+//! [`STREAMLINE_COUNT`] random seed points, `dx/dt = v(x)` is integrated with
+//! RK4 in steps of half a lattice spacing (in simulation time units) for up to
+//! [`STREAMLINE_MAX_STEPS`] steps, stopping where a path leaves the lattice or
+//! the flow stalls. The field changes every tick, so the seeds are redrawn
+//! each tick too and the lines show the instantaneous flow.
+//!
+//! All lines share one vertex-colored line-list mesh, blue at the seed and red
+//! at the tip, shown while `VisualModeSettings::show_streamlines` is on.
+
+use bevy::prelude::*;
+use bevy::render::mesh::PrimitiveTopology;
+use bevy::render::render_asset::RenderAssetUsages;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::app::SimulationState;
+use crate::pru::cell::{PruCell, PruDynamics};
+use crate::pru::universe::PruUniverse;
+use crate::ui::controls::VisualModeSettings;
+
+/// Streamlines traced per tick.
+pub const STREAMLINE_COUNT: usize = 50;
+/// Most RK4 steps per streamline.
+pub const STREAMLINE_MAX_STEPS: usize = 100;
+/// RK4 step, in lattice spacings per unit speed.
+const STREAMLINE_STEP_SPACINGS: f32 = 0.5;
+/// Speeds below this count as a stalled flow and end the line.
+const STREAMLINE_MIN_SPEED: f32 = 1e-6;
+
+const SEED_COLOR: Color = Color::srgb(0.2, 0.45, 1.0);
+const TIP_COLOR: Color = Color::srgb(1.0, 0.25, 0.2);
+
+/// Draws the streamline overlay when `VisualModeSettings::show_streamlines` is on.
+pub struct StreamlinePlugin;
+
+impl Plugin for StreamlinePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_streamlines)
+            .add_systems(Update, update_streamlines);
+    }
+}
+
+/// Marker for the entity carrying the streamline mesh.
+#[derive(Component)]
+pub struct StreamlineMesh;
+
+/// Mass-weighted velocity on the lattice's rest grid, deposited with CIC weights.
+#[derive(Clone, Debug)]
+pub struct VelocityGrid {
+    /// World position of grid node `(0, 0, 0)`.
+    origin: Vec3,
+    spacing: f32,
+    dims: UVec3,
+    velocities: Vec<Vec3>,
+}
+
+impl VelocityGrid {
+    /// Deposit `(position, velocity, mass)` bodies onto `universe`'s rest grid.
+    ///
+    /// Bodies outside the grid deposit onto its nearest edge cells.
+    pub fn deposit(
+        universe: &PruUniverse,
+        bodies: impl IntoIterator<Item = (Vec3, Vec3, f32)>,
+    ) -> Self {
+        let dims = universe.grid_dimensions;
+        let mut grid = Self {
+            origin: -universe.center_offset(),
+            spacing: universe.spacing,
+            dims,
+            velocities: vec![Vec3::ZERO; dims.element_product() as usize],
+        };
+        let mut masses = vec![0.0f32; grid.velocities.len()];
+        for (position, velocity, mass) in bodies {
+            let Some(weights) = grid.cic_weights(position, true) else {
+                continue;
+            };
+            for (index, weight) in weights {
+                grid.velocities[index] += velocity * (mass * weight);
+                masses[index] += mass * weight;
+            }
+        }
+        for (velocity, mass) in grid.velocities.iter_mut().zip(masses) {
+            *velocity = if mass > 0.0 {
+                *velocity / mass
+            } else {
+                Vec3::ZERO
+            };
+        }
+        grid
+    }
+
+    /// Velocity at `position`, or `None` outside the grid.
+    pub fn sample(&self, position: Vec3) -> Option<Vec3> {
+        self.cic_weights(position, false).map(|weights| {
+            weights
+                .iter()
+                .map(|(index, weight)| self.velocities[*index] * *weight)
+                .sum()
+        })
+    }
+
+    /// The grid's world-space corners.
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        let extent = self.dims.saturating_sub(UVec3::ONE).as_vec3() * self.spacing;
+        (self.origin, self.origin + extent)
+    }
+
+    /// The 8 nodes around `position` and their trilinear weights.
+    ///
+    /// Outside the grid, `clamp` moves the position to its edge; otherwise
+    /// there are no weights. A flat axis of one node puts all weight on it.
+    fn cic_weights(&self, position: Vec3, clamp: bool) -> Option<[(usize, f32); 8]> {
+        if self.velocities.is_empty() {
+            return None;
+        }
+        let max = self.dims.saturating_sub(UVec3::ONE).as_vec3();
+        let mut coords = (position - self.origin) / self.spacing;
+        if clamp {
+            coords = coords.clamp(Vec3::ZERO, max);
+        } else if coords.cmplt(Vec3::ZERO).any() || coords.cmpgt(max).any() {
+            return None;
+        }
+        let base = coords
+            .floor()
+            .min((max - Vec3::ONE).max(Vec3::ZERO))
+            .as_uvec3();
+        let frac = (coords - base.as_vec3()).clamp(Vec3::ZERO, Vec3::ONE);
+        let mut weights = [(0, 0.0); 8];
+        for (corner, slot) in weights.iter_mut().enumerate() {
+            let offset = UVec3::new(
+                corner as u32 & 1,
+                (corner as u32 >> 1) & 1,
+                corner as u32 >> 2,
+            );
+            let node = (base + offset).min(self.dims - UVec3::ONE);
+            let weight = Vec3::select(offset.cmpeq(UVec3::ONE), frac, Vec3::ONE - frac);
+            *slot = (
+                ((node.x * self.dims.y + node.y) * self.dims.z + node.z) as usize,
+                weight.element_product(),
+            );
+        }
+        Some(weights)
+    }
+}
+
+/// Trace `dx/dt = v(x)` from `seed` with RK4 steps of `h`, returning the visited points.
+///
+/// Stops after `max_steps`, or where the field is undefined or stalls.
+pub fn trace_streamline(grid: &VelocityGrid, seed: Vec3, h: f32, max_steps: usize) -> Vec<Vec3> {
+    let mut points = vec![seed];
+    let mut x = seed;
+    for _ in 0..max_steps {
+        let Some(k1) = grid.sample(x) else {
+            break;
+        };
+        if k1.length() < STREAMLINE_MIN_SPEED {
+            break;
+        }
+        let Some(k2) = grid.sample(x + k1 * (h * 0.5)) else {
+            break;
+        };
+        let Some(k3) = grid.sample(x + k2 * (h * 0.5)) else {
+            break;
+        };
+        let Some(k4) = grid.sample(x + k3 * h) else {
+            break;
+        };
+        x += (k1 + 2.0 * k2 + 2.0 * k3 + k4) * (h / 6.0);
+        if grid.sample(x).is_none() {
+            break;
+        }
+        points.push(x);
+    }
+    points
+}
+
+/// One line-list mesh of all `lines`, each colored from seed to tip over `max_steps`.
+pub fn streamline_mesh(lines: &[Vec<Vec3>], max_steps: usize) -> Mesh {
+    let segments: usize = lines.iter().map(|line| line.len().saturating_sub(1)).sum();
+    let mut positions: Vec<[f32; 3]> = Vec::with_capacity(segments * 2);
+    let mut colors: Vec<[f32; 4]> = Vec::with_capacity(segments * 2);
+    let color_at = |step: usize| {
+        let t = step as f32 / max_steps.max(1) as f32;
+        SEED_COLOR
+            .to_linear()
+            .mix(&TIP_COLOR.to_linear(), t)
+            .to_f32_array()
+    };
+    for line in lines {
+        for (step, pair) in line.windows(2).enumerate() {
+            positions.push(pair[0].to_array());
+            colors.push(color_at(step));
+            positions.push(pair[1].to_array());
+            colors.push(color_at(step + 1));
+        }
+    }
+
+    Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::RENDER_WORLD)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+}
+
+pub fn setup_streamlines(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(streamline_mesh(&[], STREAMLINE_MAX_STEPS)),
+            material: materials.add(StandardMaterial {
+                unlit: true,
+                ..Default::default()
+            }),
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        },
+        StreamlineMesh,
+        Name::new("Streamlines"),
+    ));
+}
+
+/// Re-seed and retrace the streamlines when the overlay is shown or the simulation advances.
+pub fn update_streamlines(
+    sim_state: Res<SimulationState>,
+    modes: Res<VisualModeSettings>,
+    universe: Option<Res<PruUniverse>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    cells: Query<(&PruCell, &PruDynamics)>,
+    mut overlay: Query<(&Handle<Mesh>, &mut Visibility), With<StreamlineMesh>>,
+) {
+    let Ok((mesh_handle, mut visibility)) = overlay.get_single_mut() else {
+        return;
+    };
+    let target_visibility = if modes.show_streamlines {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    if *visibility != target_visibility {
+        *visibility = target_visibility;
+    }
+    if !modes.show_streamlines || !(sim_state.advanced_this_frame() || modes.is_changed()) {
+        return;
+    }
+    let Some(universe) = universe else {
+        return;
+    };
+    let _span = info_span!("streamlines").entered();
+
+    let grid = VelocityGrid::deposit(
+        &universe,
+        cells.iter().map(|(cell, dynamics)| {
+            (
+                cell.position,
+                dynamics.velocity_at_integer_tick,
+                dynamics.mass,
+            )
+        }),
+    );
+    // Seeded by tick, so a paused frame redraws the same lines.
+    let mut rng = StdRng::seed_from_u64(sim_state.tick);
+    let (min, max) = grid.bounds();
+    let h = universe.spacing * STREAMLINE_STEP_SPACINGS;
+    let lines: Vec<Vec<Vec3>> = (0..STREAMLINE_COUNT)
+        .map(|_| {
+            let seed = Vec3::new(
+                rng.gen_range(min.x..=max.x),
+                rng.gen_range(min.y..=max.y),
+                rng.gen_range(min.z..=max.z),
+            );
+            trace_streamline(&grid, seed, h, STREAMLINE_MAX_STEPS)
+        })
+        .collect();
+    if let Some(mesh) = meshes.get_mut(mesh_handle) {
+        *mesh = streamline_mesh(&lines, STREAMLINE_MAX_STEPS);
+    }
+}
@@ -0,0 +1,252 @@
+//! Lattice stress overlay: the relational kernel's edges drawn as force-scaled struts.
+//!
+//! Edges come from the active kernel's
+//! [`GravityKernel::edges`](crate::pru::gravity_relational::GravityKernel::edges);
+//! kernels without a fixed stencil draw nothing. Each edge between two cells
+//! carries a force of magnitude `|weight| * m_a * m_b * G_eff`. Edges touching
+//! the heaviest cells are drawn as thin prisms whose radius is proportional to
+//! that force; edges whose end cells are closing on each other (compressive)
+//! are red, those drifting apart (tensile) blue. All struts share one mesh
+//! that is rebuilt whenever the simulation advances, so the overlay costs a
+//! single draw call.
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+
+use crate::app::SimulationState;
+use crate::pru::cell::{PruCell, PruDynamics};
+use crate::pru::gravity::GravityParams;
+use crate::pru::gravity_relational::ActiveGravityKernel;
+use crate::pru::universe::PruUniverse;
+use crate::ui::controls::VisualModeSettings;
+
+/// Only edges touching this many of the heaviest cells are drawn.
+pub const STRESS_MAX_CELLS: usize = 500;
+/// Strut radius for the strongest edge, in lattice spacings.
+const STRESS_MAX_RADIUS: f32 = 0.12;
+/// Thinnest strut radius, as a fraction of the strongest.
+const STRESS_MIN_RADIUS_FRACTION: f32 = 0.05;
+/// Sides of each strut's prism.
+const STRUT_SIDES: usize = 6;
+
+const COMPRESSIVE_COLOR: Color = Color::srgb(1.0, 0.25, 0.2);
+const TENSILE_COLOR: Color = Color::srgb(0.25, 0.45, 1.0);
+
+/// Draws the relational lattice stress overlay when `VisualModeSettings::show_lattice_stress` is on.
+pub struct StressVisualizationPlugin;
+
+impl Plugin for StressVisualizationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_lattice_stress)
+            .add_systems(Update, update_lattice_stress);
+    }
+}
+
+/// Marker for the entity carrying the stress strut mesh.
+#[derive(Component)]
+pub struct LatticeStressMesh;
+
+/// One kernel edge and the force it carries.
+#[derive(Clone, Copy, Debug)]
+pub struct StressEdge {
+    pub start: Vec3,
+    pub end: Vec3,
+    pub force: f32,
+    /// The end cells are approaching each other.
+    pub compressive: bool,
+}
+
+/// A cell's state as far as the stress overlay is concerned.
+#[derive(Clone, Copy, Debug)]
+pub struct StressCell {
+    pub grid_coords: UVec3,
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub mass: f32,
+}
+
+/// Kernel edges touching the `max_cells` heaviest cells, each listed once.
+///
+/// An edge across a periodic boundary is drawn from its heavy end
+/// along the minimum-image separation, so it leaves the lattice edge instead
+/// of spanning the whole lattice.
+pub fn lattice_stress_edges(
+    universe: &PruUniverse,
+    cells: &[StressCell],
+    kernel_edges: &[(IVec3, Vec3)],
+    g_effective: f32,
+    max_cells: usize,
+) -> Vec<StressEdge> {
+    let by_coords: HashMap<UVec3, usize> = cells
+        .iter()
+        .enumerate()
+        .map(|(index, cell)| (cell.grid_coords, index))
+        .collect();
+
+    let mut heaviest: Vec<usize> = (0..cells.len()).collect();
+    if heaviest.len() > max_cells {
+        heaviest
+            .select_nth_unstable_by(max_cells, |a, b| cells[*b].mass.total_cmp(&cells[*a].mass));
+        heaviest.truncate(max_cells);
+    }
+
+    let mut edges = Vec::new();
+    let mut seen: HashSet<(usize, usize)> = HashSet::new();
+    for a in heaviest {
+        let cell_a = &cells[a];
+        for (offset, weight) in kernel_edges {
+            let Some(coords) = universe.wrap_coords(cell_a.grid_coords.as_ivec3() + *offset) else {
+                continue;
+            };
+            let Some(&b) = by_coords.get(&coords) else {
+                continue;
+            };
+            if !seen.insert((a.min(b), a.max(b))) {
+                continue;
+            }
+            let cell_b = &cells[b];
+            let separation = universe.minimum_image(cell_b.position - cell_a.position);
+            let closing_rate = (cell_b.velocity - cell_a.velocity).dot(separation);
+            edges.push(StressEdge {
+                start: cell_a.position,
+                end: cell_a.position + separation,
+                force: weight.length() * cell_a.mass * cell_b.mass * g_effective,
+                compressive: closing_rate < 0.0,
+            });
+        }
+    }
+    edges
+}
+
+/// Build one mesh of vertex-colored prisms, the strongest edge `max_radius` thick.
+pub fn stress_mesh(edges: &[StressEdge], max_radius: f32) -> Mesh {
+    let max_force = edges
+        .iter()
+        .map(|edge| edge.force.abs())
+        .fold(0.0, f32::max);
+    let mut positions: Vec<[f32; 3]> = Vec::with_capacity(edges.len() * STRUT_SIDES * 2);
+    let mut normals: Vec<[f32; 3]> = Vec::with_capacity(positions.capacity());
+    let mut colors: Vec<[f32; 4]> = Vec::with_capacity(positions.capacity());
+    let mut indices: Vec<u32> = Vec::with_capacity(edges.len() * STRUT_SIDES * 6);
+
+    for edge in edges {
+        let axis = edge.end - edge.start;
+        let Some(direction) = axis.try_normalize() else {
+            continue;
+        };
+        let strength = if max_force > 0.0 {
+            edge.force.abs() / max_force
+        } else {
+            0.0
+        };
+        let radius = max_radius * strength.max(STRESS_MIN_RADIUS_FRACTION);
+        let color = if edge.compressive {
+            COMPRESSIVE_COLOR
+        } else {
+            TENSILE_COLOR
+        };
+        let color = color.to_linear().to_f32_array();
+        let (u, v) = direction.any_orthonormal_pair();
+
+        let base = positions.len() as u32;
+        for side in 0..STRUT_SIDES {
+            let angle = side as f32 / STRUT_SIDES as f32 * std::f32::consts::TAU;
+            let normal = u * angle.cos() + v * angle.sin();
+            for end in [edge.start, edge.end] {
+                positions.push((end + normal * radius).to_array());
+                normals.push(normal.to_array());
+                colors.push(color);
+            }
+        }
+        for side in 0..STRUT_SIDES as u32 {
+            let next = (side + 1) % STRUT_SIDES as u32;
+            let (a0, a1) = (base + side * 2, base + side * 2 + 1);
+            let (b0, b1) = (base + next * 2, base + next * 2 + 1);
+            indices.extend_from_slice(&[a0, b0, a1, a1, b0, b1]);
+        }
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+pub fn setup_lattice_stress(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(stress_mesh(&[], 0.0)),
+            material: materials.add(StandardMaterial {
+                unlit: true,
+                ..Default::default()
+            }),
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        },
+        LatticeStressMesh,
+        Name::new("Lattice Stress"),
+    ));
+}
+
+/// Rebuild the stress struts when the overlay is shown or the simulation advances.
+#[allow(clippy::too_many_arguments)]
+pub fn update_lattice_stress(
+    sim_state: Res<SimulationState>,
+    modes: Res<VisualModeSettings>,
+    gravity: Res<GravityParams>,
+    universe: Option<Res<PruUniverse>>,
+    kernel: Option<Res<ActiveGravityKernel>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    cells: Query<(&PruCell, &PruDynamics)>,
+    mut overlay: Query<(&Handle<Mesh>, &mut Visibility), With<LatticeStressMesh>>,
+) {
+    let Ok((mesh_handle, mut visibility)) = overlay.get_single_mut() else {
+        return;
+    };
+    let target_visibility = if modes.show_lattice_stress {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    if *visibility != target_visibility {
+        *visibility = target_visibility;
+    }
+    if !modes.show_lattice_stress || !(sim_state.advanced_this_frame() || modes.is_changed()) {
+        return;
+    }
+    let (Some(universe), Some(kernel)) = (universe, kernel) else {
+        return;
+    };
+    let _span = info_span!("lattice_stress").entered();
+
+    let cells: Vec<StressCell> = cells
+        .iter()
+        .map(|(cell, dynamics)| StressCell {
+            grid_coords: cell.grid_coords,
+            position: cell.position,
+            velocity: dynamics.velocity,
+            mass: dynamics.mass,
+        })
+        .collect();
+    let edges = lattice_stress_edges(
+        &universe,
+        &cells,
+        &kernel.0.edges(),
+        gravity.g_effective * gravity.relational_g_scale,
+        STRESS_MAX_CELLS,
+    );
+    if let Some(mesh) = meshes.get_mut(mesh_handle) {
+        *mesh = stress_mesh(&edges, STRESS_MAX_RADIUS * universe.spacing);
+    }
+}
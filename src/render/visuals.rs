@@ -1,11 +1,26 @@
+use std::collections::HashMap;
+
 use bevy::prelude::*;
 
+use crate::agents::analysis::{DensityFilaments, FilamentKind, GalaxyFilamentGraph};
+use crate::astro::galaxy::Galaxy;
+use crate::pru::cell::{DerivedFields, PruCell};
+use crate::pru::universe::PruUniverse;
+use crate::ui::controls::VisualModeSettings;
+
 /// Plugin that spawns default lighting and reference helpers for the scene.
 pub struct SceneVisualsPlugin;
 
 impl Plugin for SceneVisualsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_environment);
+        app.add_systems(Startup, setup_environment).add_systems(
+            Update,
+            (
+                draw_galaxy_filaments,
+                draw_density_filaments,
+                draw_ua_gradient_arrows,
+            ),
+        );
     }
 }
 
@@ -38,3 +53,111 @@ fn setup_environment(mut commands: Commands) {
         Name::new("Fill Light"),
     ));
 }
+
+/// Draw the galaxy MST, highlighting filaments over void-boundary edges.
+fn draw_galaxy_filaments(
+    graph: Res<GalaxyFilamentGraph>,
+    galaxies: Query<&Galaxy>,
+    mut gizmos: Gizmos,
+) {
+    if !graph.render_edges || graph.edges.is_empty() {
+        return;
+    }
+
+    let centers: HashMap<u32, Vec3> = galaxies.iter().map(|g| (g.id, g.center)).collect();
+    for &(a, b, length) in graph.edges.iter() {
+        let (Some(&start), Some(&end)) = (centers.get(&a), centers.get(&b)) else {
+            continue;
+        };
+        let color = match graph.edge_kind(length) {
+            FilamentKind::Filament => Color::srgba(0.55, 0.8, 1.0, 0.8),
+            FilamentKind::VoidBoundary => Color::srgba(0.5, 0.45, 0.6, 0.3),
+        };
+        gizmos.line(start, end, color);
+    }
+}
+
+/// Strands drawn around each density filament's axis to suggest a tube.
+const FILAMENT_TUBE_STRANDS: usize = 6;
+/// Tube radius, in world units, of the least dense filament.
+const FILAMENT_TUBE_RADIUS: f32 = 0.3;
+
+/// Draw density-confirmed filaments as faint tubes, alongside the MST edges.
+fn draw_density_filaments(
+    graph: Res<GalaxyFilamentGraph>,
+    filaments: Res<DensityFilaments>,
+    mut gizmos: Gizmos,
+) {
+    if !graph.render_edges || filaments.filaments.is_empty() {
+        return;
+    }
+
+    let color = Color::srgba(0.6, 0.85, 1.0, 0.25);
+    let weakest = filaments
+        .filaments
+        .iter()
+        .map(|filament| filament.mean_density)
+        .fold(f32::INFINITY, f32::min)
+        .max(f32::EPSILON);
+    for filament in filaments.filaments.iter() {
+        let Ok(axis) = Dir3::new(filament.end - filament.start) else {
+            continue;
+        };
+        let radius = FILAMENT_TUBE_RADIUS * (filament.mean_density / weakest).sqrt().min(2.0);
+        let (u, v) = axis.any_orthonormal_pair();
+        for strand in 0..FILAMENT_TUBE_STRANDS {
+            let angle = strand as f32 / FILAMENT_TUBE_STRANDS as f32 * std::f32::consts::TAU;
+            let offset = (u * angle.cos() + v * angle.sin()) * radius;
+            gizmos.line(filament.start + offset, filament.end + offset, color);
+        }
+        gizmos.circle(filament.start, axis, radius, color);
+        gizmos.circle(filament.end, axis, radius, color);
+    }
+}
+
+/// Only this many of the steepest UA gradients get an arrow.
+const UA_GRADIENT_MAX_ARROWS: usize = 400;
+/// Length of the steepest gradient's arrow, in lattice spacings.
+const UA_GRADIENT_ARROW_LENGTH: f32 = 0.8;
+
+/// Draw arrows along ∇UA on the cells where it is steepest.
+fn draw_ua_gradient_arrows(
+    modes: Res<VisualModeSettings>,
+    universe: Option<Res<PruUniverse>>,
+    cells: Query<(&PruCell, &DerivedFields)>,
+    mut gizmos: Gizmos,
+) {
+    if !modes.show_ua_gradient_arrows {
+        return;
+    }
+    let Some(universe) = universe else {
+        return;
+    };
+
+    let mut steepest: Vec<(Vec3, Vec3)> = cells
+        .iter()
+        .map(|(cell, derived)| (cell.position, derived.ua_gradient))
+        .collect();
+    if steepest.len() > UA_GRADIENT_MAX_ARROWS {
+        steepest.select_nth_unstable_by(UA_GRADIENT_MAX_ARROWS, |a, b| {
+            b.1.length_squared().total_cmp(&a.1.length_squared())
+        });
+        steepest.truncate(UA_GRADIENT_MAX_ARROWS);
+    }
+    let max = steepest
+        .iter()
+        .map(|(_, gradient)| gradient.length())
+        .fold(0.0, f32::max);
+    if max <= f32::EPSILON {
+        return;
+    }
+
+    let scale = UA_GRADIENT_ARROW_LENGTH * universe.spacing / max;
+    for (position, gradient) in steepest {
+        gizmos.arrow(
+            position,
+            position + gradient * scale,
+            Color::srgba(1.0, 0.8, 0.4, 0.7),
+        );
+    }
+}
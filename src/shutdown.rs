@@ -0,0 +1,199 @@
+//! Graceful shutdown: flush buffered output and summarize the run on exit.
+//!
+//! On the frame an [`AppExit`] is sent (closing the primary window sends one
+//! too), the Chrome trace from `--trace` is flushed and a [`FinalReport`] is
+//! logged: ticks run, peak density, structure counts, and the final energy
+//! drift. With `--final-report <path>`, the report and the retained agent
+//! reports are also written to that file. Anything not present in the world,
+//! such as the trace or the agent report log, is skipped.
+//!
+//! A process killed outright (SIGKILL, a crash) never reaches the exit
+//! frame, so nothing is written then.
+
+use std::fmt;
+use std::path::PathBuf;
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+
+use crate::agents::events::AstroReportLog;
+use crate::app::{arg_value, SimulationState};
+use crate::astro::formation::StructureCounts;
+use crate::pru::gravity::SimulationEnergy;
+use crate::pru::universe::FieldMetrics;
+
+/// Destination for the final report, parsed from the command line.
+#[derive(Resource, Clone, Debug)]
+pub struct FinalReportPath(pub PathBuf);
+
+/// Parse `--final-report <path>` or `--final-report=<path>` from the process arguments.
+pub fn final_report_path_from_args() -> Option<FinalReportPath> {
+    arg_value("--final-report").map(FinalReportPath)
+}
+
+/// Summary of a run, written once as the app exits.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FinalReport {
+    pub scenario_name: String,
+    pub ticks: u64,
+    pub simulation_time: f32,
+    /// Largest `FieldMetrics::max_density` seen since the last universe reset.
+    pub peak_density: f32,
+    pub stars: usize,
+    pub black_holes: usize,
+    pub galaxies: usize,
+    pub planets: usize,
+    /// `SimulationEnergy::relative_drift` at exit, if a baseline was captured.
+    pub energy_drift: Option<f64>,
+}
+
+impl fmt::Display for FinalReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Final report: {}", self.scenario_name)?;
+        writeln!(
+            f,
+            "  ticks: {} (t = {:.2})",
+            self.ticks, self.simulation_time
+        )?;
+        writeln!(f, "  peak density: {:.3}", self.peak_density)?;
+        writeln!(
+            f,
+            "  structures: {} stars, {} black holes, {} galaxies, {} planets",
+            self.stars, self.black_holes, self.galaxies, self.planets
+        )?;
+        match self.energy_drift {
+            Some(drift) => write!(f, "  energy drift: {:+.3}%", drift * 100.0),
+            None => write!(f, "  energy drift: n/a"),
+        }
+    }
+}
+
+/// Running totals the final report needs but no other resource keeps.
+#[derive(Resource, Default, Debug)]
+pub struct RunSummary {
+    pub peak_density: f32,
+    /// `SimulationState::generation` the totals belong to.
+    generation: u64,
+    /// The report written at exit, once it has been.
+    pub written: Option<FinalReport>,
+}
+
+/// Tracks the run and writes its final report on exit.
+pub struct ShutdownPlugin;
+
+impl Plugin for ShutdownPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunSummary>()
+            .add_systems(Update, track_run_summary)
+            .add_systems(Last, write_final_report);
+    }
+}
+
+/// Keep the peak density of the current universe.
+pub fn track_run_summary(
+    sim_state: Res<SimulationState>,
+    metrics: Res<FieldMetrics>,
+    mut summary: ResMut<RunSummary>,
+) {
+    if summary.generation != sim_state.generation {
+        summary.generation = sim_state.generation;
+        summary.peak_density = 0.0;
+    }
+    if metrics.max_density > summary.peak_density {
+        summary.peak_density = metrics.max_density;
+    }
+}
+
+/// On the first `AppExit`, flush the trace and log (and optionally save) the final report.
+#[allow(clippy::too_many_arguments)]
+pub fn write_final_report(
+    mut exits: EventReader<AppExit>,
+    sim_state: Res<SimulationState>,
+    mut summary: ResMut<RunSummary>,
+    counts: Option<Res<StructureCounts>>,
+    energy: Option<Res<SimulationEnergy>>,
+    reports: Option<Res<AstroReportLog>>,
+    path: Option<Res<FinalReportPath>>,
+    trace: Option<NonSend<tracing_chrome::FlushGuard>>,
+) {
+    if exits.read().last().is_none() || summary.written.is_some() {
+        return;
+    }
+
+    if let Some(trace) = trace {
+        trace.flush();
+    }
+
+    let counts = counts.as_deref();
+    let report = FinalReport {
+        scenario_name: sim_state.scenario_name.clone(),
+        ticks: sim_state.tick,
+        simulation_time: sim_state.simulation_time,
+        peak_density: summary.peak_density,
+        stars: counts.map_or(0, |counts| counts.stars),
+        black_holes: counts.map_or(0, |counts| counts.black_holes),
+        galaxies: counts.map_or(0, |counts| counts.galaxies),
+        planets: counts.map_or(0, |counts| counts.planets),
+        energy_drift: energy.and_then(|energy| energy.relative_drift),
+    };
+    for line in report.to_string().lines() {
+        info!("{line}");
+    }
+
+    if let Some(path) = path {
+        let mut text = format!("{report}\n");
+        if let Some(reports) = reports.filter(|reports| !reports.reports.is_empty()) {
+            text.push_str("\nReports:\n");
+            for entry in &reports.reports {
+                text.push_str(&format!(
+                    "  [{}] {:?}: {}\n",
+                    entry.tick, entry.severity, entry.summary
+                ));
+            }
+        }
+        match std::fs::write(&path.0, text) {
+            Ok(()) => info!("Final report written to {}", path.0.display()),
+            Err(err) => warn!(
+                "Could not write the final report to {}: {err}",
+                path.0.display()
+            ),
+        }
+    }
+    summary.written = Some(report);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn app_exit_writes_the_final_report() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<SimulationState>()
+            .init_resource::<FieldMetrics>()
+            .add_plugins(ShutdownPlugin);
+        app.update();
+        assert!(app.world().resource::<RunSummary>().written.is_none());
+
+        app.world_mut().send_event(AppExit::Success);
+        app.update();
+        assert!(app.world().resource::<RunSummary>().written.is_some());
+    }
+
+    #[test]
+    fn bare_world_without_trace_or_reports_still_writes() {
+        let mut world = World::new();
+        world.init_resource::<Events<AppExit>>();
+        world.init_resource::<SimulationState>();
+        world.init_resource::<RunSummary>();
+        world.send_event(AppExit::Success);
+
+        world.run_system_once(write_final_report);
+
+        let written = world.resource::<RunSummary>().written.clone();
+        assert_eq!(written.map(|report| report.ticks), Some(0));
+    }
+}
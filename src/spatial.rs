@@ -0,0 +1,298 @@
+//! Shared spatial index for proximity queries across systems.
+//!
+//! The octree is rebuilt once per simulation tick (and whenever new stars or
+//! black holes appear) from every cell, star, and black hole position, so
+//! formation and analysis systems can ask for neighbors without scanning
+//! every entity themselves.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use bevy::prelude::*;
+
+use crate::app::SimulationState;
+use crate::astro::black_hole::BlackHole;
+use crate::astro::star::Star;
+use crate::pru::cell::PruCell;
+
+/// Points stored in a leaf before it splits into eight children.
+const LEAF_CAPACITY: usize = 16;
+/// Depth limit guarding against endless splits of coincident points.
+const MAX_DEPTH: u32 = 12;
+
+struct OctreeNode {
+    center: Vec3,
+    half_extent: f32,
+    /// Index of the first of eight consecutive children, if split.
+    children: Option<u32>,
+    /// Indices into `SpatialOctree::points` held by this leaf.
+    items: Vec<u32>,
+}
+
+impl OctreeNode {
+    fn new(center: Vec3, half_extent: f32) -> Self {
+        Self {
+            center,
+            half_extent,
+            children: None,
+            items: Vec::new(),
+        }
+    }
+
+    /// Squared distance from `point` to the node's bounding cube (0 inside).
+    fn distance2_to(&self, point: Vec3) -> f32 {
+        let excess = ((point - self.center).abs() - Vec3::splat(self.half_extent)).max(Vec3::ZERO);
+        excess.length_squared()
+    }
+
+    fn octant(&self, point: Vec3) -> u32 {
+        (point.x >= self.center.x) as u32
+            | ((point.y >= self.center.y) as u32) << 1
+            | ((point.z >= self.center.z) as u32) << 2
+    }
+}
+
+/// Octree over entity positions, rebuilt by [`update_spatial_octree`].
+#[derive(Resource, Default)]
+pub struct SpatialOctree {
+    points: Vec<(Entity, Vec3)>,
+    nodes: Vec<OctreeNode>,
+    /// Tick the tree was last rebuilt on.
+    pub built_tick: Option<u64>,
+}
+
+impl SpatialOctree {
+    /// Rebuild the tree from scratch over `points`.
+    pub fn rebuild(&mut self, points: Vec<(Entity, Vec3)>) {
+        self.points = points;
+        self.nodes.clear();
+        if self.points.is_empty() {
+            return;
+        }
+
+        let (min, max) = self.points.iter().fold(
+            (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+            |(min, max), (_, p)| (min.min(*p), max.max(*p)),
+        );
+        let half_extent = ((max - min).max_element() * 0.5).max(0.5) * 1.01;
+        self.nodes
+            .push(OctreeNode::new((min + max) * 0.5, half_extent));
+
+        for index in 0..self.points.len() as u32 {
+            self.insert(index);
+        }
+    }
+
+    fn insert(&mut self, index: u32) {
+        let position = self.points[index as usize].1;
+        let mut node = 0usize;
+        let mut depth = 0;
+        loop {
+            if let Some(first_child) = self.nodes[node].children {
+                node = (first_child + self.nodes[node].octant(position)) as usize;
+                depth += 1;
+                continue;
+            }
+
+            self.nodes[node].items.push(index);
+            if self.nodes[node].items.len() > LEAF_CAPACITY && depth < MAX_DEPTH {
+                self.split(node);
+            }
+            return;
+        }
+    }
+
+    fn split(&mut self, node: usize) {
+        let center = self.nodes[node].center;
+        let quarter = self.nodes[node].half_extent * 0.5;
+        let first_child = self.nodes.len() as u32;
+        for octant in 0..8u32 {
+            let offset = Vec3::new(
+                if octant & 1 != 0 { quarter } else { -quarter },
+                if octant & 2 != 0 { quarter } else { -quarter },
+                if octant & 4 != 0 { quarter } else { -quarter },
+            );
+            self.nodes.push(OctreeNode::new(center + offset, quarter));
+        }
+
+        let items = std::mem::take(&mut self.nodes[node].items);
+        self.nodes[node].children = Some(first_child);
+        for index in items {
+            let position = self.points[index as usize].1;
+            let child = (first_child + self.nodes[node].octant(position)) as usize;
+            self.nodes[child].items.push(index);
+        }
+    }
+
+    /// Entities within `radius` of `center`.
+    pub fn query_radius(&self, center: Vec3, radius: f32) -> Vec<Entity> {
+        let mut found = Vec::new();
+        if self.nodes.is_empty() {
+            return found;
+        }
+
+        let radius2 = radius * radius;
+        let mut stack = vec![0usize];
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            if node.distance2_to(center) > radius2 {
+                continue;
+            }
+            match node.children {
+                Some(first_child) => {
+                    stack.extend((first_child..first_child + 8).map(|c| c as usize))
+                }
+                None => found.extend(node.items.iter().filter_map(|&i| {
+                    let (entity, position) = self.points[i as usize];
+                    (position.distance_squared(center) <= radius2).then_some(entity)
+                })),
+            }
+        }
+        found
+    }
+
+    /// The `k` entities closest to `center`, nearest first, with their distances.
+    pub fn k_nearest(&self, center: Vec3, k: usize) -> Vec<(Entity, f32)> {
+        if self.nodes.is_empty() || k == 0 {
+            return Vec::new();
+        }
+
+        // Best-first over nodes (min-heap by distance) with a max-heap of the best k points.
+        let mut frontier = BinaryHeap::new();
+        frontier.push(HeapEntry {
+            distance2: 0.0,
+            index: 0,
+        });
+        let mut best: BinaryHeap<HeapEntry> = BinaryHeap::with_capacity(k + 1);
+
+        while let Some(HeapEntry { distance2, index }) = frontier.pop() {
+            // `frontier` yields largest-first, so distances are stored negated.
+            if best.len() == k && -distance2 > best.peek().map_or(f32::MAX, |b| b.distance2) {
+                break;
+            }
+
+            let node = &self.nodes[index];
+            match node.children {
+                Some(first_child) => {
+                    for child in first_child..first_child + 8 {
+                        let child_distance2 = self.nodes[child as usize].distance2_to(center);
+                        frontier.push(HeapEntry {
+                            distance2: -child_distance2,
+                            index: child as usize,
+                        });
+                    }
+                }
+                None => {
+                    for &item in node.items.iter() {
+                        let item_distance2 = self.points[item as usize].1.distance_squared(center);
+                        best.push(HeapEntry {
+                            distance2: item_distance2,
+                            index: item as usize,
+                        });
+                        if best.len() > k {
+                            best.pop();
+                        }
+                    }
+                }
+            }
+        }
+
+        best.into_sorted_vec()
+            .into_iter()
+            .map(|entry| (self.points[entry.index].0, entry.distance2.sqrt()))
+            .collect()
+    }
+}
+
+/// Heap entry ordered by `distance2` alone.
+struct HeapEntry {
+    distance2: f32,
+    index: usize,
+}
+
+impl PartialEq for HeapEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for HeapEntry {}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance2.total_cmp(&other.distance2)
+    }
+}
+
+/// Rebuild the octree once per tick, or sooner when new structures spawn.
+#[allow(clippy::type_complexity)]
+pub fn update_spatial_octree(
+    sim_state: Res<SimulationState>,
+    mut octree: ResMut<SpatialOctree>,
+    cells: Query<(Entity, &PruCell)>,
+    structures: Query<(Entity, &Transform), Or<(With<Star>, With<BlackHole>)>>,
+    spawned: Query<(), Or<(Added<Star>, Added<BlackHole>)>>,
+) {
+    if octree.built_tick == Some(sim_state.tick) && spawned.is_empty() {
+        return;
+    }
+    octree.built_tick = Some(sim_state.tick);
+
+    let points = cells
+        .iter()
+        .map(|(entity, cell)| (entity, cell.position))
+        .chain(
+            structures
+                .iter()
+                .map(|(entity, transform)| (entity, transform.translation)),
+        )
+        .collect();
+    octree.rebuild(points);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn k_nearest_matches_a_brute_force_search() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(11);
+        let points: Vec<(Entity, Vec3)> = (0..500)
+            .map(|i| {
+                let position = Vec3::new(
+                    rng.gen_range(-8.0..8.0),
+                    rng.gen_range(-8.0..8.0),
+                    rng.gen_range(-2.0..2.0),
+                );
+                (Entity::from_raw(i), position)
+            })
+            .collect();
+        let mut octree = SpatialOctree::default();
+        octree.rebuild(points.clone());
+
+        for center in [
+            Vec3::ZERO,
+            Vec3::new(7.5, -7.5, 0.0),
+            Vec3::new(20.0, 0.0, 0.0),
+        ] {
+            let mut brute: Vec<(Entity, f32)> = points
+                .iter()
+                .map(|(entity, position)| (*entity, position.distance(center)))
+                .collect();
+            brute.sort_by(|a, b| a.1.total_cmp(&b.1));
+            for k in [1, 5, 40] {
+                assert_eq!(octree.k_nearest(center, k), brute[..k], "k={k} at {center}");
+            }
+        }
+        assert_eq!(octree.k_nearest(Vec3::ZERO, 900).len(), points.len());
+        assert!(SpatialOctree::default().k_nearest(Vec3::ZERO, 3).is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+//! Entity references that outlive a frame.
+//!
+//! Galaxies, stars, and black holes can be despawned between any two systems,
+//! so a bare `Entity` kept in a resource or component may point at nothing (or,
+//! after id reuse, at something else). Long-lived references go through
+//! [`TrackedEntity`] instead: every lookup goes through a query and clears the
+//! reference the first time the entity no longer matches, so callers only ever
+//! see `Some` for a live target and can fall back cleanly on `None`.
+
+use bevy::ecs::query::{QueryData, QueryFilter, ROQueryItem};
+use bevy::prelude::*;
+
+/// Optional entity reference that forgets its target once it is gone.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TrackedEntity(Option<Entity>);
+
+impl TrackedEntity {
+    pub fn set(&mut self, entity: Entity) {
+        self.0 = Some(entity);
+    }
+
+    pub fn clear(&mut self) {
+        self.0 = None;
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Read the target through `query`, clearing the reference if it no longer matches.
+    ///
+    /// A despawned entity and one that lost the queried components are treated
+    /// alike: either way it is no longer the thing that was being tracked.
+    pub fn get<'q, D: QueryData, F: QueryFilter>(
+        &mut self,
+        query: &'q Query<'_, '_, D, F>,
+    ) -> Option<ROQueryItem<'q, D>> {
+        let entity = self.0?;
+        let item = query.get(entity).ok();
+        if item.is_none() {
+            self.0 = None;
+        }
+        item
+    }
+
+    /// Mutable counterpart of [`TrackedEntity::get`].
+    pub fn get_mut<'q, D: QueryData, F: QueryFilter>(
+        &mut self,
+        query: &'q mut Query<'_, '_, D, F>,
+    ) -> Option<D::Item<'q>> {
+        let entity = self.0?;
+        let item = query.get_mut(entity).ok();
+        if item.is_none() {
+            self.0 = None;
+        }
+        item
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::agents::analysis::{analyze_agents, AnalysisSchedule};
+    use crate::agents::astro_agent::{AgentTelemetry, AstroAgent, AstroAgentKind};
+    use crate::agents::events::AstroReportLog;
+    use crate::app::SimulationState;
+    use crate::astro::formation::FormationSettings;
+    use crate::astro::galaxy::{Galaxy, StarGalaxyMembership};
+    use crate::astro::star::{Star, SOLAR_METALLICITY};
+    use crate::render::camera::{follow_target, OrbitCameraSettings};
+    use crate::spatial::SpatialOctree;
+
+    fn galaxy(id: u32) -> Galaxy {
+        Galaxy {
+            id,
+            total_mass: 100.0,
+            radius: 3.0,
+            num_stars: 1,
+            center: Vec3::ZERO,
+            region_key: UVec3::ZERO,
+        }
+    }
+
+    #[test]
+    fn the_camera_leaves_follow_mode_when_its_target_despawns() {
+        let mut world = World::new();
+        let target = world.spawn(Transform::from_xyz(1.0, 2.0, 3.0)).id();
+        let mut settings = OrbitCameraSettings::default();
+        settings.follow.set(target);
+        world.insert_resource(settings);
+
+        world.run_system_once(follow_target);
+        let settings = world.resource::<OrbitCameraSettings>();
+        assert_eq!(settings.focus, Vec3::new(1.0, 2.0, 3.0));
+        assert!(settings.follow.is_set());
+
+        world.despawn(target);
+        world.run_system_once(follow_target);
+        let settings = world.resource::<OrbitCameraSettings>();
+        assert!(!settings.follow.is_set());
+        assert_eq!(settings.focus, Vec3::new(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn analysis_skips_stars_whose_galaxy_despawned() {
+        let mut world = World::new();
+        world.insert_resource(SimulationState {
+            steps_this_frame: 1,
+            tick: 100,
+            ..Default::default()
+        });
+        world.init_resource::<FormationSettings>();
+        world.init_resource::<AnalysisSchedule>();
+        world.init_resource::<AstroReportLog>();
+        world.init_resource::<SpatialOctree>();
+
+        let survivor = world
+            .spawn((
+                galaxy(1),
+                AstroAgent::new(1, AstroAgentKind::GalaxyAgent),
+                AgentTelemetry::default(),
+            ))
+            .id();
+        let doomed = world
+            .spawn((
+                galaxy(2),
+                AstroAgent::new(2, AstroAgentKind::GalaxyAgent),
+                AgentTelemetry::default(),
+            ))
+            .id();
+        let star = Star {
+            mass: 1.0,
+            radius: 0.1,
+            temperature: 5000.0,
+            luminosity: 1.0,
+            metallicity: SOLAR_METALLICITY,
+        };
+        let members = [survivor, doomed].map(|galaxy| {
+            let mut membership = StarGalaxyMembership::default();
+            membership.galaxy.set(galaxy);
+            world.spawn((star.clone(), membership)).id()
+        });
+
+        world.despawn(doomed);
+        world.run_system_once(analyze_agents);
+
+        let membership =
+            |world: &World, entity| *world.get::<StarGalaxyMembership>(entity).unwrap();
+        let [kept, orphaned] = members.map(|entity| membership(&world, entity));
+        assert!(kept.galaxy.is_set());
+        assert!(!orphaned.galaxy.is_set());
+        assert!(!orphaned.stripped);
+        let telemetry = world.get::<AgentTelemetry>(survivor).unwrap();
+        assert_eq!(telemetry.last_star_count, 1);
+    }
+}
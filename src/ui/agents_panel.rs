@@ -1,15 +1,57 @@
+use std::collections::{HashMap, VecDeque};
+
 use bevy::prelude::*;
 
-use crate::agents::astro_agent::{AstroAgent, AstroAgentKind};
-use crate::agents::events::AstroReportLog;
-use crate::astro::galaxy::Galaxy;
+use crate::agents::analysis::CosmologyDistanceCalculator;
+use crate::agents::astro_agent::{AgentTelemetry, AstroAgent, AstroAgentKind};
+use crate::agents::events::{AstroReportLog, ReportSeverity};
+use crate::astro::black_hole::BlackHole;
+use crate::astro::formation::{FormationRateMonitor, FORMATION_RATE_BINS};
+use crate::astro::galaxy::{Galaxy, SelectedGalaxy};
+use crate::render::camera::{OrbitCamera, OrbitCameraSettings};
+
+/// Number of accretion samples rendered in a black hole agent's sparkline.
+const SPARKLINE_WIDTH: usize = 16;
+/// ASCII ramp used for text sparklines, lowest to highest.
+const SPARKLINE_LEVELS: [char; 8] = ['_', '.', ',', '-', '~', '=', '*', '#'];
+
+/// Column holding one [`AgentRow`] per agent.
+#[derive(Component)]
+pub struct AgentList;
 
+/// Text line summarizing a single agent; clicking a galaxy's row selects the galaxy.
 #[derive(Component)]
-pub struct AgentListText;
+pub struct AgentRow {
+    pub agent: Entity,
+}
+
+/// Shown in place of the rows while there are no agents.
+#[derive(Component)]
+pub struct AgentListPlaceholder;
 
 #[derive(Component)]
 pub struct AgentReportText;
 
+#[derive(Component)]
+pub struct AgentFocusText;
+
+#[derive(Component)]
+pub struct FormationRateText;
+
+/// One bar of the formation-rate chart; `index` 0 is the newest bin.
+#[derive(Component)]
+pub struct FormationRateBar {
+    pub index: usize,
+    pub black_holes: bool,
+}
+
+/// Tallest formation-rate bar in pixels.
+const FORMATION_BAR_MAX_HEIGHT: f32 = 48.0;
+const STAR_RATE_COLOR: Color = Color::srgb(0.35, 0.6, 1.0);
+const BLACK_HOLE_RATE_COLOR: Color = Color::srgb(1.0, 0.6, 0.2);
+const SELECTED_ROW_COLOR: Color = Color::srgba(0.55, 0.3, 0.8, 0.45);
+const HOVERED_ROW_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.08);
+
 pub fn setup_agent_panel(mut commands: Commands) {
     commands
         .spawn(NodeBundle {
@@ -37,83 +79,364 @@ pub fn setup_agent_panel(mut commands: Commands) {
             ));
 
             root.spawn((
-                TextBundle::from_sections([TextSection::new(
-                    "Agents loading...",
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                AgentList,
+            ))
+            .with_children(|list| {
+                list.spawn((
+                    TextBundle::from_section("No agents yet", agent_row_style()),
+                    AgentListPlaceholder,
+                ));
+            });
+
+            root.spawn((
+                TextBundle::from_section(
+                    "",
                     TextStyle {
-                        font_size: 14.0,
-                        color: Color::srgb(0.8, 0.85, 0.95),
+                        font_size: 13.0,
+                        color: Color::srgb(0.75, 0.85, 0.8),
                         ..Default::default()
                     },
-                )]),
-                AgentListText,
+                ),
+                AgentFocusText,
             ));
 
             root.spawn((
                 TextBundle::from_sections([TextSection::new(
                     "Recent Events",
                     TextStyle {
-                        font_size: 16.0,
+                        font_size: 13.0,
                         color: Color::srgb(0.85, 0.9, 1.0),
                         ..Default::default()
                     },
                 )]),
                 AgentReportText,
             ));
+
+            root.spawn((
+                TextBundle::from_section(
+                    "Formation history",
+                    TextStyle {
+                        font_size: 13.0,
+                        color: Color::srgb(0.85, 0.9, 1.0),
+                        ..Default::default()
+                    },
+                ),
+                FormationRateText,
+            ));
+
+            root.spawn(NodeBundle {
+                style: Style {
+                    height: Val::Px(FORMATION_BAR_MAX_HEIGHT + 4.0),
+                    align_items: AlignItems::FlexEnd,
+                    ..Default::default()
+                },
+                background_color: Color::srgba(0.02, 0.03, 0.05, 0.6).into(),
+                ..Default::default()
+            })
+            .with_children(|chart| {
+                // Oldest bin on the left; each bin is a blue star bar beside an orange black hole bar.
+                for index in (0..FORMATION_RATE_BINS).rev() {
+                    for (black_holes, color) in
+                        [(false, STAR_RATE_COLOR), (true, BLACK_HOLE_RATE_COLOR)]
+                    {
+                        chart.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Px(3.0),
+                                    height: Val::Px(0.0),
+                                    margin: UiRect::right(Val::Px(if black_holes {
+                                        1.0
+                                    } else {
+                                        0.0
+                                    })),
+                                    ..Default::default()
+                                },
+                                background_color: color.into(),
+                                ..Default::default()
+                            },
+                            FormationRateBar { index, black_holes },
+                        ));
+                    }
+                }
+            });
         });
 }
 
-pub fn update_agent_panel(
-    agents: Query<(&AstroAgent, Option<&Galaxy>)>,
-    reports: Res<AstroReportLog>,
-    mut list_text: Query<&mut Text, With<AgentListText>>,
-    mut report_text: Query<&mut Text, With<AgentReportText>>,
+/// Redraw the star/black hole formation-rate chart when a bin closes or fills.
+pub fn update_formation_rate_chart(
+    monitor: Res<FormationRateMonitor>,
+    mut label: Query<&mut Text, With<FormationRateText>>,
+    mut bars: Query<(&mut Style, &FormationRateBar)>,
 ) {
-    if let Ok(mut text) = list_text.get_single_mut() {
-        let mut lines = Vec::new();
-        for (agent, galaxy) in agents.iter() {
-            let summary = match agent.kind {
-                AstroAgentKind::GalaxyAgent => {
-                    if let Some(galaxy) = galaxy {
-                        format!(
-                            "#{} Galaxy mass {:.1}, stars {}, r={:.1}",
-                            galaxy.id, galaxy.total_mass, galaxy.num_stars, galaxy.radius
-                        )
-                    } else {
-                        format!("#{} Galaxy agent", agent.id)
-                    }
+    if !monitor.is_changed() {
+        return;
+    }
+
+    let peak = monitor
+        .star_rate_history
+        .iter()
+        .chain(monitor.bh_rate_history.iter())
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    for (mut style, bar) in bars.iter_mut() {
+        let history = if bar.black_holes {
+            &monitor.bh_rate_history
+        } else {
+            &monitor.star_rate_history
+        };
+        let count = history.iter().rev().nth(bar.index).copied().unwrap_or(0);
+        style.height = Val::Px(count as f32 / peak as f32 * FORMATION_BAR_MAX_HEIGHT);
+    }
+
+    if let Ok(mut text) = label.get_single_mut() {
+        text.sections[0].value = format!(
+            "Formation per {} ticks: stars {}, BHs {} (peak {})",
+            monitor.bin_width_ticks,
+            monitor.star_rate_history.back().copied().unwrap_or(0),
+            monitor.bh_rate_history.back().copied().unwrap_or(0),
+            peak
+        );
+    }
+}
+
+fn agent_row_style() -> TextStyle {
+    TextStyle {
+        font_size: 14.0,
+        color: Color::srgb(0.78, 0.84, 0.95),
+        ..Default::default()
+    }
+}
+
+/// One-line summary of an agent and the structure it watches.
+fn agent_summary(
+    agent: &AstroAgent,
+    galaxy: Option<&Galaxy>,
+    black_hole: Option<&BlackHole>,
+    telemetry: Option<&AgentTelemetry>,
+) -> String {
+    match agent.kind {
+        AstroAgentKind::GalaxyAgent => {
+            if let Some(galaxy) = galaxy {
+                let metallicity = telemetry.map(|t| t.mean_metallicity).unwrap_or(0.0);
+                let systems = telemetry.map(|t| t.planetary_systems).unwrap_or(0);
+                format!(
+                    "#{} Galaxy mass {:.1}, stars {}, systems {}, r={:.1}, Z={:.3}",
+                    galaxy.id,
+                    galaxy.total_mass,
+                    galaxy.num_stars,
+                    systems,
+                    galaxy.radius,
+                    metallicity
+                )
+            } else {
+                format!("#{} Galaxy agent", agent.id)
+            }
+        }
+        AstroAgentKind::BlackHoleAgent => match (black_hole, telemetry) {
+            (Some(bh), Some(telemetry)) => format!(
+                "#{} Black hole m={:.2} acc [{}]",
+                agent.id,
+                bh.mass,
+                sparkline(&telemetry.accretion_rate_history)
+            ),
+            _ => format!("#{} Black hole agent", agent.id),
+        },
+    }
+}
+
+/// Keep one text row per agent, rewriting a row only when its agent changed.
+///
+/// Rows are spawned for new agents and despawned with their agent, so a busy
+/// galaxy only re-lays out its own line; unchanged agents cost a change check.
+#[allow(clippy::type_complexity)]
+pub fn update_agent_list(
+    mut commands: Commands,
+    agents: Query<(
+        Entity,
+        Ref<AstroAgent>,
+        Option<Ref<Galaxy>>,
+        Option<Ref<BlackHole>>,
+        Option<Ref<AgentTelemetry>>,
+    )>,
+    list: Query<Entity, With<AgentList>>,
+    mut rows: Query<(Entity, &AgentRow, &mut Text)>,
+    mut placeholder: Query<&mut Style, With<AgentListPlaceholder>>,
+) {
+    let Ok(list) = list.get_single() else {
+        return;
+    };
+
+    let mut row_of: HashMap<Entity, Entity> = HashMap::new();
+    for (row, agent_row, _) in rows.iter() {
+        if agents.contains(agent_row.agent) {
+            row_of.insert(agent_row.agent, row);
+        } else {
+            commands.entity(row).despawn_recursive();
+        }
+    }
+
+    for (entity, agent, galaxy, black_hole, telemetry) in agents.iter() {
+        let changed = agent.is_changed()
+            || galaxy.as_ref().is_some_and(|g| g.is_changed())
+            || black_hole.as_ref().is_some_and(|bh| bh.is_changed())
+            || telemetry.as_ref().is_some_and(|t| t.is_changed());
+        let existing = row_of.get(&entity).copied();
+        if existing.is_some() && !changed {
+            continue;
+        }
+
+        let summary = agent_summary(
+            &agent,
+            galaxy.as_deref(),
+            black_hole.as_deref(),
+            telemetry.as_deref(),
+        );
+        match existing.and_then(|row| rows.get_mut(row).ok()) {
+            Some((_, _, mut text)) => {
+                if text.sections[0].value != summary {
+                    text.sections[0].value = summary;
                 }
-                AstroAgentKind::ClusterAgent => format!("#{} Cluster agent", agent.id),
-                AstroAgentKind::BlackHoleAgent => format!("#{} Black hole agent", agent.id),
-            };
-            lines.push(summary);
+            }
+            None => {
+                let row = commands
+                    .spawn((
+                        TextBundle::from_section(summary, agent_row_style()),
+                        Interaction::default(),
+                        AgentRow { agent: entity },
+                    ))
+                    .id();
+                commands.entity(list).add_child(row);
+            }
         }
+    }
 
-        if lines.is_empty() {
-            lines.push("No agents yet".to_string());
+    if let Ok(mut style) = placeholder.get_single_mut() {
+        let display = if agents.is_empty() {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if style.display != display {
+            style.display = display;
         }
+    }
+}
 
-        text.sections = vec![TextSection::new(
-            lines.join("\n"),
-            TextStyle {
-                font_size: 14.0,
-                color: Color::srgb(0.78, 0.84, 0.95),
-                ..Default::default()
-            },
-        )];
+/// Select (or deselect) a galaxy whose row was clicked and highlight the selected row.
+pub fn select_galaxy_from_agent_list(
+    mut selected: ResMut<SelectedGalaxy>,
+    galaxies: Query<(), With<Galaxy>>,
+    mut rows: Query<(&AgentRow, Ref<Interaction>, &mut BackgroundColor)>,
+) {
+    for (row, interaction, _) in rows.iter() {
+        if *interaction == Interaction::Pressed
+            && interaction.is_changed()
+            && galaxies.contains(row.agent)
+        {
+            selected.0 = if selected.0 == Some(row.agent) {
+                None
+            } else {
+                Some(row.agent)
+            };
+        }
+    }
+    for (row, interaction, mut color) in rows.iter_mut() {
+        // `update_ui_buttons` paints every hovered node; rows keep their own colors.
+        let fill = if selected.0 == Some(row.agent) {
+            SELECTED_ROW_COLOR
+        } else if *interaction != Interaction::None && galaxies.contains(row.agent) {
+            HOVERED_ROW_COLOR
+        } else {
+            Color::NONE
+        };
+        if color.0 != fill {
+            color.0 = fill;
+        }
+    }
+}
+
+/// Refresh the focused-galaxy line and, when the report log changes, the recent events.
+pub fn update_agent_panel(
+    galaxies: Query<&Galaxy, With<AstroAgent>>,
+    reports: Res<AstroReportLog>,
+    camera_settings: Res<OrbitCameraSettings>,
+    cosmology: Res<CosmologyDistanceCalculator>,
+    camera: Query<&Transform, With<OrbitCamera>>,
+    mut focus_text: Query<&mut Text, With<AgentFocusText>>,
+    mut report_text: Query<&mut Text, (With<AgentReportText>, Without<AgentFocusText>)>,
+) {
+    if let Ok(mut text) = focus_text.get_single_mut() {
+        // The galaxy containing the camera focus is the one being inspected.
+        let focused = galaxies
+            .iter()
+            .filter(|g| (g.center - camera_settings.focus).length() < g.radius)
+            .min_by(|a, b| {
+                let da = (a.center - camera_settings.focus).length_squared();
+                let db = (b.center - camera_settings.focus).length_squared();
+                da.total_cmp(&db)
+            });
+
+        let focus = match (focused, camera.get_single()) {
+            (Some(galaxy), Ok(camera_transform)) => {
+                let proper = (galaxy.center - camera_transform.translation).length();
+                let z = cosmology.redshift_for_distance(proper, cosmology.hubble);
+                let luminosity = cosmology.luminosity_distance(z, cosmology.hubble);
+                format!(
+                    "Focus: Galaxy #{} d={:.2} z={:.3} d_L={:.2}",
+                    galaxy.id, proper, z, luminosity
+                )
+            }
+            _ => String::new(),
+        };
+        if text.sections[0].value != focus {
+            text.sections[0].value = focus;
+        }
     }
 
+    if !reports.is_changed() {
+        return;
+    }
     if let Ok(mut text) = report_text.get_single_mut() {
         let mut lines = vec!["Recent Events".to_string()];
         for report in reports.reports.iter().rev().take(5) {
-            lines.push(format!("[{}] {}", report.tick, report.summary));
+            let marker = match report.severity {
+                ReportSeverity::Info => "",
+                ReportSeverity::Warning => "! ",
+                ReportSeverity::Critical => "!! ",
+            };
+            lines.push(format!("[{}] {}{}", report.tick, marker, report.summary));
         }
-        text.sections = vec![TextSection::new(
-            lines.join("\n"),
-            TextStyle {
-                font_size: 13.0,
-                color: Color::srgb(0.85, 0.9, 1.0),
-                ..Default::default()
-            },
-        )];
+        text.sections[0].value = lines.join("\n");
     }
 }
+
+/// Render the most recent samples as a fixed-width ASCII sparkline.
+fn sparkline(samples: &VecDeque<f32>) -> String {
+    let recent: Vec<f32> = samples
+        .iter()
+        .skip(samples.len().saturating_sub(SPARKLINE_WIDTH))
+        .copied()
+        .collect();
+    let max_sample = recent.iter().cloned().fold(0.0f32, f32::max);
+    if max_sample <= 0.0 {
+        return SPARKLINE_LEVELS[0].to_string().repeat(recent.len());
+    }
+
+    let top = (SPARKLINE_LEVELS.len() - 1) as f32;
+    recent
+        .iter()
+        .map(|sample| {
+            let level = ((sample / max_sample).clamp(0.0, 1.0) * top).round() as usize;
+            SPARKLINE_LEVELS[level]
+        })
+        .collect()
+}
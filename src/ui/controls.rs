@@ -1,10 +1,49 @@
+use std::collections::VecDeque;
+
 use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use bevy::window::PrimaryWindow;
+use serde::{Deserialize, Serialize};
 
-use crate::app::SimulationState;
-use crate::pru::gravity::{GravityMode, GravityParams, SimulationEnergy};
-use crate::pru::universe::{FieldMetrics, PruUniverse};
+use crate::agents::analysis::GalaxyFilamentGraph;
+use crate::agents::events::ReportSeverity;
+use crate::app::{
+    MaterialUpdateStats, ResetUniverse, SimulationState, CURVATURE_ABOVE_COLOR,
+    CURVATURE_BELOW_COLOR, DEFAULT_SCENARIO_NAME,
+};
+use crate::astro::formation::{
+    FormationActivity, FormationKind, FormationPreset, FormationSettings, StructureCounts,
+};
+use crate::astro::galaxy::Galaxy;
+use crate::breakpoints::{Breakpoint, BreakpointSettings};
+use crate::pru::cell::{PruCell, PruDynamics};
+use crate::pru::cmb::CmbSettings;
+use crate::pru::correlation::{CorrelationSettings, TwoPointCorrelation};
+use crate::pru::gravity::{
+    GravityMode, GravityParams, Pinned, SimulationEnergy, DEFAULT_MACRO_BLOCK_SIZE,
+    MACRO_BLOCK_RANGE,
+};
+use crate::pru::gravity_relational::{relational_g_scale, ActiveGravityKernel};
+use crate::pru::rewind::RewindBuffer;
+use crate::pru::separation::MinSeparation;
+use crate::pru::universe::{
+    FieldMetrics, FieldSettings, LatticeComplexity, PruUniverse, RegionOfInterest, RoiMetrics,
+};
+use crate::render::animation::AnimationSettings;
+use crate::render::camera::{OrbitCameraSettings, ToneMappingSettings};
+use crate::render::interpolation::RenderInterpolation;
+use crate::render::isosurface::IsosurfaceSettings;
+use crate::spatial::SpatialOctree;
+use crate::ui::toasts::{Toasts, TOAST_SECS};
+use crate::ui::view_settings::ResetViewButton;
 
 pub const DENSITY_BAR_COUNT: usize = 40;
+/// Height of an empty density bar, in pixels.
+const DENSITY_BAR_MIN_HEIGHT: f32 = 6.0;
+/// Height a full-scale sample adds on top of the empty bar, in pixels.
+const DENSITY_BAR_RANGE: f32 = 60.0;
+/// Vertical padding of the density history graph, in pixels.
+const DENSITY_GRAPH_PADDING: f32 = 6.0;
 
 #[derive(Component)]
 pub(crate) struct StatusText;
@@ -24,6 +63,9 @@ pub(crate) struct PauseLabel;
 #[derive(Component)]
 pub(crate) struct StepButton;
 
+#[derive(Component)]
+pub(crate) struct ResetButton;
+
 #[derive(Component)]
 pub(crate) struct SpeedButton {
     delta: f32,
@@ -41,6 +83,92 @@ pub(crate) struct CurvatureToggle;
 #[derive(Component)]
 pub(crate) struct CurvatureLabel;
 
+#[derive(Component)]
+pub(crate) struct VelocityToggle;
+
+#[derive(Component)]
+pub(crate) struct VelocityLabel;
+
+#[derive(Component)]
+pub(crate) struct AccelerationToggle;
+
+#[derive(Component)]
+pub(crate) struct AccelerationLabel;
+
+#[derive(Component)]
+pub(crate) struct CellScaleToggle;
+
+#[derive(Component)]
+pub(crate) struct CellScaleLabel;
+
+#[derive(Component)]
+pub(crate) struct OverlayRateToggle;
+
+#[derive(Component)]
+pub(crate) struct OverlayRateLabel;
+
+#[derive(Component)]
+pub(crate) struct OverlayScalingToggle;
+
+#[derive(Component)]
+pub(crate) struct OverlayScalingLabel;
+
+#[derive(Component)]
+pub(crate) struct OverlayBoundButton {
+    min_delta: f32,
+    max_delta: f32,
+}
+
+#[derive(Component)]
+pub(crate) struct OverlayRangeText;
+
+#[derive(Component)]
+pub(crate) struct SplitScreenToggle;
+
+#[derive(Component)]
+pub(crate) struct SplitScreenLabel;
+
+#[derive(Component)]
+pub(crate) struct SwapSidesButton;
+
+#[derive(Component)]
+pub(crate) struct SplitSideToggle;
+
+#[derive(Component)]
+pub(crate) struct SplitSideLabel;
+
+/// Names the overlay on each half while split screen is on.
+#[derive(Component)]
+pub(crate) struct SplitScreenText;
+
+#[derive(Component)]
+pub(crate) struct IsosurfaceToggle;
+
+#[derive(Component)]
+pub(crate) struct IsosurfaceLabel;
+
+#[derive(Component)]
+pub(crate) struct StreamlineToggle;
+
+#[derive(Component)]
+pub(crate) struct StreamlineLabel;
+
+#[derive(Component)]
+pub(crate) struct IsoLevelButton {
+    delta: f32,
+}
+
+#[derive(Component)]
+pub(crate) struct SmoothingKernelButton;
+
+#[derive(Component)]
+pub(crate) struct SmoothingKernelLabel;
+
+#[derive(Component)]
+pub(crate) struct SmoothingRadiusButton {
+    delta: f32,
+}
+
 #[derive(Component)]
 pub(crate) struct GravityToggle;
 
@@ -61,6 +189,15 @@ pub(crate) struct GravityAdjustButton {
     delta: f32,
 }
 
+#[derive(Component)]
+pub(crate) struct CalibrateGravityButton;
+
+#[derive(Component)]
+pub(crate) struct CmbToggle;
+
+#[derive(Component)]
+pub(crate) struct CmbLabel;
+
 #[derive(Component)]
 pub(crate) struct DampingAdjustButton {
     delta: f32,
@@ -71,11 +208,116 @@ pub(crate) struct SofteningAdjustButton {
     delta: f32,
 }
 
+#[derive(Component)]
+pub(crate) struct FormationIntervalButton {
+    kind: FormationKind,
+    delta: i64,
+}
+
+#[derive(Component)]
+pub(crate) struct FormationIntervalText;
+
+#[derive(Component)]
+pub(crate) struct FormationPresetButton;
+
+#[derive(Component)]
+pub(crate) struct FormationPresetLabel;
+
+/// Preset description, shown while the preset button is hovered.
+#[derive(Component)]
+pub(crate) struct FormationPresetTooltip;
+
+/// Cycles a breakpoint through off, once, and repeat.
+#[derive(Component)]
+pub(crate) struct BreakpointButton(Breakpoint);
+
+#[derive(Component)]
+pub(crate) struct BreakpointLabel(Breakpoint);
+
+/// Scrub track: clicking or dragging picks how far back to rewind, live at the right end.
+#[derive(Component)]
+pub(crate) struct RewindTrack;
+
+/// Part of the scrub track up to the shown tick.
+#[derive(Component)]
+pub(crate) struct RewindFill;
+
+#[derive(Component)]
+pub(crate) struct RewindLabel;
+
+/// Moves the rewind by `delta` ticks; `None` returns to live.
+#[derive(Component)]
+pub(crate) struct RewindStepButton {
+    delta: Option<i64>,
+}
+
 #[derive(Component)]
 pub(crate) struct DensityBar {
     pub index: usize,
 }
 
+/// Vertical scale of the density history graph: the sample drawn at full height.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct DensityHistoryScale {
+    pub max_sample: f32,
+}
+
+impl Default for DensityHistoryScale {
+    fn default() -> Self {
+        Self { max_sample: 1e-4 }
+    }
+}
+
+impl DensityHistoryScale {
+    /// `value` as a fraction of the graph height, clamped to `0..=1`.
+    pub fn normalized(&self, value: f32) -> f32 {
+        (value / self.max_sample).clamp(0.0, 1.0)
+    }
+
+    /// Height in pixels of a bar (or marker) for `value`.
+    pub fn bar_height(&self, value: f32) -> f32 {
+        DENSITY_BAR_MIN_HEIGHT + self.normalized(value) * DENSITY_BAR_RANGE
+    }
+}
+
+/// Formation threshold drawn across the density history graph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DensityThreshold {
+    Star,
+    Galaxy,
+}
+
+impl DensityThreshold {
+    fn value(self, settings: &FormationSettings) -> f32 {
+        match self {
+            DensityThreshold::Star => settings.star_density_threshold,
+            DensityThreshold::Galaxy => settings.galaxy_density_threshold,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            DensityThreshold::Star => "star",
+            DensityThreshold::Galaxy => "galaxy",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            DensityThreshold::Star => Color::srgba(1.0, 0.85, 0.4, 0.8),
+            DensityThreshold::Galaxy => Color::srgba(0.75, 0.5, 1.0, 0.8),
+        }
+    }
+}
+
+/// Horizontal line marking a formation threshold on the density history graph.
+#[derive(Component)]
+pub(crate) struct DensityThresholdMarker(DensityThreshold);
+
+/// Label of a [`DensityThresholdMarker`].
+#[derive(Component)]
+pub(crate) struct DensityThresholdLabel(DensityThreshold);
+
 #[derive(Resource, Clone)]
 pub(crate) struct UiColorScheme {
     normal: Color,
@@ -83,11 +325,254 @@ pub(crate) struct UiColorScheme {
     pressed: Color,
 }
 
+/// How an overlay maps its scalar field onto the color ramp.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum OverlayScaling {
+    /// User-set bounds that stay put across runs.
+    Fixed,
+    /// Track the current min/max of the field.
+    Adaptive,
+    /// Track the 5th–95th percentile of the field to resist outliers.
+    Percentile,
+}
+
+impl OverlayScaling {
+    pub fn next(self) -> Self {
+        match self {
+            OverlayScaling::Fixed => OverlayScaling::Adaptive,
+            OverlayScaling::Adaptive => OverlayScaling::Percentile,
+            OverlayScaling::Percentile => OverlayScaling::Fixed,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            OverlayScaling::Fixed => "Fixed",
+            OverlayScaling::Adaptive => "Adaptive",
+            OverlayScaling::Percentile => "Percentile",
+        }
+    }
+}
+
+/// Value range an overlay normalizes against.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct OverlayRange {
+    pub scaling: OverlayScaling,
+    pub fixed_min: f32,
+    pub fixed_max: f32,
+}
+
+impl OverlayRange {
+    pub fn fixed(min: f32, max: f32) -> Self {
+        Self {
+            scaling: OverlayScaling::Fixed,
+            fixed_min: min,
+            fixed_max: max,
+        }
+    }
+
+    /// Resolve the active bounds given the field's current extent and percentile range.
+    pub fn bounds(&self, extent: (f32, f32), percentiles: (f32, f32)) -> (f32, f32) {
+        match self.scaling {
+            OverlayScaling::Fixed => (self.fixed_min, self.fixed_max),
+            OverlayScaling::Adaptive => extent,
+            OverlayScaling::Percentile => percentiles,
+        }
+    }
+
+    /// Bounds centered on zero, for signed fields like curvature.
+    ///
+    /// Fixed ranges use the larger magnitude of their two ends; adaptive and
+    /// percentile ranges use the largest and 95th-percentile magnitudes.
+    pub fn symmetric_bounds(&self, max_abs: f32, abs_percentile: f32) -> (f32, f32) {
+        let extent = match self.scaling {
+            OverlayScaling::Fixed => self.fixed_min.abs().max(self.fixed_max.abs()),
+            OverlayScaling::Adaptive => max_abs,
+            OverlayScaling::Percentile => abs_percentile,
+        };
+        (-extent, extent)
+    }
+
+    /// Map `value` into 0..1 against `bounds`, collapsing degenerate ranges to 0.
+    pub fn normalize(value: f32, bounds: (f32, f32)) -> f32 {
+        let span = bounds.1 - bounds.0;
+        if span <= f32::EPSILON {
+            return 0.0;
+        }
+        ((value - bounds.0) / span).clamp(0.0, 1.0)
+    }
+
+    /// Nudge one of the manual bounds, keeping min strictly below max.
+    pub fn adjust(&mut self, min_delta: f32, max_delta: f32) {
+        self.fixed_min += min_delta;
+        self.fixed_max += max_delta;
+        if self.fixed_max - self.fixed_min < 0.05 {
+            if min_delta != 0.0 {
+                self.fixed_min = self.fixed_max - 0.05;
+            } else {
+                self.fixed_max = self.fixed_min + 0.05;
+            }
+        }
+    }
+}
+
+/// Which quantity drives the rendered size of each cell.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ScaleMode {
+    DerivedDensity,
+    UaMassLock,
+    Velocity,
+    Uniform,
+}
+
+impl ScaleMode {
+    pub fn next(self) -> Self {
+        match self {
+            ScaleMode::DerivedDensity => ScaleMode::UaMassLock,
+            ScaleMode::UaMassLock => ScaleMode::Velocity,
+            ScaleMode::Velocity => ScaleMode::Uniform,
+            ScaleMode::Uniform => ScaleMode::DerivedDensity,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ScaleMode::DerivedDensity => "Density",
+            ScaleMode::UaMassLock => "UA Lock",
+            ScaleMode::Velocity => "Velocity",
+            ScaleMode::Uniform => "Uniform",
+        }
+    }
+}
+
+/// Which overlay currently colors the cells.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VisualMode {
+    Density,
+    Curvature,
+    Velocity,
+    /// Cells colored by `log10 |a|` of the solver's acceleration.
+    Acceleration,
+    Binding,
+    /// Cells colored by the magnitude of the UA lock gradient.
+    UaGradient,
+    /// No overlay: cells show their lock-seeded colors (IGM cells stay translucent).
+    Seed,
+}
+
+impl VisualMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            VisualMode::Density => "Density",
+            VisualMode::Curvature => "Curvature",
+            VisualMode::Velocity => "Velocity",
+            VisualMode::Acceleration => "Acceleration",
+            VisualMode::Binding => "Binding",
+            VisualMode::UaGradient => "UA Gradient",
+            VisualMode::Seed => "Seed",
+        }
+    }
+}
+
+/// Half of the split screen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SplitSide {
+    #[default]
+    Left,
+    Right,
+}
+
+/// Two overlays at once: cells left of the screen's vertical center line
+/// use `left_mode`, cells right of it `right_mode`.
+///
+/// While it is on, the overlay buttons and keys change the `editing` side's
+/// overlay. Turning it on puts the active overlay on that side.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct SplitScreenMode {
+    pub enabled: bool,
+    pub left_mode: VisualMode,
+    pub right_mode: VisualMode,
+    pub editing: SplitSide,
+}
+
+impl Default for SplitScreenMode {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            left_mode: VisualMode::Density,
+            right_mode: VisualMode::Velocity,
+            editing: SplitSide::Left,
+        }
+    }
+}
+
+impl SplitScreenMode {
+    pub fn swap(&mut self) {
+        std::mem::swap(&mut self.left_mode, &mut self.right_mode);
+    }
+
+    /// Overlay on the side the controls edit.
+    pub fn editing_mode(&self) -> VisualMode {
+        match self.editing {
+            SplitSide::Left => self.left_mode,
+            SplitSide::Right => self.right_mode,
+        }
+    }
+
+    pub fn set_editing_mode(&mut self, mode: VisualMode) {
+        match self.editing {
+            SplitSide::Left => self.left_mode = mode,
+            SplitSide::Right => self.right_mode = mode,
+        }
+    }
+
+    /// Overlays on the left and right halves; both are `active` while split screen is off.
+    pub fn modes(&self, active: VisualMode) -> (VisualMode, VisualMode) {
+        if self.enabled {
+            (self.left_mode, self.right_mode)
+        } else {
+            (active, active)
+        }
+    }
+}
+
 /// Visualization toggles for scalar overlays.
-#[derive(Resource, Clone, Copy)]
+#[derive(Resource, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct VisualModeSettings {
     pub show_density_coloring: bool,
     pub show_curvature_coloring: bool,
+    pub show_velocity_coloring: bool,
+    /// Color cells by `log10 |a|` (see `log_acceleration`), exposing the gravity solver's forces.
+    pub show_acceleration_coloring: bool,
+    /// Color cells by whether they are bound to the lattice (see `SimulationEnergy::is_bound`).
+    pub show_binding_coloring: bool,
+    /// Color cells by |∇UA| (see `DerivedFields::ua_gradient`).
+    pub show_ua_gradient_coloring: bool,
+    /// Draw an arrow along ∇UA on the cells with the steepest gradients.
+    pub show_ua_gradient_arrows: bool,
+    /// Draw the relational kernel's edges around the heaviest cells, sized by force.
+    pub show_lattice_stress: bool,
+    /// Trace streamlines through the lattice's velocity field (see `render::streamlines`).
+    pub show_streamlines: bool,
+    /// Draw the selected galaxy's halo and region and tint its cells (see `render::galaxy_debug`).
+    pub show_galaxy_debug: bool,
+    /// Shift each cell's seed-view hue by a small offset hashed from its grid coordinates.
+    pub seed_hue_offset: bool,
+    pub density_range: OverlayRange,
+    pub curvature_range: OverlayRange,
+    pub velocity_range: OverlayRange,
+    /// Bounds in `log10 |a|`.
+    pub acceleration_range: OverlayRange,
+    pub ua_gradient_range: OverlayRange,
+    pub scale_mode: ScaleMode,
+    /// Cell materials refresh over this many frames (1 = every cell every frame).
+    pub overlay_update_slices: u32,
+    /// Overlay switches cross-fade at this rate (fraction of the fade per second).
+    pub transition_speed: f32,
+    /// Progress of the current overlay cross-fade, 0 at the switch and 1 once settled.
+    #[serde(skip)]
+    pub color_blend_alpha: f32,
 }
 
 impl Default for VisualModeSettings {
@@ -95,15 +580,86 @@ impl Default for VisualModeSettings {
         Self {
             show_density_coloring: true,
             show_curvature_coloring: false,
+            show_velocity_coloring: false,
+            show_acceleration_coloring: false,
+            show_binding_coloring: false,
+            show_ua_gradient_coloring: false,
+            show_ua_gradient_arrows: false,
+            show_lattice_stress: false,
+            show_streamlines: false,
+            show_galaxy_debug: false,
+            seed_hue_offset: false,
+            density_range: OverlayRange::fixed(0.0, 3.5),
+            curvature_range: OverlayRange::fixed(-1.25, 1.25),
+            velocity_range: OverlayRange::fixed(0.0, 1.0),
+            acceleration_range: OverlayRange {
+                scaling: OverlayScaling::Percentile,
+                ..OverlayRange::fixed(-3.0, 1.0)
+            },
+            ua_gradient_range: OverlayRange {
+                scaling: OverlayScaling::Percentile,
+                ..OverlayRange::fixed(0.0, 1.0)
+            },
+            scale_mode: ScaleMode::DerivedDensity,
+            overlay_update_slices: 4,
+            transition_speed: 3.0,
+            color_blend_alpha: 1.0,
         }
     }
 }
 
 impl VisualModeSettings {
+    pub fn active_mode(&self) -> VisualMode {
+        if self.show_density_coloring {
+            VisualMode::Density
+        } else if self.show_curvature_coloring {
+            VisualMode::Curvature
+        } else if self.show_velocity_coloring {
+            VisualMode::Velocity
+        } else if self.show_acceleration_coloring {
+            VisualMode::Acceleration
+        } else if self.show_binding_coloring {
+            VisualMode::Binding
+        } else if self.show_ua_gradient_coloring {
+            VisualMode::UaGradient
+        } else {
+            VisualMode::Seed
+        }
+    }
+
+    /// Range settings for the overlay the range controls currently act on.
+    pub fn active_range_mut(&mut self) -> &mut OverlayRange {
+        if self.show_curvature_coloring {
+            &mut self.curvature_range
+        } else if self.show_velocity_coloring {
+            &mut self.velocity_range
+        } else if self.show_acceleration_coloring {
+            &mut self.acceleration_range
+        } else if self.show_ua_gradient_coloring {
+            &mut self.ua_gradient_range
+        } else {
+            &mut self.density_range
+        }
+    }
+
+    /// Show `mode`, turning every other overlay off.
+    pub fn show_mode(&mut self, mode: VisualMode) {
+        self.show_density_coloring = mode == VisualMode::Density;
+        self.show_curvature_coloring = mode == VisualMode::Curvature;
+        self.show_velocity_coloring = mode == VisualMode::Velocity;
+        self.show_acceleration_coloring = mode == VisualMode::Acceleration;
+        self.show_binding_coloring = mode == VisualMode::Binding;
+        self.show_ua_gradient_coloring = mode == VisualMode::UaGradient;
+    }
+
     pub fn toggle_density(&mut self) {
         self.show_density_coloring = !self.show_density_coloring;
         if self.show_density_coloring {
             self.show_curvature_coloring = false;
+            self.show_velocity_coloring = false;
+            self.show_acceleration_coloring = false;
+            self.show_binding_coloring = false;
+            self.show_ua_gradient_coloring = false;
         }
     }
 
@@ -111,6 +667,54 @@ impl VisualModeSettings {
         self.show_curvature_coloring = !self.show_curvature_coloring;
         if self.show_curvature_coloring {
             self.show_density_coloring = false;
+            self.show_velocity_coloring = false;
+            self.show_acceleration_coloring = false;
+            self.show_binding_coloring = false;
+            self.show_ua_gradient_coloring = false;
+        }
+    }
+
+    pub fn toggle_velocity(&mut self) {
+        self.show_velocity_coloring = !self.show_velocity_coloring;
+        if self.show_velocity_coloring {
+            self.show_density_coloring = false;
+            self.show_curvature_coloring = false;
+            self.show_acceleration_coloring = false;
+            self.show_binding_coloring = false;
+            self.show_ua_gradient_coloring = false;
+        }
+    }
+
+    pub fn toggle_acceleration(&mut self) {
+        self.show_acceleration_coloring = !self.show_acceleration_coloring;
+        if self.show_acceleration_coloring {
+            self.show_density_coloring = false;
+            self.show_curvature_coloring = false;
+            self.show_velocity_coloring = false;
+            self.show_binding_coloring = false;
+            self.show_ua_gradient_coloring = false;
+        }
+    }
+
+    pub fn toggle_binding(&mut self) {
+        self.show_binding_coloring = !self.show_binding_coloring;
+        if self.show_binding_coloring {
+            self.show_density_coloring = false;
+            self.show_curvature_coloring = false;
+            self.show_velocity_coloring = false;
+            self.show_acceleration_coloring = false;
+            self.show_ua_gradient_coloring = false;
+        }
+    }
+
+    pub fn toggle_ua_gradient(&mut self) {
+        self.show_ua_gradient_coloring = !self.show_ua_gradient_coloring;
+        if self.show_ua_gradient_coloring {
+            self.show_density_coloring = false;
+            self.show_curvature_coloring = false;
+            self.show_velocity_coloring = false;
+            self.show_acceleration_coloring = false;
+            self.show_binding_coloring = false;
         }
     }
 }
@@ -152,7 +756,7 @@ pub fn setup_ui(mut commands: Commands) {
                     column.spawn((
                         TextBundle::from_sections([
                             TextSection::new(
-                                "PRU Universe Simulation\n",
+                                format!("{DEFAULT_SCENARIO_NAME}\n"),
                                 TextStyle {
                                     font_size: 20.0,
                                     color: Color::srgb(0.9, 0.95, 1.0),
@@ -230,6 +834,7 @@ pub fn setup_ui(mut commands: Commands) {
                             spawn_button(row, "Step", StepButton, (), &colors);
                             spawn_button(row, "Slower", SpeedButton { delta: -0.1 }, (), &colors);
                             spawn_button(row, "Faster", SpeedButton { delta: 0.1 }, (), &colors);
+                            spawn_button(row, "Reset", ResetButton, (), &colors);
                         });
 
                     column
@@ -257,6 +862,34 @@ pub fn setup_ui(mut commands: Commands) {
                                 CurvatureLabel,
                                 &colors,
                             );
+                            spawn_button(
+                                row,
+                                "Velocity Overlay",
+                                VelocityToggle,
+                                VelocityLabel,
+                                &colors,
+                            );
+                            spawn_button(
+                                row,
+                                "Accel Overlay",
+                                AccelerationToggle,
+                                AccelerationLabel,
+                                &colors,
+                            );
+                            spawn_button(
+                                row,
+                                "Cell Scale: Density",
+                                CellScaleToggle,
+                                CellScaleLabel,
+                                &colors,
+                            );
+                            spawn_button(
+                                row,
+                                "Overlay Rate: 1/4",
+                                OverlayRateToggle,
+                                OverlayRateLabel,
+                                &colors,
+                            );
                         });
 
                     column
@@ -270,51 +903,214 @@ pub fn setup_ui(mut commands: Commands) {
                             ..Default::default()
                         })
                         .with_children(|row| {
-                            spawn_button(row, "Gravity", GravityToggle, GravityLabel, &colors);
-                            spawn_button(row, "Mode", GravityModeToggle, GravityModeLabel, &colors);
                             spawn_button(
                                 row,
-                                "G -",
-                                GravityAdjustButton { delta: -0.05 },
-                                (),
+                                "Range: Fixed",
+                                OverlayScalingToggle,
+                                OverlayScalingLabel,
                                 &colors,
                             );
                             spawn_button(
                                 row,
-                                "G +",
-                                GravityAdjustButton { delta: 0.05 },
+                                "Lo -",
+                                OverlayBoundButton {
+                                    min_delta: -0.1,
+                                    max_delta: 0.0,
+                                },
                                 (),
                                 &colors,
                             );
                             spawn_button(
                                 row,
-                                "Damp -",
-                                DampingAdjustButton { delta: -0.002 },
+                                "Lo +",
+                                OverlayBoundButton {
+                                    min_delta: 0.1,
+                                    max_delta: 0.0,
+                                },
                                 (),
                                 &colors,
                             );
                             spawn_button(
                                 row,
-                                "Damp +",
-                                DampingAdjustButton { delta: 0.002 },
+                                "Hi -",
+                                OverlayBoundButton {
+                                    min_delta: 0.0,
+                                    max_delta: -0.1,
+                                },
                                 (),
                                 &colors,
                             );
                             spawn_button(
                                 row,
-                                "Soft -",
-                                SofteningAdjustButton { delta: -0.02 },
+                                "Hi +",
+                                OverlayBoundButton {
+                                    min_delta: 0.0,
+                                    max_delta: 0.1,
+                                },
                                 (),
                                 &colors,
                             );
                             spawn_button(
                                 row,
-                                "Soft +",
-                                SofteningAdjustButton { delta: 0.02 },
-                                (),
+                                "Split Screen (Off)",
+                                SplitScreenToggle,
+                                SplitScreenLabel,
                                 &colors,
                             );
-                        });
+                            spawn_button(row, "Swap Sides", SwapSidesButton, (), &colors);
+                            spawn_button(
+                                row,
+                                "Editing: Left",
+                                SplitSideToggle,
+                                SplitSideLabel,
+                                &colors,
+                            );
+                        });
+
+                    // The signed legend sections stay empty unless a diverging overlay is active.
+                    let legend_style = |color: Color| TextStyle {
+                        font_size: 14.0,
+                        color,
+                        ..Default::default()
+                    };
+                    column.spawn((
+                        TextBundle::from_sections([
+                            TextSection::new(
+                                "Overlay range",
+                                legend_style(Color::srgb(0.8, 0.9, 1.0)),
+                            ),
+                            TextSection::new("", legend_style(CURVATURE_BELOW_COLOR)),
+                            TextSection::new("", legend_style(CURVATURE_ABOVE_COLOR)),
+                        ]),
+                        OverlayRangeText,
+                    ));
+                    column.spawn((
+                        TextBundle::from_section("", legend_style(Color::srgb(0.8, 0.9, 1.0))),
+                        SplitScreenText,
+                    ));
+
+                    column
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Row,
+                                column_gap: Val::Px(8.0),
+                                ..Default::default()
+                            },
+                            background_color: Color::NONE.into(),
+                            ..Default::default()
+                        })
+                        .with_children(|row| {
+                            spawn_button(
+                                row,
+                                "Isosurface (Off)",
+                                IsosurfaceToggle,
+                                IsosurfaceLabel,
+                                &colors,
+                            );
+                            spawn_button(row, "Iso -", IsoLevelButton { delta: -1.0 }, (), &colors);
+                            spawn_button(row, "Iso +", IsoLevelButton { delta: 1.0 }, (), &colors);
+                            spawn_button(
+                                row,
+                                "Streamlines (Off)",
+                                StreamlineToggle,
+                                StreamlineLabel,
+                                &colors,
+                            );
+                            spawn_button(row, "Reset View", ResetViewButton, (), &colors);
+                        });
+
+                    column
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Row,
+                                column_gap: Val::Px(8.0),
+                                ..Default::default()
+                            },
+                            background_color: Color::NONE.into(),
+                            ..Default::default()
+                        })
+                        .with_children(|row| {
+                            spawn_button(
+                                row,
+                                "Kernel: Gaussian",
+                                SmoothingKernelButton,
+                                SmoothingKernelLabel,
+                                &colors,
+                            );
+                            spawn_button(
+                                row,
+                                "Smoothing -",
+                                SmoothingRadiusButton { delta: -0.25 },
+                                (),
+                                &colors,
+                            );
+                            spawn_button(
+                                row,
+                                "Smoothing +",
+                                SmoothingRadiusButton { delta: 0.25 },
+                                (),
+                                &colors,
+                            );
+                        });
+
+                    column
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Row,
+                                column_gap: Val::Px(8.0),
+                                ..Default::default()
+                            },
+                            background_color: Color::NONE.into(),
+                            ..Default::default()
+                        })
+                        .with_children(|row| {
+                            spawn_button(row, "Gravity", GravityToggle, GravityLabel, &colors);
+                            spawn_button(row, "Mode", GravityModeToggle, GravityModeLabel, &colors);
+                            spawn_button(
+                                row,
+                                "G -",
+                                GravityAdjustButton { delta: -0.05 },
+                                (),
+                                &colors,
+                            );
+                            spawn_button(
+                                row,
+                                "G +",
+                                GravityAdjustButton { delta: 0.05 },
+                                (),
+                                &colors,
+                            );
+                            spawn_button(
+                                row,
+                                "Damp -",
+                                DampingAdjustButton { delta: -0.002 },
+                                (),
+                                &colors,
+                            );
+                            spawn_button(
+                                row,
+                                "Damp +",
+                                DampingAdjustButton { delta: 0.002 },
+                                (),
+                                &colors,
+                            );
+                            spawn_button(
+                                row,
+                                "Soft -",
+                                SofteningAdjustButton { delta: -0.02 },
+                                (),
+                                &colors,
+                            );
+                            spawn_button(
+                                row,
+                                "Soft +",
+                                SofteningAdjustButton { delta: 0.02 },
+                                (),
+                                &colors,
+                            );
+                            spawn_button(row, "Calibrate G", CalibrateGravityButton, (), &colors);
+                            spawn_button(row, "CMB Pressure (Off)", CmbToggle, CmbLabel, &colors);
+                        });
 
                     column.spawn((
                         TextBundle::from_section(
@@ -328,14 +1124,174 @@ pub fn setup_ui(mut commands: Commands) {
                         GravityParamsText,
                     ));
 
+                    column
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Row,
+                                column_gap: Val::Px(8.0),
+                                ..Default::default()
+                            },
+                            background_color: Color::NONE.into(),
+                            ..Default::default()
+                        })
+                        .with_children(|row| {
+                            spawn_button(
+                                row,
+                                "Preset: Custom",
+                                FormationPresetButton,
+                                FormationPresetLabel,
+                                &colors,
+                            );
+                            for (name, kind) in [
+                                ("Stars", FormationKind::Stars),
+                                ("BHs", FormationKind::BlackHoles),
+                                ("Galaxies", FormationKind::Galaxies),
+                            ] {
+                                for (sign, delta) in [("-", -2), ("+", 2)] {
+                                    spawn_button(
+                                        row,
+                                        &format!("{name} {sign}"),
+                                        FormationIntervalButton { kind, delta },
+                                        (),
+                                        &colors,
+                                    );
+                                }
+                            }
+                        });
+
+                    column.spawn((
+                        TextBundle::from_section(
+                            "Formation intervals",
+                            TextStyle {
+                                font_size: 14.0,
+                                color: Color::srgb(0.8, 0.9, 1.0),
+                                ..Default::default()
+                            },
+                        ),
+                        FormationIntervalText,
+                    ));
+
+                    column.spawn((
+                        TextBundle {
+                            text: Text::from_section(
+                                FormationPreset::Custom.description(),
+                                TextStyle {
+                                    font_size: 13.0,
+                                    color: Color::srgb(0.95, 0.9, 0.7),
+                                    ..Default::default()
+                                },
+                            ),
+                            style: Style {
+                                display: Display::None,
+                                max_width: Val::Px(420.0),
+                                ..Default::default()
+                            },
+                            background_color: Color::srgba(0.05, 0.05, 0.08, 0.9).into(),
+                            ..Default::default()
+                        },
+                        FormationPresetTooltip,
+                    ));
+
+                    column
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Row,
+                                column_gap: Val::Px(8.0),
+                                ..Default::default()
+                            },
+                            background_color: Color::NONE.into(),
+                            ..Default::default()
+                        })
+                        .with_children(|row| {
+                            let breakpoints = BreakpointSettings::default();
+                            for breakpoint in Breakpoint::ALL {
+                                spawn_button(
+                                    row,
+                                    &breakpoints.label(breakpoint),
+                                    BreakpointButton(breakpoint),
+                                    BreakpointLabel(breakpoint),
+                                    &colors,
+                                );
+                            }
+                        });
+
+                    column
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Row,
+                                align_items: AlignItems::Center,
+                                column_gap: Val::Px(8.0),
+                                ..Default::default()
+                            },
+                            background_color: Color::NONE.into(),
+                            ..Default::default()
+                        })
+                        .with_children(|row| {
+                            row.spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Px(180.0),
+                                        height: Val::Px(14.0),
+                                        border: UiRect::all(Val::Px(1.0)),
+                                        ..Default::default()
+                                    },
+                                    background_color: Color::srgba(0.02, 0.03, 0.05, 0.6).into(),
+                                    border_color: BorderColor(Color::srgba(0.5, 0.6, 0.7, 0.6)),
+                                    ..Default::default()
+                                },
+                                RelativeCursorPosition::default(),
+                                RewindTrack,
+                            ))
+                            .with_children(|track| {
+                                track.spawn((
+                                    NodeBundle {
+                                        style: Style {
+                                            width: Val::Percent(100.0),
+                                            height: Val::Percent(100.0),
+                                            ..Default::default()
+                                        },
+                                        background_color: Color::srgb(0.3, 0.5, 0.9).into(),
+                                        ..Default::default()
+                                    },
+                                    RewindFill,
+                                ));
+                            });
+                            for (label, delta) in [
+                                ("-10", Some(-10)),
+                                ("-1", Some(-1)),
+                                ("+1", Some(1)),
+                                ("+10", Some(10)),
+                                ("Live", None),
+                            ] {
+                                spawn_button(row, label, RewindStepButton { delta }, (), &colors);
+                            }
+                        });
+
+                    column.spawn((
+                        TextBundle::from_section(
+                            "Rewind: live",
+                            TextStyle {
+                                font_size: 14.0,
+                                color: Color::srgb(0.8, 0.9, 1.0),
+                                ..Default::default()
+                            },
+                        ),
+                        RewindLabel,
+                    ));
+
                     column
                         .spawn(NodeBundle {
                             style: Style {
                                 width: Val::Px(260.0),
-                                height: Val::Px(80.0),
+                                height: Val::Px(
+                                    DENSITY_BAR_MIN_HEIGHT
+                                        + DENSITY_BAR_RANGE
+                                        + 2.0 * DENSITY_GRAPH_PADDING
+                                        + 2.0,
+                                ),
                                 align_items: AlignItems::FlexEnd,
                                 column_gap: Val::Px(2.0),
-                                padding: UiRect::axes(Val::Px(8.0), Val::Px(6.0)),
+                                padding: UiRect::axes(Val::Px(8.0), Val::Px(DENSITY_GRAPH_PADDING)),
                                 ..Default::default()
                             },
                             background_color: Color::srgba(0.02, 0.03, 0.05, 0.6).into(),
@@ -357,12 +1313,51 @@ pub fn setup_ui(mut commands: Commands) {
                                     DensityBar { index: i },
                                 ));
                             }
+                            for threshold in [DensityThreshold::Star, DensityThreshold::Galaxy] {
+                                graph
+                                    .spawn((
+                                        NodeBundle {
+                                            style: Style {
+                                                position_type: PositionType::Absolute,
+                                                left: Val::Px(0.0),
+                                                right: Val::Px(0.0),
+                                                height: Val::Px(1.0),
+                                                ..Default::default()
+                                            },
+                                            background_color: threshold.color().into(),
+                                            ..Default::default()
+                                        },
+                                        DensityThresholdMarker(threshold),
+                                    ))
+                                    .with_children(|marker| {
+                                        marker.spawn((
+                                            TextBundle {
+                                                text: Text::from_section(
+                                                    threshold.label(),
+                                                    TextStyle {
+                                                        font_size: 9.0,
+                                                        color: threshold.color(),
+                                                        ..Default::default()
+                                                    },
+                                                ),
+                                                style: Style {
+                                                    position_type: PositionType::Absolute,
+                                                    right: Val::Px(2.0),
+                                                    bottom: Val::Px(1.0),
+                                                    ..Default::default()
+                                                },
+                                                ..Default::default()
+                                            },
+                                            DensityThresholdLabel(threshold),
+                                        ));
+                                    });
+                            }
                         });
                 });
         });
 }
 
-fn spawn_button<C1: Component, C2: Bundle>(
+pub(crate) fn spawn_button<C1: Component, C2: Bundle>(
     parent: &mut ChildBuilder,
     label: &str,
     component: C1,
@@ -400,10 +1395,21 @@ fn spawn_button<C1: Component, C2: Bundle>(
 }
 
 /// Keyboard shortcuts mirroring the UI controls.
+#[allow(clippy::too_many_arguments)]
 pub fn keyboard_controls(
     mut sim_state: ResMut<SimulationState>,
     mut modes: ResMut<VisualModeSettings>,
     mut gravity: ResMut<GravityParams>,
+    mut filaments: ResMut<GalaxyFilamentGraph>,
+    mut separation: ResMut<MinSeparation>,
+    mut isosurface: ResMut<IsosurfaceSettings>,
+    mut tone_mapping: ResMut<ToneMappingSettings>,
+    mut roi: ResMut<RegionOfInterest>,
+    mut correlation: ResMut<CorrelationSettings>,
+    mut animation: ResMut<AnimationSettings>,
+    mut interpolation: ResMut<RenderInterpolation>,
+    mut field_settings: ResMut<FieldSettings>,
+    mut resets: EventWriter<ResetUniverse>,
     keys: Res<ButtonInput<KeyCode>>,
 ) {
     if keys.just_pressed(KeyCode::Space) {
@@ -412,6 +1418,28 @@ pub fn keyboard_controls(
     if keys.just_pressed(KeyCode::Period) {
         sim_state.step_once();
     }
+    if keys.just_pressed(KeyCode::KeyR) {
+        let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+            resets.send(ResetUniverse {
+                reset_camera: shift,
+            });
+        } else if shift {
+            // Double the block size, wrapping back to the smallest past the largest.
+            let size = match gravity.macro_block_size {
+                Some(size) if size < MACRO_BLOCK_RANGE.1 => size * 2,
+                Some(_) => MACRO_BLOCK_RANGE.0,
+                None => DEFAULT_MACRO_BLOCK_SIZE,
+            };
+            gravity.set_macro_block_size(Some(size));
+        } else {
+            let size = match gravity.macro_block_size {
+                Some(_) => None,
+                None => Some(DEFAULT_MACRO_BLOCK_SIZE),
+            };
+            gravity.set_macro_block_size(size);
+        }
+    }
     if keys.just_pressed(KeyCode::Minus) || keys.just_pressed(KeyCode::NumpadSubtract) {
         sim_state.adjust_speed(-0.1);
     }
@@ -424,7 +1452,33 @@ pub fn keyboard_controls(
     if keys.just_pressed(KeyCode::KeyC) {
         modes.toggle_curvature();
     }
-    if keys.just_pressed(KeyCode::KeyG) {
+    if keys.just_pressed(KeyCode::KeyV) {
+        if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            modes.toggle_acceleration();
+        } else {
+            modes.toggle_velocity();
+        }
+    }
+    if keys.just_pressed(KeyCode::KeyB) {
+        modes.toggle_binding();
+    }
+    if keys.just_pressed(KeyCode::KeyE) {
+        if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            modes.show_ua_gradient_arrows = !modes.show_ua_gradient_arrows;
+        } else {
+            modes.toggle_ua_gradient();
+        }
+    }
+    if keys.just_pressed(KeyCode::KeyS) {
+        modes.show_lattice_stress = !modes.show_lattice_stress;
+    }
+    if keys.just_pressed(KeyCode::KeyF) {
+        filaments.render_edges = !filaments.render_edges;
+    }
+    // Shift+G calibrates G instead (see `calibrate_gravity`).
+    if keys.just_pressed(KeyCode::KeyG)
+        && !keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+    {
         gravity.enabled = !gravity.enabled;
     }
     if keys.just_pressed(KeyCode::KeyM) {
@@ -433,31 +1487,201 @@ pub fn keyboard_controls(
             GravityMode::RelationalLattice => GravityMode::NaiveNBody,
         };
     }
+    if keys.just_pressed(KeyCode::KeyN) {
+        separation.enabled = !separation.enabled;
+    }
+    if keys.just_pressed(KeyCode::KeyI) {
+        isosurface.enabled = !isosurface.enabled;
+    }
+    if keys.just_pressed(KeyCode::KeyH) {
+        if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            modes.seed_hue_offset = !modes.seed_hue_offset;
+        } else {
+            tone_mapping.hdr = !tone_mapping.hdr;
+        }
+    }
+    if keys.just_pressed(KeyCode::KeyK) {
+        tone_mapping.curve = tone_mapping.curve.next();
+    }
+    if keys.just_pressed(KeyCode::KeyO) {
+        roi.track_followed_galaxy = !roi.track_followed_galaxy;
+        if !roi.track_followed_galaxy {
+            roi.bounds = None;
+        }
+    }
+    if keys.just_pressed(KeyCode::KeyP) {
+        correlation.enabled = !correlation.enabled;
+    }
+    if keys.just_pressed(KeyCode::KeyA) {
+        animation.enabled = !animation.enabled;
+    }
+    if keys.just_pressed(KeyCode::KeyJ) {
+        interpolation.enabled = !interpolation.enabled;
+    }
+    if keys.just_pressed(KeyCode::KeyT) {
+        if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            modes.show_streamlines = !modes.show_streamlines;
+        } else {
+            gravity.targets = gravity.targets.next();
+        }
+    }
+    if keys.just_pressed(KeyCode::KeyY) {
+        gravity.damping_model = gravity.damping_model.next();
+    }
+    if keys.just_pressed(KeyCode::KeyQ) {
+        let radius = field_settings.smoothing_radius - 0.25;
+        field_settings.set_smoothing_radius(radius);
+    }
+    if keys.just_pressed(KeyCode::KeyW) {
+        let radius = field_settings.smoothing_radius + 0.25;
+        field_settings.set_smoothing_radius(radius);
+    }
+    if keys.just_pressed(KeyCode::KeyZ) {
+        field_settings.kernel = field_settings.kernel.next();
+    }
     if keys.just_pressed(KeyCode::BracketLeft) {
-        gravity.g_effective = (gravity.g_effective - 0.05).max(0.0);
+        let g = gravity.g_effective - 0.05;
+        gravity.set_g(g);
     }
     if keys.just_pressed(KeyCode::BracketRight) {
-        gravity.g_effective = (gravity.g_effective + 0.05).clamp(0.0, 5.0);
+        let g = gravity.g_effective + 0.05;
+        gravity.set_g(g);
     }
     if keys.just_pressed(KeyCode::Comma) {
-        gravity.damping = (gravity.damping - 0.002).max(0.0);
+        let damping = gravity.damping - 0.002;
+        gravity.set_damping(damping);
     }
     if keys.just_pressed(KeyCode::Slash) {
-        gravity.damping = (gravity.damping + 0.002).min(1.0);
+        let damping = gravity.damping + 0.002;
+        gravity.set_damping(damping);
     }
     if keys.just_pressed(KeyCode::Semicolon) {
-        gravity.softening_length = (gravity.softening_length - 0.02).max(0.01);
+        let softening = gravity.softening_length - 0.02;
+        gravity.set_softening(softening);
     }
     if keys.just_pressed(KeyCode::Quote) {
-        gravity.softening_length = (gravity.softening_length + 0.02).min(2.0);
+        let softening = gravity.softening_length + 0.02;
+        gravity.set_softening(softening);
     }
 }
 
+/// Bodies farther than this from the camera focus can't be picked for pinning.
+pub(crate) const PIN_PICK_RADIUS: f32 = 2.0;
+
+/// `X` toggles [`Pinned`] on the body nearest the camera focus, as of the last
+/// [`SpatialOctree`] rebuild.
+#[allow(clippy::type_complexity)]
+pub fn toggle_pinned_body(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    camera: Res<OrbitCameraSettings>,
+    octree: Res<SpatialOctree>,
+    bodies: Query<(Entity, &Transform, Has<Pinned>, Option<&Name>), With<PruDynamics>>,
+    mut toasts: ResMut<Toasts>,
+) {
+    if !keys.just_pressed(KeyCode::KeyX) {
+        return;
+    }
+    let nearest = octree
+        .k_nearest(camera.focus, 1)
+        .into_iter()
+        .filter(|(_, distance)| *distance <= PIN_PICK_RADIUS)
+        .find_map(|(entity, _)| bodies.get(entity).ok());
+    let Some((entity, transform, pinned, name)) = nearest else {
+        info!("No body within {PIN_PICK_RADIUS} of the camera focus to pin");
+        toasts.push(
+            "No body near the camera focus to pin",
+            ReportSeverity::Warning,
+            TOAST_SECS,
+        );
+        return;
+    };
+
+    let name = name.map(Name::as_str).unwrap_or("body");
+    if pinned {
+        commands.entity(entity).remove::<Pinned>();
+        info!("Unpinned {name} at {}", transform.translation);
+        toasts.push(format!("Unpinned {name}"), ReportSeverity::Info, TOAST_SECS);
+    } else {
+        commands.entity(entity).insert(Pinned);
+        info!("Pinned {name} at {}", transform.translation);
+        toasts.push(format!("Pinned {name}"), ReportSeverity::Info, TOAST_SECS);
+    }
+}
+
+/// `Shift+G` or the calibrate button sets `relational_g_scale` so the
+/// relational solver matches the naive one.
+///
+/// The reference pair is two mean-mass cells one lattice spacing apart. Only
+/// the relational solver's factor changes, so `g_effective` stays shared and
+/// calibrating twice gives the same scale.
+pub fn calibrate_gravity(
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<CalibrateGravityButton>)>,
+    mut gravity: ResMut<GravityParams>,
+    kernel: Option<Res<ActiveGravityKernel>>,
+    universe: Option<Res<PruUniverse>>,
+    cells: Query<&PruDynamics, With<PruCell>>,
+    mut toasts: ResMut<Toasts>,
+) {
+    let pressed = buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+        || (keys.just_pressed(KeyCode::KeyG)
+            && keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]));
+    if !pressed {
+        return;
+    }
+    let (Some(kernel), Some(universe)) = (kernel, universe) else {
+        return;
+    };
+    let count = cells.iter().len();
+    let mean_mass = if count > 0 {
+        cells.iter().map(|dynamics| dynamics.mass).sum::<f32>() / count as f32
+    } else {
+        1.0
+    };
+
+    let previous = gravity.relational_g_scale;
+    let Some(scale) = relational_g_scale(
+        kernel.0.as_ref(),
+        &gravity,
+        universe.spacing,
+        (mean_mass, mean_mass),
+    ) else {
+        toasts.push(
+            "The relational kernel exerts no force to calibrate against",
+            ReportSeverity::Warning,
+            TOAST_SECS,
+        );
+        return;
+    };
+    if gravity.relational_g_scale != scale {
+        gravity.relational_g_scale = scale;
+    }
+    info!(
+        "Calibrated relational G scale {previous:.3} -> {scale:.3} (relational matches naive for m = {mean_mass:.3} at r = {:.3})",
+        universe.spacing
+    );
+    toasts.push(
+        format!(
+            "Calibrated relational G: {:.3} × {scale:.3} = {:.3}",
+            gravity.g_effective,
+            gravity.g_effective * scale
+        ),
+        ReportSeverity::Info,
+        TOAST_SECS,
+    );
+}
+
 /// React to UI button interactions and update button visuals.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn update_ui_buttons(
     mut sim_state: ResMut<SimulationState>,
     mut modes: ResMut<VisualModeSettings>,
     mut gravity: ResMut<GravityParams>,
+    mut cmb: ResMut<CmbSettings>,
+    mut resets: EventWriter<ResetUniverse>,
     colors: Res<UiColorScheme>,
     mut interaction_query: Query<
         (
@@ -466,6 +1690,7 @@ pub fn update_ui_buttons(
             Option<&SpeedButton>,
             Option<&PauseButton>,
             Option<&StepButton>,
+            Option<&ResetButton>,
             Option<&DensityToggle>,
             Option<&CurvatureToggle>,
             Option<&GravityToggle>,
@@ -473,6 +1698,7 @@ pub fn update_ui_buttons(
             Option<&GravityAdjustButton>,
             Option<&DampingAdjustButton>,
             Option<&SofteningAdjustButton>,
+            Option<&CmbToggle>,
         ),
         Changed<Interaction>,
     >,
@@ -484,6 +1710,7 @@ pub fn update_ui_buttons(
         speed_button,
         pause_button,
         step_button,
+        reset_button,
         density_toggle,
         curvature_toggle,
         gravity_toggle,
@@ -491,6 +1718,7 @@ pub fn update_ui_buttons(
         gravity_adjust,
         damping_adjust,
         softening_adjust,
+        cmb_toggle,
     ) in interaction_query.iter_mut()
     {
         match *interaction {
@@ -503,6 +1731,8 @@ pub fn update_ui_buttons(
                     sim_state.adjust_speed(speed_button.delta);
                 } else if step_button.is_some() {
                     sim_state.step_once();
+                } else if reset_button.is_some() {
+                    resets.send(ResetUniverse::default());
                 } else if density_toggle.is_some() {
                     modes.toggle_density();
                 } else if curvature_toggle.is_some() {
@@ -515,12 +1745,16 @@ pub fn update_ui_buttons(
                         GravityMode::RelationalLattice => GravityMode::NaiveNBody,
                     };
                 } else if let Some(adj) = gravity_adjust {
-                    gravity.g_effective = (gravity.g_effective + adj.delta).clamp(0.0, 5.0);
+                    let g = gravity.g_effective + adj.delta;
+                    gravity.set_g(g);
                 } else if let Some(adj) = damping_adjust {
-                    gravity.damping = (gravity.damping + adj.delta).clamp(0.0, 1.0);
+                    let damping = gravity.damping + adj.delta;
+                    gravity.set_damping(damping);
                 } else if let Some(adj) = softening_adjust {
-                    gravity.softening_length =
-                        (gravity.softening_length + adj.delta).clamp(0.01, 3.0);
+                    let softening = gravity.softening_length + adj.delta;
+                    gravity.set_softening(softening);
+                } else if cmb_toggle.is_some() {
+                    cmb.enabled = !cmb.enabled;
                 }
             }
             Interaction::Hovered => {
@@ -545,9 +1779,18 @@ pub fn update_ui_buttons(
 pub fn update_status_text(
     sim_state: Res<SimulationState>,
     universe: Option<Res<PruUniverse>>,
+    tone_mapping: Res<ToneMappingSettings>,
+    counts: Res<StructureCounts>,
+    activity: Res<FormationActivity>,
     mut query: Query<&mut Text, With<StatusText>>,
 ) {
     if let Ok(mut text) = query.get_single_mut() {
+        if sim_state.is_changed() {
+            let header = format!("{}\n", sim_state.scenario_name);
+            if text.sections[0].value != header {
+                text.sections[0].value = header;
+            }
+        }
         let cell_count = universe.as_ref().map(|u| u.total_cells).unwrap_or(0);
         text.sections[1].value = format!(
             "State: {}\nTick: {}\nSim time: {:.2} s\nTime scale: {:.2}x\nCells: {}",
@@ -561,31 +1804,145 @@ pub fn update_status_text(
             sim_state.time_scale,
             cell_count
         );
+        text.sections[1].value.push_str(&format!(
+            "\nStars: {}  BHs: {}\nGalaxies: {}  Planets: {}",
+            counts.stars, counts.black_holes, counts.galaxies, counts.planets
+        ));
+        if let Some(tick) = activity.tick {
+            let formed: Vec<String> = [
+                (activity.stars, "star", "stars"),
+                (activity.black_holes, "black hole", "black holes"),
+                (activity.galaxies, "galaxy", "galaxies"),
+            ]
+            .into_iter()
+            .filter(|(count, _, _)| *count > 0)
+            .map(|(count, one, many)| format!("+{count} {}", if count == 1 { one } else { many }))
+            .collect();
+            text.sections[1].value.push_str(&format!(
+                "\nLast formation: tick {tick}, {}",
+                formed.join(", ")
+            ));
+        }
+        if tone_mapping.hdr {
+            text.sections[1]
+                .value
+                .push_str(&format!("\nHDR: {}", tone_mapping.curve.label()));
+        }
+        if sim_state.behind_real_time {
+            text.sections[1]
+                .value
+                .push_str("\nRunning slower than real time");
+        }
+    }
+}
+
+/// Keep the window title on the scenario name and current tick.
+pub fn update_window_title(
+    sim_state: Res<SimulationState>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !sim_state.is_changed() {
+        return;
+    }
+    let title = format!(
+        "PRU Universe — {} [tick={}]",
+        sim_state.scenario_name, sim_state.tick
+    );
+    for mut window in windows.iter_mut() {
+        if window.title != title {
+            window.title = title.clone();
+        }
     }
 }
 
 /// Show density/curvature metrics and a tiny sparkline style bar chart.
+#[allow(clippy::too_many_arguments)]
 pub fn update_metrics_text(
     metrics: Res<FieldMetrics>,
+    material_stats: Res<MaterialUpdateStats>,
+    complexity: Res<LatticeComplexity>,
+    roi: Res<RegionOfInterest>,
+    roi_metrics: Res<RoiMetrics>,
+    correlation_settings: Res<CorrelationSettings>,
+    correlation: Res<TwoPointCorrelation>,
     mut text_query: Query<&mut Text, With<MetricsText>>,
 ) {
     if let Ok(mut text) = text_query.get_single_mut() {
         text.sections[1].value = format!(
-            "Avg density: {:.3}\nMin/Max density: {:.3} / {:.3}\nAvg curvature: {:.3}",
-            metrics.avg_density, metrics.min_density, metrics.max_density, metrics.avg_curvature,
+            "Avg density: {:.3}\nMin/Max density: {:.3} / {:.3}\nAvg curvature: {:.3}\nMean |∇UA|: {:.3}\nMaterial uploads/frame: {}\nComplexity: {} bytes\nIGM fraction: {:.1}%",
+            metrics.avg_density,
+            metrics.min_density,
+            metrics.max_density,
+            metrics.avg_curvature,
+            metrics.mean_ua_gradient_magnitude,
+            material_stats.uploads_last_frame,
+            complexity.compressed_size,
+            metrics.igm_fraction * 100.0,
         );
+        if roi.bounds.is_some() {
+            text.sections[1].value.push_str(&format!(
+                "\nROI ({} cells): density {:.3}, curvature {:.3}, KE {:.3}",
+                roi_metrics.cells,
+                roi_metrics.avg_density,
+                roi_metrics.avg_curvature,
+                roi_metrics.kinetic_energy,
+            ));
+        } else if roi.track_followed_galaxy {
+            text.sections[1]
+                .value
+                .push_str("\nROI: follow a galaxy (L) to measure it");
+        }
+        if correlation_settings.enabled {
+            match correlation.tick {
+                None => text.sections[1].value.push_str("\nξ(r): computing…"),
+                Some(tick) => {
+                    let bins: Vec<String> = correlation
+                        .xi
+                        .iter()
+                        .map(|xi| format!("{xi:+.2}"))
+                        .collect();
+                    text.sections[1].value.push_str(&format!(
+                        "\nξ(r), Δr {:.2} @ tick {tick}: {}",
+                        correlation.bin_width,
+                        bins.join(" ")
+                    ));
+                }
+            }
+        }
     }
 }
 
+/// Keep a tracking region of interest on the followed galaxy's halo.
+pub fn update_region_of_interest(
+    camera: Res<OrbitCameraSettings>,
+    mut roi: ResMut<RegionOfInterest>,
+    galaxies: Query<&Galaxy>,
+) {
+    if !roi.track_followed_galaxy {
+        return;
+    }
+    // Work on a copy: clearing a stale follow target is the camera's job.
+    let mut follow = camera.follow;
+    let bounds = follow.get(&galaxies).map(|galaxy| {
+        let half_extent = Vec3::splat(galaxy.radius);
+        (galaxy.center - half_extent, galaxy.center + half_extent)
+    });
+    if roi.bounds != bounds {
+        roi.bounds = bounds;
+    }
+}
+
+/// Rescale the density history to its largest sample and redraw the bars.
 pub fn update_density_history_bars(
     metrics: Res<FieldMetrics>,
+    mut scale: ResMut<DensityHistoryScale>,
     mut bar_query: Query<(&mut Style, &mut BackgroundColor, &DensityBar)>,
 ) {
     if !metrics.is_changed() {
         return;
     }
 
-    let mut samples: Vec<f32> = metrics.density_history.iter().cloned().collect();
+    let mut samples = resample_history(&metrics.density_history, DENSITY_BAR_COUNT);
     while samples.len() < DENSITY_BAR_COUNT {
         samples.insert(0, 0.0);
     }
@@ -593,22 +1950,480 @@ pub fn update_density_history_bars(
         .iter()
         .cloned()
         .fold(0.0001f32, |a, b| a.max(b.abs()));
+    // Markers follow the scale, so only flag it when it actually moves.
+    scale.set_if_neq(DensityHistoryScale { max_sample });
 
     for (mut style, mut color, bar) in bar_query.iter_mut() {
         if let Some(sample) = samples.iter().rev().nth(bar.index) {
-            let normalized = (sample / max_sample).clamp(0.0, 1.0);
-            style.height = Val::Px(6.0 + normalized * 60.0);
+            let normalized = scale.normalized(*sample);
+            style.height = Val::Px(scale.bar_height(*sample));
             *color = Color::srgb(0.25 + normalized * 0.5, 0.6, 0.95).into();
         }
     }
 }
 
+/// Place the formation threshold markers on the density history's current scale.
+///
+/// A threshold above the scale sits at the top of the graph, its label marked `↑`.
+pub fn update_density_threshold_markers(
+    scale: Res<DensityHistoryScale>,
+    settings: Res<FormationSettings>,
+    mut markers: Query<(&DensityThresholdMarker, &mut Style)>,
+    mut labels: Query<(&DensityThresholdLabel, &mut Text)>,
+) {
+    if !(scale.is_changed() || settings.is_changed()) {
+        return;
+    }
+    for (marker, mut style) in markers.iter_mut() {
+        let value = marker.0.value(&settings);
+        style.bottom = Val::Px(DENSITY_GRAPH_PADDING + scale.bar_height(value));
+    }
+    for (label, mut text) in labels.iter_mut() {
+        let value = label.0.value(&settings);
+        let off_scale = if value > scale.max_sample { " ↑" } else { "" };
+        text.sections[0].value = format!("{} {value:.1}{off_scale}", label.0.label());
+    }
+}
+
+/// Average `history` into at most `bars` buckets, oldest first.
+///
+/// Histories no longer than `bars` are returned as-is; longer ones are split
+/// into equal spans so the whole buffer fits the display.
+fn resample_history(history: &VecDeque<f32>, bars: usize) -> Vec<f32> {
+    if history.len() <= bars {
+        return history.iter().copied().collect();
+    }
+
+    (0..bars)
+        .map(|bar| {
+            let start = bar * history.len() / bars;
+            let end = (bar + 1) * history.len() / bars;
+            history.range(start..end).sum::<f32>() / (end - start) as f32
+        })
+        .collect()
+}
+
+/// Apply the cell scale, overlay rate, and overlay range buttons.
+pub fn update_visual_mode_buttons(
+    mut modes: ResMut<VisualModeSettings>,
+    velocity_buttons: Query<&Interaction, (Changed<Interaction>, With<VelocityToggle>)>,
+    acceleration_buttons: Query<&Interaction, (Changed<Interaction>, With<AccelerationToggle>)>,
+    scale_buttons: Query<&Interaction, (Changed<Interaction>, With<CellScaleToggle>)>,
+    rate_buttons: Query<&Interaction, (Changed<Interaction>, With<OverlayRateToggle>)>,
+    scaling_buttons: Query<&Interaction, (Changed<Interaction>, With<OverlayScalingToggle>)>,
+    bound_buttons: Query<(&Interaction, &OverlayBoundButton), Changed<Interaction>>,
+) {
+    for interaction in velocity_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            modes.toggle_velocity();
+        }
+    }
+
+    for interaction in acceleration_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            modes.toggle_acceleration();
+        }
+    }
+
+    for interaction in scale_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            modes.scale_mode = modes.scale_mode.next();
+        }
+    }
+
+    for interaction in rate_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            modes.overlay_update_slices = match modes.overlay_update_slices {
+                1 => 2,
+                2 => 4,
+                4 => 8,
+                _ => 1,
+            };
+        }
+    }
+
+    for interaction in scaling_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            let range = modes.active_range_mut();
+            range.scaling = range.scaling.next();
+        }
+    }
+
+    for (interaction, button) in bound_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            let range = modes.active_range_mut();
+            range.scaling = OverlayScaling::Fixed;
+            range.adjust(button.min_delta, button.max_delta);
+        }
+    }
+}
+
+/// Apply the isosurface buttons and refresh their label.
+pub fn update_isosurface_controls(
+    mut settings: ResMut<IsosurfaceSettings>,
+    toggle_buttons: Query<&Interaction, (Changed<Interaction>, With<IsosurfaceToggle>)>,
+    level_buttons: Query<(&Interaction, &IsoLevelButton), Changed<Interaction>>,
+    mut label: Query<&mut Text, With<IsosurfaceLabel>>,
+) {
+    for interaction in toggle_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            settings.enabled = !settings.enabled;
+        }
+    }
+
+    for (interaction, button) in level_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            settings.iso_level = (settings.iso_level + button.delta).max(0.0);
+        }
+    }
+
+    if !settings.is_changed() {
+        return;
+    }
+    if let Ok(mut text) = label.get_single_mut() {
+        text.sections[0].value = if settings.enabled {
+            format!("Isosurface @ {:.1}", settings.iso_level)
+        } else {
+            "Isosurface (Off)".to_string()
+        };
+    }
+}
+
+/// Apply the split screen, swap, and side buttons and refresh their labels.
+///
+/// While split screen is on, the active overlay is the edited side's: picking
+/// a side or swapping shows that side's overlay, and switching the overlay
+/// with the other buttons or keys changes that side.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn update_split_screen_controls(
+    mut modes: ResMut<VisualModeSettings>,
+    mut split: ResMut<SplitScreenMode>,
+    toggle_buttons: Query<&Interaction, (Changed<Interaction>, With<SplitScreenToggle>)>,
+    swap_buttons: Query<&Interaction, (Changed<Interaction>, With<SwapSidesButton>)>,
+    side_buttons: Query<&Interaction, (Changed<Interaction>, With<SplitSideToggle>)>,
+    mut label: Query<
+        &mut Text,
+        (
+            With<SplitScreenLabel>,
+            Without<SplitScreenText>,
+            Without<SplitSideLabel>,
+        ),
+    >,
+    mut sides: Query<
+        &mut Text,
+        (
+            With<SplitScreenText>,
+            Without<SplitScreenLabel>,
+            Without<SplitSideLabel>,
+        ),
+    >,
+    mut side_label: Query<
+        &mut Text,
+        (
+            With<SplitSideLabel>,
+            Without<SplitScreenLabel>,
+            Without<SplitScreenText>,
+        ),
+    >,
+) {
+    for interaction in toggle_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            split.enabled = !split.enabled;
+            if split.enabled {
+                let active = modes.active_mode();
+                split.set_editing_mode(active);
+            }
+        }
+    }
+    let mut show_editing_side = false;
+    for interaction in side_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            split.editing = match split.editing {
+                SplitSide::Left => SplitSide::Right,
+                SplitSide::Right => SplitSide::Left,
+            };
+            show_editing_side = true;
+        }
+    }
+    for interaction in swap_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            split.swap();
+            show_editing_side = true;
+        }
+    }
+
+    let (active, editing) = (modes.active_mode(), split.editing_mode());
+    if split.enabled && active != editing {
+        if show_editing_side {
+            modes.show_mode(editing);
+        } else {
+            split.set_editing_mode(active);
+        }
+    }
+
+    if !split.is_changed() {
+        return;
+    }
+    if let Ok(mut text) = label.get_single_mut() {
+        text.sections[0].value = if split.enabled {
+            "Split Screen (On)".to_string()
+        } else {
+            "Split Screen (Off)".to_string()
+        };
+    }
+    if let Ok(mut text) = side_label.get_single_mut() {
+        text.sections[0].value = match split.editing {
+            SplitSide::Left => "Editing: Left".to_string(),
+            SplitSide::Right => "Editing: Right".to_string(),
+        };
+    }
+    if let Ok(mut text) = sides.get_single_mut() {
+        text.sections[0].value = if split.enabled {
+            format!(
+                "Left: {} | Right: {}",
+                split.left_mode.label(),
+                split.right_mode.label()
+            )
+        } else {
+            String::new()
+        };
+    }
+}
+
+/// Apply the streamline button and refresh its label.
+pub fn update_streamline_controls(
+    mut modes: ResMut<VisualModeSettings>,
+    toggle_buttons: Query<&Interaction, (Changed<Interaction>, With<StreamlineToggle>)>,
+    mut label: Query<&mut Text, With<StreamlineLabel>>,
+) {
+    for interaction in toggle_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            modes.show_streamlines = !modes.show_streamlines;
+        }
+    }
+
+    if !modes.is_changed() {
+        return;
+    }
+    if let Ok(mut text) = label.get_single_mut() {
+        text.sections[0].value = if modes.show_streamlines {
+            "Streamlines (On)".to_string()
+        } else {
+            "Streamlines (Off)".to_string()
+        };
+    }
+}
+
+/// Apply the smoothing kernel and radius buttons and refresh their label.
+pub fn update_smoothing_controls(
+    mut settings: ResMut<FieldSettings>,
+    kernel_buttons: Query<&Interaction, (Changed<Interaction>, With<SmoothingKernelButton>)>,
+    radius_buttons: Query<(&Interaction, &SmoothingRadiusButton), Changed<Interaction>>,
+    mut label: Query<&mut Text, With<SmoothingKernelLabel>>,
+) {
+    for interaction in kernel_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            settings.kernel = settings.kernel.next();
+        }
+    }
+
+    for (interaction, button) in radius_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            let radius = settings.smoothing_radius + button.delta;
+            settings.set_smoothing_radius(radius);
+        }
+    }
+
+    if !settings.is_changed() {
+        return;
+    }
+    if let Ok(mut text) = label.get_single_mut() {
+        text.sections[0].value = format!("Kernel: {}", settings.kernel.label());
+    }
+}
+
+/// Apply the formation preset and interval buttons and refresh their readouts.
+///
+/// The preset button cycles through [`FormationPreset`]s, replacing every
+/// threshold at once. The interval buttons only act under `Custom`, so a
+/// named preset always shows its tuned values. Picking a preset also renames
+/// the scenario after it.
+#[allow(clippy::type_complexity)]
+pub fn update_formation_controls(
+    mut settings: ResMut<FormationSettings>,
+    mut sim_state: ResMut<SimulationState>,
+    field_settings: Res<FieldSettings>,
+    preset_button: Query<Ref<Interaction>, With<FormationPresetButton>>,
+    buttons: Query<(&Interaction, &FormationIntervalButton), Changed<Interaction>>,
+    mut texts: ParamSet<(
+        Query<&mut Text, With<FormationIntervalText>>,
+        Query<&mut Text, With<FormationPresetLabel>>,
+        Query<(&mut Text, &mut Style), With<FormationPresetTooltip>>,
+    )>,
+) {
+    let preset_interaction = preset_button.get_single().ok();
+    if let Some(interaction) = &preset_interaction {
+        if interaction.is_changed() && **interaction == Interaction::Pressed {
+            *settings = FormationSettings::from_preset(settings.preset.next());
+            sim_state.scenario_name = settings.preset.scenario_name().to_string();
+        }
+    }
+    for (interaction, button) in buttons.iter() {
+        if *interaction == Interaction::Pressed && settings.preset == FormationPreset::Custom {
+            settings.adjust_interval(button.kind, button.delta);
+        }
+    }
+
+    if let Ok((mut text, mut style)) = texts.p2().get_single_mut() {
+        let display = match preset_interaction.as_deref() {
+            Some(Interaction::Hovered | Interaction::Pressed) => Display::Flex,
+            _ => Display::None,
+        };
+        if style.display != display {
+            style.display = display;
+        }
+        let description = settings.preset.description();
+        if text.sections[0].value != description {
+            text.sections[0].value = description.to_string();
+        }
+    }
+
+    if !settings.is_changed() && !field_settings.is_changed() {
+        return;
+    }
+    if let Ok(mut text) = texts.p0().get_single_mut() {
+        let locked = if settings.preset == FormationPreset::Custom {
+            ""
+        } else {
+            " (preset; choose Custom to edit)"
+        };
+        text.sections[0].value = format!(
+            "Formation every: stars {} / BHs {} / galaxies {} ticks{locked}\nDensity thresholds apply at {} smoothing, h = {:.2} spacings",
+            settings.star_interval,
+            settings.black_hole_interval,
+            settings.galaxy_interval,
+            field_settings.kernel.label(),
+            field_settings.smoothing_radius,
+        );
+    }
+    if let Ok(mut text) = texts.p1().get_single_mut() {
+        text.sections[0].value = format!("Preset: {}", settings.preset.label());
+    }
+}
+
+/// Cycle the breakpoint buttons and keep their labels in step with the triggers.
+///
+/// Labels also refresh when a trigger disarms itself after firing.
+pub fn update_breakpoint_controls(
+    mut settings: ResMut<BreakpointSettings>,
+    sim_state: Res<SimulationState>,
+    buttons: Query<(&Interaction, &BreakpointButton), Changed<Interaction>>,
+    mut labels: Query<(&mut Text, &BreakpointLabel)>,
+) {
+    for (interaction, button) in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            settings.cycle(button.0, sim_state.tick);
+        }
+    }
+
+    if !settings.is_changed() {
+        return;
+    }
+    for (mut text, label) in labels.iter_mut() {
+        let value = settings.label(label.0);
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+/// Scrub the rewind buffer from the track, the step buttons, and the arrow keys.
+///
+/// Left/Right step one tick, ten with Shift. Dragging along the track picks
+/// a tick, the oldest buffered at the left end and live at the right.
+pub fn update_rewind_controls(
+    mut buffer: ResMut<RewindBuffer>,
+    keys: Res<ButtonInput<KeyCode>>,
+    track: Query<(&Interaction, &RelativeCursorPosition), With<RewindTrack>>,
+    buttons: Query<(&Interaction, &RewindStepButton), Changed<Interaction>>,
+    mut fill: Query<&mut Style, With<RewindFill>>,
+    mut label: Query<&mut Text, With<RewindLabel>>,
+) {
+    let step = if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        10
+    } else {
+        1
+    };
+    if keys.just_pressed(KeyCode::ArrowLeft) {
+        buffer.scrub_by(-step);
+    }
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        buffer.scrub_by(step);
+    }
+    for (interaction, button) in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            match button.delta {
+                Some(delta) => buffer.scrub_by(delta),
+                None => buffer.scrub_to(0),
+            }
+        }
+    }
+    if let Ok((Interaction::Pressed, cursor)) = track.get_single() {
+        if let Some(position) = cursor.normalized {
+            let back = (1.0 - position.x.clamp(0.0, 1.0)) * buffer.max_offset() as f32;
+            buffer.scrub_to(back.round() as usize);
+        }
+    }
+
+    if !buffer.is_changed() {
+        return;
+    }
+    let max_offset = buffer.max_offset();
+    let shown = if max_offset == 0 {
+        1.0
+    } else {
+        1.0 - buffer.offset() as f32 / max_offset as f32
+    };
+    if let Ok(mut style) = fill.get_single_mut() {
+        let width = Val::Percent(shown * 100.0);
+        if style.width != width {
+            style.width = width;
+        }
+    }
+    if let Ok(mut text) = label.get_single_mut() {
+        let tick = buffer.shown().map_or(0, |snapshot| snapshot.tick);
+        let value = if buffer.offset() == 0 {
+            format!("Rewind: live, {} ticks buffered", buffer.len())
+        } else {
+            format!(
+                "Rewind: tick {tick} ({} back of {}); resume or step to continue from here",
+                buffer.offset(),
+                max_offset
+            )
+        };
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
 pub fn update_overlay_labels(
     modes: Res<VisualModeSettings>,
-    mut density_label: Query<&mut Text, With<DensityLabel>>,
-    mut curvature_label: Query<&mut Text, With<CurvatureLabel>>,
+    metrics: Res<FieldMetrics>,
+    field_settings: Res<FieldSettings>,
+    mut labels: ParamSet<(
+        Query<&mut Text, With<DensityLabel>>,
+        Query<&mut Text, With<CurvatureLabel>>,
+        Query<&mut Text, With<CellScaleLabel>>,
+        Query<&mut Text, With<OverlayRateLabel>>,
+        Query<&mut Text, With<OverlayScalingLabel>>,
+        Query<&mut Text, With<OverlayRangeText>>,
+        Query<&mut Text, With<VelocityLabel>>,
+        Query<&mut Text, With<AccelerationLabel>>,
+    )>,
 ) {
-    if let Ok(mut text) = density_label.get_single_mut() {
+    if let Ok(mut text) = labels.p0().get_single_mut() {
         text.sections[0].value = if modes.show_density_coloring {
             "Density Overlay (On)".to_string()
         } else {
@@ -616,23 +2431,130 @@ pub fn update_overlay_labels(
         };
     }
 
-    if let Ok(mut text) = curvature_label.get_single_mut() {
+    if let Ok(mut text) = labels.p1().get_single_mut() {
         text.sections[0].value = if modes.show_curvature_coloring {
             "Curvature Overlay (On)".to_string()
         } else {
             "Curvature Overlay (Off)".to_string()
         };
     }
+
+    if let Ok(mut text) = labels.p2().get_single_mut() {
+        text.sections[0].value = format!("Cell Scale: {}", modes.scale_mode.label());
+    }
+
+    if let Ok(mut text) = labels.p3().get_single_mut() {
+        text.sections[0].value = format!("Overlay Rate: 1/{}", modes.overlay_update_slices);
+    }
+
+    if let Ok(mut text) = labels.p6().get_single_mut() {
+        text.sections[0].value = if modes.show_velocity_coloring {
+            "Velocity Overlay (On)".to_string()
+        } else {
+            "Velocity Overlay (Off)".to_string()
+        };
+    }
+
+    if let Ok(mut text) = labels.p7().get_single_mut() {
+        text.sections[0].value = if modes.show_acceleration_coloring {
+            "Accel Overlay (On)".to_string()
+        } else {
+            "Accel Overlay (Off)".to_string()
+        };
+    }
+
+    let (name, range, bounds) = if modes.show_curvature_coloring {
+        (
+            "Curvature",
+            modes.curvature_range,
+            modes
+                .curvature_range
+                .symmetric_bounds(metrics.max_abs_curvature, metrics.abs_curvature_percentile),
+        )
+    } else if modes.show_ua_gradient_coloring {
+        (
+            "UA gradient",
+            modes.ua_gradient_range,
+            modes.ua_gradient_range.bounds(
+                (0.0, metrics.max_ua_gradient_magnitude),
+                metrics.ua_gradient_percentiles,
+            ),
+        )
+    } else if modes.show_velocity_coloring {
+        (
+            "Speed",
+            modes.velocity_range,
+            modes
+                .velocity_range
+                .bounds(metrics.capped_speed_extent(), metrics.speed_percentiles),
+        )
+    } else if modes.show_acceleration_coloring {
+        (
+            "log10 |a|",
+            modes.acceleration_range,
+            modes.acceleration_range.bounds(
+                (metrics.min_log_acceleration, metrics.max_log_acceleration),
+                metrics.log_acceleration_percentiles,
+            ),
+        )
+    } else {
+        (
+            "Density",
+            modes.density_range,
+            modes.density_range.bounds(
+                (metrics.min_density, metrics.max_density),
+                metrics.density_percentiles,
+            ),
+        )
+    };
+
+    if let Ok(mut text) = labels.p4().get_single_mut() {
+        text.sections[0].value = format!("Range: {}", range.scaling.label());
+    }
+
+    if let Ok(mut text) = labels.p5().get_single_mut() {
+        let legend = if modes.show_curvature_coloring {
+            text.sections[0].value = format!("{} range ({}): ", name, range.scaling.label());
+            [
+                format!("{:.3} UB below neighbors", bounds.0),
+                format!("  |  +{:.3} UB above neighbors", bounds.1),
+            ]
+        } else {
+            text.sections[0].value = format!(
+                "{} range ({}): {:.3} .. {:.3}",
+                name,
+                range.scaling.label(),
+                bounds.0,
+                bounds.1
+            );
+            [String::new(), String::new()]
+        };
+        if name == "Density" {
+            text.sections[0].value.push_str(&format!(
+                " ({}, h = {:.2} spacings)",
+                field_settings.kernel.label(),
+                field_settings.smoothing_radius
+            ));
+        }
+        for (section, value) in text.sections.iter_mut().skip(1).zip(legend) {
+            section.value = value;
+        }
+    }
 }
 
 /// Update on-screen gravity toggles and parameter readout.
+#[allow(clippy::type_complexity)]
 pub fn update_gravity_labels(
     params: Res<GravityParams>,
-    mut gravity_label: Query<&mut Text, With<GravityLabel>>,
-    mut gravity_mode_label: Query<&mut Text, With<GravityModeLabel>>,
-    mut params_text: Query<&mut Text, With<GravityParamsText>>,
+    cmb: Res<CmbSettings>,
+    mut labels: ParamSet<(
+        Query<&mut Text, With<GravityLabel>>,
+        Query<&mut Text, With<GravityModeLabel>>,
+        Query<&mut Text, With<GravityParamsText>>,
+        Query<&mut Text, With<CmbLabel>>,
+    )>,
 ) {
-    if let Ok(mut text) = gravity_label.get_single_mut() {
+    if let Ok(mut text) = labels.p0().get_single_mut() {
         text.sections[0].value = if params.enabled {
             "Gravity (On)".to_string()
         } else {
@@ -640,31 +2562,42 @@ pub fn update_gravity_labels(
         };
     }
 
-    if let Ok(mut text) = gravity_mode_label.get_single_mut() {
-        text.sections[0].value = match params.mode {
-            GravityMode::NaiveNBody => "Mode: Naive N-Body".to_string(),
-            GravityMode::RelationalLattice => "Mode: Relational Lattice".to_string(),
-        };
+    if let Ok(mut text) = labels.p1().get_single_mut() {
+        text.sections[0].value = format!("Mode: {}", params.mode.label());
     }
 
-    if let Ok(mut text) = params_text.get_single_mut() {
+    if let Ok(mut text) = labels.p2().get_single_mut() {
         text.sections[0].value = format!(
-            "G_eff: {:.2}\nSoftening: {:.3}\nDamping: {:.4}\nMax Accel: {:.0}\nSolver: {}",
+            "G_eff: {:.2}\nSoftening: {:.3}\nDamping: {:.4} ({})\nMax Accel: {:.0}\nSolver: {}\nTargets: {}\nMacro: {}",
             params.g_effective,
             params.softening_length,
             params.damping,
+            params.damping_model.label(),
             params.max_acceleration,
             match params.mode {
                 GravityMode::NaiveNBody => "Naive N-Body",
                 GravityMode::RelationalLattice => "Relational",
-            }
+            },
+            params.targets.label(),
+            params
+                .macro_block_size
+                .map_or("off".to_string(), |size| format!("{size}³ blocks"))
         );
     }
+
+    if let Ok(mut text) = labels.p3().get_single_mut() {
+        text.sections[0].value = if cmb.enabled {
+            format!("CMB Pressure ({:.3} K)", cmb.temperature)
+        } else {
+            "CMB Pressure (Off)".to_string()
+        };
+    }
 }
 
 /// Show kinetic/potential/total energy and relative drift.
 pub fn update_energy_text(
     energy: Res<SimulationEnergy>,
+    metrics: Res<FieldMetrics>,
     mut text_query: Query<&mut Text, With<EnergyText>>,
 ) {
     if let Ok(mut text) = text_query.get_single_mut() {
@@ -672,10 +2605,20 @@ pub fn update_energy_text(
             .relative_drift
             .map(|d| format!("{:.2e}", d))
             .unwrap_or_else(|| "n/a".to_string());
+        let momentum_drift_str = energy
+            .momentum_drift
+            .map(|d| format!("{:.2e}", d))
+            .unwrap_or_else(|| "n/a".to_string());
 
         text.sections[1].value = format!(
-            "Kinetic: {:>10.4}\nPotential: {:>10.4}\nTotal: {:>10.4}\nΔE/E0: {}",
-            energy.kinetic, energy.potential, energy.total, drift_str
+            "Kinetic: {:>10.4}\nPotential: {:>10.4}\nTotal: {:>10.4}\nΔE/E0: {}\n|p|: {:.4} (Δp: {})\nAccretion power: {:.4}",
+            energy.kinetic,
+            energy.potential,
+            energy.total,
+            drift_str,
+            metrics.total_linear_momentum.length(),
+            momentum_drift_str,
+            energy.accretion_power
         );
     }
 }
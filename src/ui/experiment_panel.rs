@@ -0,0 +1,332 @@
+use bevy::prelude::*;
+
+use crate::experiment::comparison::{ComparisonView, UniverseSnapshot};
+use crate::experiment::sweep::{ParameterSweep, SweepField};
+use crate::ui::controls::{spawn_button, UiColorScheme};
+
+/// Width, in characters, of the longest bar in the sweep plot.
+const PLOT_WIDTH: usize = 18;
+
+#[derive(Component)]
+pub(crate) struct SweepParameterToggle;
+
+#[derive(Component)]
+pub(crate) struct SweepParameterLabel;
+
+#[derive(Component)]
+pub(crate) struct SweepMetricToggle;
+
+#[derive(Component)]
+pub(crate) struct SweepMetricLabel;
+
+#[derive(Component)]
+pub(crate) struct SweepRangeButton {
+    field: SweepField,
+    direction: f32,
+}
+
+#[derive(Component)]
+pub(crate) struct RunSweepButton;
+
+#[derive(Component)]
+pub(crate) struct RunSweepLabel;
+
+#[derive(Component)]
+pub(crate) struct SweepSpecText;
+
+#[derive(Component)]
+pub(crate) struct SweepPlotText;
+
+/// Side-by-side metrics of the A/B comparison, when one is running.
+#[derive(Component)]
+pub(crate) struct ComparisonText;
+
+pub fn setup_experiment_panel(mut commands: Commands, colors: Res<UiColorScheme>) {
+    let row_style = Style {
+        flex_direction: FlexDirection::Row,
+        column_gap: Val::Px(6.0),
+        ..Default::default()
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(16.0),
+                bottom: Val::Px(12.0),
+                width: Val::Px(320.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                padding: UiRect::axes(Val::Px(10.0), Val::Px(8.0)),
+                ..Default::default()
+            },
+            background_color: Color::srgba(0.04, 0.04, 0.08, 0.7).into(),
+            ..Default::default()
+        })
+        .with_children(|root| {
+            root.spawn(TextBundle::from_section(
+                "Experiment",
+                TextStyle {
+                    font_size: 18.0,
+                    color: Color::srgb(0.85, 0.9, 1.0),
+                    ..Default::default()
+                },
+            ));
+
+            root.spawn(NodeBundle {
+                style: row_style.clone(),
+                background_color: Color::NONE.into(),
+                ..Default::default()
+            })
+            .with_children(|row| {
+                spawn_button(
+                    row,
+                    "Param",
+                    SweepParameterToggle,
+                    SweepParameterLabel,
+                    &colors,
+                );
+                spawn_button(row, "Metric", SweepMetricToggle, SweepMetricLabel, &colors);
+                spawn_button(row, "Run Sweep", RunSweepButton, RunSweepLabel, &colors);
+            });
+
+            root.spawn(NodeBundle {
+                style: row_style.clone(),
+                background_color: Color::NONE.into(),
+                ..Default::default()
+            })
+            .with_children(|row| {
+                for (label, field, direction) in [
+                    ("From -", SweepField::Start, -1.0),
+                    ("From +", SweepField::Start, 1.0),
+                    ("To -", SweepField::End, -1.0),
+                    ("To +", SweepField::End, 1.0),
+                ] {
+                    spawn_button(
+                        row,
+                        label,
+                        SweepRangeButton { field, direction },
+                        (),
+                        &colors,
+                    );
+                }
+            });
+
+            root.spawn(NodeBundle {
+                style: row_style,
+                background_color: Color::NONE.into(),
+                ..Default::default()
+            })
+            .with_children(|row| {
+                for (label, direction) in [("Step -", -1.0), ("Step +", 1.0)] {
+                    spawn_button(
+                        row,
+                        label,
+                        SweepRangeButton {
+                            field: SweepField::Step,
+                            direction,
+                        },
+                        (),
+                        &colors,
+                    );
+                }
+            });
+
+            root.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 14.0,
+                        color: Color::srgb(0.8, 0.9, 1.0),
+                        ..Default::default()
+                    },
+                ),
+                SweepSpecText,
+            ));
+
+            root.spawn((
+                TextBundle::from_section(
+                    "No sweep results yet",
+                    TextStyle {
+                        font_size: 13.0,
+                        color: Color::srgb(0.78, 0.84, 0.95),
+                        ..Default::default()
+                    },
+                ),
+                SweepPlotText,
+            ));
+
+            root.spawn((
+                TextBundle::from_section(
+                    "No comparison (start with --compare <param>=<value>)",
+                    TextStyle {
+                        font_size: 13.0,
+                        color: Color::srgb(0.78, 0.84, 0.95),
+                        ..Default::default()
+                    },
+                ),
+                ComparisonText,
+            ));
+        });
+}
+
+pub fn update_experiment_buttons(
+    mut sweep: ResMut<ParameterSweep>,
+    parameter_buttons: Query<&Interaction, (Changed<Interaction>, With<SweepParameterToggle>)>,
+    metric_buttons: Query<&Interaction, (Changed<Interaction>, With<SweepMetricToggle>)>,
+    run_buttons: Query<&Interaction, (Changed<Interaction>, With<RunSweepButton>)>,
+    range_buttons: Query<(&Interaction, &SweepRangeButton), Changed<Interaction>>,
+) {
+    for interaction in parameter_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            sweep.cycle_parameter();
+        }
+    }
+
+    for interaction in metric_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            sweep.metric = sweep.metric.next();
+        }
+    }
+
+    for (interaction, button) in range_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            sweep.spec.adjust(button.field, button.direction);
+        }
+    }
+
+    for interaction in run_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            sweep.start();
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn update_experiment_panel(
+    sweep: Res<ParameterSweep>,
+    mut texts: ParamSet<(
+        Query<&mut Text, With<SweepParameterLabel>>,
+        Query<&mut Text, With<SweepMetricLabel>>,
+        Query<&mut Text, With<RunSweepLabel>>,
+        Query<&mut Text, With<SweepSpecText>>,
+        Query<&mut Text, With<SweepPlotText>>,
+    )>,
+) {
+    if let Ok(mut text) = texts.p0().get_single_mut() {
+        text.sections[0].value = format!("Param: {}", sweep.spec.parameter.label());
+    }
+
+    if let Ok(mut text) = texts.p1().get_single_mut() {
+        text.sections[0].value = format!("Metric: {}", sweep.metric.label());
+    }
+
+    if let Ok(mut text) = texts.p2().get_single_mut() {
+        text.sections[0].value = match sweep.progress() {
+            Some((completed, total)) => format!("Running {completed}/{total}"),
+            None => "Run Sweep".to_string(),
+        };
+    }
+
+    if let Ok(mut text) = texts.p3().get_single_mut() {
+        let spec = sweep.spec;
+        text.sections[0].value = format!(
+            "{} {:.2} -> {:.2} step {:.2} ({} runs x {} ticks)",
+            spec.parameter.label(),
+            spec.start,
+            spec.end,
+            spec.step,
+            spec.values().len(),
+            spec.ticks
+        );
+    }
+
+    if let Ok(mut text) = texts.p4().get_single_mut() {
+        text.sections[0].value = match &sweep.results {
+            Some((spec, samples)) => {
+                let values: Vec<f64> = samples.iter().map(|s| sweep.metric.sample(s)).collect();
+                let peak = values.iter().fold(0.0f64, |acc, v| acc.max(v.abs()));
+                let mut lines = vec![format!(
+                    "{} vs {}",
+                    sweep.metric.label(),
+                    spec.parameter.label()
+                )];
+                for (sample, value) in samples.iter().zip(values.iter()) {
+                    let filled = if peak > 0.0 {
+                        ((value.abs() / peak) * PLOT_WIDTH as f64).round() as usize
+                    } else {
+                        0
+                    };
+                    lines.push(format!(
+                        "{:>5.2} |{:<width$}| {}",
+                        sample.value,
+                        "#".repeat(filled),
+                        format_metric(*value),
+                        width = PLOT_WIDTH
+                    ));
+                }
+                lines.join("\n")
+            }
+            None => "No sweep results yet".to_string(),
+        };
+    }
+}
+
+fn format_metric(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.2e}")
+    }
+}
+
+/// Fill the comparison table: one row per metric, universe A then B.
+pub fn update_comparison_text(
+    view: Option<Res<ComparisonView>>,
+    mut text: Query<&mut Text, With<ComparisonText>>,
+) {
+    let Some(view) = view else {
+        return;
+    };
+    if !view.is_changed() {
+        return;
+    }
+    let Ok(mut text) = text.get_single_mut() else {
+        return;
+    };
+    let drift = |snapshot: &UniverseSnapshot| {
+        snapshot
+            .energy_drift
+            .map_or("n/a".to_string(), |drift| format!("{drift:.2e}"))
+    };
+    let row = |name: &str, a: String, b: String| format!("\n{name:<10}{a:>12}{b:>12}");
+    let (a, b) = (&view.a, &view.b);
+    let mut value = format!(
+        "A/B: B has {}\n{:<10}{:>12}{:>12}",
+        view.label, "", "A", "B"
+    );
+    value.push_str(&row("Tick", a.tick.to_string(), b.tick.to_string()));
+    value.push_str(&row("Stars", a.stars.to_string(), b.stars.to_string()));
+    value.push_str(&row(
+        "BHs",
+        a.black_holes.to_string(),
+        b.black_holes.to_string(),
+    ));
+    value.push_str(&row(
+        "Galaxies",
+        a.galaxies.to_string(),
+        b.galaxies.to_string(),
+    ));
+    value.push_str(&row(
+        "Avg dens",
+        format!("{:.3}", a.avg_density),
+        format!("{:.3}", b.avg_density),
+    ));
+    value.push_str(&row("dE/E0", drift(a), drift(b)));
+    value.push_str(&row(
+        "|p|",
+        format!("{:.4}", a.momentum),
+        format!("{:.4}", b.momentum),
+    ));
+    text.sections[0].value = value;
+}
@@ -0,0 +1,263 @@
+//! Cell inspector: read out and edit the lattice cell nearest the camera focus.
+//!
+//! The inspected cell follows the focus the same way `X` picks a body to pin.
+//! The lock buttons write straight into the cell: a UA change re-derives the
+//! cell's mass (see [`PruCell::set_ua_mass_lock`]), and both changes recolor
+//! the cell at once. Derived fields pick the edit up on the next physics tick,
+//! even while paused.
+
+use bevy::prelude::*;
+
+use crate::app::RefreshCellColor;
+use crate::astro::formation::{FormationSettings, GalaxyScanTask};
+use crate::astro::galaxy::{Galaxy, SelectedGalaxy};
+use crate::pru::cell::{DerivedFields, PruCell, PruDynamics};
+use crate::pru::universe::PruUniverse;
+use crate::render::camera::OrbitCameraSettings;
+use crate::ui::controls::{spawn_button, UiColorScheme, PIN_PICK_RADIUS};
+
+/// Largest UA lock the inspector will set.
+const MAX_UA_LOCK: f64 = 50.0;
+/// UB locks are seeded in `-1..1`; the inspector keeps them there.
+const UB_LOCK_RANGE: (f64, f64) = (-1.0, 1.0);
+
+/// The cell the inspector currently shows, if any lies near the camera focus.
+#[derive(Resource, Default)]
+pub struct CellInspector {
+    pub cell: Option<Entity>,
+}
+
+/// One inspector button's edit.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum LockEdit {
+    /// Add to the UA lock.
+    AddUa(f64),
+    /// Multiply the UA lock.
+    ScaleUa(f64),
+    /// Add to the UB lock.
+    AddUb(f64),
+}
+
+impl LockEdit {
+    /// Apply the edit to `cell`, keeping its mass in step with the UA lock.
+    pub fn apply(self, cell: &mut PruCell, dynamics: &mut PruDynamics) {
+        match self {
+            LockEdit::AddUa(delta) => {
+                let ua = (cell.ua_mass_lock + delta).min(MAX_UA_LOCK);
+                cell.set_ua_mass_lock(ua, dynamics);
+            }
+            LockEdit::ScaleUa(factor) => {
+                let ua = (cell.ua_mass_lock * factor).min(MAX_UA_LOCK);
+                cell.set_ua_mass_lock(ua, dynamics);
+            }
+            LockEdit::AddUb(delta) => {
+                cell.ub_geom_lock =
+                    (cell.ub_geom_lock + delta).clamp(UB_LOCK_RANGE.0, UB_LOCK_RANGE.1);
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct LockEditButton(LockEdit);
+
+#[derive(Component)]
+pub(crate) struct InspectorText;
+
+pub fn setup_inspector_panel(mut commands: Commands, colors: Res<UiColorScheme>) {
+    let row_style = Style {
+        flex_direction: FlexDirection::Row,
+        column_gap: Val::Px(6.0),
+        ..Default::default()
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(352.0),
+                bottom: Val::Px(12.0),
+                width: Val::Px(280.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                padding: UiRect::axes(Val::Px(10.0), Val::Px(8.0)),
+                ..Default::default()
+            },
+            background_color: Color::srgba(0.04, 0.04, 0.08, 0.7).into(),
+            ..Default::default()
+        })
+        .with_children(|root| {
+            root.spawn(TextBundle::from_section(
+                "Cell Inspector",
+                TextStyle {
+                    font_size: 18.0,
+                    color: Color::srgb(0.85, 0.9, 1.0),
+                    ..Default::default()
+                },
+            ));
+
+            root.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 14.0,
+                        color: Color::srgb(0.8, 0.9, 1.0),
+                        ..Default::default()
+                    },
+                ),
+                InspectorText,
+            ));
+
+            for edits in [
+                [
+                    ("UA -", LockEdit::AddUa(-0.1)),
+                    ("UA +", LockEdit::AddUa(0.1)),
+                    ("UA x2", LockEdit::ScaleUa(2.0)),
+                ],
+                [
+                    ("UB -", LockEdit::AddUb(-0.1)),
+                    ("UB +", LockEdit::AddUb(0.1)),
+                    ("UA /2", LockEdit::ScaleUa(0.5)),
+                ],
+            ] {
+                root.spawn(NodeBundle {
+                    style: row_style.clone(),
+                    background_color: Color::NONE.into(),
+                    ..Default::default()
+                })
+                .with_children(|row| {
+                    for (label, edit) in edits {
+                        spawn_button(row, label, LockEditButton(edit), (), &colors);
+                    }
+                });
+            }
+        });
+}
+
+/// Point the inspector at the cell nearest the camera focus.
+pub fn select_inspected_cell(
+    camera: Res<OrbitCameraSettings>,
+    mut inspector: ResMut<CellInspector>,
+    cells: Query<(Entity, &PruCell)>,
+) {
+    let nearest = cells
+        .iter()
+        .map(|(entity, cell)| (cell.position.distance_squared(camera.focus), entity))
+        .filter(|(distance2, _)| *distance2 <= PIN_PICK_RADIUS * PIN_PICK_RADIUS)
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, entity)| entity);
+    if inspector.cell != nearest {
+        inspector.cell = nearest;
+    }
+}
+
+/// Apply the lock buttons to the inspected cell and refresh the readout.
+#[allow(clippy::too_many_arguments)]
+pub fn update_cell_inspector(
+    mut commands: Commands,
+    inspector: Res<CellInspector>,
+    universe: Option<Res<PruUniverse>>,
+    selected: Res<SelectedGalaxy>,
+    formation: Res<FormationSettings>,
+    scan_task: Res<GalaxyScanTask>,
+    galaxies: Query<&Galaxy>,
+    buttons: Query<(&Interaction, &LockEditButton), Changed<Interaction>>,
+    mut cells: Query<(&mut PruCell, &mut PruDynamics, &DerivedFields)>,
+    mut text: Query<&mut Text, With<InspectorText>>,
+) {
+    let inspected = inspector.cell.and_then(|entity| {
+        cells
+            .get_mut(entity)
+            .ok()
+            .map(|components| (entity, components))
+    });
+    let Some((entity, (mut cell, mut dynamics, derived))) = inspected else {
+        if let Ok(mut text) = text.get_single_mut() {
+            let hint = format!("Move the focus within {PIN_PICK_RADIUS} of a cell");
+            if text.sections[0].value != hint {
+                text.sections[0].value = hint;
+            }
+        }
+        return;
+    };
+
+    for (interaction, button) in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            button.0.apply(&mut cell, &mut dynamics);
+            commands.entity(entity).insert(RefreshCellColor);
+        }
+    }
+
+    if let Ok(mut text) = text.get_single_mut() {
+        let coords = cell.grid_coords;
+        // Face neighbors across the lattice boundaries, so wrapped axes show at the edges.
+        let neighbors = universe.map_or(0, |universe| universe.neighbors(coords).count());
+        let mut readout = format!(
+            "Cell ({}, {}, {}), {neighbors} neighbors\nUA lock {:.3} -> mass {:.3}\nUB lock {:.3}\nDensity {:.3}  Curvature {:.3}",
+            coords.x,
+            coords.y,
+            coords.z,
+            cell.ua_mass_lock,
+            dynamics.mass,
+            cell.ub_geom_lock,
+            derived.local_density,
+            derived.curvature_proxy,
+        );
+        if let Some(galaxy) = selected.0.and_then(|entity| galaxies.get(entity).ok()) {
+            let member = galaxy.claims(
+                coords,
+                derived.local_density,
+                formation.galaxy_density_threshold,
+                scan_task.region_size(&formation),
+            );
+            let relation = if member { "In" } else { "Not in" };
+            readout.push_str(&format!("\n{relation} selected Galaxy #{}", galaxy.id));
+        }
+        if text.sections[0].value != readout {
+            text.sections[0].value = readout;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::pru::cell::MIN_CELL_MASS;
+
+    #[test]
+    fn editing_the_ua_lock_re_derives_the_cell_mass() {
+        let mut world = World::new();
+        world.init_resource::<SelectedGalaxy>();
+        world.init_resource::<FormationSettings>();
+        world.init_resource::<GalaxyScanTask>();
+        let cell = world
+            .spawn((
+                PruCell::new(Vec3::ZERO, UVec3::ZERO, 1.5, 0.0),
+                PruDynamics {
+                    mass: 1.5,
+                    ..Default::default()
+                },
+                DerivedFields::default(),
+            ))
+            .id();
+        world.insert_resource(CellInspector { cell: Some(cell) });
+        let button = world
+            .spawn((Interaction::Pressed, LockEditButton(LockEdit::ScaleUa(4.0))))
+            .id();
+
+        world.run_system_once(update_cell_inspector);
+        assert_eq!(world.get::<PruCell>(cell).unwrap().ua_mass_lock, 6.0);
+        assert_eq!(world.get::<PruDynamics>(cell).unwrap().mass, 6.0);
+        assert!(world.get::<RefreshCellColor>(cell).is_some());
+
+        // Draining the lock leaves the cell at the floor mass setup uses.
+        world
+            .entity_mut(button)
+            .insert(LockEditButton(LockEdit::AddUa(-10.0)));
+        world.run_system_once(update_cell_inspector);
+        assert_eq!(world.get::<PruCell>(cell).unwrap().ua_mass_lock, 0.0);
+        assert_eq!(world.get::<PruDynamics>(cell).unwrap().mass, MIN_CELL_MASS);
+    }
+}
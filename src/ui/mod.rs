@@ -2,34 +2,109 @@
 
 use bevy::prelude::*;
 
-use crate::ui::agents_panel::{setup_agent_panel, update_agent_panel};
+use crate::ui::agents_panel::{
+    select_galaxy_from_agent_list, setup_agent_panel, update_agent_list, update_agent_panel,
+    update_formation_rate_chart,
+};
 use crate::ui::controls::{
-    keyboard_controls, setup_ui, update_density_history_bars, update_energy_text,
-    update_gravity_labels, update_metrics_text, update_overlay_labels, update_status_text,
-    update_ui_buttons,
+    calibrate_gravity, keyboard_controls, setup_ui, toggle_pinned_body, update_breakpoint_controls,
+    update_density_history_bars, update_density_threshold_markers, update_energy_text,
+    update_formation_controls, update_gravity_labels, update_isosurface_controls,
+    update_metrics_text, update_overlay_labels, update_region_of_interest, update_rewind_controls,
+    update_smoothing_controls, update_split_screen_controls, update_status_text,
+    update_streamline_controls, update_ui_buttons, update_visual_mode_buttons, update_window_title,
+    DensityHistoryScale,
+};
+use crate::ui::experiment_panel::{
+    setup_experiment_panel, update_comparison_text, update_experiment_buttons,
+    update_experiment_panel,
+};
+use crate::ui::inspector::{
+    select_inspected_cell, setup_inspector_panel, update_cell_inspector, CellInspector,
 };
+use crate::ui::timeline::{
+    record_timeline_events, setup_event_timeline, update_event_timeline, EventTimeline,
+};
+use crate::ui::toasts::{setup_toast_stack, toast_critical_reports, update_toasts, Toasts};
+use crate::ui::view_settings::{load_view_settings, reset_view_settings, save_view_settings};
 
 pub mod agents_panel;
 pub mod controls;
+pub mod experiment_panel;
+pub mod inspector;
+pub mod timeline;
+pub mod toasts;
+pub mod view_settings;
 
 /// Plugin encapsulating UI setup and interactions.
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, (setup_ui, setup_agent_panel))
+        app.init_resource::<CellInspector>()
+            .init_resource::<Toasts>()
+            .init_resource::<EventTimeline>()
+            .init_resource::<DensityHistoryScale>()
+            .add_systems(PreStartup, load_view_settings)
+            .add_systems(Last, save_view_settings)
+            .add_systems(
+                Startup,
+                (
+                    setup_ui,
+                    setup_agent_panel,
+                    setup_experiment_panel.after(setup_ui),
+                    setup_inspector_panel.after(setup_ui),
+                    setup_toast_stack,
+                    setup_event_timeline,
+                ),
+            )
             .add_systems(
                 Update,
                 (
                     keyboard_controls,
+                    toggle_pinned_body,
                     update_ui_buttons,
                     update_status_text,
-                    update_metrics_text,
+                    update_window_title,
+                    update_region_of_interest,
+                    update_metrics_text.after(update_region_of_interest),
                     update_energy_text,
                     update_density_history_bars,
                     update_overlay_labels,
+                    update_visual_mode_buttons,
                     update_gravity_labels,
+                    update_isosurface_controls,
+                    update_smoothing_controls,
+                    update_formation_controls,
+                    update_agent_list,
                     update_agent_panel,
+                    update_formation_rate_chart,
+                    update_experiment_buttons,
+                    update_experiment_panel,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    (select_inspected_cell, update_cell_inspector).chain(),
+                    reset_view_settings,
+                    (toast_critical_reports, update_toasts).chain(),
+                    update_density_threshold_markers.after(update_density_history_bars),
+                    update_breakpoint_controls,
+                    update_comparison_text,
+                    update_rewind_controls,
+                    update_streamline_controls,
+                    update_split_screen_controls
+                        .after(keyboard_controls)
+                        .after(update_ui_buttons)
+                        .after(update_visual_mode_buttons),
+                    calibrate_gravity,
+                    (record_timeline_events, update_event_timeline)
+                        .chain()
+                        .after(update_ui_buttons),
+                    select_galaxy_from_agent_list
+                        .after(update_ui_buttons)
+                        .after(update_agent_list),
                 ),
             );
     }
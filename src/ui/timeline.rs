@@ -0,0 +1,331 @@
+//! Event timeline: agent reports as tick marks along the run so far.
+//!
+//! A strip along the bottom edge spans tick 0 to the current tick and shows
+//! every structured report as a mark at its tick, colored by severity. The
+//! strip rescales as the run grows; reports too close together to tell
+//! apart share a bin, drawn as one mark in its most severe color, so at most
+//! [`MAX_TIMELINE_MARKS`] marks are ever drawn. Hovering a mark shows its
+//! reports, and clicking one moves the camera to where the latest of them
+//! happened. A universe reset clears the timeline.
+//!
+//! [`AstroReportLog`] keeps only its most recent reports, so the timeline
+//! copies each one as it arrives and keeps its own record of the run.
+
+use bevy::prelude::*;
+
+use crate::agents::events::{AstroReportLog, ReportSeverity};
+use crate::app::SimulationState;
+use crate::render::camera::OrbitCameraSettings;
+use crate::ui::toasts::{Toasts, TOAST_SECS};
+
+/// Most marks on the strip; denser reports are binned together.
+pub const MAX_TIMELINE_MARKS: usize = 120;
+/// Most reports the timeline remembers; the oldest are dropped past it.
+pub const MAX_TIMELINE_EVENTS: usize = 10_000;
+/// Reports listed in a mark's tooltip before it summarizes the rest.
+const TOOLTIP_REPORTS: usize = 3;
+
+/// One report on the timeline.
+#[derive(Clone, Debug)]
+pub struct TimelineEvent {
+    pub tick: u64,
+    pub severity: ReportSeverity,
+    pub summary: String,
+    pub position: Option<Vec3>,
+}
+
+/// Reports sharing one mark on the strip.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimelineBin {
+    /// Position along the strip, 0 at tick 0 and 1 at the current tick.
+    pub fraction: f32,
+    pub severity: ReportSeverity,
+    /// Range into `EventTimeline::events`.
+    pub first: usize,
+    pub count: usize,
+}
+
+/// Every report of the current run, and the marks they are drawn as.
+#[derive(Resource, Default)]
+pub struct EventTimeline {
+    pub events: Vec<TimelineEvent>,
+    pub bins: Vec<TimelineBin>,
+    /// Tick the strip ends at when `bins` were computed.
+    end_tick: u64,
+    /// `AstroReportLog::pushed` already copied in.
+    seen: u64,
+    /// `SimulationState::generation` the events belong to.
+    generation: u64,
+}
+
+impl EventTimeline {
+    /// Add `event` in tick order; a report stamped with an earlier tick goes before later ones.
+    pub fn push(&mut self, event: TimelineEvent) {
+        let at = self
+            .events
+            .partition_point(|other| other.tick <= event.tick);
+        self.events.insert(at, event);
+        if self.events.len() > MAX_TIMELINE_EVENTS {
+            let overflow = self.events.len() - MAX_TIMELINE_EVENTS;
+            self.events.drain(0..overflow);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.bins.clear();
+        self.end_tick = 0;
+    }
+
+    /// Group the events into at most `max_marks` bins over ticks `0..=end_tick`.
+    ///
+    /// Events are kept in tick order, so each bin is a contiguous run of them.
+    pub fn rebin(&mut self, end_tick: u64, max_marks: usize) {
+        self.end_tick = end_tick;
+        self.bins.clear();
+        let span = end_tick.max(1) as f64;
+        let slots = max_marks.max(1) as f64;
+        let mut current_slot = None;
+        for (index, event) in self.events.iter().enumerate() {
+            let fraction = (event.tick as f64 / span).min(1.0);
+            let slot = ((fraction * slots) as usize).min(max_marks.max(1) - 1);
+            match self.bins.last_mut() {
+                Some(bin) if current_slot == Some(slot) => {
+                    bin.count += 1;
+                    bin.severity = bin.severity.max(event.severity);
+                }
+                _ => {
+                    current_slot = Some(slot);
+                    self.bins.push(TimelineBin {
+                        fraction: ((slot as f64 + 0.5) / slots) as f32,
+                        severity: event.severity,
+                        first: index,
+                        count: 1,
+                    });
+                }
+            }
+        }
+    }
+
+    /// The events drawn as `bin`.
+    pub fn bin_events(&self, bin: &TimelineBin) -> &[TimelineEvent] {
+        let end = (bin.first + bin.count).min(self.events.len());
+        &self.events[bin.first.min(end)..end]
+    }
+
+    /// Tooltip for `bin`: its reports, newest first, e.g. `[1200] 3 stars collapsed…`.
+    pub fn describe(&self, bin: &TimelineBin) -> String {
+        let events = self.bin_events(bin);
+        let mut lines: Vec<String> = events
+            .iter()
+            .rev()
+            .take(TOOLTIP_REPORTS)
+            .map(|event| format!("[{}] {}", event.tick, event.summary))
+            .collect();
+        if events.len() > TOOLTIP_REPORTS {
+            lines.push(format!(
+                "…and {} more since tick {}",
+                events.len() - TOOLTIP_REPORTS,
+                events[0].tick
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+/// The strip the marks sit on.
+#[derive(Component)]
+pub(crate) struct TimelineStrip;
+
+/// Pooled mark node drawing `EventTimeline::bins[index]`, hidden when there is no such bin.
+#[derive(Component)]
+pub(crate) struct TimelineMark(usize);
+
+/// Text above the strip describing the hovered mark.
+#[derive(Component)]
+pub(crate) struct TimelineTooltip;
+
+/// Mark color for a severity.
+fn mark_color(severity: ReportSeverity) -> Color {
+    match severity {
+        ReportSeverity::Info => Color::srgb(0.5, 0.75, 1.0),
+        ReportSeverity::Warning => Color::srgb(1.0, 0.8, 0.3),
+        ReportSeverity::Critical => Color::srgb(1.0, 0.35, 0.3),
+    }
+}
+
+pub fn setup_event_timeline(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(16.0),
+                    bottom: Val::Px(4.0),
+                    width: Val::Percent(40.0),
+                    height: Val::Px(14.0),
+                    ..Default::default()
+                },
+                background_color: Color::srgba(0.05, 0.06, 0.1, 0.8).into(),
+                ..Default::default()
+            },
+            TimelineStrip,
+            Name::new("Event Timeline"),
+        ))
+        .with_children(|strip| {
+            for index in 0..MAX_TIMELINE_MARKS {
+                strip.spawn((
+                    ButtonBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            width: Val::Px(3.0),
+                            height: Val::Percent(100.0),
+                            display: Display::None,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    TimelineMark(index),
+                ));
+            }
+            strip.spawn((
+                TextBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(0.0),
+                        bottom: Val::Px(18.0),
+                        ..Default::default()
+                    },
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 13.0,
+                            color: Color::srgb(0.9, 0.92, 1.0),
+                            ..Default::default()
+                        },
+                    ),
+                    background_color: Color::srgba(0.05, 0.06, 0.1, 0.9).into(),
+                    visibility: Visibility::Hidden,
+                    ..Default::default()
+                },
+                TimelineTooltip,
+            ));
+        });
+}
+
+/// Copy newly pushed reports onto the timeline and rebin it as the run grows.
+pub fn record_timeline_events(
+    sim_state: Res<SimulationState>,
+    reports: Res<AstroReportLog>,
+    mut timeline: ResMut<EventTimeline>,
+) {
+    if timeline.generation != sim_state.generation {
+        timeline.clear();
+        timeline.generation = sim_state.generation;
+    }
+    let new = reports.pushed.saturating_sub(timeline.seen) as usize;
+    timeline.seen = reports.pushed;
+    let start = reports.reports.len().saturating_sub(new);
+    for report in &reports.reports[start..] {
+        timeline.push(TimelineEvent {
+            tick: report.tick,
+            severity: report.severity,
+            summary: report.summary.clone(),
+            position: report.position,
+        });
+    }
+    if new > 0 || timeline.end_tick != sim_state.tick {
+        timeline.rebin(sim_state.tick, MAX_TIMELINE_MARKS);
+    }
+}
+
+/// Lay the marks out, show the hovered mark's tooltip, and jump the camera to a clicked one.
+pub fn update_event_timeline(
+    timeline: Res<EventTimeline>,
+    mut camera: ResMut<OrbitCameraSettings>,
+    mut toasts: ResMut<Toasts>,
+    mut marks: Query<
+        (
+            &TimelineMark,
+            Ref<Interaction>,
+            &mut Style,
+            &mut BackgroundColor,
+        ),
+        Without<TimelineTooltip>,
+    >,
+    mut tooltip: Query<(&mut Text, &mut Style, &mut Visibility), With<TimelineTooltip>>,
+) {
+    let mut hovered = None;
+    for (mark, interaction, mut style, mut color) in marks.iter_mut() {
+        let bin = timeline.bins.get(mark.0);
+        if timeline.is_changed() {
+            match bin {
+                Some(bin) => {
+                    style.display = Display::Flex;
+                    style.left = Val::Percent(bin.fraction * 100.0);
+                }
+                None => style.display = Display::None,
+            }
+        }
+        let Some(bin) = bin else {
+            continue;
+        };
+        // `update_ui_buttons` paints every hovered button; keep the severity color.
+        let fill = mark_color(bin.severity);
+        if color.0 != fill {
+            color.0 = fill;
+        }
+        match *interaction {
+            Interaction::Pressed => {
+                hovered = Some(bin);
+                if !interaction.is_changed() {
+                    continue;
+                }
+                let located = timeline
+                    .bin_events(bin)
+                    .iter()
+                    .rev()
+                    .find_map(|event| event.position.map(|position| (event.tick, position)));
+                match located {
+                    Some((tick, position)) => {
+                        camera.follow.clear();
+                        camera.focus = position;
+                        info!("Camera moved to the tick {tick} event at {position}");
+                    }
+                    None => toasts.push(
+                        "That event has no location to jump to",
+                        ReportSeverity::Info,
+                        TOAST_SECS,
+                    ),
+                }
+            }
+            Interaction::Hovered => hovered = Some(bin),
+            Interaction::None => {}
+        }
+    }
+
+    let Ok((mut text, mut style, mut visibility)) = tooltip.get_single_mut() else {
+        return;
+    };
+    match hovered {
+        Some(bin) => {
+            let description = timeline.describe(bin);
+            if text.sections[0].value != description {
+                text.sections[0].value = description;
+            }
+            // Stop short of the right end so long summaries stay on screen.
+            let left = Val::Percent(bin.fraction.min(0.6) * 100.0);
+            if style.left != left {
+                style.left = left;
+            }
+            if *visibility != Visibility::Inherited {
+                *visibility = Visibility::Inherited;
+            }
+        }
+        None => {
+            if *visibility != Visibility::Hidden {
+                *visibility = Visibility::Hidden;
+            }
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! Transient notifications stacked in the bottom-right corner.
+//!
+//! Anything with a brief message for the user pushes it onto [`Toasts`];
+//! systems outside the UI take the resource as an `Option` so headless runs,
+//! which have no UI, simply skip it. A message already on screen is not
+//! repeated: its toast counts the repeat ("×3") and restarts its timer.
+//! Toasts live on real time, so they expire while paused or slowed down, and
+//! fade out over their last [`TOAST_FADE_SECS`].
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::agents::events::{AstroReportLog, ReportSeverity};
+
+/// Most toasts on screen at once; pushing past it drops the oldest.
+pub const MAX_VISIBLE_TOASTS: usize = 4;
+/// Seconds a toast takes to fade out at the end of its duration.
+pub const TOAST_FADE_SECS: f32 = 0.5;
+/// Default seconds on screen for a routine message.
+pub const TOAST_SECS: f32 = 3.0;
+
+/// One message on screen.
+#[derive(Clone, Debug)]
+pub struct Toast {
+    /// Stable identity linking the toast to its UI node.
+    pub id: u64,
+    pub message: String,
+    pub severity: ReportSeverity,
+    /// Seconds on screen, fade included.
+    pub duration: f32,
+    /// Seconds since the toast was pushed or last repeated.
+    pub age: f32,
+    /// How many times the message was pushed while on screen.
+    pub count: u32,
+}
+
+impl Toast {
+    /// Opacity for the current age: 1 until the fade starts, then down to 0.
+    pub fn alpha(&self) -> f32 {
+        let fade = TOAST_FADE_SECS.min(self.duration).max(f32::EPSILON);
+        ((self.duration - self.age) / fade).clamp(0.0, 1.0)
+    }
+
+    /// The message with its repeat count, e.g. `Pinned body ×3`.
+    pub fn text(&self) -> String {
+        if self.count > 1 {
+            format!("{} ×{}", self.message, self.count)
+        } else {
+            self.message.clone()
+        }
+    }
+}
+
+/// Toasts on screen, oldest first.
+#[derive(Resource, Default)]
+pub struct Toasts {
+    pub toasts: VecDeque<Toast>,
+    next_id: u64,
+}
+
+impl Toasts {
+    /// Show `message` for `duration` seconds, or bump the matching toast already shown.
+    pub fn push(&mut self, message: impl Into<String>, severity: ReportSeverity, duration: f32) {
+        let message = message.into();
+        let duration = duration.max(TOAST_FADE_SECS);
+        if let Some(toast) = self
+            .toasts
+            .iter_mut()
+            .find(|toast| toast.message == message && toast.severity == severity)
+        {
+            toast.count += 1;
+            toast.age = 0.0;
+            toast.duration = toast.duration.max(duration);
+            return;
+        }
+
+        self.toasts.push_back(Toast {
+            id: self.next_id,
+            message,
+            severity,
+            duration,
+            age: 0.0,
+            count: 1,
+        });
+        self.next_id += 1;
+        while self.toasts.len() > MAX_VISIBLE_TOASTS {
+            self.toasts.pop_front();
+        }
+    }
+
+    /// Age every toast by `seconds` and drop the expired ones.
+    pub fn advance(&mut self, seconds: f32) {
+        for toast in self.toasts.iter_mut() {
+            toast.age += seconds;
+        }
+        self.toasts.retain(|toast| toast.age < toast.duration);
+    }
+}
+
+/// Container the toast nodes are stacked in.
+#[derive(Component)]
+pub(crate) struct ToastStack;
+
+/// UI node showing the toast with this id.
+#[derive(Component)]
+pub(crate) struct ToastNode(u64);
+
+/// Background and text color for a severity, before fading.
+fn toast_colors(severity: ReportSeverity) -> (Color, Color) {
+    match severity {
+        ReportSeverity::Info => (
+            Color::srgba(0.08, 0.1, 0.16, 0.9),
+            Color::srgb(0.85, 0.9, 1.0),
+        ),
+        ReportSeverity::Warning => (
+            Color::srgba(0.25, 0.18, 0.04, 0.9),
+            Color::srgb(1.0, 0.85, 0.45),
+        ),
+        ReportSeverity::Critical => (
+            Color::srgba(0.3, 0.05, 0.05, 0.92),
+            Color::srgb(1.0, 0.7, 0.65),
+        ),
+    }
+}
+
+pub fn setup_toast_stack(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(16.0),
+                bottom: Val::Px(12.0),
+                max_width: Val::Px(360.0),
+                // Newest at the bottom, older toasts pushed up above it.
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::FlexEnd,
+                row_gap: Val::Px(6.0),
+                ..Default::default()
+            },
+            // Above the panels sharing the corner; toasts are brief.
+            z_index: ZIndex::Global(10),
+            background_color: Color::NONE.into(),
+            ..Default::default()
+        },
+        ToastStack,
+    ));
+}
+
+/// Age the toasts and spawn, fade, and despawn their nodes to match.
+pub fn update_toasts(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut toasts: ResMut<Toasts>,
+    stack: Query<Entity, With<ToastStack>>,
+    mut nodes: Query<(Entity, &ToastNode, &mut BackgroundColor, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    if !toasts.toasts.is_empty() {
+        toasts.advance(time.delta_seconds());
+    }
+    let Ok(stack) = stack.get_single() else {
+        return;
+    };
+
+    let mut shown = Vec::new();
+    for (entity, node, mut background, children) in nodes.iter_mut() {
+        let Some(toast) = toasts.toasts.iter().find(|toast| toast.id == node.0) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        shown.push(toast.id);
+        let (fill, ink) = toast_colors(toast.severity);
+        let alpha = toast.alpha();
+        let fill = fill.with_alpha(fill.alpha() * alpha);
+        if background.0 != fill {
+            background.0 = fill;
+        }
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                let value = toast.text();
+                let color = ink.with_alpha(alpha);
+                if text.sections[0].value != value {
+                    text.sections[0].value = value;
+                }
+                if text.sections[0].style.color != color {
+                    text.sections[0].style.color = color;
+                }
+            }
+        }
+    }
+
+    for toast in toasts
+        .toasts
+        .iter()
+        .filter(|toast| !shown.contains(&toast.id))
+    {
+        let (fill, ink) = toast_colors(toast.severity);
+        let node = commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        padding: UiRect::axes(Val::Px(10.0), Val::Px(6.0)),
+                        ..Default::default()
+                    },
+                    background_color: fill.into(),
+                    ..Default::default()
+                },
+                ToastNode(toast.id),
+            ))
+            .with_children(|node| {
+                node.spawn(TextBundle::from_section(
+                    toast.text(),
+                    TextStyle {
+                        font_size: 14.0,
+                        color: ink,
+                        ..Default::default()
+                    },
+                ));
+            })
+            .id();
+        commands.entity(stack).add_child(node);
+    }
+}
+
+/// Raise a toast for each critical agent report as it arrives.
+pub fn toast_critical_reports(
+    reports: Res<AstroReportLog>,
+    mut seen: Local<u64>,
+    mut toasts: ResMut<Toasts>,
+) {
+    let new = (reports.pushed - *seen) as usize;
+    *seen = reports.pushed;
+    let start = reports.reports.len().saturating_sub(new);
+    for report in &reports.reports[start..] {
+        if report.severity == ReportSeverity::Critical {
+            toasts.push(report.summary.clone(), ReportSeverity::Critical, 6.0);
+        }
+    }
+}
@@ -0,0 +1,460 @@
+//! Persisted view configuration, kept apart from the simulation state.
+//!
+//! Camera pose, overlay choices, and display toggles describe how the
+//! universe is being looked at rather than what is simulated, so they are
+//! saved to [`view_settings_path`] (RON) and restored on the next launch,
+//! along with the window size and the last formation preset picked. The file
+//! is written when the app exits and every [`AUTOSAVE_INTERVAL_SECS`] while
+//! the view differs from what was last saved. A missing file means first
+//! launch; a malformed one, or one written in another
+//! [`VIEW_SETTINGS_VERSION`], is logged and ignored. Either way the app starts
+//! from defaults. The Reset View button deletes the file and returns to them.
+
+use std::path::{Path, PathBuf};
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use serde::{Deserialize, Serialize};
+
+use crate::agents::analysis::GalaxyFilamentGraph;
+use crate::agents::events::ReportSeverity;
+use crate::astro::formation::{FormationPreset, FormationSettings};
+use crate::render::camera::{OrbitCameraSettings, ToneMappingSettings};
+use crate::render::isosurface::IsosurfaceSettings;
+use crate::ui::controls::VisualModeSettings;
+use crate::ui::toasts::{Toasts, TOAST_SECS};
+
+/// Name of the saved view file.
+pub const VIEW_SETTINGS_FILE: &str = "view_settings.ron";
+/// Directory under the platform config directory that holds [`VIEW_SETTINGS_FILE`].
+const CONFIG_DIR_NAME: &str = "pru_simulation_engine";
+/// Format of the saved view; bump it when a change would misread older files.
+///
+/// Files without a `version` predate it and count as version 1.
+pub const VIEW_SETTINGS_VERSION: u32 = 1;
+/// Seconds between background saves of a changed view.
+pub const AUTOSAVE_INTERVAL_SECS: f32 = 30.0;
+
+/// Where the view is saved: `view_settings.ron` in the per-user config directory.
+///
+/// That is `$XDG_CONFIG_HOME` (or `~/.config`) on Linux, `~/Library/Application
+/// Support` on macOS, and `%APPDATA%` on Windows. Without one, the file goes
+/// in the working directory, where earlier builds kept it.
+pub fn view_settings_path() -> PathBuf {
+    let var = |name: &str| {
+        std::env::var_os(name)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+    let config_dir = if cfg!(target_os = "windows") {
+        var("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        var("HOME").map(|home| home.join("Library/Application Support"))
+    } else {
+        var("XDG_CONFIG_HOME").or_else(|| var("HOME").map(|home| home.join(".config")))
+    };
+    match config_dir {
+        Some(dir) => dir.join(CONFIG_DIR_NAME).join(VIEW_SETTINGS_FILE),
+        None => PathBuf::from(VIEW_SETTINGS_FILE),
+    }
+}
+
+/// Orbit camera pose and input sensitivities.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraView {
+    pub focus: [f32; 3],
+    pub radius: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub up_axis: [f32; 3],
+    pub rotate_sensitivity: f32,
+    pub pan_sensitivity: f32,
+    pub zoom_sensitivity: f32,
+    pub fov_auto: bool,
+}
+
+impl Default for CameraView {
+    fn default() -> Self {
+        Self::from(&OrbitCameraSettings::default())
+    }
+}
+
+impl From<&OrbitCameraSettings> for CameraView {
+    fn from(settings: &OrbitCameraSettings) -> Self {
+        Self {
+            focus: settings.focus.to_array(),
+            radius: settings.radius,
+            yaw: settings.yaw,
+            pitch: settings.pitch,
+            up_axis: settings.up_axis.to_array(),
+            rotate_sensitivity: settings.rotate_sensitivity,
+            pan_sensitivity: settings.pan_sensitivity,
+            zoom_sensitivity: settings.zoom_sensitivity,
+            fov_auto: settings.fov_auto,
+        }
+    }
+}
+
+impl CameraView {
+    fn apply(&self, settings: &mut OrbitCameraSettings) {
+        settings.focus = Vec3::from_array(self.focus);
+        settings.radius = self.radius;
+        settings.yaw = self.yaw;
+        settings.pitch = self.pitch;
+        settings.up_axis = Vec3::from_array(self.up_axis);
+        settings.rotate_sensitivity = self.rotate_sensitivity;
+        settings.pan_sensitivity = self.pan_sensitivity;
+        settings.zoom_sensitivity = self.zoom_sensitivity;
+        settings.fov_auto = self.fov_auto;
+    }
+}
+
+/// Everything about how the scene is viewed, in one serializable resource.
+///
+/// Fields missing from a saved file take their defaults, so files written by
+/// older builds still load.
+#[derive(Resource, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewSettings {
+    pub version: u32,
+    pub camera: CameraView,
+    pub overlays: VisualModeSettings,
+    pub show_filaments: bool,
+    pub isosurface_enabled: bool,
+    pub iso_level: f32,
+    pub tone_mapping: ToneMappingSettings,
+    pub formation_preset: FormationPreset,
+    /// Logical width and height of the primary window; `None` keeps the platform default.
+    pub window_size: Option<[f32; 2]>,
+}
+
+impl Default for ViewSettings {
+    fn default() -> Self {
+        Self::capture(
+            &OrbitCameraSettings::default(),
+            &VisualModeSettings::default(),
+            &GalaxyFilamentGraph::default(),
+            &IsosurfaceSettings::default(),
+            &ToneMappingSettings::default(),
+            &FormationSettings::default(),
+        )
+    }
+}
+
+impl ViewSettings {
+    /// Snapshot the live view resources; the window size is left unset.
+    pub fn capture(
+        camera: &OrbitCameraSettings,
+        overlays: &VisualModeSettings,
+        filaments: &GalaxyFilamentGraph,
+        isosurface: &IsosurfaceSettings,
+        tone_mapping: &ToneMappingSettings,
+        formation: &FormationSettings,
+    ) -> Self {
+        Self {
+            version: VIEW_SETTINGS_VERSION,
+            camera: CameraView::from(camera),
+            overlays: *overlays,
+            show_filaments: filaments.render_edges,
+            isosurface_enabled: isosurface.enabled,
+            iso_level: isosurface.iso_level,
+            tone_mapping: *tone_mapping,
+            formation_preset: formation.preset,
+            window_size: None,
+        }
+    }
+
+    /// Write this view into the live resources.
+    ///
+    /// The formation thresholds are replaced only when the saved preset
+    /// differs from the live one, so matching settings keep any tweaks.
+    pub fn apply(
+        &self,
+        camera: &mut OrbitCameraSettings,
+        overlays: &mut VisualModeSettings,
+        filaments: &mut GalaxyFilamentGraph,
+        isosurface: &mut IsosurfaceSettings,
+        tone_mapping: &mut ToneMappingSettings,
+        formation: &mut FormationSettings,
+    ) {
+        self.camera.apply(camera);
+        *overlays = self.overlays;
+        filaments.render_edges = self.show_filaments;
+        isosurface.enabled = self.isosurface_enabled;
+        isosurface.iso_level = self.iso_level;
+        *tone_mapping = self.tone_mapping;
+        if formation.preset != self.formation_preset {
+            *formation = FormationSettings::from_preset(self.formation_preset);
+        }
+    }
+
+    pub fn to_ron(self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(&self, ron::ser::PrettyConfig::default())
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(text)
+    }
+
+    /// Read a saved view, or `None` when the file is missing, malformed, or another version.
+    pub fn load(path: &Path) -> Option<Self> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(err) => {
+                warn!("Ignoring view settings {}: {err}", path.display());
+                return None;
+            }
+        };
+        match Self::from_ron(&text) {
+            Ok(view) if view.version != VIEW_SETTINGS_VERSION => {
+                warn!(
+                    "Ignoring view settings {}: version {}, expected {VIEW_SETTINGS_VERSION}",
+                    path.display(),
+                    view.version
+                );
+                None
+            }
+            Ok(view) => Some(view),
+            Err(err) => {
+                warn!("Ignoring malformed view settings {}: {err}", path.display());
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let text = self.to_ron().map_err(std::io::Error::other)?;
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, text)
+    }
+}
+
+/// Size the primary window to `size`, ignoring degenerate values.
+fn apply_window_size(
+    size: Option<[f32; 2]>,
+    windows: &mut Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Some([width, height]) = size else {
+        return;
+    };
+    if !(width.is_finite() && height.is_finite() && width >= 1.0 && height >= 1.0) {
+        return;
+    }
+    if let Ok(mut window) = windows.get_single_mut() {
+        window.resolution.set(width, height);
+    }
+}
+
+/// Restore the saved view before the camera and UI are set up.
+#[allow(clippy::too_many_arguments)]
+pub fn load_view_settings(
+    mut commands: Commands,
+    mut camera: ResMut<OrbitCameraSettings>,
+    mut overlays: ResMut<VisualModeSettings>,
+    mut filaments: ResMut<GalaxyFilamentGraph>,
+    mut isosurface: ResMut<IsosurfaceSettings>,
+    mut tone_mapping: ResMut<ToneMappingSettings>,
+    mut formation: ResMut<FormationSettings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let path = view_settings_path();
+    let view = match ViewSettings::load(&path) {
+        Some(view) => {
+            info!("Restoring view settings from {}", path.display());
+            view.apply(
+                &mut camera,
+                &mut overlays,
+                &mut filaments,
+                &mut isosurface,
+                &mut tone_mapping,
+                &mut formation,
+            );
+            apply_window_size(view.window_size, &mut windows);
+            view
+        }
+        None => ViewSettings::capture(
+            &camera,
+            &overlays,
+            &filaments,
+            &isosurface,
+            &tone_mapping,
+            &formation,
+        ),
+    };
+    commands.insert_resource(view);
+}
+
+/// Save the view on exit, and every [`AUTOSAVE_INTERVAL_SECS`] when it changed.
+///
+/// `ViewSettings` holds the view as last saved or loaded; nothing is written
+/// while the live view still matches it. The window size is tracked every
+/// frame because the window is already gone by the time the app exits.
+#[allow(clippy::too_many_arguments)]
+pub fn save_view_settings(
+    mut exits: EventReader<AppExit>,
+    time: Res<Time<Real>>,
+    mut since_save: Local<f32>,
+    mut window_size: Local<Option<[f32; 2]>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut view: ResMut<ViewSettings>,
+    camera: Res<OrbitCameraSettings>,
+    overlays: Res<VisualModeSettings>,
+    filaments: Res<GalaxyFilamentGraph>,
+    isosurface: Res<IsosurfaceSettings>,
+    tone_mapping: Res<ToneMappingSettings>,
+    formation: Res<FormationSettings>,
+) {
+    if let Ok(window) = windows.get_single() {
+        *window_size = Some([window.resolution.width(), window.resolution.height()]);
+    }
+    *since_save += time.delta_seconds();
+    let exiting = exits.read().last().is_some();
+    if !exiting && *since_save < AUTOSAVE_INTERVAL_SECS {
+        return;
+    }
+    *since_save = 0.0;
+
+    let mut current = ViewSettings::capture(
+        &camera,
+        &overlays,
+        &filaments,
+        &isosurface,
+        &tone_mapping,
+        &formation,
+    );
+    current.window_size = window_size.or(view.window_size);
+    let (Ok(current_text), Ok(saved_text)) = (current.to_ron(), view.to_ron()) else {
+        return;
+    };
+    if current_text == saved_text {
+        return;
+    }
+    let path = view_settings_path();
+    match current.save(&path) {
+        Ok(()) => *view = current,
+        Err(err) => warn!("Could not save view settings to {}: {err}", path.display()),
+    }
+}
+
+/// Marker for the button that deletes the saved view and restores the defaults.
+#[derive(Component)]
+pub(crate) struct ResetViewButton;
+
+/// Delete the saved view and put every view resource back to its default.
+///
+/// The window keeps its current size. Nothing is written again until the
+/// view changes.
+#[allow(clippy::too_many_arguments)]
+pub fn reset_view_settings(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<ResetViewButton>)>,
+    mut view: ResMut<ViewSettings>,
+    mut camera: ResMut<OrbitCameraSettings>,
+    mut overlays: ResMut<VisualModeSettings>,
+    mut filaments: ResMut<GalaxyFilamentGraph>,
+    mut isosurface: ResMut<IsosurfaceSettings>,
+    mut tone_mapping: ResMut<ToneMappingSettings>,
+    mut formation: ResMut<FormationSettings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut toasts: ResMut<Toasts>,
+) {
+    if !buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        return;
+    }
+
+    let path = view_settings_path();
+    match std::fs::remove_file(&path) {
+        Ok(()) => info!("Deleted view settings {}", path.display()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => {
+            warn!("Could not delete view settings {}: {err}", path.display());
+            toasts.push(
+                "Could not delete the saved view",
+                ReportSeverity::Warning,
+                TOAST_SECS,
+            );
+        }
+    }
+
+    let defaults = ViewSettings::default();
+    defaults.apply(
+        &mut camera,
+        &mut overlays,
+        &mut filaments,
+        &mut isosurface,
+        &mut tone_mapping,
+        &mut formation,
+    );
+    *view = ViewSettings {
+        window_size: windows
+            .get_single()
+            .ok()
+            .map(|window| [window.resolution.width(), window.resolution.height()]),
+        ..defaults
+    };
+    toasts.push("View reset to defaults", ReportSeverity::Info, TOAST_SECS);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A scratch file path unique to this test process.
+    fn scratch_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("pru_view_settings_{}", std::process::id()))
+            .join(name)
+    }
+
+    #[test]
+    fn a_customized_view_survives_a_save_and_load() {
+        let mut view = ViewSettings::default();
+        view.camera.focus = [1.5, -2.0, 3.25];
+        view.camera.radius = 42.0;
+        view.camera.fov_auto = !view.camera.fov_auto;
+        view.overlays.show_velocity_coloring = true;
+        view.overlays.show_streamlines = true;
+        view.show_filaments = !view.show_filaments;
+        view.isosurface_enabled = true;
+        view.iso_level = 0.375;
+        view.tone_mapping.hdr = true;
+        view.formation_preset = FormationPreset::GalaxyRich;
+        view.window_size = Some([1600.0, 900.0]);
+        assert_ne!(
+            view.to_ron().unwrap(),
+            ViewSettings::default().to_ron().unwrap()
+        );
+
+        let path = scratch_path("round_trip.ron");
+        view.save(&path).unwrap();
+        let loaded = ViewSettings::load(&path).expect("saved view loads");
+        std::fs::remove_file(&path).ok();
+        assert_eq!(loaded.to_ron().unwrap(), view.to_ron().unwrap());
+        assert_eq!(loaded.camera, view.camera);
+        assert_eq!(loaded.window_size, view.window_size);
+    }
+
+    #[test]
+    fn a_malformed_file_falls_back_to_the_defaults() {
+        let path = scratch_path("malformed.ron");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "(camera: (radius: \"far\"), iso_level: ").unwrap();
+        assert!(ViewSettings::load(&path).is_none());
+
+        // A well-formed file from another version is ignored the same way.
+        std::fs::write(&path, "(version: 99, iso_level: 0.9)").unwrap();
+        let fallback = ViewSettings::load(&path).unwrap_or_default();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(
+            fallback.to_ron().unwrap(),
+            ViewSettings::default().to_ron().unwrap()
+        );
+    }
+}